@@ -0,0 +1,71 @@
+use serde::Serialize;
+
+use crate::markdown;
+
+// Alt texts that describe nothing
+const PLACEHOLDER_ALTS: &[&str] = &[
+    "image", "img", "picture", "pic", "photo", "screenshot", "screen shot", "capture", "capture d'écran",
+    "figure", "graphic", "illustration", "icon", "icône", "alt", "alt text", "texte alternatif",
+    "placeholder", "untitled", "sans titre", "todo", "tbd", "insert alt", "description",
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageIssue {
+    /// "missing" or "placeholder".
+    pub kind: &'static str,
+    pub url: String,
+    pub alt: String,
+    /// UTF-16 offset of the alt text (or of the image URL when the tag has no
+    /// alt attribute), so the frontend can select what needs fixing.
+    pub offset: usize,
+    /// UTF-16 length in the markdown source.
+    pub length: usize,
+    pub line: usize,
+}
+
+/// An alt that is just the file name, as tools insert by default.
+fn is_file_name(alt: &str, url: &str) -> bool {
+    let file = url.rsplit(['/', '\\']).next().unwrap_or(url);
+    let file = percent_encoding::percent_decode_str(file).decode_utf8_lossy();
+    let stem = file.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(&file);
+    alt.eq_ignore_ascii_case(&file) || alt.eq_ignore_ascii_case(stem)
+}
+
+fn is_placeholder(alt: &str, url: &str) -> bool {
+    let normalized = alt.trim().trim_end_matches(['.', ':']).to_lowercase();
+    PLACEHOLDER_ALTS.contains(&normalized.as_str()) || is_file_name(alt.trim(), url)
+}
+
+/// Lists images without a usable alt text. `<img alt="">` marks a decorative
+/// image and is not reported; an empty markdown alt is.
+#[tauri::command]
+pub fn audit_images(markdown: &str) -> Vec<ImageIssue> {
+    markdown::links(markdown)
+        .into_iter()
+        .filter(|link| link.is_image)
+        .filter_map(|link| {
+            let is_html = link.start > 0 && markdown[..link.start].ends_with(['"', '\'']);
+            let url = link.url(markdown);
+            let (start, end) = link.text.unwrap_or((link.start, link.end));
+            let alt = if link.text.is_some() { &markdown[start..end] } else { "" };
+
+            let kind = if link.text.is_none() || (alt.trim().is_empty() && !is_html) {
+                "missing"
+            } else if !alt.trim().is_empty() && is_placeholder(alt, url) {
+                "placeholder"
+            } else {
+                return None;
+            };
+            let offset = markdown::utf16_offset(markdown, start);
+            Some(ImageIssue {
+                kind,
+                url: url.to_string(),
+                alt: alt.to_string(),
+                offset,
+                length: markdown::utf16_offset(markdown, end) - offset,
+                line: markdown::line_at(markdown, start),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,566 @@
+use pulldown_cmark::{html, Options, Parser};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::frontmatter::{self, Frontmatter};
+use crate::{assets, markdown, math, scopes};
+
+/// Info strings of fenced blocks holding one card: the question, a `---`
+/// line, the answer.
+const CARD_BLOCKS: &[&str] = &["flashcard", "anki", "card"];
+/// Fixed so every export reuses the same note type in Anki.
+const MODEL_ID: i64 = 1_712_000_000_000;
+const DECK_CONFIG_ID: i64 = 1;
+const CARD_CSS: &str = ".card { font-family: sans-serif; font-size: 20px; text-align: left; color: black; background-color: white; }\n\
+pre { text-align: left; background: #f4f4f4; padding: 0.5em; overflow-x: auto; }\n\
+table { border-collapse: collapse; }\ntd, th { border: 1px solid #ccc; padding: 0.2em 0.5em; }";
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnkiOptions {
+    /// Deck of the cards, instead of the frontmatter's `deck` or the title.
+    pub deck: Option<String>,
+    /// Tags added to every card, besides the document's.
+    pub tags: Vec<String>,
+    /// Cards go to a subdeck per section: "Deck::Section::Subsection".
+    pub subdecks: bool,
+    /// Headings of this level are questions; by default a heading is one
+    /// when it ends with "?".
+    pub question_level: Option<usize>,
+    /// Document being exported; image paths start from its folder.
+    pub document_path: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Flashcard {
+    /// Markdown of the question.
+    pub front: String,
+    /// Markdown of the answer.
+    pub back: String,
+    pub deck: String,
+    pub tags: Vec<String>,
+    /// Line of the question in the document, from 1.
+    pub line: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnkiExport {
+    pub path: String,
+    pub cards: usize,
+    pub decks: Vec<String>,
+    pub media: usize,
+}
+
+/// A question heading whose answer is still being read.
+struct OpenQuestion {
+    front: String,
+    level: usize,
+    line: usize,
+    sections: Vec<String>,
+    answer: Vec<String>,
+}
+
+struct CardBlock {
+    fence: (char, usize),
+    line: usize,
+    lines: Vec<String>,
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim()))
+}
+
+/// Byte offset of a `::` outside code spans, and the run length (3 for a
+/// reversible `:::` card).
+fn separator(line: &str) -> Option<(usize, usize)> {
+    let bytes = line.as_bytes();
+    let mut in_code = false;
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        match bytes[i] {
+            b'`' => in_code = !in_code,
+            b'\\' => i += 1,
+            b':' if !in_code && bytes[i + 1] == b':' => {
+                let run = bytes[i..].iter().take_while(|&&b| b == b':').count();
+                return (run <= 3).then_some((i, run));
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Strips a bullet or number so `- Question :: Answer` list items work.
+fn without_list_marker(line: &str) -> &str {
+    let trimmed = line.trim_start();
+    if let Some(rest) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")).or_else(|| trimmed.strip_prefix("+ ")) {
+        return rest;
+    }
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    match trimmed[digits..].strip_prefix(". ").or_else(|| trimmed[digits..].strip_prefix(") ")) {
+        Some(rest) if digits > 0 => rest,
+        _ => trimmed,
+    }
+}
+
+/// Anki tags have no spaces and nest with `::`.
+fn anki_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').replace('/', "::").split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+/// Reads every card of a document, in order.
+fn flashcards(md: &str, options: &AnkiOptions) -> Vec<Flashcard> {
+    let fields: Frontmatter = frontmatter::parse(md);
+    let path = options.document_path.as_deref().map(Path::new);
+    let base_deck = options
+        .deck
+        .clone()
+        .filter(|d| !d.trim().is_empty())
+        .or_else(|| fields.first_text(&["anki_deck", "deck"]).map(str::to_string))
+        .unwrap_or_else(|| frontmatter::title(md, path));
+    let mut document_tags: Vec<String> = options.tags.iter().chain(&fields.list("tags")).map(|t| anki_tag(t)).collect();
+    document_tags.retain(|t| !t.is_empty());
+
+    let deck = |sections: &[String]| {
+        let mut deck = base_deck.trim().to_string();
+        if options.subdecks {
+            for section in sections.iter().filter(|s| **s != base_deck) {
+                deck.push_str("::");
+                deck.push_str(&section.replace("::", ":"));
+            }
+        }
+        deck
+    };
+    let card = |front: &str, back: &str, sections: &[String], line: usize| {
+        let mut tags = document_tags.clone();
+        for tag in markdown::tags(&format!("{}\n{}", front, back)) {
+            let tag = anki_tag(&tag.name);
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        Flashcard { front: front.trim().to_string(), back: back.trim().to_string(), deck: deck(sections), tags, line }
+    };
+
+    let mut cards = Vec::new();
+    let mut sections: Vec<(usize, String)> = Vec::new();
+    let mut question: Option<OpenQuestion> = None;
+    let mut block: Option<CardBlock> = None;
+    let mut fences = markdown::FenceState::default();
+    let mut in_code = false;
+    let finish = |question: OpenQuestion, cards: &mut Vec<Flashcard>| {
+        let answer = question.answer.join("\n");
+        if !answer.trim().is_empty() {
+            cards.push(card(&question.front, &answer, &question.sections, question.line));
+        }
+    };
+
+    let body_start = markdown::frontmatter_len(md);
+    let first_line = md[..body_start].lines().count();
+    for (i, line) in md[body_start..].lines().enumerate() {
+        let number = first_line + i + 1;
+        let section_titles = || sections.iter().map(|(_, t)| t.clone()).collect::<Vec<_>>();
+
+        if let Some(open) = &mut block {
+            let (c, n) = open.fence;
+            if markdown::fence_marker(line).is_some_and(|(mc, mn)| mc == c && mn >= n) && line.trim().chars().all(|x| x == c) {
+                let open = block.take().unwrap();
+                let text = open.lines.join("\n");
+                let (front, back) = text.split_once("\n---\n").unwrap_or((&text, ""));
+                if !front.trim().is_empty() && !back.trim().is_empty() {
+                    cards.push(card(front, back, &section_titles(), open.line));
+                }
+            } else {
+                open.lines.push(line.to_string());
+            }
+            continue;
+        }
+
+        let was_code = in_code;
+        let code = fences.is_code(line);
+        in_code = code && !(was_code && markdown::fence_marker(line).is_some());
+        if code && !was_code {
+            let info = line.trim_start().trim_start_matches(['`', '~']).split_whitespace().next().unwrap_or_default().to_lowercase();
+            if CARD_BLOCKS.contains(&info.as_str()) {
+                block = Some(CardBlock { fence: markdown::fence_marker(line).unwrap(), line: number, lines: Vec::new() });
+                fences = markdown::FenceState::default();
+                in_code = false;
+                continue;
+            }
+        }
+        if code {
+            if let Some(open) = &mut question {
+                open.answer.push(line.to_string());
+            }
+            continue;
+        }
+
+        if let Some((level, title)) = heading(line) {
+            if question.as_ref().is_some_and(|q| level <= q.level) {
+                finish(question.take().unwrap(), &mut cards);
+            }
+            if let Some(open) = &mut question {
+                open.answer.push(line.to_string());
+                continue;
+            }
+            let is_question = match options.question_level {
+                Some(question_level) => level == question_level,
+                None => title.ends_with('?'),
+            };
+            if is_question {
+                question = Some(OpenQuestion { front: title.to_string(), level, line: number, sections: section_titles(), answer: Vec::new() });
+            } else {
+                sections.retain(|(l, _)| *l < level);
+                sections.push((level, title.to_string()));
+            }
+            continue;
+        }
+
+        if let Some(open) = &mut question {
+            open.answer.push(line.to_string());
+            continue;
+        }
+        let text = without_list_marker(line);
+        if let Some((at, run)) = separator(text) {
+            let (front, back) = (text[..at].trim(), text[at + run..].trim());
+            if !front.is_empty() && !back.is_empty() {
+                cards.push(card(front, back, &section_titles(), number));
+                if run == 3 {
+                    cards.push(card(back, front, &section_titles(), number));
+                }
+            }
+        }
+    }
+    if let Some(open) = question {
+        finish(open, &mut cards);
+    }
+    cards
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Files the cards show, under the unique names Anki stores them as.
+#[derive(Default)]
+struct Media {
+    names: HashMap<PathBuf, String>,
+    files: Vec<(String, PathBuf)>,
+}
+
+impl Media {
+    fn add(&mut self, file: &Path) -> String {
+        if let Some(name) = self.names.get(file) {
+            return name.clone();
+        }
+        let original = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "image".to_string());
+        let mut name = original.clone();
+        let mut n = 2;
+        while self.files.iter().any(|(taken, _)| *taken == name) {
+            name = match original.rsplit_once('.') {
+                Some((stem, ext)) => format!("{}-{}.{}", stem, n, ext),
+                None => format!("{}-{}", original, n),
+            };
+            n += 1;
+        }
+        self.names.insert(file.to_path_buf(), name.clone());
+        self.files.push((name.clone(), file.to_path_buf()));
+        name
+    }
+}
+
+/// HTML of one side of a card. Local images become media files and TeX math
+/// uses the `\(...\)` delimiters of Anki's MathJax.
+fn card_html(md: &str, document_dir: Option<&Path>, media: &mut Media) -> String {
+    let mut replacements = Vec::new();
+    for link in markdown::links(md).iter().filter(|l| l.is_image) {
+        let url = link.url(md);
+        let file = assets::resolve_link(url, document_dir);
+        if assets::is_local_link(url) && file.is_file() {
+            replacements.push(assets::replacement(md, link, media.add(&file)));
+        }
+    }
+    let md = markdown::replace_ranges(md, replacements);
+
+    // Math is set aside so markdown leaves its backslashes and underscores
+    let spans = math::find_math(&md);
+    let mut formulas = Vec::new();
+    let mut replacements = Vec::new();
+    for span in spans {
+        let formula = if span.display { format!("\\[{}\\]", span.tex) } else { format!("\\({}\\)", span.tex) };
+        replacements.push((span.start, span.end, format!("OMMANKIMATH{}X", formulas.len())));
+        formulas.push(escape(&formula));
+    }
+    let md = markdown::replace_ranges(&md, replacements);
+
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut out = String::new();
+    html::push_html(&mut out, Parser::new_ext(&md, options));
+    // Backwards so "…MATH1X" is not found inside "…MATH11X"
+    for (i, formula) in formulas.iter().enumerate().rev() {
+        out = out.replace(&format!("OMMANKIMATH{}X", i), formula);
+    }
+    let out = out.trim();
+    // A lone paragraph reads better unwrapped on a card
+    match out.strip_prefix("<p>").and_then(|o| o.strip_suffix("</p>")) {
+        Some(inner) if !inner.contains("<p>") => inner.to_string(),
+        _ => out.to_string(),
+    }
+}
+
+fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&amp;", "&")
+}
+
+/// Positive id from a hash, below 2^53 so JavaScript in Anki reads it
+/// exactly.
+fn stable_id(text: &str) -> i64 {
+    let hash = Sha256::digest(text.as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash[..8]);
+    (u64::from_be_bytes(bytes) >> 11) as i64
+}
+
+/// Guid of a note, the same on every export so reimporting updates it.
+fn guid(card: &Flashcard) -> String {
+    let hash = Sha256::digest(format!("{}\u{1f}{}", card.deck, card.front).as_bytes());
+    hash[..10].iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn checksum(sort_field: &str) -> i64 {
+    let hash = Sha1::digest(sort_field.as_bytes());
+    i64::from(u32::from_be_bytes([hash[0], hash[1], hash[2], hash[3]]))
+}
+
+const SCHEMA: &str = "
+CREATE TABLE col (id integer primary key, crt integer not null, mod integer not null, scm integer not null, ver integer not null,
+    dty integer not null, usn integer not null, ls integer not null, conf text not null, models text not null, decks text not null,
+    dconf text not null, tags text not null);
+CREATE TABLE notes (id integer primary key, guid text not null, mid integer not null, mod integer not null, usn integer not null,
+    tags text not null, flds text not null, sfld integer not null, csum integer not null, flags integer not null, data text not null);
+CREATE TABLE cards (id integer primary key, nid integer not null, did integer not null, ord integer not null, mod integer not null,
+    usn integer not null, type integer not null, queue integer not null, due integer not null, ivl integer not null,
+    factor integer not null, reps integer not null, lapses integer not null, left integer not null, odue integer not null,
+    odid integer not null, flags integer not null, data text not null);
+CREATE TABLE revlog (id integer primary key, cid integer not null, usn integer not null, ease integer not null, ivl integer not null,
+    lastIvl integer not null, factor integer not null, time integer not null, type integer not null);
+CREATE TABLE graves (usn integer not null, oid integer not null, type integer not null);
+CREATE INDEX ix_notes_usn on notes (usn);
+CREATE INDEX ix_cards_usn on cards (usn);
+CREATE INDEX ix_revlog_usn on revlog (usn);
+CREATE INDEX ix_cards_nid on cards (nid);
+CREATE INDEX ix_cards_sched on cards (did, queue, due);
+CREATE INDEX ix_revlog_cid on revlog (cid);
+CREATE INDEX ix_notes_csum on notes (csum);
+";
+
+fn deck_json(id: i64, name: &str, now: i64) -> serde_json::Value {
+    json!({
+        "id": id, "name": name, "desc": "", "mod": now, "usn": -1, "dyn": 0, "conf": DECK_CONFIG_ID,
+        "collapsed": false, "browserCollapsed": false, "extendNew": 0, "extendRev": 0,
+        "newToday": [0, 0], "revToday": [0, 0], "lrnToday": [0, 0], "timeToday": [0, 0],
+    })
+}
+
+/// The collection database of an .apkg, in Anki's legacy schema that every
+/// version imports.
+fn write_collection(path: &Path, cards: &[(Flashcard, String, String)], decks: &[String]) -> Result<(), String> {
+    let db_error = |e: rusqlite::Error| format!("Erreur lors de l'écriture du paquet Anki: {}", e);
+    let _ = std::fs::remove_file(path);
+    let db = Connection::open(path).map_err(db_error)?;
+    db.execute_batch(SCHEMA).map_err(db_error)?;
+
+    let now = chrono::Utc::now().timestamp();
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let deck_ids: HashMap<&str, i64> = decks.iter().map(|d| (d.as_str(), stable_id(&format!("deck:{}", d)))).collect();
+    let mut deck_map = serde_json::Map::new();
+    deck_map.insert("1".to_string(), deck_json(1, "Default", now));
+    for (name, id) in &deck_ids {
+        deck_map.insert(id.to_string(), deck_json(*id, name, now));
+    }
+    let first_deck = decks.first().and_then(|d| deck_ids.get(d.as_str())).copied().unwrap_or(1);
+    let field = |name: &str, ord: usize| json!({ "name": name, "ord": ord, "sticky": false, "rtl": false, "font": "Arial", "size": 20, "media": [] });
+    let models = json!({ MODEL_ID.to_string(): {
+        "id": MODEL_ID, "name": "OhMyMarkdown", "type": 0, "mod": now, "usn": -1, "sortf": 0, "did": first_deck,
+        "tmpls": [{
+            "name": "Carte 1", "ord": 0, "qfmt": "{{Recto}}", "afmt": "{{FrontSide}}<hr id=answer>{{Verso}}",
+            "did": null, "bqfmt": "", "bafmt": "",
+        }],
+        "flds": [field("Recto", 0), field("Verso", 1)],
+        "css": CARD_CSS,
+        "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+        "latexPost": "\\end{document}",
+        "latexsvg": false,
+        "req": [[0, "any", [0]]],
+        "tags": [], "vers": [],
+    }});
+    let dconf = json!({ DECK_CONFIG_ID.to_string(): {
+        "id": DECK_CONFIG_ID, "name": "Default", "mod": 0, "usn": 0, "dyn": false, "maxTaken": 60, "timer": 0,
+        "autoplay": true, "replayq": true,
+        "new": { "delays": [1, 10], "ints": [1, 4, 7], "initialFactor": 2500, "order": 1, "perDay": 20, "bury": false, "separate": true },
+        "rev": { "perDay": 200, "ease4": 1.3, "fuzz": 0.05, "minSpace": 1, "ivlFct": 1, "maxIvl": 36500, "bury": false, "hardFactor": 1.2 },
+        "lapse": { "delays": [10], "mult": 0, "minInt": 1, "leechFails": 8, "leechAction": 0 },
+    }});
+    let conf = json!({
+        "activeDecks": [1], "curDeck": 1, "newSpread": 0, "collapseTime": 1200, "timeLim": 0, "estTimes": true,
+        "dueCounts": true, "curModel": MODEL_ID.to_string(), "nextPos": cards.len() + 1, "sortType": "noteFld", "sortBackwards": false,
+        "addToCur": true,
+    });
+    let day_start = now - now.rem_euclid(86_400);
+    db.execute(
+        "INSERT INTO col VALUES (1, ?1, ?2, ?3, 11, 0, 0, 0, ?4, ?5, ?6, ?7, '{}')",
+        params![day_start, now_ms, now_ms, conf.to_string(), models.to_string(), serde_json::Value::Object(deck_map).to_string(), dconf.to_string()],
+    )
+    .map_err(db_error)?;
+
+    for (i, (card, front, back)) in cards.iter().enumerate() {
+        let id = now_ms + i as i64;
+        let sort_field = strip_html(front);
+        let tags = if card.tags.is_empty() { String::new() } else { format!(" {} ", card.tags.join(" ")) };
+        db.execute(
+            "INSERT INTO notes VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')",
+            params![id, guid(card), MODEL_ID, now, tags, format!("{}\u{1f}{}", front, back), sort_field, checksum(&sort_field)],
+        )
+        .map_err(db_error)?;
+        let deck = deck_ids.get(card.deck.as_str()).copied().unwrap_or(1);
+        db.execute(
+            "INSERT INTO cards VALUES (?1, ?2, ?3, 0, ?4, -1, 0, 0, ?5, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+            params![id, id, deck, now, i as i64 + 1],
+        )
+        .map_err(db_error)?;
+    }
+    db.close().map_err(|(_, e)| db_error(e))
+}
+
+fn write_apkg(output: &Path, cards: &[(Flashcard, String, String)], decks: &[String], media: &Media) -> Result<(), String> {
+    let collection = output.with_extension("anki2.part");
+    let result = write_collection(&collection, cards, decks).and_then(|_| {
+        let write_error = |e: zip::result::ZipError| format!("Erreur lors de l'écriture du paquet Anki: {}", e);
+        let file = File::create(output).map_err(|e| format!("Impossible de créer {}: {}", output.display(), e))?;
+        let mut zip = ZipWriter::new(file);
+        let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let mut add = |name: &str, data: &[u8]| {
+            zip.start_file(name, options)
+                .and_then(|_| zip.write_all(data).map_err(Into::into))
+                .map_err(write_error)
+        };
+        let db = std::fs::read(&collection).map_err(|e| format!("Impossible de lire {}: {}", collection.display(), e))?;
+        add("collection.anki2", &db)?;
+        // Media are numbered entries, with a map back to their names
+        let mut map = serde_json::Map::new();
+        for (i, (name, file)) in media.files.iter().enumerate() {
+            let data = std::fs::read(file).map_err(|e| format!("Impossible de lire {}: {}", file.display(), e))?;
+            add(&i.to_string(), &data)?;
+            map.insert(i.to_string(), json!(name));
+        }
+        add("media", serde_json::Value::Object(map).to_string().as_bytes())?;
+        zip.finish().map_err(write_error)?;
+        Ok(())
+    });
+    let _ = std::fs::remove_file(&collection);
+    result
+}
+
+fn tsv_field(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\"").replace('\t', " "))
+}
+
+/// Text file for Anki's import dialog, the header lines saying which columns
+/// hold the deck and tags. Images go to a folder beside it, for Anki's
+/// `collection.media`.
+fn write_tsv(output: &Path, cards: &[(Flashcard, String, String)], media: &Media) -> Result<(), String> {
+    let mut tsv = String::from("#separator:tab\n#html:true\n#notetype:Basic\n#deck column:3\n#tags column:4\n");
+    for (card, front, back) in cards {
+        let row = [tsv_field(front), tsv_field(back), tsv_field(&card.deck), tsv_field(&card.tags.join(" "))];
+        tsv.push_str(&row.join("\t"));
+        tsv.push('\n');
+    }
+    std::fs::write(output, tsv).map_err(|e| format!("Impossible d'écrire {}: {}", output.display(), e))?;
+    if !media.files.is_empty() {
+        let stem = output.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let dir = output.with_file_name(format!("{}_media", stem));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Impossible de créer {}: {}", dir.display(), e))?;
+        for (name, file) in &media.files {
+            std::fs::copy(file, dir.join(name)).map_err(|e| format!("Impossible de copier {}: {}", file.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// The cards a document holds, for a preview before exporting: questions in
+/// headings (ending with "?" or at `questionLevel`) answered by their
+/// section, `Question :: Answer` lines (`:::` adds the reverse card) and
+/// ```` ```flashcard ```` blocks.
+#[tauri::command]
+pub fn preview_flashcards(markdown: &str, options: Option<AnkiOptions>) -> Vec<Flashcard> {
+    flashcards(markdown, &options.unwrap_or_default())
+}
+
+/// Exports the cards of a document as an Anki package (.apkg) or a text
+/// file for Anki's import (.tsv, .txt), chosen by the output's extension.
+#[tauri::command]
+pub async fn export_anki(app: AppHandle, markdown: String, output_path: String, options: Option<AnkiOptions>) -> Result<AnkiExport, String> {
+    let output = scopes::check(&app, &output_path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let extension = output.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        if !matches!(extension.as_str(), "apkg" | "tsv" | "txt") {
+            return Err(format!("Format Anki inconnu: .{} (choisissez .apkg ou .tsv)", extension));
+        }
+        let cards = flashcards(&markdown, &options);
+        if cards.is_empty() {
+            return Err("Le document ne contient aucune carte".to_string());
+        }
+
+        let document_dir = options.document_path.as_deref().map(Path::new).and_then(Path::parent);
+        let mut media = Media::default();
+        let mut decks: Vec<String> = Vec::new();
+        let rendered: Vec<(Flashcard, String, String)> = cards
+            .into_iter()
+            .map(|card| {
+                if !decks.contains(&card.deck) {
+                    decks.push(card.deck.clone());
+                }
+                let front = card_html(&card.front, document_dir, &mut media);
+                let back = card_html(&card.back, document_dir, &mut media);
+                (card, front, back)
+            })
+            .collect();
+        if extension == "apkg" {
+            write_apkg(&output, &rendered, &decks, &media)?;
+        } else {
+            write_tsv(&output, &rendered, &media)?;
+        }
+        Ok(AnkiExport { path: output_path, cards: rendered.len(), decks, media: media.files.len() })
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de l'export Anki: {}", e))?
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::{assets, markdown, pandoc, scopes, updates, workspace};
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkspaceZipOptions {
+    /// Adds an HTML rendering of every note, with links between notes
+    /// pointing at the HTML files.
+    pub html: bool,
+    /// With `html`, leaves the markdown sources out of the archive.
+    pub html_only: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceZipReport {
+    pub notes: usize,
+    pub assets: usize,
+    /// Linked files left out because they live outside the workspace or are
+    /// missing.
+    pub skipped: Vec<String>,
+}
+
+/// Archive path of a file: relative to the workspace, with forward slashes.
+fn entry_name(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/")
+}
+
+/// Points local links to notes at their HTML rendering.
+fn link_html(md: &str, dir: Option<&Path>) -> String {
+    let replacements = markdown::links(md)
+        .iter()
+        .filter(|link| !link.is_image && assets::is_local_link(link.url(md)))
+        .filter_map(|link| {
+            let url = link.url(md);
+            let (path, fragment) = url.split_at(url.find('#').unwrap_or(url.len()));
+            if !markdown::is_markdown_file(&assets::resolve_link(path, dir)) {
+                return None;
+            }
+            let html = Path::new(path).with_extension("html");
+            Some(assets::replacement(md, link, format!("{}{}", html.to_string_lossy().replace('\\', "/"), fragment)))
+        })
+        .collect();
+    markdown::replace_ranges(md, replacements)
+}
+
+fn to_html(md: &str, note: &Path) -> Result<Vec<u8>, String> {
+    let title = note.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let title = format!("pagetitle={}", title);
+    let html = pandoc::convert(md, &["-f", "markdown", "-t", "html5", "--standalone", "--metadata", &title], note.parent())
+        .map_err(|e| format!("{} ({})", e, note.display()))?;
+    Ok(crate::svg::sanitize_embedded(&String::from_utf8_lossy(&html)).into_bytes())
+}
+
+fn add(zip: &mut ZipWriter<File>, name: String, data: &[u8]) -> Result<(), String> {
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(name, options)
+        .and_then(|_| zip.write_all(data).map_err(Into::into))
+        .map_err(|e| format!("Erreur lors de l'écriture de l'archive: {}", e))
+}
+
+fn export(root: &Path, output: &Path, options: &WorkspaceZipOptions) -> Result<WorkspaceZipReport, String> {
+    let notes = workspace::markdown_files(root);
+    let mut linked = BTreeSet::new();
+    let mut skipped = BTreeSet::new();
+
+    let file = File::create(output).map_err(|e| format!("Impossible de créer {}: {}", output.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+
+    for note in &notes {
+        let Ok(md) = std::fs::read_to_string(note) else { continue };
+        let dir = note.parent();
+        for link in markdown::links(&md) {
+            let url = link.url(&md);
+            if !assets::is_local_link(url) {
+                continue;
+            }
+            let target = assets::resolve_link(url, dir);
+            if markdown::is_markdown_file(&target) {
+                continue;
+            }
+            if target.starts_with(root) && target.is_file() {
+                linked.insert(target);
+            } else {
+                skipped.insert(target.to_string_lossy().to_string());
+            }
+        }
+
+        let name = entry_name(root, note);
+        if !(options.html && options.html_only) {
+            add(&mut zip, name.clone(), md.as_bytes())?;
+        }
+        if options.html {
+            let html = to_html(&link_html(&md, dir), note)?;
+            add(&mut zip, Path::new(&name).with_extension("html").to_string_lossy().to_string(), &html)?;
+        }
+    }
+
+    let assets: Vec<PathBuf> = linked.into_iter().filter(|p| p != output).collect();
+    for asset in &assets {
+        let data = std::fs::read(asset).map_err(|e| format!("Impossible de lire {}: {}", asset.display(), e))?;
+        add(&mut zip, entry_name(root, asset), &data)?;
+    }
+    zip.finish().map_err(|e| format!("Erreur lors de l'écriture de l'archive: {}", e))?;
+
+    Ok(WorkspaceZipReport { notes: notes.len(), assets: assets.len(), skipped: skipped.into_iter().collect() })
+}
+
+/// Zips the workspace's notes and the files they link to, keeping the folder
+/// layout so every relative link still resolves inside the archive.
+#[tauri::command]
+pub async fn export_workspace_zip(app: AppHandle, output_path: String, options: Option<WorkspaceZipOptions>) -> Result<WorkspaceZipReport, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    let output = scopes::check(&app, &output_path)?;
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _busy = updates::busy()?;
+        let result = export(&root, &output, &options);
+        if result.is_err() {
+            let _ = std::fs::remove_file(&output);
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de l'export du dossier de travail: {}", e))?
+}
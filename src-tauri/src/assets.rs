@@ -0,0 +1,635 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::{markdown, scopes};
+
+/// Suffix of the per-document assets folder: `notes.md` keeps its media in
+/// `notes.assets/` next to it.
+pub const ASSETS_SUFFIX: &str = ".assets";
+
+const MAX_CONCURRENT_DOWNLOADS: usize = 6;
+const REMOTE_IMAGE_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REMOTE_IMAGE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Assets folder of a saved document.
+pub fn assets_dir_for(document_path: &Path) -> PathBuf {
+    let stem = document_path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    document_path.with_file_name(format!("{}{}", stem, ASSETS_SUFFIX))
+}
+
+/// Default folder of the assets of unsaved documents, in app data.
+pub const UNSAVED_ASSETS_DIR: &str = "unsaved-assets";
+
+/// Root of the assets of documents that have not been saved yet.
+pub fn unsaved_assets_root(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::directories::media_dir(app)
+}
+
+/// Fresh, empty assets folder for a document that has no location yet.
+pub fn new_unsaved_assets_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let dir = unique_path(&unsaved_assets_root(app)?, &stamp.to_string());
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// Assets folder to write into: next to the document when it has been saved
+/// (in the site's static folder for pages of a static site), otherwise a new
+/// folder in app data.
+pub fn target_assets_dir(app: &AppHandle, document_path: Option<&str>) -> Result<PathBuf, String> {
+    match document_path {
+        Some(path) => {
+            let path = &scopes::check(app, path)?;
+            let dir = crate::static_site::assets_location(path).map(|site| site.dir).unwrap_or_else(|| assets_dir_for(path));
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+            Ok(dir)
+        }
+        None => new_unsaved_assets_dir(app),
+    }
+}
+
+/// Returns `dir/name`, or `dir/name-1.ext`, `dir/name-2.ext`… if taken.
+pub fn unique_path(dir: &Path, file_name: &str) -> PathBuf {
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let path = Path::new(file_name);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|n| dir.join(format!("{}-{}{}", stem, n, ext)))
+        .find(|p| !p.exists())
+        .unwrap_or(candidate)
+}
+
+/// Moves a file, falling back to copy + delete across volumes.
+pub fn move_file(from: &Path, to: &Path) -> Result<(), String> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to)
+        .map_err(|e| format!("Impossible de copier {} vers {}: {}", from.display(), to.display(), e))?;
+    std::fs::remove_file(from)
+        .map_err(|e| format!("Impossible de supprimer {}: {}", from.display(), e))
+}
+
+/// Moves a file or folder, copying then deleting when it crosses volumes.
+pub fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Impossible de créer le dossier {}: {}", parent.display(), e))?;
+    }
+    if !from.is_dir() {
+        return move_file(from, to);
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    for entry in walkdir::WalkDir::new(from).into_iter().filter_map(|e| e.ok()) {
+        let target = to.join(entry.path().strip_prefix(from).unwrap_or(entry.path()));
+        let result = if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target)
+        } else {
+            std::fs::copy(entry.path(), &target).map(|_| ())
+        };
+        result.map_err(|e| format!("Impossible de copier {}: {}", entry.path().display(), e))?;
+    }
+    std::fs::remove_dir_all(from).map_err(|e| format!("Impossible de supprimer {}: {}", from.display(), e))
+}
+
+/// True for destinations that point at a local file rather than a URL or an
+/// anchor.
+pub fn is_local_link(url: &str) -> bool {
+    !(url.is_empty()
+        || url.starts_with('#')
+        || url.starts_with("data:")
+        || url.starts_with("mailto:")
+        || (url.contains("://") && !url.starts_with("file://")))
+}
+
+/// Resolves a local link destination against the document's directory.
+pub fn resolve_link(url: &str, base_dir: Option<&Path>) -> PathBuf {
+    let url = url.strip_prefix("file://").unwrap_or(url);
+    let url = url.split(['#', '?']).next().unwrap_or(url);
+    let decoded = percent_encoding::percent_decode_str(url).decode_utf8_lossy().to_string();
+    let path = PathBuf::from(decoded);
+    match base_dir {
+        Some(base) if path.is_relative() => normalize(&base.join(path)),
+        _ => normalize(&path),
+    }
+}
+
+/// The file a local link of an export points to: relative links are looked
+/// up in `resource_dirs` in order, as pandoc does.
+pub fn resolve_resource(url: &str, resource_dirs: &[PathBuf]) -> Option<PathBuf> {
+    let path = resolve_link(url, None);
+    if path.is_relative() {
+        resource_dirs.iter().map(|dir| normalize(&dir.join(&path))).find(|p| p.is_file())
+    } else {
+        Some(path)
+    }
+}
+
+/// Lexically removes `.` and `..` components.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Link destination for `target` as written in a document living in
+/// `base_dir`: relative with forward slashes, or absolute when there is no
+/// base. Wrapped in angle brackets when it contains spaces.
+pub fn link_to(target: &Path, base_dir: Option<&Path>) -> String {
+    let path = base_dir
+        .and_then(|base| pathdiff::diff_paths(target, base))
+        .unwrap_or_else(|| target.to_path_buf());
+    bracketed(path.to_string_lossy().replace('\\', "/"))
+}
+
+fn bracketed(link: String) -> String {
+    if link.contains(' ') || link.contains('(') || link.contains(')') {
+        format!("<{}>", link)
+    } else {
+        link
+    }
+}
+
+/// Link to a file of the document's assets folder: its URL for pages of a
+/// static site, which serves the folder elsewhere, relative otherwise.
+pub fn document_link(target: &Path, document_path: Option<&str>) -> String {
+    let document_path = document_path.map(Path::new);
+    if let Some(site) = document_path.and_then(crate::static_site::assets_location) {
+        if let Ok(relative) = target.strip_prefix(&site.dir) {
+            return bracketed(format!("{}/{}", site.url, relative.to_string_lossy().replace('\\', "/")));
+        }
+    }
+    link_to(target, document_path.and_then(Path::parent))
+}
+
+/// Rewrites the destination at `link` in place, dropping any angle brackets
+/// around it so `link_to` can add them back when needed.
+pub fn replacement(md: &str, link: &markdown::LinkRef, new_url: String) -> (usize, usize, String) {
+    let bytes = md.as_bytes();
+    let bracketed = link.start > 0 && bytes[link.start - 1] == b'<' && bytes.get(link.end) == Some(&b'>');
+    if bracketed {
+        (link.start - 1, link.end + 1, new_url)
+    } else {
+        (link.start, link.end, new_url)
+    }
+}
+
+/// Rewrites links into `extract_dir` (where pandoc dumped media) so they point
+/// at the same files from the document's point of view.
+pub fn rewrite_extracted_links(md: &str, extract_dir: &Path, document_path: Option<&Path>) -> String {
+    let document_dir = document_path.and_then(Path::parent);
+    let replacements = markdown::links(md)
+        .iter()
+        .filter(|link| is_local_link(link.url(md)))
+        .filter_map(|link| {
+            let target = resolve_link(link.url(md), None);
+            target.starts_with(extract_dir).then(|| replacement(md, link, link_to(&target, document_dir)))
+        })
+        .collect();
+    markdown::replace_ranges(md, replacements)
+}
+
+/// Rewrites links to files under `from_dir` so they point at the same files
+/// under `to_dir`, after that folder was moved.
+pub fn relocate_links(md: &str, document_dir: Option<&Path>, from_dir: &Path, to_dir: &Path) -> String {
+    let replacements = markdown::links(md)
+        .iter()
+        .filter(|link| is_local_link(link.url(md)))
+        .filter_map(|link| {
+            let target = resolve_link(link.url(md), document_dir);
+            let rest = target.strip_prefix(from_dir).ok()?;
+            Some(replacement(md, link, link_to(&to_dir.join(rest), document_dir)))
+        })
+        .collect();
+    markdown::replace_ranges(md, replacements)
+}
+
+/// Moves or copies the assets a document uses into the assets folder of its
+/// new location and rewrites the links. Files coming from unsaved-document
+/// folders are moved; files from another document's assets folder are copied,
+/// since that document still exists after a "save as". Other relative links
+/// are rewritten so they still resolve from the new location.
+///
+/// Relative links of unsaved documents are resolved against the working
+/// directory, which is where pandoc used to drop extracted media.
+pub fn migrate(app: &AppHandle, md: &str, old_document_path: Option<&Path>, new_document_path: &Path) -> Result<String, String> {
+    let old_dir = match old_document_path.and_then(Path::parent) {
+        Some(dir) => Some(dir.to_path_buf()),
+        None => std::env::current_dir().ok(),
+    };
+    let new_dir = new_document_path.parent();
+    let new_assets = assets_dir_for(new_document_path);
+    let unsaved_root = unsaved_assets_root(app)?;
+    let old_assets = old_document_path.map(assets_dir_for);
+
+    let mut replacements = Vec::new();
+    for link in markdown::links(md) {
+        let url = link.url(md);
+        if !is_local_link(url) {
+            continue;
+        }
+        let target = resolve_link(url, old_dir.as_deref());
+        if !target.exists() || (target.starts_with(&new_assets) && old_dir.as_deref() == new_dir) {
+            continue;
+        }
+
+        let fragment = url.find('#').filter(|_| !link.is_image).map(|i| &url[i..]).unwrap_or("");
+        let from_unsaved = target.starts_with(&unsaved_root);
+        let from_old_assets = old_assets.as_ref().is_some_and(|dir| target.starts_with(dir));
+        if !target.is_file() || (!from_unsaved && !from_old_assets) {
+            let is_relative = !url.starts_with("file://") && Path::new(url).is_relative();
+            let new_url = format!("{}{}", link_to(&target, new_dir), fragment);
+            if is_relative && new_url != url {
+                replacements.push(replacement(md, &link, new_url));
+            }
+            continue;
+        }
+
+        std::fs::create_dir_all(&new_assets)
+            .map_err(|e| format!("Impossible de créer le dossier {}: {}", new_assets.display(), e))?;
+        let file_name = target.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let dest = unique_path(&new_assets, &file_name);
+        if from_unsaved {
+            move_file(&target, &dest)?;
+        } else {
+            std::fs::copy(&target, &dest)
+                .map_err(|e| format!("Impossible de copier {}: {}", target.display(), e))?;
+        }
+        replacements.push(replacement(md, &link, format!("{}{}", link_to(&dest, new_dir), fragment)));
+    }
+
+    Ok(markdown::replace_ranges(md, replacements))
+}
+
+/// Called when a document is saved to a new location: brings its assets
+/// along, fixes the remaining relative links and returns the updated markdown.
+#[tauri::command]
+pub fn migrate_document_assets(app: AppHandle, markdown: &str, old_document_path: Option<&str>, new_document_path: &str) -> Result<String, String> {
+    let old_document_path = scopes::check_optional(&app, old_document_path)?;
+    let new_document_path = scopes::check(&app, new_document_path)?;
+    migrate(&app, markdown, old_document_path.as_deref(), &new_document_path)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentMove {
+    pub new_path: String,
+    /// Asset files moved along with the document.
+    pub moved_assets: Vec<String>,
+    /// Links to the document updated in other notes of the workspace.
+    pub links_updated: usize,
+    pub files_updated: Vec<String>,
+}
+
+/// Moves a document to another folder. The files it links to that no other
+/// note uses move into its new assets folder; shared files stay where they
+/// are and the links to them are rewritten. Outside a workspace only the
+/// document's own assets folder counts as unshared.
+#[tauri::command]
+pub fn move_document(app: AppHandle, path: &str, dest_dir: &str) -> Result<DocumentMove, String> {
+    let old = scopes::check(&app, path)?;
+    let dest_dir = scopes::check(&app, dest_dir)?;
+    let name = old.file_name().ok_or("Chemin invalide")?;
+    if !old.is_file() || !dest_dir.is_dir() {
+        return Err(format!("Impossible de déplacer {} vers {}", old.display(), dest_dir.display()));
+    }
+    let new = dest_dir.join(name);
+    if new.exists() {
+        return Err(format!("{} existe déjà", new.display()));
+    }
+    let (md, format) = crate::safe_save::read_text(&old)?;
+
+    let root = app.state::<crate::workspace::Workspace>().root().ok().filter(|root| old.starts_with(root) && new.starts_with(root));
+    let shared = root.as_ref().map(|root| {
+        let others: Vec<PathBuf> = crate::workspace::markdown_files(root).into_iter().filter(|p| *p != old).collect();
+        referenced_files(&others)
+    });
+    let sources = match &root {
+        Some(_) => crate::backlinks::linking_notes(&app, &old)?,
+        None => Vec::new(),
+    };
+    crate::locks::ensure_all_writable(&app, sources.iter().map(PathBuf::as_path).chain([old.as_path()]))?;
+
+    move_file(&old, &new)?;
+    if let Some(root) = &root {
+        crate::favorites::on_moved(root, &old, &new)?;
+    }
+    let (old_dir, new_dir) = (old.parent(), new.parent());
+    let (old_assets, new_assets) = (assets_dir_for(&old), assets_dir_for(&new));
+    let mut moved: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut replacements = Vec::new();
+    for link in markdown::links(&md) {
+        let url = link.url(&md);
+        if !is_local_link(url) {
+            continue;
+        }
+        let target = resolve_link(url, old_dir);
+        let exclusive = match &shared {
+            Some(shared) => !shared.contains(&target),
+            None => target.starts_with(&old_assets),
+        };
+        let mut new_target = target.clone();
+        if target == old {
+            new_target = new.clone();
+        } else if let Some(dest) = moved.get(&target) {
+            new_target = dest.clone();
+        } else if exclusive && target.is_file() && !markdown::is_markdown_file(&target) {
+            let dest = match target.strip_prefix(&old_assets) {
+                Ok(rest) => new_assets.join(rest),
+                Err(_) => unique_path(&new_assets, &target.file_name().unwrap_or_default().to_string_lossy()),
+            };
+            move_path(&target, &dest)?;
+            moved.insert(target.clone(), dest.clone());
+            new_target = dest;
+        }
+
+        let is_relative = !url.starts_with("file://") && Path::new(url).is_relative();
+        if is_relative || new_target != target {
+            let fragment = url.find('#').filter(|_| !link.is_image).map(|i| &url[i..]).unwrap_or("");
+            let base = if is_relative { new_dir } else { None };
+            let new_url = format!("{}{}", link_to(&new_target, base), fragment);
+            if new_url != url {
+                replacements.push(replacement(&md, &link, new_url));
+            }
+        }
+    }
+    let updated = markdown::replace_ranges(&md, replacements);
+    if updated != md {
+        crate::safe_save::write_text(&app, &new, &updated, &format)?;
+    }
+    if std::fs::read_dir(&old_assets).is_ok_and(|mut d| d.next().is_none()) {
+        let _ = std::fs::remove_dir(&old_assets);
+    }
+
+    let (links_updated, files_updated) = match &root {
+        Some(root) => crate::backlinks::relink(&app, &sources, &old, &new, root)?,
+        None => (0, Vec::new()),
+    };
+    let mut moved_assets: Vec<String> = moved.into_values().map(|p| p.to_string_lossy().to_string()).collect();
+    moved_assets.sort();
+    Ok(DocumentMove { new_path: new.to_string_lossy().to_string(), moved_assets, links_updated, files_updated })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadedImage {
+    pub url: String,
+    pub path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadFailure {
+    pub url: String,
+    pub error: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizeReport {
+    pub markdown: String,
+    pub downloaded: Vec<DownloadedImage>,
+    pub failures: Vec<DownloadFailure>,
+}
+
+/// File name for a downloaded image, from the URL path or the content type.
+fn remote_file_name(url: &str, content_type: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let last = path.rsplit('/').next().unwrap_or("");
+    let decoded = percent_encoding::percent_decode_str(last).decode_utf8_lossy();
+    let mut name: String = decoded
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '-' })
+        .collect();
+    if name.trim_matches(['-', '.']).is_empty() {
+        name = "image".to_string();
+    }
+    if Path::new(&name).extension().is_none() {
+        let ext = match content_type.split(';').next().unwrap_or("").trim() {
+            "image/jpeg" => "jpg",
+            "image/gif" => "gif",
+            "image/webp" => "webp",
+            "image/svg+xml" => "svg",
+            _ => "png",
+        };
+        name = format!("{}.{}", name, ext);
+    }
+    name
+}
+
+fn download_image(agent: &ureq::Agent, url: &str, dir: &Path) -> Result<PathBuf, String> {
+    let response = agent.get(url).call().map_err(crate::http::error_message)?;
+    let content_type = response.content_type().to_string();
+    if !content_type.starts_with("image/") && content_type != "application/octet-stream" {
+        return Err(format!("Le contenu n'est pas une image ({})", content_type));
+    }
+    let mut bytes = Vec::new();
+    response.into_reader()
+        .take(MAX_REMOTE_IMAGE_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Erreur pendant le téléchargement: {}", e))?;
+    if bytes.len() as u64 > MAX_REMOTE_IMAGE_BYTES {
+        return Err("Image trop volumineuse".to_string());
+    }
+
+    // Downloads run in parallel, so claim the file name atomically
+    let name = remote_file_name(url, &content_type);
+    let (mut file, dest) = loop {
+        let dest = unique_path(dir, &name);
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&dest) {
+            Ok(file) => break (file, dest),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Impossible de créer {}: {}", dest.display(), e)),
+        }
+    };
+    file.write_all(&bytes)
+        .map_err(|e| format!("Impossible d'enregistrer {}: {}", dest.display(), e))?;
+    Ok(dest)
+}
+
+/// Downloads the document's `http(s)` images into `assets_dir` (a few at a
+/// time, each with a timeout) and rewrites their links to the local copies.
+/// Images that fail to download keep their remote URL and are reported.
+pub fn localize_remote(md: &str, assets_dir: &Path, document_path: Option<&Path>) -> Result<LocalizeReport, String> {
+    std::fs::create_dir_all(assets_dir)
+        .map_err(|e| format!("Impossible de créer le dossier {}: {}", assets_dir.display(), e))?;
+    let links: Vec<markdown::LinkRef> = markdown::links(md)
+        .into_iter()
+        .filter(|l| l.is_image && (l.url(md).starts_with("http://") || l.url(md).starts_with("https://")))
+        .collect();
+
+    let mut urls: Vec<&str> = links.iter().map(|l| l.url(md)).collect();
+    urls.sort_unstable();
+    urls.dedup();
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(10))
+        .timeout(REMOTE_IMAGE_TIMEOUT)
+        .build();
+    let mut results: HashMap<&str, Result<PathBuf, String>> = HashMap::new();
+    for batch in urls.chunks(MAX_CONCURRENT_DOWNLOADS) {
+        let batch_results: Vec<(&str, Result<PathBuf, String>)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|&url| {
+                    let agent = &agent;
+                    (url, scope.spawn(move || download_image(agent, url, assets_dir)))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|(url, h)| (url, h.join().unwrap_or_else(|_| Err("Téléchargement interrompu".to_string()))))
+                .collect()
+        });
+        results.extend(batch_results);
+    }
+
+    let document_dir = document_path.and_then(Path::parent);
+    let replacements = links
+        .iter()
+        .filter_map(|link| match results.get(link.url(md)) {
+            Some(Ok(path)) => Some(replacement(md, link, link_to(path, document_dir))),
+            _ => None,
+        })
+        .collect();
+
+    let mut downloaded = Vec::new();
+    let mut failures = Vec::new();
+    for (url, result) in results {
+        match result {
+            Ok(path) => downloaded.push(DownloadedImage { url: url.to_string(), path: path.to_string_lossy().to_string() }),
+            Err(error) => failures.push(DownloadFailure { url: url.to_string(), error }),
+        }
+    }
+
+    Ok(LocalizeReport {
+        markdown: markdown::replace_ranges(md, replacements),
+        downloaded,
+        failures,
+    })
+}
+
+#[tauri::command]
+pub async fn localize_remote_images(app: AppHandle, markdown: String, assets_dir: String, document_path: Option<String>) -> Result<LocalizeReport, String> {
+    let assets_dir = scopes::check(&app, &assets_dir)?;
+    let document_path = scopes::check_optional(&app, document_path.as_deref())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        localize_remote(&markdown, &assets_dir, document_path.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Erreur lors du téléchargement des images: {}", e))?
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnusedAsset {
+    pub path: String,
+    pub size: u64,
+}
+
+fn is_assets_dir(path: &Path) -> bool {
+    path.is_dir() && path.file_name().is_some_and(|n| n.to_string_lossy().ends_with(ASSETS_SUFFIX))
+}
+
+/// Local files referenced by the given markdown documents.
+pub fn referenced_files(documents: &[PathBuf]) -> HashSet<PathBuf> {
+    let mut referenced = HashSet::new();
+    for doc in documents {
+        // In whatever encoding the note was saved
+        let Ok((md, _)) = crate::safe_save::read_text(doc) else { continue };
+        let dir = doc.parent();
+        for link in markdown::links(&md) {
+            let url = link.url(&md);
+            if is_local_link(url) {
+                referenced.insert(resolve_link(url, dir));
+            }
+        }
+    }
+    referenced
+}
+
+/// Lists files inside assets folders that no document links to. `path` is a
+/// single document (only its own assets folder is checked) or a workspace
+/// folder (every assets folder below it, against every document below it).
+pub fn find_unused(path: &Path) -> Result<Vec<UnusedAsset>, String> {
+    let (documents, assets_dirs): (Vec<PathBuf>, Vec<PathBuf>) = if path.is_dir() {
+        let mut documents = Vec::new();
+        let mut assets_dirs = Vec::new();
+        for entry in walkdir::WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
+            let p = entry.path();
+            if is_assets_dir(p) {
+                assets_dirs.push(p.to_path_buf());
+            } else if entry.file_type().is_file() && markdown::is_markdown_file(p) {
+                documents.push(p.to_path_buf());
+            }
+        }
+        (documents, assets_dirs)
+    } else if path.is_file() {
+        (vec![path.to_path_buf()], vec![assets_dir_for(path)])
+    } else {
+        return Err(format!("Chemin introuvable: {}", path.display()));
+    };
+
+    let referenced = referenced_files(&documents);
+    let mut unused = Vec::new();
+    for dir in assets_dirs.iter().filter(|d| d.is_dir()) {
+        for entry in walkdir::WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let file = normalize(entry.path());
+            if !referenced.contains(&file) {
+                unused.push(UnusedAsset {
+                    size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                    path: file.to_string_lossy().to_string(),
+                });
+            }
+        }
+    }
+    unused.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(unused)
+}
+
+#[tauri::command]
+pub fn find_unused_assets(app: AppHandle, path: &str) -> Result<Vec<UnusedAsset>, String> {
+    find_unused(&scopes::check(&app, path)?)
+}
+
+/// Moves the given asset files to the trash and returns the trash entry's
+/// folder.
+#[tauri::command]
+pub fn trash_assets(app: AppHandle, paths: Vec<String>) -> Result<String, String> {
+    let paths = paths.iter().map(|path| scopes::check(&app, path)).collect::<Result<Vec<PathBuf>, _>>()?;
+    Ok(crate::trash::move_to_trash(&app, &paths)?.to_string_lossy().to_string())
+}
+
+/// Assets folder of a document, created if needed.
+#[tauri::command]
+pub fn get_assets_dir(app: AppHandle, document_path: Option<&str>) -> Result<String, String> {
+    Ok(target_assets_dir(&app, document_path)?.to_string_lossy().to_string())
+}
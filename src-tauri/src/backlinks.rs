@@ -0,0 +1,333 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::{assets, locks, markdown, safe_save, watcher, workspace};
+
+const MAX_CONTEXT_CHARS: usize = 200;
+
+/// Where a link points, kept unresolved so that wikilinks follow notes being
+/// created or deleted.
+enum Target {
+    /// Lowercased wikilink target without extension: "note" or "folder/note".
+    Wiki(String),
+    File(PathBuf),
+}
+
+struct OutLink {
+    target: Target,
+    line: usize,
+    offset: usize,
+    length: usize,
+    context: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backlink {
+    pub source: String,
+    pub line: usize,
+    /// UTF-16 range of the link target in the source note.
+    pub offset: usize,
+    pub length: usize,
+    /// The line the link appears on.
+    pub context: String,
+}
+
+/// Outgoing links of every note of the workspace, built on first use and
+/// kept current from saves and watcher events.
+#[derive(Default)]
+pub struct BacklinkIndex {
+    root: Mutex<Option<PathBuf>>,
+    notes: Mutex<HashMap<PathBuf, Vec<OutLink>>>,
+}
+
+fn wiki_key(target: &str) -> String {
+    let target = target.trim().replace('\\', "/").to_lowercase();
+    match target.rsplit_once('.') {
+        Some((stem, ext)) if markdown::MARKDOWN_EXTENSIONS.contains(&ext) => stem.to_string(),
+        _ => target,
+    }
+}
+
+fn out_link(md: &str, target: Target, start: usize, end: usize) -> OutLink {
+    let line_start = md[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = md[start..].find('\n').map(|i| start + i).unwrap_or(md.len());
+    let offset = markdown::utf16_offset(md, start);
+    OutLink {
+        target,
+        line: markdown::line_at(md, start),
+        offset,
+        length: markdown::utf16_offset(md, end) - offset,
+        context: md[line_start..line_end].trim().chars().take(MAX_CONTEXT_CHARS).collect(),
+    }
+}
+
+fn note_links(path: &Path, md: &str) -> Vec<OutLink> {
+    let dir = path.parent();
+    let mut links: Vec<OutLink> = markdown::wikilinks(md)
+        .into_iter()
+        .map(|link| out_link(md, Target::Wiki(wiki_key(link.target(md))), link.start, link.end))
+        .collect();
+    for link in markdown::links(md).into_iter().filter(|l| !l.is_image) {
+        let url = link.url(md);
+        if !assets::is_local_link(url) {
+            continue;
+        }
+        let target = assets::resolve_link(url, dir);
+        if markdown::is_markdown_file(&target) {
+            links.push(out_link(md, Target::File(target), link.start, link.end));
+        }
+    }
+    links
+}
+
+fn read_note(path: &Path) -> Option<Vec<OutLink>> {
+    let md = std::fs::read_to_string(path).ok()?;
+    Some(note_links(path, &md))
+}
+
+/// Whether a link resolves to `note`. Wikilinks match the file name, or the
+/// end of the path relative to the workspace for "folder/note" targets.
+fn points_to(target: &Target, note: &Path, root: &Path) -> bool {
+    match target {
+        Target::File(path) => path == note,
+        Target::Wiki(key) if key.contains('/') => {
+            let relative = note.strip_prefix(root).unwrap_or(note).with_extension("");
+            let relative = relative.to_string_lossy().replace('\\', "/").to_lowercase();
+            relative == *key || relative.ends_with(&format!("/{}", key))
+        }
+        Target::Wiki(key) => note.file_stem().is_some_and(|s| s.to_string_lossy().to_lowercase() == *key),
+    }
+}
+
+/// Whether the wikilink target `target` ("note", "folder/note.md") resolves
+/// to `note`.
+pub fn wikilink_points_to(target: &str, note: &Path, root: &Path) -> bool {
+    points_to(&Target::Wiki(wiki_key(target)), note, root)
+}
+
+impl BacklinkIndex {
+    fn ensure(&self, root: &Path) {
+        let mut indexed_root = self.root.lock().unwrap();
+        if indexed_root.as_deref() != Some(root) {
+            *self.notes.lock().unwrap() = workspace::markdown_files(root)
+                .into_iter()
+                .filter_map(|path| Some((path.clone(), read_note(&path)?)))
+                .collect();
+            *indexed_root = Some(root.to_path_buf());
+        }
+    }
+
+    fn update(&self, path: &Path) {
+        let Some(root) = self.root.lock().unwrap().clone() else { return };
+        let mut notes = self.notes.lock().unwrap();
+        notes.remove(path);
+        if path.starts_with(&root) && markdown::is_markdown_file(path) {
+            if let Some(links) = read_note(path) {
+                notes.insert(path.to_path_buf(), links);
+            }
+        }
+    }
+}
+
+/// Links pointing at `note` from other notes of the workspace.
+pub fn backlinks(app: &AppHandle, note: &Path) -> Result<Vec<Backlink>, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    let index = app.state::<BacklinkIndex>();
+    index.ensure(&root);
+    let note = assets::normalize(note);
+
+    let notes = index.notes.lock().unwrap();
+    let mut found: Vec<Backlink> = notes
+        .iter()
+        .filter(|(source, _)| **source != note)
+        .flat_map(|(source, links)| {
+            links.iter().filter(|l| points_to(&l.target, &note, &root)).map(move |l| Backlink {
+                source: source.to_string_lossy().to_string(),
+                line: l.line,
+                offset: l.offset,
+                length: l.length,
+                context: l.context.clone(),
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| a.source.cmp(&b.source).then(a.line.cmp(&b.line)));
+    Ok(found)
+}
+
+/// Every link between two notes of the workspace, as `(source, target)`
+/// pairs, once per occurrence.
+pub fn resolved_links(app: &AppHandle) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    let index = app.state::<BacklinkIndex>();
+    index.ensure(&root);
+    let notes = index.notes.lock().unwrap();
+
+    // Wikilinks are matched against the notes sharing their last segment
+    let mut by_stem: HashMap<String, Vec<&PathBuf>> = HashMap::new();
+    for path in notes.keys() {
+        if let Some(stem) = path.file_stem() {
+            by_stem.entry(stem.to_string_lossy().to_lowercase()).or_default().push(path);
+        }
+    }
+    let mut resolved = Vec::new();
+    for (source, links) in notes.iter() {
+        for link in links {
+            let targets: Vec<&PathBuf> = match &link.target {
+                Target::File(path) => notes.get_key_value(path).map(|(k, _)| k).into_iter().collect(),
+                Target::Wiki(key) => {
+                    let stem = key.rsplit('/').next().unwrap_or(key);
+                    by_stem.get(stem).into_iter().flatten().copied().filter(|n| points_to(&link.target, n, &root)).collect()
+                }
+            };
+            resolved.extend(targets.into_iter().filter(|t| *t != source).map(|t| (source.clone(), t.clone())));
+        }
+    }
+    Ok(resolved)
+}
+
+/// Rewrites the links of `md` (the content of `source`) that point at `old`
+/// so they point at `new`, keeping each link's style: bare or "folder/note"
+/// wikilinks, relative or absolute paths, and `#heading` fragments. Returns
+/// the new content and the number of links changed.
+fn rewrite_links(md: &str, source: &Path, old: &Path, new: &Path, root: &Path) -> (String, usize) {
+    let mut replacements = Vec::new();
+    for link in markdown::wikilinks(md) {
+        let target = link.target(md);
+        if !points_to(&Target::Wiki(wiki_key(target)), old, root) {
+            continue;
+        }
+        let with_ext = markdown::is_markdown_file(Path::new(target.trim()));
+        let new_target = if target.contains(['/', '\\']) {
+            new.strip_prefix(root).unwrap_or(new)
+        } else {
+            Path::new(new.file_name().unwrap_or_default())
+        };
+        let new_target = if with_ext { new_target.to_path_buf() } else { new_target.with_extension("") };
+        replacements.push((link.start, link.end, new_target.to_string_lossy().replace('\\', "/")));
+    }
+    for link in markdown::links(md).iter().filter(|l| !l.is_image) {
+        let url = link.url(md);
+        if !assets::is_local_link(url) || assets::resolve_link(url, source.parent()) != old {
+            continue;
+        }
+        let new_url = if url.starts_with("file://") || Path::new(url).is_absolute() {
+            assets::link_to(new, None)
+        } else {
+            assets::link_to(new, source.parent())
+        };
+        let fragment = url.find('#').map(|i| &url[i..]).unwrap_or("");
+        replacements.push(assets::replacement(md, link, format!("{}{}", new_url, fragment)));
+    }
+    let count = replacements.len();
+    (markdown::replace_ranges(md, replacements), count)
+}
+
+/// Called by the watcher to keep the index in step with the files on disk.
+pub fn on_changes(app: &AppHandle, changes: &[watcher::FileChange]) {
+    let index = app.state::<BacklinkIndex>();
+    for change in changes {
+        index.update(Path::new(&change.path));
+        if let Some(new_path) = &change.new_path {
+            index.update(Path::new(new_path));
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_backlinks(app: AppHandle, path: &str) -> Result<Vec<Backlink>, String> {
+    backlinks(&app, Path::new(path))
+}
+
+/// Other notes linking to `note`, to pass to `relink` once it has moved.
+pub fn linking_notes(app: &AppHandle, note: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut sources: Vec<PathBuf> = backlinks(app, note)?.into_iter().map(|b| PathBuf::from(b.source)).collect();
+    sources.dedup();
+    Ok(sources)
+}
+
+/// Points the links of `sources` to the note moved from `old` to `new`.
+/// Returns the number of links changed and the notes rewritten.
+pub fn relink(app: &AppHandle, sources: &[PathBuf], old: &Path, new: &Path, root: &Path) -> Result<(usize, Vec<String>), String> {
+    let index = app.state::<BacklinkIndex>();
+    let mut links_updated = 0;
+    let mut files_updated = Vec::new();
+    for source in sources {
+        let Ok((md, format)) = safe_save::read_text(source) else { continue };
+        let (updated, count) = rewrite_links(&md, source, old, new, root);
+        if count > 0 {
+            safe_save::write_text(app, source, &updated, &format)?;
+            files_updated.push(source.to_string_lossy().to_string());
+            links_updated += count;
+        }
+        index.update(source);
+    }
+    Ok((links_updated, files_updated))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteRename {
+    pub new_path: String,
+    pub links_updated: usize,
+    /// Notes whose content was rewritten.
+    pub files_updated: Vec<String>,
+}
+
+/// Renames a note in place and rewrites the links pointing at it across the
+/// workspace. The note's assets folder follows the new name.
+#[tauri::command]
+pub fn rename_note(app: AppHandle, path: &str, new_name: &str) -> Result<NoteRename, String> {
+    if new_name.is_empty() || new_name.contains(['/', '\\']) || new_name.starts_with('.') {
+        return Err(format!("Nom invalide: {}", new_name));
+    }
+    let root = app.state::<workspace::Workspace>().root()?;
+    let old = assets::normalize(Path::new(path));
+    if !old.starts_with(&root) || !old.is_file() {
+        return Err(format!("Note introuvable dans le dossier de travail: {}", path));
+    }
+    let new = if markdown::is_markdown_file(Path::new(new_name)) {
+        old.with_file_name(new_name)
+    } else {
+        old.with_file_name(format!("{}.{}", new_name, old.extension().unwrap_or_default().to_string_lossy()))
+    };
+    if new.exists() {
+        return Err(format!("{} existe déjà", new.display()));
+    }
+
+    let sources = linking_notes(&app, &old)?;
+    locks::ensure_all_writable(&app, sources.iter().map(PathBuf::as_path).chain([old.as_path()]))?;
+    std::fs::rename(&old, &new).map_err(|e| format!("Impossible de renommer {}: {}", old.display(), e))?;
+    crate::favorites::on_moved(&root, &old, &new)?;
+
+    let (old_assets, new_assets) = (assets::assets_dir_for(&old), assets::assets_dir_for(&new));
+    let assets_moved = old_assets.is_dir() && !new_assets.exists() && std::fs::rename(&old_assets, &new_assets).is_ok();
+
+    let (mut links_updated, mut files_updated) = relink(&app, &sources, &old, &new, &root)?;
+    // The note may link to itself, and to its renamed assets folder
+    if let Ok((md, format)) = safe_save::read_text(&new) {
+        let (mut updated, count) = rewrite_links(&md, &new, &old, &new, &root);
+        if assets_moved {
+            updated = assets::relocate_links(&updated, new.parent(), &old_assets, &new_assets);
+        }
+        if updated != md {
+            safe_save::write_text(&app, &new, &updated, &format)?;
+            files_updated.push(new.to_string_lossy().to_string());
+        }
+        links_updated += count;
+    }
+    let index = app.state::<BacklinkIndex>();
+    index.update(&old);
+    index.update(&new);
+    Ok(NoteRename { new_path: new.to_string_lossy().to_string(), links_updated, files_updated })
+}
+
+/// Re-reads a note's links after it was saved.
+#[tauri::command]
+pub fn update_backlinks(app: AppHandle, path: &str) -> Result<(), String> {
+    app.state::<BacklinkIndex>().update(&crate::scopes::check(&app, path)?);
+    Ok(())
+}
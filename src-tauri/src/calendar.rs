@@ -0,0 +1,202 @@
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+use crate::{markdown, scopes, workspace};
+
+/// Signifiers of the Obsidian Tasks format, which puts its metadata after the
+/// description.
+const TASK_SIGNIFIERS: &[char] = &['📅', '⏳', '🛫', '✅', '➕', '❌', '🔁', '⏫', '🔼', '🔽', '🔺', '⏬', '🆔', '⛔'];
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DueTask {
+    pub path: String,
+    /// 1-based.
+    pub line: usize,
+    pub text: String,
+    /// YYYY-MM-DD.
+    pub due: String,
+    pub done: bool,
+}
+
+fn date_at(text: &str) -> Option<(NaiveDate, usize)> {
+    let trimmed = text.trim_start();
+    let skipped = text.len() - trimmed.len();
+    let date = NaiveDate::parse_from_str(trimmed.get(..10)?, "%Y-%m-%d").ok()?;
+    Some((date, skipped + 10))
+}
+
+/// The `due:2024-06-01` or Dataview `[due:: 2024-06-01]` annotation of a task,
+/// with the byte range to drop from its description.
+fn due_field(text: &str) -> Option<(NaiveDate, usize, usize)> {
+    for (start, _) in text.match_indices("due:") {
+        let before = text[..start].chars().next_back();
+        if before.is_some_and(|c| !c.is_whitespace() && c != '[' && c != '(') {
+            continue;
+        }
+        let rest = &text[start + 4..];
+        let rest_start = start + 4 + usize::from(rest.starts_with(':'));
+        let Some((date, len)) = date_at(&text[rest_start..]) else {
+            continue;
+        };
+        let mut end = rest_start + len;
+        let mut start = start;
+        if before.is_some_and(|c| c == '[' || c == '(') && text[end..].starts_with([']', ')']) {
+            start -= 1;
+            end += 1;
+        }
+        return Some((date, start, end));
+    }
+    None
+}
+
+/// A task item (`- [ ] …`) and its checked state.
+fn task_item(line: &str) -> Option<(bool, &str)> {
+    let line = line.trim_start();
+    let rest = match line.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None => {
+            let digits = line.find(|c: char| !c.is_ascii_digit()).filter(|&n| n > 0)?;
+            line[digits..].strip_prefix(['.', ')'])?
+        }
+    };
+    let rest = rest.strip_prefix(' ')?.trim_start();
+    let done = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    Some((done, rest[3..].trim()))
+}
+
+fn parse_task(line: &str) -> Option<(bool, String, NaiveDate)> {
+    let (done, text) = task_item(line)?;
+    let (due, description) = match text.find('📅').and_then(|i| date_at(&text[i + '📅'.len_utf8()..])) {
+        Some((date, _)) => (date, text.to_string()),
+        None => {
+            let (date, start, end) = due_field(text)?;
+            (date, format!("{}{}", &text[..start], &text[end..]))
+        }
+    };
+    let description = match description.find(TASK_SIGNIFIERS) {
+        Some(i) => &description[..i],
+        None => &description,
+    };
+    let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some((done, description, due))
+}
+
+/// Tasks of the workspace annotated with a due date, soonest first.
+pub fn due_tasks(root: &Path) -> Vec<DueTask> {
+    let mut tasks = Vec::new();
+    for path in workspace::markdown_files(root) {
+        let Ok(md) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        let mut fences = markdown::FenceState::default();
+        // Skip the frontmatter without shifting line numbers
+        let frontmatter_lines = md[..markdown::frontmatter_len(&md)].lines().count();
+        for (i, line) in md.lines().enumerate().skip(frontmatter_lines) {
+            if fences.is_code(line) {
+                continue;
+            }
+            if let Some((done, text, due)) = parse_task(line) {
+                tasks.push(DueTask { path: relative.clone(), line: i + 1, text, due: due.format("%Y-%m-%d").to_string(), done });
+            }
+        }
+    }
+    tasks.sort_by(|a, b| a.due.cmp(&b.due).then_with(|| a.path.cmp(&b.path)).then(a.line.cmp(&b.line)));
+    tasks
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace(';', "\\;").replace(',', "\\,").replace('\n', "\\n")
+}
+
+/// Content lines are folded at 75 octets, continuations starting with a space.
+fn fold(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// An iCalendar with an all-day event on the due date of each task. Events
+/// rather than to-dos, since most calendar apps ignore VTODO.
+pub fn ics(tasks: &[DueTask], name: &str, include_done: bool) -> String {
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+    for line in ["BEGIN:VCALENDAR", "VERSION:2.0", "PRODID:-//OhMyMarkdown//Tasks//FR", "CALSCALE:GREGORIAN", "METHOD:PUBLISH"] {
+        fold(line, &mut out);
+    }
+    fold(&format!("X-WR-CALNAME:{}", escape(name)), &mut out);
+    // Identical tasks in a note still need distinct UIDs
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for task in tasks.iter().filter(|t| include_done || !t.done) {
+        let Ok(due) = NaiveDate::parse_from_str(&task.due, "%Y-%m-%d") else {
+            continue;
+        };
+        let key = format!("{}\u{1f}{}", task.path, task.text);
+        let occurrence = seen.entry(key.clone()).or_default();
+        *occurrence += 1;
+        let hash = Sha256::digest(format!("{}\u{1f}{}", key, occurrence).as_bytes());
+        let uid: String = hash[..16].iter().map(|b| format!("{:02x}", b)).collect();
+        let end = due.succ_opt().unwrap_or(due);
+        let summary = if task.done { format!("✔ {}", task.text) } else { task.text.clone() };
+        fold("BEGIN:VEVENT", &mut out);
+        fold(&format!("UID:{}@ohmymarkdown", uid), &mut out);
+        fold(&format!("DTSTAMP:{}", stamp), &mut out);
+        fold(&format!("DTSTART;VALUE=DATE:{}", due.format("%Y%m%d")), &mut out);
+        fold(&format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")), &mut out);
+        fold(&format!("SUMMARY:{}", escape(&summary)), &mut out);
+        fold(&format!("DESCRIPTION:{}", escape(&format!("{}, ligne {}", task.path, task.line))), &mut out);
+        fold("TRANSP:TRANSPARENT", &mut out);
+        fold("END:VEVENT", &mut out);
+    }
+    fold("END:VCALENDAR", &mut out);
+    out
+}
+
+/// Name shown by calendar apps for the workspace at `root`.
+pub fn calendar_name(root: &Path) -> String {
+    let name = root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    format!("Tâches — {}", name)
+}
+
+/// Tasks of the workspace with a due date, written as `📅 2024-06-01`
+/// (Obsidian Tasks), `due:2024-06-01` or `[due:: 2024-06-01]`.
+#[tauri::command]
+pub async fn list_due_tasks(app: AppHandle) -> Result<Vec<DueTask>, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    tauri::async_runtime::spawn_blocking(move || due_tasks(&root))
+        .await
+        .map_err(|e| format!("Erreur lors de la recherche des tâches: {}", e))
+}
+
+/// Writes the workspace's tasks with a due date to an .ics file at `output`
+/// and returns how many events it holds. Completed tasks are left out unless
+/// `include_done` is set.
+#[tauri::command]
+pub async fn export_tasks_ics(app: AppHandle, output: String, include_done: Option<bool>) -> Result<usize, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    let output = scopes::check(&app, &output)?;
+    let include_done = include_done.unwrap_or(false);
+    tauri::async_runtime::spawn_blocking(move || {
+        let tasks = due_tasks(&root);
+        std::fs::write(&output, ics(&tasks, &calendar_name(&root), include_done)).map_err(|e| format!("Impossible d'écrire {}: {}", output.display(), e))?;
+        Ok(tasks.iter().filter(|t| include_done || !t.done).count())
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de l'export du calendrier: {}", e))?
+}
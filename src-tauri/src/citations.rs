@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::frontmatter::{self, Frontmatter};
+use crate::{crossref, http, markdown, paths};
+
+/// The official CSL style repository.
+const STYLES_URL: &str = "https://raw.githubusercontent.com/citation-style-language/styles/master";
+
+/// How an export resolves citations.
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CitationOptions {
+    /// Bibliography files, instead of the frontmatter's `bibliography`.
+    pub bibliography: Vec<String>,
+    /// Name of a downloaded style, path to a .csl file or URL, instead of the
+    /// frontmatter's `csl`.
+    pub csl: Option<String>,
+    /// Document being exported; relative paths start from its folder.
+    pub document_path: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationCheck {
+    pub citekeys: Vec<String>,
+    /// Keys no bibliography defines.
+    pub missing: Vec<String>,
+    pub bibliography: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct CslStyle {
+    pub name: String,
+    pub title: String,
+    pub path: String,
+}
+
+fn styles_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    paths::app_data_subdir(app, "csl")
+}
+
+fn resolve(path: &str, dir: Option<&Path>) -> PathBuf {
+    let path = Path::new(path.trim());
+    match dir {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Distinct citation keys of the document, in order.
+fn citekeys(md: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for citation in markdown::citations(md) {
+        let key = citation.key(md);
+        if !crossref::is_reference(key) && !keys.iter().any(|k| k == key) {
+            keys.push(key.to_string());
+        }
+    }
+    keys
+}
+
+/// Keys a bibliography defines, None for formats not read here.
+fn defined_keys(path: &Path) -> Result<Option<HashSet<String>>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Impossible de lire la bibliographie {}: {}", path.display(), e))?;
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let keys = match ext.as_str() {
+        "bib" | "bibtex" => content
+            .lines()
+            .filter_map(|line| {
+                let entry = line.trim_start().strip_prefix('@')?;
+                let (kind, rest) = entry.split_once(['{', '('])?;
+                let kind = kind.trim().to_lowercase();
+                (!matches!(kind.as_str(), "comment" | "string" | "preamble")).then(|| rest.split(',').next().unwrap_or_default().trim().to_string())
+            })
+            .collect(),
+        "json" => {
+            let items: Vec<serde_json::Value> =
+                serde_json::from_str(&content).map_err(|e| format!("Bibliographie CSL JSON invalide ({}): {}", path.display(), e))?;
+            items
+                .iter()
+                .filter_map(|item| match item.get("id")? {
+                    serde_json::Value::String(id) => Some(id.clone()),
+                    id => Some(id.to_string()),
+                })
+                .collect()
+        }
+        "yaml" | "yml" => content
+            .lines()
+            .filter_map(|line| line.trim_start().trim_start_matches("- ").strip_prefix("id:"))
+            .map(|id| id.trim().trim_matches(['"', '\'']).to_string())
+            .collect(),
+        _ => return Ok(None),
+    };
+    Ok(Some(keys))
+}
+
+/// Bibliography files of the export, resolved from the document's folder.
+fn bibliography(md: &str, options: &CitationOptions) -> Vec<PathBuf> {
+    let fields: Frontmatter = frontmatter::parse(md);
+    let dir = options.document_path.as_deref().map(Path::new).and_then(Path::parent);
+    let files = if options.bibliography.is_empty() { fields.list("bibliography") } else { options.bibliography.clone() };
+    files.iter().filter(|f| !f.trim().is_empty()).map(|f| resolve(f, dir)).collect()
+}
+
+/// Cited keys missing from every bibliography. Empty when one of them is in a
+/// format that can't be checked.
+fn missing_keys(keys: &[String], bibliography: &[PathBuf]) -> Result<Vec<String>, String> {
+    let mut defined = HashSet::new();
+    for file in bibliography {
+        match defined_keys(file)? {
+            Some(keys) => defined.extend(keys),
+            None => return Ok(Vec::new()),
+        }
+    }
+    Ok(keys.iter().filter(|k| !defined.contains(*k)).cloned().collect())
+}
+
+fn style_path(app: &AppHandle, csl: &str, dir: Option<&Path>) -> Result<String, String> {
+    let csl = csl.trim();
+    if csl.starts_with("https://") || csl.starts_with("http://") {
+        return Ok(csl.to_string());
+    }
+    let path = if csl.ends_with(".csl") || csl.contains(['/', '\\']) {
+        resolve(csl, dir)
+    } else {
+        styles_dir(app)?.join(format!("{}.csl", csl))
+    };
+    if path.is_file() {
+        Ok(path.to_string_lossy().to_string())
+    } else {
+        Err(format!("Style CSL introuvable: {} (téléchargez-le ou indiquez un fichier .csl)", csl))
+    }
+}
+
+/// Pandoc arguments for citeproc, after checking every citation resolves.
+/// Empty when the document has no bibliography; frontmatter `references`
+/// are passed through unchecked.
+pub fn pandoc_args(app: &AppHandle, md: &str, options: &CitationOptions) -> Result<Vec<String>, String> {
+    let fields: Frontmatter = frontmatter::parse(md);
+    let files = bibliography(md, options);
+    if files.is_empty() && fields.get("references").is_none() {
+        return Ok(Vec::new());
+    }
+    if let Some(file) = files.iter().find(|f| !f.is_file()) {
+        return Err(format!("Bibliographie introuvable: {}", file.display()));
+    }
+    if !files.is_empty() {
+        let missing = missing_keys(&citekeys(md), &files)?;
+        if !missing.is_empty() {
+            return Err(format!("Citations absentes de la bibliographie: {}", missing.join(", ")));
+        }
+    }
+
+    let mut args = vec!["--citeproc".to_string()];
+    args.extend(files.iter().map(|f| format!("--bibliography={}", f.display())));
+    let dir = options.document_path.as_deref().map(Path::new).and_then(Path::parent);
+    if let Some(csl) = options.csl.as_deref().or(fields.text("csl")) {
+        args.push(format!("--csl={}", style_path(app, csl, dir)?));
+    }
+    Ok(args)
+}
+
+/// The citations of a document and those its bibliography lacks, to warn
+/// before exporting.
+#[tauri::command]
+pub fn check_citations(markdown: &str, options: Option<CitationOptions>) -> Result<CitationCheck, String> {
+    let options = options.unwrap_or_default();
+    let files = bibliography(markdown, &options);
+    let citekeys = citekeys(markdown);
+    let existing: Vec<PathBuf> = files.iter().filter(|f| f.is_file()).cloned().collect();
+    let missing = if existing.is_empty() { citekeys.clone() } else { missing_keys(&citekeys, &existing)? };
+    Ok(CitationCheck {
+        citekeys,
+        missing,
+        bibliography: files.iter().map(|f| f.to_string_lossy().to_string()).collect(),
+    })
+}
+
+fn style_title(csl: &str) -> Option<String> {
+    let start = csl.find("<title>")? + "<title>".len();
+    let end = start + csl[start..].find("</title>")?;
+    Some(csl[start..end].replace("&amp;", "&").trim().to_string())
+}
+
+#[tauri::command]
+pub fn list_csl_styles(app: AppHandle) -> Result<Vec<CslStyle>, String> {
+    let entries = std::fs::read_dir(styles_dir(&app)?).map_err(|e| format!("Impossible de lire les styles CSL: {}", e))?;
+    let mut styles: Vec<CslStyle> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "csl"))
+        .map(|path| {
+            let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            let title = std::fs::read_to_string(&path).ok().and_then(|csl| style_title(&csl)).unwrap_or_else(|| name.clone());
+            CslStyle { name, title, path: path.to_string_lossy().to_string() }
+        })
+        .collect();
+    styles.sort_by_key(|s| s.title.to_lowercase());
+    Ok(styles)
+}
+
+fn fetch_style(path: &str) -> Result<Option<String>, String> {
+    match http::agent().get(&format!("{}/{}.csl", STYLES_URL, path)).call() {
+        Ok(response) => response.into_string().map(Some).map_err(|e| format!("Style CSL illisible: {}", e)),
+        Err(ureq::Error::Status(404, _)) => Ok(None),
+        Err(e) => Err(http::error_message(e)),
+    }
+}
+
+/// Downloads a style of the CSL repository by name, e.g. "apa" or
+/// "chicago-author-date", for `csl: apa` in a frontmatter.
+#[tauri::command]
+pub async fn download_csl_style(app: AppHandle, name: String) -> Result<CslStyle, String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Nom de style CSL invalide: {}", name));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let csl = match fetch_style(&name)? {
+            Some(csl) => csl,
+            None => {
+                let dependent = fetch_style(&format!("dependent/{}", name))?.ok_or_else(|| format!("Style CSL inconnu: {}", name))?;
+                // Dependent styles only name their parent, which does the formatting
+                let parent = dependent
+                    .split("<link ")
+                    .find(|link| link.contains("independent-parent"))
+                    .and_then(|link| link.split("href=\"").nth(1)?.split('"').next())
+                    .and_then(|href| href.rsplit('/').next())
+                    .ok_or_else(|| format!("Style CSL incomplet: {}", name))?
+                    .to_string();
+                fetch_style(&parent)?.ok_or_else(|| format!("Style CSL inconnu: {}", parent))?
+            }
+        };
+        let path = styles_dir(&app)?.join(format!("{}.csl", name));
+        std::fs::write(&path, &csl).map_err(|e| format!("Impossible d'enregistrer le style CSL: {}", e))?;
+        Ok(CslStyle { title: style_title(&csl).unwrap_or_else(|| name.clone()), name, path: path.to_string_lossy().to_string() })
+    })
+    .await
+    .map_err(|e| format!("Erreur lors du téléchargement du style CSL: {}", e))?
+}
+
+#[tauri::command]
+pub fn remove_csl_style(app: AppHandle, name: &str) -> Result<(), String> {
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("Nom de style CSL invalide: {}", name));
+    }
+    let path = styles_dir(&app)?.join(format!("{}.csl", name));
+    std::fs::remove_file(&path).map_err(|e| format!("Impossible de supprimer le style CSL: {}", e))
+}
@@ -0,0 +1,224 @@
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::frontmatter;
+use crate::publish::{self, Published};
+use crate::{http, pandoc};
+
+const TARGET: &str = "confluence";
+const ID_KEY: &str = "confluence_page_id";
+const VERSION_KEY: &str = "confluence_version";
+/// Stands for an attachment in the HTML until it becomes a storage macro.
+const ATTACHMENT_PREFIX: &str = "attachment:";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfluenceAccount {
+    /// "https://example.atlassian.net/wiki" for Cloud, the server's address
+    /// otherwise.
+    url: String,
+    /// Cloud only: the Atlassian account email, used with an API token.
+    email: Option<String>,
+    /// API token (Cloud) or personal access token (Server/Data Center).
+    token: String,
+}
+
+pub fn validate(account: &Value) -> Result<(), String> {
+    let account: ConfluenceAccount =
+        serde_json::from_value(account.clone()).map_err(|e| format!("Compte Confluence invalide: {}", e))?;
+    if !account.url.starts_with("https://") && !account.url.starts_with("http://") {
+        return Err(format!("Adresse Confluence invalide: {}", account.url));
+    }
+    if account.token.trim().is_empty() {
+        return Err("Jeton d'accès Confluence requis".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct Page {
+    id: String,
+    version: Version,
+    #[serde(rename = "_links")]
+    links: Links,
+}
+
+#[derive(Deserialize)]
+struct Version {
+    number: u64,
+}
+
+#[derive(Deserialize)]
+struct Links {
+    base: Option<String>,
+    webui: Option<String>,
+}
+
+struct Client {
+    agent: ureq::Agent,
+    base: String,
+    auth: String,
+}
+
+impl Client {
+    fn new(account: &ConfluenceAccount) -> Self {
+        let auth = match account.email.as_deref().filter(|e| !e.trim().is_empty()) {
+            Some(email) => {
+                let credentials = format!("{}:{}", email.trim(), account.token.trim());
+                format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(credentials))
+            }
+            None => format!("Bearer {}", account.token.trim()),
+        };
+        Client { agent: http::agent(), base: format!("{}/rest/api", account.url.trim_end_matches('/')), auth }
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        self.agent.request(method, &format!("{}/{}", self.base, path)).set("Authorization", &self.auth)
+    }
+
+    fn page(&self, response: Result<ureq::Response, ureq::Error>) -> Result<Page, String> {
+        response
+            .map_err(http::error_message)?
+            .into_json()
+            .map_err(|e| format!("Réponse inattendue de Confluence: {}", e))
+    }
+
+    /// Adds or replaces an attachment of the page.
+    fn attach(&self, page_id: &str, name: &str, file: &Path) -> Result<(), String> {
+        let bytes = std::fs::read(file).map_err(|e| format!("Impossible de lire {}: {}", file.display(), e))?;
+        let (content_type, body) = http::multipart(&[("minorEdit", "true")], "file", name, &bytes, publish::image_mime(file));
+        self.request("PUT", &format!("content/{}/child/attachment", page_id))
+            .set("X-Atlassian-Token", "no-check")
+            .set("Content-Type", &content_type)
+            .send_bytes(&body)
+            .map_err(http::error_message)?;
+        Ok(())
+    }
+}
+
+fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;")
+}
+
+/// Value of an attribute in an HTML tag.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let end = tag[start..].find('"')? + start;
+    Some(&tag[start..end])
+}
+
+/// Turns pandoc's HTML into Confluence storage format, where images are
+/// `ac:image` macros pointing at attachments or URLs.
+fn to_storage(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("<img ") {
+        let Some(len) = rest[start..].find('>') else { break };
+        let tag = &rest[start..start + len + 1];
+        out.push_str(&rest[..start]);
+        let src = attribute(tag, "src").unwrap_or_default().replace("&amp;", "&");
+        let alt = attribute(tag, "alt").map(|a| format!(" ac:alt=\"{}\"", a)).unwrap_or_default();
+        let resource = match src.strip_prefix(ATTACHMENT_PREFIX) {
+            Some(name) => format!("<ri:attachment ri:filename=\"{}\" />", escape_attribute(name)),
+            None => format!("<ri:url ri:value=\"{}\" />", escape_attribute(&src)),
+        };
+        out.push_str(&format!("<ac:image{}>{}</ac:image>", alt, resource));
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Publishes the document as a Confluence page under `space` (and
+/// `parent_id` when given), or updates the page it was published as before.
+/// Local images become attachments of the page. The page id and version are
+/// recorded in the frontmatter; an update is refused when the page was
+/// edited in Confluence since, unless `force` is set.
+#[tauri::command]
+pub async fn publish_to_confluence(
+    app: AppHandle,
+    account: String,
+    content: String,
+    path: Option<String>,
+    space: Option<String>,
+    parent_id: Option<String>,
+    force: Option<bool>,
+) -> Result<Published, String> {
+    publish::blocking(move || {
+        let settings: ConfluenceAccount = publish::account(&app, TARGET, &account)?;
+        let client = Client::new(&settings);
+        let document_path = crate::scopes::check_optional(&app, path.as_deref())?;
+        let document_dir = document_path.as_deref().and_then(Path::parent);
+        let post = publish::post(&content, document_path.as_deref(), ID_KEY);
+        let recorded_version = frontmatter::parse(&content).text(VERSION_KEY).and_then(|v| v.parse::<u64>().ok());
+
+        // Attachments need unique names within the page
+        let mut attachments: Vec<(String, PathBuf)> = Vec::new();
+        let (body, _) = publish::upload_images(&app, &post.body, document_dir, |file| {
+            let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let taken = |n: &str| attachments.iter().any(|(a, _)| a == n);
+            let mut unique = name.clone();
+            let mut n = 2;
+            while taken(&unique) {
+                unique = match name.rsplit_once('.') {
+                    Some((stem, ext)) => format!("{}-{}.{}", stem, n, ext),
+                    None => format!("{}-{}", name, n),
+                };
+                n += 1;
+            }
+            attachments.push((unique.clone(), file.to_path_buf()));
+            Ok(format!("{}{}", ATTACHMENT_PREFIX, unique))
+        })?;
+        let storage = to_storage(&pandoc::to_html_fragment(&body, document_dir)?);
+        let body = json!({ "storage": { "value": storage, "representation": "storage" } });
+
+        let existing = match &post.id {
+            Some(id) => match client.request("GET", &format!("content/{}", publish::checked_id(id, |c| c.is_ascii_digit())?)).query("expand", "version").call() {
+                Err(ureq::Error::Status(404, _)) => None,
+                response => Some(client.page(response)?),
+            },
+            None => None,
+        };
+        let page = match existing {
+            Some(existing) => {
+                if let Some(recorded) = recorded_version.filter(|v| *v < existing.version.number && !force.unwrap_or(false)) {
+                    return Err(format!(
+                        "La page a été modifiée dans Confluence depuis la dernière publication (version {} au lieu de {})",
+                        existing.version.number, recorded
+                    ));
+                }
+                for (name, file) in &attachments {
+                    client.attach(&existing.id, name, file)?;
+                }
+                client.page(client.request("PUT", &format!("content/{}", existing.id)).send_json(json!({
+                    "id": existing.id,
+                    "type": "page",
+                    "title": post.title,
+                    "version": { "number": existing.version.number + 1 },
+                    "body": body,
+                })))?
+            }
+            None => {
+                let space = space.filter(|s| !s.trim().is_empty()).ok_or("Choisissez l'espace Confluence de la page")?;
+                let mut fields = json!({ "type": "page", "title": post.title, "space": { "key": space.trim() }, "body": body });
+                if let Some(parent) = parent_id.filter(|p| !p.trim().is_empty()) {
+                    fields["ancestors"] = json!([{ "id": parent.trim() }]);
+                }
+                let page = client.page(client.request("POST", "content").send_json(fields))?;
+                for (name, file) in &attachments {
+                    client.attach(&page.id, name, file)?;
+                }
+                page
+            }
+        };
+
+        let url = format!("{}{}", page.links.base.unwrap_or_default(), page.links.webui.unwrap_or_default());
+        let markdown = publish::record_id(&content, ID_KEY, &page.id);
+        let markdown = frontmatter::set(&markdown, VERSION_KEY, &page.version.number.to_string());
+        Ok(Published { markdown, id: page.id, url, images_uploaded: attachments.len() })
+    })
+    .await
+}
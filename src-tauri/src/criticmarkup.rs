@@ -0,0 +1,117 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::markdown;
+
+/// Openings and closings of CriticMarkup marks, by kind.
+const MARKS: &[(&str, &str, &str)] = &[
+    ("{++", "++}", "insertion"),
+    ("{--", "--}", "deletion"),
+    ("{~~", "~~}", "substitution"),
+    ("{==", "==}", "highlight"),
+    ("{>>", "<<}", "comment"),
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    /// "insertion", "deletion", "substitution", "highlight" or "comment".
+    pub kind: &'static str,
+    /// Inserted, deleted, replaced or highlighted text.
+    pub text: String,
+    /// New text of a substitution.
+    pub replacement: Option<String>,
+    /// Comment attached to a highlight, or of a lone comment.
+    pub comment: Option<String>,
+    pub author: Option<String>,
+    /// YYYY-MM-DD.
+    pub date: Option<String>,
+    /// UTF-16 offset in the markdown source.
+    pub offset: usize,
+    pub length: usize,
+    pub line: usize,
+}
+
+/// Splits the `Author, 2024-01-01 : text` prefix docx imports give comments.
+fn attribution(comment: &str) -> (Option<String>, Option<String>, String) {
+    let Some((by, text)) = comment.split_once(" : ").filter(|(by, _)| by.chars().count() <= 80) else {
+        return (None, None, comment.to_string());
+    };
+    let is_date = |s: &str| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").is_ok();
+    let (author, date) = match by.rsplit_once(", ") {
+        Some((author, date)) if is_date(date) => (Some(author.trim()), Some(date.trim())),
+        _ if is_date(by) => (None, Some(by.trim())),
+        _ => (Some(by.trim()), None),
+    };
+    (author.map(str::to_string), date.map(str::to_string), text.trim().to_string())
+}
+
+/// The mark opening at `at` in `line`, as (kind, inner text, end).
+fn mark_at(line: &str, at: usize) -> Option<(&'static str, &str, usize)> {
+    let rest = &line[at..];
+    let (open, close, kind) = MARKS.iter().find(|(open, _, _)| rest.starts_with(open))?;
+    let inner_len = rest[open.len()..].find(close)?;
+    Some((kind, &rest[open.len()..open.len() + inner_len], at + open.len() + inner_len + close.len()))
+}
+
+/// CriticMarkup annotations of a document, outside code, in order.
+pub fn annotations(md: &str) -> Vec<Annotation> {
+    let mut out = Vec::new();
+    for (line_start, line) in markdown::prose_lines(md) {
+        let mut i = 0;
+        while let Some(found) = line[i..].find('{').map(|p| i + p) {
+            let Some((kind, inner, mut end)) = mark_at(line, found) else {
+                i = found + 1;
+                continue;
+            };
+            let (mut text, mut replacement, mut comment) = (inner.to_string(), None, None);
+            match kind {
+                "substitution" => {
+                    let (old, new) = inner.split_once("~>").unwrap_or((inner, ""));
+                    text = old.to_string();
+                    replacement = Some(new.to_string());
+                }
+                "comment" => {
+                    text = String::new();
+                    comment = Some(inner.to_string());
+                }
+                "highlight" => {
+                    if let Some(("comment", note, note_end)) = mark_at(line, end) {
+                        comment = Some(note.to_string());
+                        end = note_end;
+                    }
+                }
+                _ => {}
+            }
+            let (author, date, comment) = match comment {
+                Some(comment) => {
+                    let (author, date, comment) = attribution(&comment);
+                    (author, date, Some(comment))
+                }
+                None => (None, None, None),
+            };
+            let start = line_start + found;
+            let offset = markdown::utf16_offset(md, start);
+            out.push(Annotation {
+                kind,
+                text,
+                replacement,
+                comment,
+                author,
+                date,
+                offset,
+                length: markdown::utf16_offset(md, line_start + end) - offset,
+                line: markdown::line_at(md, start),
+            });
+            i = end;
+        }
+    }
+    out
+}
+
+/// Revisions and comments of a document written as CriticMarkup, such as
+/// those kept from a Word import.
+#[tauri::command]
+pub fn list_annotations(markdown: &str) -> Vec<Annotation> {
+    annotations(markdown)
+}
@@ -0,0 +1,87 @@
+use serde::Deserialize;
+use std::process::Command;
+
+use crate::markdown;
+
+const PROGRAM: &str = "pandoc-crossref";
+/// Label prefixes pandoc-crossref resolves, as in `{#fig:plan}` and `@fig:plan`.
+const PREFIXES: &[&str] = &["fig:", "tbl:", "eq:", "sec:", "lst:"];
+
+/// Numbering of figures, tables and equations with pandoc-crossref.
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CrossrefOptions {
+    /// None runs the filter when it is installed and the document has labels.
+    pub enabled: Option<bool>,
+    /// Numbers per chapter (1.1, 1.2…) instead of through the document.
+    pub chapters: bool,
+    /// Makes `@fig:` references links to what they name.
+    pub link_references: Option<bool>,
+    /// Numbers every display equation, labelled or not.
+    pub number_all_equations: bool,
+    /// Words before reference numbers, e.g. "fig." or "Figure".
+    pub figure_prefix: Option<String>,
+    pub table_prefix: Option<String>,
+    pub equation_prefix: Option<String>,
+    pub section_prefix: Option<String>,
+}
+
+/// True for keys like `fig:plan` that name a figure, table or equation rather
+/// than a bibliography entry.
+pub fn is_reference(key: &str) -> bool {
+    PREFIXES.iter().any(|p| key.starts_with(p))
+}
+
+fn has_labels(md: &str) -> bool {
+    PREFIXES.iter().any(|p| md.contains(&format!("{{#{}", p))) || markdown::citations(md).iter().any(|c| is_reference(c.key(md)))
+}
+
+fn version() -> Option<String> {
+    let output = Command::new(PROGRAM).arg("--version").output().ok().filter(|o| o.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).lines().next().unwrap_or_default().trim().to_string())
+}
+
+/// `--filter` and settings for pandoc-crossref, which has to run before
+/// citeproc so its references aren't taken for citations.
+pub fn pandoc_args(md: &str, options: &CrossrefOptions) -> Result<Vec<String>, String> {
+    let enabled = match options.enabled {
+        Some(false) => return Ok(Vec::new()),
+        Some(true) => true,
+        None => has_labels(md),
+    };
+    if !enabled {
+        return Ok(Vec::new());
+    }
+    if version().is_none() {
+        if options.enabled == Some(true) {
+            return Err("pandoc-crossref est introuvable. Installez-le pour numéroter figures, tableaux et équations.".to_string());
+        }
+        return Ok(Vec::new());
+    }
+    let mut args = vec![format!("--filter={}", PROGRAM)];
+    let mut metadata = |key: &str, value: &str| args.push(format!("--metadata={}:{}", key, value));
+    if options.chapters {
+        metadata("chapters", "true");
+    }
+    metadata("linkReferences", if options.link_references.unwrap_or(true) { "true" } else { "false" });
+    if options.number_all_equations {
+        metadata("autoEqnLabels", "true");
+    }
+    for (key, value) in [
+        ("figPrefix", &options.figure_prefix),
+        ("tblPrefix", &options.table_prefix),
+        ("eqnPrefix", &options.equation_prefix),
+        ("secPrefix", &options.section_prefix),
+    ] {
+        if let Some(value) = value.as_deref().filter(|v| !v.trim().is_empty()) {
+            metadata(key, value);
+        }
+    }
+    Ok(args)
+}
+
+/// Version of the installed pandoc-crossref, None when it can't be run.
+#[tauri::command]
+pub fn check_pandoc_crossref() -> Option<String> {
+    version()
+}
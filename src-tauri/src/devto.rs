@@ -0,0 +1,103 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::http;
+use crate::publish::{self, Published};
+
+const TARGET: &str = "devto";
+const ID_KEY: &str = "devto_id";
+const API_URL: &str = "https://dev.to/api/articles";
+const MAX_TAGS: usize = 4;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DevToAccount {
+    /// Generated under Settings › Extensions.
+    api_key: String,
+}
+
+pub fn validate(account: &Value) -> Result<(), String> {
+    let account: DevToAccount = serde_json::from_value(account.clone()).map_err(|e| format!("Compte Dev.to invalide: {}", e))?;
+    if account.api_key.trim().is_empty() {
+        return Err("Clé d'API Dev.to requise".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct Article {
+    id: u64,
+    url: String,
+}
+
+/// Dev.to tags are lowercase alphanumeric, four at most.
+fn tags(names: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for name in names {
+        let tag: String = name.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags.truncate(MAX_TAGS);
+    tags
+}
+
+/// Publishes the document's markdown as a Dev.to article, or updates the
+/// article it was published as before. Dev.to has no upload API, so images
+/// must already be online.
+#[tauri::command]
+pub async fn publish_to_devto(app: AppHandle, account: String, content: String, path: Option<String>) -> Result<Published, String> {
+    publish::blocking(move || {
+        let settings: DevToAccount = publish::account(&app, TARGET, &account)?;
+        let document_path = crate::scopes::check_optional(&app, path.as_deref())?;
+        let post = publish::post(&content, document_path.as_deref(), ID_KEY);
+        publish::ensure_remote_images(&post.body, post.feature_image.as_deref())?;
+
+        let mut article = json!({
+            "title": post.title,
+            "body_markdown": post.body,
+            "published": !post.draft,
+            "tags": tags(&post.tags),
+        });
+        for (field, value) in [
+            ("description", &post.summary),
+            ("canonical_url", &post.canonical_url),
+            ("series", &post.series),
+            ("main_image", &post.feature_image),
+        ] {
+            if let Some(value) = value {
+                article[field] = json!(value);
+            }
+        }
+
+        let agent = http::agent();
+        let request = |method: &str, url: &str| {
+            agent
+                .request(method, url)
+                .set("api-key", settings.api_key.trim())
+                .set("Accept", "application/vnd.forem.api-v1+json")
+        };
+        let body = json!({ "article": article });
+        let response = match &post.id {
+            Some(id) => match request("PUT", &format!("{}/{}", API_URL, publish::checked_id(id, |c| c.is_ascii_digit())?)).send_json(&body) {
+                Err(ureq::Error::Status(404, _)) => request("POST", API_URL).send_json(&body),
+                result => result,
+            },
+            None => request("POST", API_URL).send_json(&body),
+        };
+        let saved: Article = response
+            .map_err(http::error_message)?
+            .into_json()
+            .map_err(|e| format!("Réponse inattendue de Dev.to: {}", e))?;
+        let id = saved.id.to_string();
+        Ok(Published {
+            markdown: publish::record_id(&content, ID_KEY, &id),
+            id,
+            url: saved.url,
+            images_uploaded: 0,
+        })
+    })
+    .await
+}
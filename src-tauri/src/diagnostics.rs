@@ -0,0 +1,187 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::{diagrams, directories, paths, settings};
+
+/// Programs the app runs, with the argument that prints their version.
+const TOOLS: &[(&str, &str)] = &[
+    ("pandoc", "--version"),
+    ("wkhtmltopdf", "--version"),
+    ("pandoc-crossref", "--version"),
+    ("xelatex", "--version"),
+    ("lualatex", "--version"),
+    ("pdflatex", "--version"),
+    ("latex", "--version"),
+    ("dvisvgm", "--version"),
+    ("mmdc", "--version"),
+    ("plantuml", "-version"),
+    ("dot", "-V"),
+    ("java", "-version"),
+    ("git", "--version"),
+];
+
+/// Errors since launch by area ("export", "import", or what the frontend
+/// reports).
+static ERRORS: Mutex<BTreeMap<String, ErrorCount>> = Mutex::new(BTreeMap::new());
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorCount {
+    pub count: u32,
+    pub last_message: String,
+    pub last_at: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolVersion {
+    pub name: &'static str,
+    /// First line of its version output; None when it isn't found.
+    pub version: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskSpace {
+    pub name: String,
+    pub path: String,
+    pub free_bytes: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    pub os: String,
+    pub os_version: Option<String>,
+    pub arch: String,
+    pub app_version: String,
+    pub webview_version: Option<String>,
+    pub locale: Option<String>,
+    pub path: Vec<String>,
+    pub tools: Vec<ToolVersion>,
+    pub disks: Vec<DiskSpace>,
+    pub errors: BTreeMap<String, ErrorCount>,
+    /// All of the above as plain text, for a bug report.
+    pub report: String,
+}
+
+/// Counts an error of `area` for the diagnostics.
+pub fn record_error(area: &str, message: &str) {
+    let Ok(mut errors) = ERRORS.lock() else { return };
+    let entry = errors
+        .entry(area.to_string())
+        .or_insert_with(|| ErrorCount { count: 0, last_message: String::new(), last_at: String::new() });
+    entry.count += 1;
+    entry.last_message = message.chars().take(300).collect();
+    entry.last_at = chrono::Local::now().to_rfc3339();
+}
+
+fn first_line(output: std::process::Output) -> Option<String> {
+    // java and dot print their version on stderr
+    let text = if output.stdout.is_empty() { output.stderr } else { output.stdout };
+    String::from_utf8_lossy(&text).lines().map(str::trim).find(|l| !l.is_empty()).map(str::to_string)
+}
+
+pub fn tool_version(name: &str, version_arg: &str) -> Option<String> {
+    let output = diagrams::tool_command(&settings::tool(name)).arg(version_arg).output().ok()?;
+    output.status.success().then(|| first_line(output)).flatten()
+}
+
+fn os_version() -> Option<String> {
+    let output = if cfg!(target_os = "windows") {
+        Command::new("cmd").args(["/C", "ver"]).output()
+    } else if cfg!(target_os = "macos") {
+        Command::new("sw_vers").arg("-productVersion").output()
+    } else {
+        Command::new("uname").arg("-sr").output()
+    };
+    output.ok().filter(|o| o.status.success()).and_then(first_line)
+}
+
+fn locale() -> Option<String> {
+    let from_env = ["LC_ALL", "LC_MESSAGES", "LANG"].iter().filter_map(|v| std::env::var(v).ok()).find(|v| !v.is_empty());
+    if from_env.is_some() || !cfg!(target_os = "windows") {
+        return from_env;
+    }
+    let output = Command::new("powershell").args(["-NoProfile", "-NonInteractive", "-Command", "(Get-Culture).Name"]).output().ok()?;
+    output.status.success().then(|| first_line(output)).flatten()
+}
+
+fn disks(app: &AppHandle) -> Vec<DiskSpace> {
+    let mut out: Vec<DiskSpace> = directories::get_directories(app.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|dir| DiskSpace { name: dir.kind.to_string(), path: dir.path, free_bytes: dir.free_bytes })
+        .collect();
+    for (name, dir) in [("data", paths::app_data_dir(app)), ("config", paths::app_config_dir(app))] {
+        if let Ok(dir) = dir {
+            out.push(DiskSpace { name: name.to_string(), free_bytes: paths::free_space(&dir), path: dir.to_string_lossy().to_string() });
+        }
+    }
+    out
+}
+
+fn megabytes(bytes: Option<u64>) -> String {
+    bytes.map(|b| format!("{} Mo libres", b / 1024 / 1024)).unwrap_or_else(|| "espace libre inconnu".to_string())
+}
+
+fn report(d: &Diagnostics) -> String {
+    let unknown = || "inconnu".to_string();
+    let mut lines = vec![
+        format!("OhMyMarkdown {}", d.app_version),
+        format!("Système: {} {} ({})", d.os, d.os_version.clone().unwrap_or_else(unknown), d.arch),
+        format!("WebView: {}", d.webview_version.clone().unwrap_or_else(unknown)),
+        format!("Langue: {}", d.locale.clone().unwrap_or_else(unknown)),
+        String::new(),
+        "Outils:".to_string(),
+    ];
+    lines.extend(d.tools.iter().map(|t| format!("  {}: {}", t.name, t.version.as_deref().unwrap_or("introuvable"))));
+    lines.push(String::new());
+    lines.push("Dossiers:".to_string());
+    lines.extend(d.disks.iter().map(|disk| format!("  {}: {} ({})", disk.name, disk.path, megabytes(disk.free_bytes))));
+    lines.push(String::new());
+    lines.push("Erreurs depuis le lancement:".to_string());
+    if d.errors.is_empty() {
+        lines.push("  aucune".to_string());
+    }
+    lines.extend(d.errors.iter().map(|(area, e)| format!("  {}: {} (dernière à {}: {})", area, e.count, e.last_at, e.last_message)));
+    lines.push(String::new());
+    lines.push("PATH:".to_string());
+    lines.extend(d.path.iter().map(|p| format!("  {}", p)));
+    lines.join("\n")
+}
+
+/// What a bug report needs to know about this machine: system, versions of
+/// the app, webview and external tools, free space, and errors since launch.
+#[tauri::command]
+pub async fn get_diagnostics(app: AppHandle) -> Result<Diagnostics, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = std::env::var_os("PATH").map(|p| std::env::split_paths(&p).map(|p| p.to_string_lossy().to_string()).collect());
+        let mut diagnostics = Diagnostics {
+            os: std::env::consts::OS.to_string(),
+            os_version: os_version(),
+            arch: std::env::consts::ARCH.to_string(),
+            app_version: app.package_info().version.to_string(),
+            webview_version: tauri::webview_version().ok(),
+            locale: locale(),
+            path: path.unwrap_or_default(),
+            tools: TOOLS.iter().map(|(name, arg)| ToolVersion { name, version: tool_version(name, arg) }).collect(),
+            disks: disks(&app),
+            errors: ERRORS.lock().map(|e| e.clone()).unwrap_or_default(),
+            report: String::new(),
+        };
+        diagnostics.report = report(&diagnostics);
+        diagnostics
+    })
+    .await
+    .map_err(|e| format!("Erreur lors du diagnostic: {}", e))
+}
+
+/// Counts an error the frontend ran into, so it shows in the diagnostics.
+#[tauri::command]
+pub fn report_error(area: String, message: String) {
+    record_error(area.trim(), &message);
+}
@@ -0,0 +1,347 @@
+use serde::{Deserialize, Serialize};
+use layout::backends::svg::SVGWriter;
+use layout::gv::{DotParser, GraphBuilder};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tauri::AppHandle;
+
+use crate::markdown;
+
+const MERMAID_LANGUAGES: &[&str] = &["mermaid"];
+const PLANTUML_LANGUAGES: &[&str] = &["plantuml", "puml"];
+const GRAPHVIZ_LANGUAGES: &[&str] = &["dot", "graphviz"];
+
+/// Where to find optional rendering backends.
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiagramOptions {
+    /// Path to a local plantuml.jar.
+    pub plantuml_jar: Option<String>,
+    /// Base URL of a PlantUML server, e.g. https://www.plantuml.com/plantuml.
+    pub plantuml_server: Option<String>,
+}
+
+/// A fenced code block holding diagram source.
+pub struct DiagramBlock {
+    pub language: String,
+    pub source: String,
+    /// Byte range of the whole block, fences included.
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagramRenderers {
+    pub mermaid: bool,
+    pub plantuml: bool,
+    /// Graphviz always renders (bundled layout engine); this reports whether
+    /// the system `dot`, which supports the full language, is used instead.
+    pub graphviz_system: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ImageKind {
+    Svg,
+    Png,
+}
+
+impl ImageKind {
+    fn extension(self) -> &'static str {
+        match self {
+            ImageKind::Svg => "svg",
+            ImageKind::Png => "png",
+        }
+    }
+}
+
+/// Language of a fence info string: "mermaid", "{.mermaid}", "mermaid title=x".
+fn fence_language(info: &str) -> String {
+    info.split_whitespace()
+        .next()
+        .unwrap_or("")
+        .trim_matches(['{', '}'])
+        .trim_start_matches('.')
+        .to_lowercase()
+}
+
+/// Finds fenced blocks whose language is one of `languages`.
+pub fn find_blocks(md: &str, languages: &[&str]) -> Vec<DiagramBlock> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(char, usize, String, usize, usize)> = None;
+    let mut pos = 0;
+
+    for line in md.split_inclusive('\n') {
+        let line_start = pos;
+        pos += line.len();
+        let content = line.trim_end_matches(['\n', '\r']);
+        let marker = markdown::fence_marker(content);
+
+        match (&open, marker) {
+            (None, Some((c, n))) => {
+                let info = content.trim_start().trim_start_matches(c);
+                open = Some((c, n, fence_language(info), line_start, pos));
+            }
+            (Some((c, n, _, _, _)), Some((mc, mn))) if *c == mc && mn >= *n && content.trim().chars().all(|x| x == mc) => {
+                if let Some((_, _, language, start, body_start)) = open.take() {
+                    if languages.contains(&language.as_str()) {
+                        blocks.push(DiagramBlock {
+                            source: md[body_start..line_start].to_string(),
+                            language,
+                            start,
+                            end: pos,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+fn is_available(program: &str, version_arg: &str) -> bool {
+    tool_command(program)
+        .arg(version_arg)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// npm and gem tools are `.cmd`/`.bat` shims on Windows, which `Command` only finds
+/// through the shell.
+pub fn tool_command(program: &str) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", program]);
+        command
+    } else {
+        Command::new(program)
+    }
+}
+
+fn cache_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::paths::app_data_subdir(app, "diagram-cache")
+}
+
+fn cache_key(language: &str, source: &str, kind: ImageKind) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(language.as_bytes());
+    hasher.update([0]);
+    hasher.update(source.as_bytes());
+    let digest = hasher.finalize();
+    let hex: String = digest.iter().take(16).map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}.{}", language, hex, kind.extension())
+}
+
+fn render_mermaid(source: &str, output: &Path) -> Result<(), String> {
+    let input = output.with_extension("mmd");
+    std::fs::write(&input, source)
+        .map_err(|e| format!("Impossible d'écrire le diagramme: {}", e))?;
+    let result = tool_command("mmdc")
+        .arg("-i").arg(&input)
+        .arg("-o").arg(output)
+        .args(["-b", "transparent"])
+        .output();
+    let _ = std::fs::remove_file(&input);
+
+    let result = result.map_err(|e| format!("Erreur lors de l'exécution de mmdc: {}. Installez @mermaid-js/mermaid-cli.", e))?;
+    if result.status.success() && output.is_file() {
+        Ok(())
+    } else {
+        Err(format!("Le rendu Mermaid a échoué: {}", String::from_utf8_lossy(&result.stderr).trim()))
+    }
+}
+
+fn plantuml_source(source: &str) -> String {
+    if source.trim_start().starts_with("@start") {
+        source.to_string()
+    } else {
+        format!("@startuml\n{}\n@enduml\n", source.trim_end())
+    }
+}
+
+/// Local PlantUML: an explicit jar, else a `plantuml` launcher on the PATH.
+fn plantuml_local_command(options: &DiagramOptions) -> Option<Command> {
+    if let Some(jar) = options.plantuml_jar.as_deref().filter(|j| Path::new(j).is_file()) {
+        let mut command = Command::new("java");
+        command.arg("-jar").arg(jar);
+        return Some(command);
+    }
+    is_available("plantuml", "-version").then(|| tool_command("plantuml"))
+}
+
+fn render_plantuml(source: &str, output: &Path, kind: ImageKind, options: &DiagramOptions) -> Result<(), String> {
+    let source = plantuml_source(source);
+
+    let bytes = if let Some(mut command) = plantuml_local_command(options) {
+        let mut child = command
+            .args([format!("-t{}", kind.extension()).as_str(), "-pipe", "-charset", "UTF-8"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Erreur lors de l'exécution de PlantUML: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(source.as_bytes())
+                .map_err(|e| format!("Erreur d'écriture vers PlantUML: {}", e))?;
+        }
+        let result = child.wait_with_output()
+            .map_err(|e| format!("Erreur lors de l'attente de PlantUML: {}", e))?;
+        if !result.status.success() {
+            return Err(format!("Le rendu PlantUML a échoué: {}", String::from_utf8_lossy(&result.stderr).trim()));
+        }
+        result.stdout
+    } else if let Some(server) = options.plantuml_server.as_deref() {
+        let url = format!("{}/{}", server.trim_end_matches('/'), kind.extension());
+        let response = crate::http::agent()
+            .post(&url)
+            .set("Content-Type", "text/plain; charset=utf-8")
+            .send_string(&source)
+            .map_err(crate::http::error_message)?;
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut response.into_reader(), &mut bytes)
+            .map_err(|e| format!("Réponse PlantUML invalide: {}", e))?;
+        if kind == ImageKind::Svg {
+            crate::svg::sanitize(&String::from_utf8_lossy(&bytes)).into_bytes()
+        } else {
+            bytes
+        }
+    } else {
+        return Err("PlantUML n'est pas configuré: indiquez un plantuml.jar ou l'URL d'un serveur".to_string());
+    };
+
+    std::fs::write(output, bytes)
+        .map_err(|e| format!("Impossible d'enregistrer le diagramme: {}", e))
+}
+
+fn render_graphviz(source: &str, output: &Path, kind: ImageKind) -> Result<(), String> {
+    if is_available("dot", "-V") {
+        let mut child = Command::new("dot")
+            .arg(format!("-T{}", kind.extension()))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Erreur lors de l'exécution de dot: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(source.as_bytes())
+                .map_err(|e| format!("Erreur d'écriture vers dot: {}", e))?;
+        }
+        let result = child.wait_with_output()
+            .map_err(|e| format!("Erreur lors de l'attente de dot: {}", e))?;
+        if !result.status.success() {
+            return Err(format!("Le rendu Graphviz a échoué: {}", String::from_utf8_lossy(&result.stderr).trim()));
+        }
+        return std::fs::write(output, result.stdout)
+            .map_err(|e| format!("Impossible d'enregistrer le diagramme: {}", e));
+    }
+
+    // The bundled engine can panic on constructs it doesn't support
+    let source = source.to_string();
+    let svg = std::panic::catch_unwind(move || {
+        let mut parser = DotParser::new(&source);
+        let graph = parser.process()?;
+        let mut builder = GraphBuilder::new();
+        builder.visit_graph(&graph);
+        let mut visual = builder.get();
+        let mut writer = SVGWriter::new();
+        visual.do_it(false, false, false, &mut writer);
+        Ok::<String, String>(writer.finalize())
+    })
+    .map_err(|_| "Le moteur Graphviz intégré ne prend pas en charge ce graphe; installez Graphviz".to_string())?
+    .map_err(|e| format!("Erreur de syntaxe DOT: {}", e))?;
+
+    std::fs::write(output, svg)
+        .map_err(|e| format!("Impossible d'enregistrer le diagramme: {}", e))
+}
+
+/// Renders a diagram to an image file, reusing a previous render of the same
+/// source when there is one.
+pub fn render_to_file(app: &AppHandle, language: &str, source: &str, kind: ImageKind, options: &DiagramOptions) -> Result<PathBuf, String> {
+    // Without the system `dot`, Graphviz can only produce SVG
+    let graphviz = GRAPHVIZ_LANGUAGES.contains(&language);
+    let kind = if graphviz && !is_available("dot", "-V") { ImageKind::Svg } else { kind };
+
+    let output = cache_dir(app)?.join(cache_key(language, source, kind));
+    if output.is_file() {
+        return Ok(output);
+    }
+
+    match language {
+        "mermaid" => render_mermaid(source, &output)?,
+        "plantuml" | "puml" => render_plantuml(source, &output, kind, options)?,
+        "dot" | "graphviz" => render_graphviz(source, &output, kind)?,
+        other => return Err(format!("Type de diagramme non pris en charge: {}", other)),
+    }
+    Ok(output)
+}
+
+/// Diagram languages that can be rendered with the tools currently installed.
+fn renderable_languages(options: &DiagramOptions) -> Vec<&'static str> {
+    let mut languages = GRAPHVIZ_LANGUAGES.to_vec();
+    if is_available("mmdc", "--version") {
+        languages.extend_from_slice(MERMAID_LANGUAGES);
+    }
+    if plantuml_local_command(options).is_some() || options.plantuml_server.is_some() {
+        languages.extend_from_slice(PLANTUML_LANGUAGES);
+    }
+    languages
+}
+
+/// Formats whose output is markdown again: diagrams stay as source there.
+fn keeps_source(to_format: &str) -> bool {
+    ["markdown", "gfm", "commonmark", "plain"].iter().any(|f| to_format.starts_with(f))
+}
+
+/// Replaces diagram blocks with links to rendered images before export.
+/// Blocks whose tool is missing are kept as code; blocks that fail to render
+/// are kept too, preceded by the error so it shows up in the output.
+pub fn render_for_export(app: &AppHandle, md: &str, to_format: &str, options: &DiagramOptions) -> String {
+    if keeps_source(to_format) {
+        return md.to_string();
+    }
+    // Word processors handle SVG poorly; everything else gets vector output
+    let kind = if matches!(to_format, "docx" | "odt" | "pptx" | "rtf") {
+        ImageKind::Png
+    } else {
+        ImageKind::Svg
+    };
+
+    let languages = renderable_languages(options);
+    let replacements = find_blocks(md, &languages)
+        .into_iter()
+        .map(|block| match render_to_file(app, &block.language, &block.source, kind, options) {
+            Ok(path) => (block.start, block.end, format!("![]({})\n", crate::assets::link_to(&path, None))),
+            Err(e) => {
+                let error = e.lines().collect::<Vec<_>>().join(" ");
+                (block.start, block.start, format!("> **Erreur de rendu du diagramme** : {}\n\n", error))
+            }
+        })
+        .collect();
+    markdown::replace_ranges(md, replacements)
+}
+
+#[tauri::command]
+pub fn get_diagram_renderers(options: Option<DiagramOptions>) -> DiagramRenderers {
+    let options = options.unwrap_or_default();
+    DiagramRenderers {
+        mermaid: is_available("mmdc", "--version"),
+        plantuml: plantuml_local_command(&options).is_some() || options.plantuml_server.is_some(),
+        graphviz_system: is_available("dot", "-V"),
+    }
+}
+
+/// Renders a diagram block to SVG markup for the preview.
+#[tauri::command]
+pub async fn render_diagram(app: AppHandle, language: String, source: String, options: Option<DiagramOptions>) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = render_to_file(&app, &language.to_lowercase(), &source, ImageKind::Svg, &options.unwrap_or_default())?;
+        std::fs::read_to_string(&path)
+            .map_err(|e| format!("Impossible de lire le rendu du diagramme: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Erreur lors du rendu du diagramme: {}", e))?
+}
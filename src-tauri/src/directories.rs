@@ -0,0 +1,160 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::{assets, paths, scopes, settings};
+
+/// Below this, a folder is refused: exports and extracted media would soon
+/// fail half-written.
+const MIN_FREE_BYTES: u64 = 200 * 1024 * 1024;
+const KINDS: &[&str] = &["temp", "exports", "media"];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryInfo {
+    /// "temp", "exports" or "media".
+    pub kind: &'static str,
+    pub path: String,
+    /// False for the app's default location.
+    pub custom: bool,
+    pub free_bytes: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryChange {
+    pub previous: String,
+    pub current: String,
+    /// Entries moved from the previous folder. Links of unsaved documents to
+    /// moved media need `relocate_media_links`.
+    pub moved: usize,
+    /// Entries left behind because the new folder already had one by that
+    /// name.
+    pub skipped: Vec<String>,
+}
+
+fn configured(directories: &settings::Directories, kind: &str) -> Option<PathBuf> {
+    let dir = match kind {
+        "temp" => &directories.temp,
+        "exports" => &directories.exports,
+        _ => &directories.media,
+    };
+    dir.as_deref().map(str::trim).filter(|d| !d.is_empty()).map(PathBuf::from)
+}
+
+fn default_dir(app: &AppHandle, kind: &str) -> Result<PathBuf, String> {
+    match kind {
+        "temp" => Ok(std::env::temp_dir()),
+        "exports" => app.path().document_dir().map_err(|e| format!("Impossible de déterminer le dossier Documents: {}", e)),
+        _ => paths::app_data_subdir(app, assets::UNSAVED_ASSETS_DIR),
+    }
+}
+
+fn current(app: &AppHandle, kind: &str) -> Result<PathBuf, String> {
+    match configured(&settings::load(app).directories, kind) {
+        Some(dir) => Ok(dir),
+        None => default_dir(app, kind),
+    }
+}
+
+/// Checks that the app can use `dir`: absolute, creatable, writable, and on
+/// a volume with room left.
+pub fn validate(dir: &Path) -> Result<(), String> {
+    if !dir.is_absolute() {
+        return Err(format!("Le dossier doit être un chemin absolu: {}", dir.display()));
+    }
+    std::fs::create_dir_all(dir).map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+    let probe = dir.join(".ohmymarkdown-write-test");
+    std::fs::write(&probe, b"").map_err(|e| format!("Le dossier {} n'est pas accessible en écriture: {}", dir.display(), e))?;
+    let _ = std::fs::remove_file(&probe);
+    match paths::free_space(dir) {
+        Some(free) if free < MIN_FREE_BYTES => {
+            Err(format!("Espace insuffisant dans {}: {} Mo libres, {} Mo requis", dir.display(), free / 1024 / 1024, MIN_FREE_BYTES / 1024 / 1024))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Moves the entries of `from` into `to`, keeping their names.
+fn move_contents(from: &Path, to: &Path) -> Result<(usize, Vec<String>), String> {
+    let mut moved = 0;
+    let mut skipped = Vec::new();
+    for entry in std::fs::read_dir(from).into_iter().flatten().filter_map(|e| e.ok()) {
+        let target = to.join(entry.file_name());
+        if target.exists() {
+            skipped.push(entry.path().to_string_lossy().to_string());
+            continue;
+        }
+        assets::move_path(&entry.path(), &target)?;
+        moved += 1;
+    }
+    Ok((moved, skipped))
+}
+
+/// Media folder of unsaved documents.
+pub fn media_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = current(app, "media")?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// Where an export to `output_path` goes: a bare file name or relative path
+/// lands in the exports folder.
+pub fn export_path(app: &AppHandle, output_path: &str) -> Result<String, String> {
+    if Path::new(output_path).is_absolute() {
+        return Ok(output_path.to_string());
+    }
+    let dir = current(app, "exports")?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+    Ok(dir.join(output_path).to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn get_directories(app: AppHandle) -> Result<Vec<DirectoryInfo>, String> {
+    let directories = settings::load(&app).directories;
+    KINDS
+        .iter()
+        .map(|kind| {
+            let custom = configured(&directories, kind);
+            let path = match &custom {
+                Some(dir) => dir.clone(),
+                None => default_dir(&app, kind)?,
+            };
+            Ok(DirectoryInfo { kind, free_bytes: paths::free_space(&path), path: path.to_string_lossy().to_string(), custom: custom.is_some() })
+        })
+        .collect()
+}
+
+/// Points the folder `kind` at `path`, or back at its default when None.
+/// Extracted media move along; temporary files are left to expire and
+/// exports, being the user's documents, stay where they were written.
+#[tauri::command]
+pub fn set_directory(app: AppHandle, kind: String, path: Option<String>) -> Result<DirectoryChange, String> {
+    if !KINDS.contains(&kind.as_str()) {
+        return Err(format!("Dossier inconnu: {} ({})", kind, KINDS.join(", ")));
+    }
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    let previous = current(&app, &kind)?;
+    let next = match &path {
+        Some(dir) => scopes::check(&app, dir)?,
+        None => default_dir(&app, &kind)?,
+    };
+    validate(&next)?;
+    if kind == "media" && previous != next && (next.starts_with(&previous) || previous.starts_with(&next)) {
+        return Err("Le nouveau dossier des médias ne peut pas contenir l'ancien ni être dedans".to_string());
+    }
+    settings::update(&app, serde_json::json!({ "directories": { kind.as_str(): path } }), None)?;
+    let (moved, skipped) = match kind.as_str() {
+        "media" if previous != next && previous.is_dir() => move_contents(&previous, &next)?,
+        _ => (0, Vec::new()),
+    };
+    Ok(DirectoryChange { previous: previous.to_string_lossy().to_string(), current: next.to_string_lossy().to_string(), moved, skipped })
+}
+
+/// Rewrites the links of a document to media moved from `from_dir` to
+/// `to_dir` by `set_directory`.
+#[tauri::command]
+pub fn relocate_media_links(markdown: &str, document_path: Option<&str>, from_dir: &str, to_dir: &str) -> String {
+    let document_dir = document_path.map(Path::new).and_then(Path::parent);
+    assets::relocate_links(markdown, document_dir, Path::new(from_dir), Path::new(to_dir))
+}
@@ -0,0 +1,225 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, State};
+
+use crate::encoding::{self, TextFormat};
+use crate::{locks, safe_save, scopes};
+
+/// Above this size a document is not sent over IPC in one piece; the
+/// frontend switches to a lightweight viewer that reads it line range by
+/// line range.
+pub const LARGE_FILE_BYTES: u64 = 5 * 1024 * 1024;
+const SNIFF_BYTES: usize = 64 * 1024;
+const MAX_CHUNK_LINES: usize = 10_000;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenedDocument {
+    /// UTF-8 text with `\n` line endings; empty for a large file.
+    pub content: String,
+    /// To pass back to `save_document`.
+    pub format: TextFormat,
+    pub large: bool,
+    pub size: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentChunk {
+    pub start_line: usize,
+    /// The requested lines, joined with `\n`.
+    pub text: String,
+    pub line_count: usize,
+    pub total_lines: usize,
+}
+
+struct LineIndex {
+    modified: Option<SystemTime>,
+    len: u64,
+    /// Byte offset of the start of each line.
+    starts: Vec<u64>,
+    format: TextFormat,
+    bom_len: u64,
+}
+
+struct PendingWrite {
+    path: PathBuf,
+    tmp: PathBuf,
+    file: File,
+    format: TextFormat,
+}
+
+/// Line indexes of the large files being viewed, and streamed writes in
+/// progress.
+#[derive(Default)]
+pub struct LargeFiles {
+    indexes: Mutex<HashMap<PathBuf, LineIndex>>,
+    writes: Mutex<HashMap<u64, PendingWrite>>,
+    next_write: AtomicU64,
+}
+
+fn read_error(path: &Path, e: std::io::Error) -> String {
+    format!("Impossible de lire {}: {}", path.display(), e)
+}
+
+/// Large mode splits the raw bytes on `\n`, which only works for encodings
+/// where that byte is always a newline; UTF-16 files are loaded whole.
+fn sniff(path: &Path) -> Result<(TextFormat, usize, bool), String> {
+    let mut prefix = Vec::with_capacity(SNIFF_BYTES);
+    File::open(path)
+        .and_then(|f| f.take(SNIFF_BYTES as u64).read_to_end(&mut prefix))
+        .map_err(|e| read_error(path, e))?;
+    let (detected, bom_len) = encoding::detect(&prefix);
+    let (_, format) = encoding::decode(&prefix);
+    Ok((format, bom_len, detected.is_ascii_compatible()))
+}
+
+fn build_index(path: &Path) -> Result<LineIndex, String> {
+    let mut file = File::open(path).map_err(|e| read_error(path, e))?;
+    let metadata = file.metadata().map_err(|e| read_error(path, e))?;
+    let (format, bom_len, _) = sniff(path)?;
+    let mut starts = vec![0];
+    let mut buffer = vec![0u8; 1024 * 1024];
+    let mut offset = 0u64;
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| read_error(path, e))?;
+        if read == 0 {
+            break;
+        }
+        starts.extend(buffer[..read].iter().enumerate().filter(|(_, &b)| b == b'\n').map(|(i, _)| offset + i as u64 + 1));
+        offset += read as u64;
+    }
+    // A trailing newline does not start another line
+    if starts.len() > 1 && starts.last() == Some(&offset) {
+        starts.pop();
+    }
+    Ok(LineIndex { modified: metadata.modified().ok(), len: metadata.len(), starts, format, bom_len: bom_len as u64 })
+}
+
+/// Reads a document in whatever encoding it was written in. Large files come
+/// back without content, to be read with `read_document_lines`.
+#[tauri::command]
+pub fn open_document(app: AppHandle, path: &str) -> Result<OpenedDocument, String> {
+    let file = &scopes::check(&app, path)?;
+    let size = std::fs::metadata(file).map_err(|e| format!("Impossible d'ouvrir {}: {}", path, e))?.len();
+    if size > LARGE_FILE_BYTES {
+        let (format, _, line_based) = sniff(file)?;
+        if line_based {
+            return Ok(OpenedDocument { content: String::new(), format, large: true, size });
+        }
+    }
+    let bytes = std::fs::read(file).map_err(|e| format!("Impossible d'ouvrir {}: {}", path, e))?;
+    let (content, format) = encoding::decode(&bytes);
+    Ok(OpenedDocument { content, format, large: false, size })
+}
+
+/// Lines `start_line..start_line + count` (0-based) of a large document.
+#[tauri::command]
+pub fn read_document_lines(
+    app: AppHandle,
+    files: State<'_, LargeFiles>,
+    path: &str,
+    start_line: usize,
+    count: usize,
+) -> Result<DocumentChunk, String> {
+    let file_path = scopes::check(&app, path)?;
+    let metadata = std::fs::metadata(&file_path).map_err(|e| read_error(&file_path, e))?;
+    let mut indexes = files.indexes.lock().unwrap();
+    let stale = indexes
+        .get(&file_path)
+        .is_none_or(|index| index.len != metadata.len() || index.modified != metadata.modified().ok());
+    if stale {
+        indexes.insert(file_path.clone(), build_index(&file_path)?);
+    }
+    let index = &indexes[&file_path];
+
+    let total_lines = index.starts.len();
+    let start_line = start_line.min(total_lines);
+    let end_line = (start_line + count.min(MAX_CHUNK_LINES)).min(total_lines);
+    let from = index.starts.get(start_line).copied().unwrap_or(index.len).max(index.bom_len);
+    let to = index.starts.get(end_line).copied().unwrap_or(index.len).max(from);
+
+    let mut bytes = vec![0u8; (to - from) as usize];
+    let mut file = File::open(&file_path).map_err(|e| read_error(&file_path, e))?;
+    file.seek(SeekFrom::Start(from))
+        .and_then(|_| file.read_exact(&mut bytes))
+        .map_err(|e| read_error(&file_path, e))?;
+    let (text, _) = encoding::encoding_of(&index.format)?.decode_without_bom_handling(&bytes);
+    let text = text.replace("\r\n", "\n");
+    Ok(DocumentChunk {
+        start_line,
+        text: text.strip_suffix('\n').unwrap_or(&text).to_string(),
+        line_count: end_line - start_line,
+        total_lines,
+    })
+}
+
+/// Writes a document back in its original encoding and line endings, or as
+/// UTF-8 with `\n` when no format is given.
+#[tauri::command]
+pub fn save_document(app: AppHandle, path: &str, content: &str, format: Option<TextFormat>) -> Result<(), String> {
+    let path = scopes::check(&app, path)?;
+    safe_save::write_text(&app, &path, content, &format.unwrap_or_default())
+}
+
+/// Starts writing a document in pieces, for content too large to send at
+/// once. The file is only replaced by `finish_document_write`.
+#[tauri::command]
+pub fn begin_document_write(
+    app: AppHandle,
+    files: State<'_, LargeFiles>,
+    path: &str,
+    format: Option<TextFormat>,
+) -> Result<u64, String> {
+    let path = scopes::check(&app, path)?;
+    locks::ensure_writable(&app, &path)?;
+    let name = path.file_name().ok_or("Chemin invalide")?.to_string_lossy().to_string();
+    // One temp file per write, so two writes to the same document never mix
+    let id = files.next_write.fetch_add(1, Ordering::SeqCst);
+    let tmp = path.with_file_name(format!(".{}.{}.{}.tmp", name, std::process::id(), id));
+    let format = format.unwrap_or_default();
+    // The BOM goes once at the start, not in every chunk
+    let mut file = File::create(&tmp).map_err(|e| format!("Impossible d'enregistrer {}: {}", path.display(), e))?;
+    let bom = encoding::encode("", &format)?;
+    file.write_all(&bom).map_err(|e| format!("Impossible d'enregistrer {}: {}", path.display(), e))?;
+
+    let format = TextFormat { bom: false, ..format };
+    files.writes.lock().unwrap().insert(id, PendingWrite { path, tmp, file, format });
+    Ok(id)
+}
+
+#[tauri::command]
+pub fn write_document_chunk(files: State<'_, LargeFiles>, id: u64, chunk: &str) -> Result<(), String> {
+    let mut writes = files.writes.lock().unwrap();
+    let write = writes.get_mut(&id).ok_or("Écriture inconnue ou terminée")?;
+    let bytes = encoding::encode(chunk, &write.format)?;
+    write.file.write_all(&bytes).map_err(|e| format!("Impossible d'enregistrer {}: {}", write.path.display(), e))
+}
+
+/// Completes a streamed write, or abandons it when `commit` is false.
+#[tauri::command]
+pub fn finish_document_write(app: AppHandle, files: State<'_, LargeFiles>, id: u64, commit: Option<bool>) -> Result<(), String> {
+    let write = files.writes.lock().unwrap().remove(&id).ok_or("Écriture inconnue ou terminée")?;
+    let PendingWrite { path, tmp, file, .. } = write;
+    if !commit.unwrap_or(true) {
+        drop(file);
+        let _ = std::fs::remove_file(&tmp);
+        return Ok(());
+    }
+    file.sync_all().map_err(|e| format!("Impossible d'enregistrer {}: {}", path.display(), e))?;
+    drop(file);
+    // The document may have been locked while it was being written
+    if let Err(e) = locks::ensure_writable(&app, &path) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e);
+    }
+    safe_save::commit(&app, &tmp, &path)?;
+    files.indexes.lock().unwrap().remove(&path);
+    Ok(())
+}
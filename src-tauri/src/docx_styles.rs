@@ -0,0 +1,82 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::pandoc::TempFile;
+use crate::paths;
+
+const SETTINGS_FILE: &str = "docx-styles.json";
+const STYLES_FILTER: &str = include_str!("docx_styles/styles.lua");
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Word style name to markdown construct.
+pub type StyleMap = BTreeMap<String, String>;
+
+/// Constructs a style can map to. Paragraph styles take "blockquote",
+/// "code[:language]", "div:class" (a fenced div) or "heading:level"; character
+/// styles "code", "emphasis", "strong" or "strikeout". Either can be "remove"d
+/// or "unwrap"ped, which keeps the text as plain markdown.
+fn validate_target(target: &str) -> Result<(), String> {
+    let (kind, arg) = target.split_once(':').unwrap_or((target, ""));
+    let valid = match kind {
+        "blockquote" | "emphasis" | "strong" | "strikeout" | "remove" | "unwrap" => arg.is_empty(),
+        "code" => arg.chars().all(|c| c.is_ascii_alphanumeric() || "+-_#.".contains(c)),
+        "div" => !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+        "heading" => arg.parse::<u8>().is_ok_and(|level| (1..=6).contains(&level)),
+        _ => false,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Correspondance de style invalide: {}", target))
+    }
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_config_dir(app)?.join(SETTINGS_FILE))
+}
+
+fn load(app: &AppHandle) -> Result<StyleMap, String> {
+    let path = settings_path(app)?;
+    match std::fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Correspondances de styles invalides ({}): {}", path.display(), e)),
+        Err(_) => Ok(StyleMap::new()),
+    }
+}
+
+fn lua_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\r', "\\r"))
+}
+
+/// The filter applying the user's style map, None when there is none. The
+/// docx must be read with `+styles` for it to see style names.
+pub fn lua_filter(app: &AppHandle) -> Result<Option<TempFile>, String> {
+    let styles = load(app)?;
+    if styles.is_empty() {
+        return Ok(None);
+    }
+    let entries: Vec<String> = styles.iter().map(|(style, target)| format!("  [{}] = {},", lua_string(style), lua_string(target))).collect();
+    let filter = format!("local STYLES = {{\n{}\n}}\n{}", entries.join("\n"), STYLES_FILTER);
+    TempFile::write("docx-styles", "lua", &filter).map(Some)
+}
+
+#[tauri::command]
+pub fn get_docx_style_map(app: AppHandle) -> Result<StyleMap, String> {
+    load(&app)
+}
+
+/// Replaces the mapping from Word styles to markdown applied to docx imports.
+#[tauri::command]
+pub fn set_docx_style_map(app: AppHandle, styles: StyleMap) -> Result<(), String> {
+    for (style, target) in &styles {
+        if style.trim().is_empty() {
+            return Err("Nom de style vide".to_string());
+        }
+        validate_target(target)?;
+    }
+    let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&styles).map_err(|e| e.to_string())?;
+    std::fs::write(settings_path(&app)?, json).map_err(|e| format!("Impossible d'enregistrer les correspondances de styles: {}", e))
+}
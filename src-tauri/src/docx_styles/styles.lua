@@ -0,0 +1,65 @@
+-- Turns the paragraphs and runs of a docx read with +styles into the
+-- markdown constructs STYLES maps their style names to. Styles it doesn't
+-- map are unwrapped, so they don't end up as fenced divs. The app defines
+-- STYLES above this line.
+
+local function target(el)
+  local style = el.attributes["custom-style"]
+  if style == nil then
+    return nil
+  end
+  local mapped = STYLES[style] or "unwrap"
+  return mapped:match("^([^:]+):?(.*)$")
+end
+
+local function lines(blocks)
+  local out = {}
+  for _, block in ipairs(blocks) do
+    out[#out + 1] = pandoc.utils.stringify(block)
+  end
+  return table.concat(out, "\n")
+end
+
+function Div(div)
+  local kind, arg = target(div)
+  if kind == nil then
+    return nil
+  elseif kind == "blockquote" then
+    return pandoc.BlockQuote(div.content)
+  elseif kind == "code" then
+    return pandoc.CodeBlock(lines(div.content), pandoc.Attr("", arg ~= "" and { arg } or {}))
+  elseif kind == "div" then
+    return pandoc.Div(div.content, pandoc.Attr("", { arg }))
+  elseif kind == "heading" then
+    local out = {}
+    for _, block in ipairs(div.content) do
+      if block.t == "Para" or block.t == "Plain" then
+        out[#out + 1] = pandoc.Header(tonumber(arg) or 2, block.content)
+      else
+        out[#out + 1] = block
+      end
+    end
+    return out
+  elseif kind == "remove" then
+    return {}
+  end
+  return div.content
+end
+
+function Span(span)
+  local kind = target(span)
+  if kind == nil then
+    return nil
+  elseif kind == "code" then
+    return pandoc.Code(pandoc.utils.stringify(span.content))
+  elseif kind == "emphasis" then
+    return pandoc.Emph(span.content)
+  elseif kind == "strong" then
+    return pandoc.Strong(span.content)
+  elseif kind == "strikeout" then
+    return pandoc.Strikeout(span.content)
+  elseif kind == "remove" then
+    return {}
+  end
+  return span.content
+end
@@ -0,0 +1,131 @@
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::{diagnostics, http, paths};
+
+/// SHA-256 of each archive the app downloads and runs code from, by URL.
+/// URLs name a version, so a checksum is added with each version bump; a
+/// URL missing here is never downloaded.
+const PINNED: &[(&str, &str)] = &[];
+
+/// Checksums of the executable files of an installed tool, written once its
+/// archive is verified and checked before each run.
+const MANIFEST_FILE: &str = ".checksums.json";
+
+/// Whether `url` has a pinned checksum, and so can be downloaded.
+pub fn is_pinned(url: &str) -> bool {
+    PINNED.iter().any(|(pinned, _)| *pinned == url)
+}
+
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Moves a file or folder that failed verification out of the way, into the
+/// app's `quarantine` folder, where it can be looked at but is never run.
+pub fn quarantine(app: &AppHandle, path: &Path) -> Result<PathBuf, String> {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let dest = paths::app_data_subdir(app, "quarantine")?.join(format!("{}-{}", chrono::Local::now().format("%Y%m%d-%H%M%S"), name));
+    std::fs::rename(path, &dest).map_err(|e| format!("Impossible de mettre {} en quarantaine: {}", path.display(), e))?;
+    diagnostics::record_error("download", &format!("{} mis en quarantaine", name));
+    Ok(dest)
+}
+
+/// Downloads `url` to `dest` and checks it against its pinned checksum. A
+/// file that doesn't match is quarantined, and the download fails.
+pub fn download_verified(app: &AppHandle, url: &str, dest: &Path) -> Result<(), String> {
+    let (_, expected) = PINNED
+        .iter()
+        .find(|(pinned, _)| *pinned == url)
+        .ok_or_else(|| format!("Téléchargement refusé: aucune empreinte SHA-256 n'est connue pour {}", url))?;
+    http::download_to_file(url, dest)?;
+    let actual = sha256_file(dest)?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        let moved = quarantine(app, dest)?;
+        return Err(format!(
+            "Le fichier téléchargé depuis {} est corrompu ou a été modifié (SHA-256 {} au lieu de {}); il a été mis en quarantaine dans {}",
+            url,
+            actual,
+            expected,
+            moved.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Files below `dir` with `extension`, by path relative to `dir`.
+fn files_with(dir: &Path, extension: &str) -> BTreeMap<String, PathBuf> {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|ext| ext == extension))
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(dir).ok()?.to_string_lossy().replace('\\', "/");
+            Some((relative, e.path().to_path_buf()))
+        })
+        .collect()
+}
+
+/// Records the checksums of the files with `extension` of a tool just
+/// extracted to `dir` from a verified archive.
+pub fn record_manifest(dir: &Path, extension: &str) -> Result<(), String> {
+    let mut manifest = BTreeMap::new();
+    for (relative, path) in files_with(dir, extension) {
+        manifest.insert(relative, sha256_file(&path)?);
+    }
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join(MANIFEST_FILE), json).map_err(|e| format!("Impossible d'enregistrer les empreintes de {}: {}", dir.display(), e))
+}
+
+fn read_manifest(dir: &Path) -> Option<BTreeMap<String, String>> {
+    std::fs::read_to_string(dir.join(MANIFEST_FILE)).ok().and_then(|json| serde_json::from_str(&json).ok())
+}
+
+/// Whether the tool in `dir` was installed from a verified archive. Older
+/// versions of the app installed tools without recording checksums.
+pub fn is_verified(dir: &Path) -> bool {
+    read_manifest(dir).is_some()
+}
+
+/// Checks a tool installed in `dir` before it runs: its files with
+/// `extension` must be those `record_manifest` saw. A tool that changed is
+/// quarantined; one installed before verification existed is left in place
+/// and must be reinstalled.
+pub fn verify_manifest(app: &AppHandle, dir: &Path, extension: &str) -> Result<(), String> {
+    let problem = match read_manifest(dir) {
+        None => {
+            return Err(format!(
+                "Lancement refusé: {} a été installé sans vérification de son empreinte; réinstallez-le pour le vérifier",
+                dir.display()
+            ))
+        }
+        Some(manifest) => {
+            let files = files_with(dir, extension);
+            let mut problem = files.keys().find(|f| !manifest.contains_key(*f)).map(|f| format!("{} a été ajouté", f));
+            for (relative, expected) in &manifest {
+                if problem.is_some() {
+                    break;
+                }
+                problem = match files.get(relative).map(|path| sha256_file(path)) {
+                    None => Some(format!("{} a été supprimé", relative)),
+                    Some(Ok(actual)) if actual == *expected => None,
+                    Some(Ok(_)) => Some(format!("{} a été modifié", relative)),
+                    Some(Err(e)) => Some(e),
+                };
+            }
+            problem
+        }
+    };
+    match problem {
+        None => Ok(()),
+        Some(problem) => {
+            let moved = quarantine(app, dir)?;
+            Err(format!("Lancement refusé: {}; l'installation a été mise en quarantaine dans {}, réinstallez-la", problem, moved.display()))
+        }
+    }
+}
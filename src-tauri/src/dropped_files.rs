@@ -0,0 +1,104 @@
+use serde::Serialize;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::pandoc::ImportOptions;
+use crate::track_changes::TrackChanges;
+use crate::{assets, images, markdown};
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "svg", "avif", "bmp", "tif", "tiff", "heic", "heif"];
+
+/// What the frontend should do with a dropped file.
+#[derive(Serialize)]
+#[serde(tag = "action", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum DropAction {
+    /// A markdown file to open as is.
+    Open { path: String },
+    /// A document converted to markdown, to open as a new unsaved document.
+    Import { path: String, markdown: String },
+    /// An image copied into the assets folder; `markdown` is the link to insert.
+    InsertImage { path: String, markdown: String },
+    /// An archive to propose for batch import.
+    OfferArchiveImport { path: String, markdown_files: usize },
+    Unsupported { path: String },
+    Failed { path: String, error: String },
+}
+
+/// Pandoc reader for the document formats that get converted on drop.
+fn pandoc_format(ext: &str) -> Option<&'static str> {
+    match ext {
+        "docx" => Some("docx"),
+        "odt" => Some("odt"),
+        "rtf" => Some("rtf"),
+        "epub" => Some("epub"),
+        "html" | "htm" | "xhtml" => Some("html"),
+        _ => None,
+    }
+}
+
+fn count_markdown_entries(path: &Path) -> Result<usize, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Impossible d'ouvrir l'archive: {}", e))?;
+    let archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("Archive invalide: {}", e))?;
+    Ok(archive.file_names().filter(|name| markdown::is_markdown_file(Path::new(name))).count())
+}
+
+fn insert_image(app: &AppHandle, source: &Path, document_path: Option<&str>) -> Result<String, String> {
+    let dir = assets::target_assets_dir(app, document_path)?;
+    // Already in the assets folder: just link it
+    let target = if source.starts_with(&dir) {
+        source.to_path_buf()
+    } else {
+        let name = source.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let target = assets::unique_path(&dir, &name);
+        std::fs::copy(source, &target)
+            .map_err(|e| format!("Impossible de copier l'image: {}", e))?;
+        images::convert_for_web(&target)?.unwrap_or(target)
+    };
+    Ok(format!("![]({})", assets::document_link(&target, document_path)))
+}
+
+fn import_options(format: &str) -> ImportOptions<'static> {
+    ImportOptions { track_changes: (format == "docx").then_some(TrackChanges::Accept), ..Default::default() }
+}
+
+fn handle_file(app: &AppHandle, path: &str, document_path: Option<&str>) -> Result<DropAction, String> {
+    let file = Path::new(path);
+    let ext = file.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let path = path.to_string();
+
+    if markdown::is_markdown_file(file) {
+        return Ok(DropAction::Open { path });
+    }
+    if ext == "pdf" {
+        return Ok(DropAction::Import { markdown: crate::convert_pdf_to_markdown(app.clone(), &path)?, path });
+    }
+    if let Some(format) = pandoc_format(&ext) {
+        return Ok(DropAction::Import { markdown: crate::convert_to_markdown(app, &path, format, None, None, &import_options(format))?, path });
+    }
+    if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        return Ok(DropAction::InsertImage { markdown: insert_image(app, file, document_path)?, path });
+    }
+    if ext == "zip" {
+        return Ok(DropAction::OfferArchiveImport { markdown_files: count_markdown_entries(file)?, path });
+    }
+    Ok(DropAction::Unsupported { path })
+}
+
+/// Routes each dropped file by type. `document_path` is the open document,
+/// whose assets folder receives dropped images.
+#[tauri::command]
+pub async fn handle_dropped_files(app: AppHandle, paths: Vec<String>, document_path: Option<String>) -> Result<Vec<DropAction>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        paths
+            .into_iter()
+            .map(|path| {
+                handle_file(&app, &path, document_path.as_deref())
+                    .unwrap_or_else(|error| DropAction::Failed { path, error })
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Erreur lors du traitement des fichiers déposés: {}", e))
+}
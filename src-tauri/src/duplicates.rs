@@ -0,0 +1,195 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::{markdown, workspace};
+
+const DEFAULT_THRESHOLD: f64 = 0.8;
+const SHINGLE_WORDS: usize = 5;
+/// Notes shorter than this are only compared for exact duplicates.
+const MIN_WORDS: usize = 20;
+const MINHASH_BANDS: usize = 16;
+const MINHASH_ROWS: usize = 4;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateFile {
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateCluster {
+    /// "exact" or "near".
+    pub kind: &'static str,
+    /// Lowest similarity between two linked notes of the cluster, 1.0 for
+    /// exact duplicates.
+    pub similarity: f64,
+    pub files: Vec<DuplicateFile>,
+}
+
+struct Note {
+    path: PathBuf,
+    hash: [u8; 32],
+    shingles: HashSet<u64>,
+}
+
+/// Lowercased words of the rendered text, so formatting-only differences
+/// don't matter.
+fn words(md: &str) -> Vec<String> {
+    markdown::to_plain_text(md)
+        .text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn read_note(path: PathBuf) -> Option<Note> {
+    let md = std::fs::read_to_string(&path).ok()?;
+    let words = words(&md);
+    if words.is_empty() {
+        return None;
+    }
+    let hash = Sha256::digest(words.join(" ").as_bytes()).into();
+    let shingles = if words.len() < MIN_WORDS {
+        HashSet::new()
+    } else {
+        words
+            .windows(SHINGLE_WORDS)
+            .map(|w| {
+                let mut hasher = DefaultHasher::new();
+                w.hash(&mut hasher);
+                hasher.finish()
+            })
+            .collect()
+    };
+    Some(Note { path, hash, shingles })
+}
+
+fn mix(mut x: u64) -> u64 {
+    // splitmix64 finalizer
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+fn minhash(shingles: &HashSet<u64>) -> Vec<u64> {
+    (0..(MINHASH_BANDS * MINHASH_ROWS) as u64)
+        .map(|seed| shingles.iter().map(|&s| mix(s ^ mix(seed + 1))).min().unwrap_or(u64::MAX))
+        .collect()
+}
+
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let common = a.intersection(b).count();
+    common as f64 / (a.len() + b.len() - common).max(1) as f64
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    parent[i] = root;
+    root
+}
+
+fn duplicate_file(path: &std::path::Path) -> DuplicateFile {
+    let metadata = std::fs::metadata(path).ok();
+    DuplicateFile {
+        path: path.to_string_lossy().to_string(),
+        size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+        modified: metadata
+            .and_then(|m| m.modified().ok())
+            .map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339()),
+    }
+}
+
+fn clusters(notes: &[Note], threshold: f64) -> Vec<DuplicateCluster> {
+    let mut result = Vec::new();
+
+    // Exact duplicates: same text once formatting is ignored
+    let mut by_hash: HashMap<[u8; 32], Vec<usize>> = HashMap::new();
+    for (i, note) in notes.iter().enumerate() {
+        by_hash.entry(note.hash).or_default().push(i);
+    }
+    let mut representative: Vec<usize> = Vec::new();
+    for group in by_hash.values() {
+        representative.push(group[0]);
+        if group.len() > 1 {
+            result.push(DuplicateCluster {
+                kind: "exact",
+                similarity: 1.0,
+                files: group.iter().map(|&i| duplicate_file(&notes[i].path)).collect(),
+            });
+        }
+    }
+
+    // Near duplicates: notes sharing a MinHash band are candidates, confirmed
+    // with the exact Jaccard similarity of their shingles
+    let candidates: Vec<usize> = representative.into_iter().filter(|&i| !notes[i].shingles.is_empty()).collect();
+    let mut buckets: HashMap<(usize, Vec<u64>), Vec<usize>> = HashMap::new();
+    for &i in &candidates {
+        let signature = minhash(&notes[i].shingles);
+        for (band, rows) in signature.chunks(MINHASH_ROWS).enumerate() {
+            buckets.entry((band, rows.to_vec())).or_default().push(i);
+        }
+    }
+    let mut parent: Vec<usize> = (0..notes.len()).collect();
+    let mut lowest: HashMap<usize, f64> = HashMap::new();
+    let mut checked = HashSet::new();
+    for bucket in buckets.values().filter(|b| b.len() > 1) {
+        for (n, &a) in bucket.iter().enumerate() {
+            for &b in &bucket[n + 1..] {
+                if !checked.insert((a.min(b), a.max(b))) {
+                    continue;
+                }
+                let similarity = jaccard(&notes[a].shingles, &notes[b].shingles);
+                if similarity >= threshold {
+                    let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+                    let low = lowest.remove(&ra).unwrap_or(1.0).min(lowest.remove(&rb).unwrap_or(1.0)).min(similarity);
+                    parent[rb] = ra;
+                    lowest.insert(ra, low);
+                }
+            }
+        }
+    }
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &i in &candidates {
+        groups.entry(find(&mut parent, i)).or_default().push(i);
+    }
+    for (root, group) in groups.into_iter().filter(|(_, g)| g.len() > 1) {
+        result.push(DuplicateCluster {
+            kind: "near",
+            similarity: lowest.get(&root).copied().unwrap_or(threshold),
+            files: group.iter().map(|&i| duplicate_file(&notes[i].path)).collect(),
+        });
+    }
+
+    for cluster in &mut result {
+        cluster.files.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+    result.sort_by(|a, b| b.files.len().cmp(&a.files.len()).then_with(|| a.files[0].path.cmp(&b.files[0].path)));
+    result
+}
+
+/// Groups of identical or nearly identical notes in the workspace.
+/// `threshold` is the share of five-word sequences two notes must have in
+/// common to count as near duplicates (0.8 by default).
+#[tauri::command]
+pub async fn find_duplicate_notes(app: AppHandle, threshold: Option<f64>) -> Result<Vec<DuplicateCluster>, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD).clamp(0.1, 1.0);
+    tauri::async_runtime::spawn_blocking(move || {
+        let notes: Vec<Note> = workspace::markdown_files(&root).into_iter().filter_map(read_note).collect();
+        clusters(&notes, threshold)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la recherche de doublons: {}", e))
+}
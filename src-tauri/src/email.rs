@@ -0,0 +1,250 @@
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::{paths, publish};
+
+const SETTINGS_FILE: &str = "email.json";
+/// Mail clients and operating systems cut longer mailto links.
+const MAX_MAILTO_BODY: usize = 1800;
+const MAILTO_VALUE: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+const MAILTO_ADDRESS: &AsciiSet = &MAILTO_VALUE.remove(b'@');
+
+// Serializes writes to the settings file
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// TLS from the start, usually port 465.
+    Tls,
+    /// Upgrade to TLS after connecting, usually port 587.
+    #[default]
+    StartTls,
+    /// Unencrypted, for local relays only.
+    None,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SmtpSettings {
+    pub host: String,
+    /// Defaults to the usual port of `security`.
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    #[serde(default)]
+    pub username: String,
+    /// Empty keeps the saved password.
+    #[serde(default)]
+    pub password: String,
+    /// Sender, "Name <address>" or just the address.
+    pub from: String,
+}
+
+/// The settings without the password, for the frontend.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmtpInfo {
+    pub host: String,
+    pub port: Option<u16>,
+    pub security: SmtpSecurity,
+    pub username: String,
+    pub from: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailOptions {
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub cc: Vec<String>,
+    #[serde(default)]
+    pub subject: String,
+    /// Message text: usually the document itself for a mailto link, a few
+    /// words around the attachment otherwise.
+    #[serde(default)]
+    pub body: String,
+    /// Exported file to attach (PDF, HTML…); needs SMTP.
+    pub attachment: Option<String>,
+    /// Sends through the configured SMTP server instead of returning a mailto
+    /// link.
+    #[serde(default)]
+    pub smtp: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailResult {
+    /// Link for the frontend to open in the mail client.
+    pub mailto: Option<String>,
+    /// The body was shortened to fit in the link.
+    pub truncated: bool,
+    pub sent: bool,
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_config_dir(app)?.join(SETTINGS_FILE))
+}
+
+fn load(app: &AppHandle) -> Option<SmtpSettings> {
+    let json = std::fs::read_to_string(settings_path(app).ok()?).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn save(app: &AppHandle, settings: Option<&SmtpSettings>) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap();
+    let path = settings_path(app)?;
+    let Some(settings) = settings else {
+        let _ = std::fs::remove_file(&path);
+        return Ok(());
+    };
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    // The file holds the password
+    crate::paths::write_private(&path, json.as_bytes()).map_err(|e| format!("Impossible d'enregistrer les réglages SMTP: {}", e))
+}
+
+fn transport(settings: &SmtpSettings) -> Result<SmtpTransport, String> {
+    let host = settings.host.trim();
+    let invalid = |e: lettre::transport::smtp::Error| format!("Serveur SMTP invalide ({}): {}", host, e);
+    let (builder, port) = match settings.security {
+        SmtpSecurity::Tls => (SmtpTransport::relay(host).map_err(invalid)?, 465),
+        SmtpSecurity::StartTls => (SmtpTransport::starttls_relay(host).map_err(invalid)?, 587),
+        SmtpSecurity::None => (SmtpTransport::builder_dangerous(host), 25),
+    };
+    let builder = builder.port(settings.port.unwrap_or(port));
+    let builder = if settings.username.is_empty() {
+        builder
+    } else {
+        builder.credentials(Credentials::new(settings.username.clone(), settings.password.clone()))
+    };
+    Ok(builder.build())
+}
+
+fn mailbox(address: &str) -> Result<Mailbox, String> {
+    address.trim().parse().map_err(|_| format!("Adresse e-mail invalide: {}", address))
+}
+
+fn attachment_type(path: &Path) -> ContentType {
+    let mime = match path.extension().map(|e| e.to_string_lossy().to_lowercase()).as_deref() {
+        Some("pdf") => "application/pdf",
+        Some("html" | "htm") => "text/html; charset=utf-8",
+        Some("docx") => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        Some("odt") => "application/vnd.oasis.opendocument.text",
+        Some("epub") => "application/epub+zip",
+        Some("md" | "markdown") => "text/markdown; charset=utf-8",
+        Some("txt") => "text/plain; charset=utf-8",
+        _ => publish::image_mime(path),
+    };
+    ContentType::parse(mime).unwrap_or(ContentType::TEXT_PLAIN)
+}
+
+fn send(settings: &SmtpSettings, options: &EmailOptions) -> Result<(), String> {
+    if options.to.is_empty() {
+        return Err("Aucun destinataire".to_string());
+    }
+    let mut builder = Message::builder().from(mailbox(&settings.from)?).subject(options.subject.clone());
+    for to in &options.to {
+        builder = builder.to(mailbox(to)?);
+    }
+    for cc in &options.cc {
+        builder = builder.cc(mailbox(cc)?);
+    }
+    let text = SinglePart::plain(options.body.clone());
+    let message = match &options.attachment {
+        Some(path) => {
+            let path = Path::new(path);
+            let bytes = std::fs::read(path).map_err(|e| format!("Impossible de lire la pièce jointe {}: {}", path.display(), e))?;
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let attachment = Attachment::new(name).body(bytes, attachment_type(path));
+            builder.multipart(MultiPart::mixed().singlepart(text).singlepart(attachment))
+        }
+        None => builder.singlepart(text),
+    }
+    .map_err(|e| format!("Impossible de préparer le message: {}", e))?;
+    transport(settings)?.send(&message).map_err(|e| format!("Envoi impossible: {}", e))?;
+    Ok(())
+}
+
+/// `mailto:` link with the recipients, subject and body, the body cut to
+/// what mail clients accept.
+fn mailto(options: &EmailOptions) -> (String, bool) {
+    let encode = |value: &str| utf8_percent_encode(value, MAILTO_VALUE).to_string();
+    let addresses = |list: &[String]| list.iter().map(|a| utf8_percent_encode(a.trim(), MAILTO_ADDRESS).to_string()).collect::<Vec<_>>().join(",");
+    let truncated = options.body.chars().count() > MAX_MAILTO_BODY;
+    let body: String = options.body.chars().take(MAX_MAILTO_BODY).collect();
+    // Line breaks are CRLF in mailto links
+    let body = body.replace("\r\n", "\n").replace('\n', "\r\n");
+    let mut fields = Vec::new();
+    if !options.cc.is_empty() {
+        fields.push(format!("cc={}", addresses(&options.cc)));
+    }
+    if !options.subject.is_empty() {
+        fields.push(format!("subject={}", encode(&options.subject)));
+    }
+    if !body.is_empty() {
+        fields.push(format!("body={}", encode(&body)));
+    }
+    let query = if fields.is_empty() { String::new() } else { format!("?{}", fields.join("&")) };
+    (format!("mailto:{}{}", addresses(&options.to), query), truncated)
+}
+
+#[tauri::command]
+pub fn get_smtp_settings(app: AppHandle) -> Option<SmtpInfo> {
+    load(&app).map(|s| SmtpInfo { host: s.host, port: s.port, security: s.security, username: s.username, from: s.from })
+}
+
+/// Saves the SMTP server settings after checking the server answers.
+#[tauri::command]
+pub async fn set_smtp_settings(app: AppHandle, settings: SmtpSettings) -> Result<(), String> {
+    if settings.host.trim().is_empty() {
+        return Err("Le serveur SMTP est obligatoire".to_string());
+    }
+    mailbox(&settings.from)?;
+    let mut settings = settings;
+    if settings.password.is_empty() {
+        settings.password = load(&app).map(|s| s.password).unwrap_or_default();
+    }
+    let settings = tauri::async_runtime::spawn_blocking(move || {
+        match transport(&settings)?.test_connection() {
+            Ok(true) => Ok(settings),
+            Ok(false) => Err(format!("Le serveur SMTP {} ne répond pas", settings.host)),
+            Err(e) => Err(format!("Connexion au serveur SMTP impossible: {}", e)),
+        }
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la connexion au serveur SMTP: {}", e))??;
+    save(&app, Some(&settings))
+}
+
+#[tauri::command]
+pub fn remove_smtp_settings(app: AppHandle) -> Result<(), String> {
+    save(&app, None)
+}
+
+/// Sends a document by e-mail: a mailto link prefilled with its content for
+/// the mail client, or, with `smtp`, a message sent from the backend with the
+/// exported file attached.
+#[tauri::command]
+pub async fn email_document(app: AppHandle, options: EmailOptions) -> Result<EmailResult, String> {
+    if !options.smtp {
+        if options.attachment.is_some() {
+            return Err("Les pièces jointes ne passent pas par un lien mailto; configurez un serveur SMTP".to_string());
+        }
+        let (mailto, truncated) = mailto(&options);
+        return Ok(EmailResult { mailto: Some(mailto), truncated, sent: false });
+    }
+    let settings = load(&app).ok_or("Aucun serveur SMTP n'est configuré")?;
+    tauri::async_runtime::spawn_blocking(move || send(&settings, &options))
+        .await
+        .map_err(|e| format!("Erreur lors de l'envoi: {}", e))??;
+    Ok(EmailResult { mailto: None, truncated: false, sent: true })
+}
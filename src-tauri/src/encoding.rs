@@ -0,0 +1,173 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use serde::{Deserialize, Serialize};
+
+/// How a text file was stored on disk, so it can be saved back the same way.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TextFormat {
+    /// WHATWG encoding name: "UTF-8", "UTF-16LE", "windows-1252"...
+    pub encoding: String,
+    pub bom: bool,
+    /// "lf", "crlf" or "cr".
+    pub line_ending: String,
+}
+
+impl Default for TextFormat {
+    fn default() -> Self {
+        TextFormat { encoding: UTF_8.name().to_string(), bom: false, line_ending: "lf".to_string() }
+    }
+}
+
+/// UTF-16 without a BOM shows up as text with a zero byte in every other
+/// position.
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    if sample.len() < 4 {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let even = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd = sample.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+    if odd * 10 > pairs * 7 && even * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even * 10 > pairs * 7 && odd * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// The encoding of `bytes` and the length of its BOM.
+pub fn detect(bytes: &[u8]) -> (&'static Encoding, usize) {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        return (encoding, bom_len);
+    }
+    if let Some(encoding) = sniff_utf16(bytes) {
+        return (encoding, 0);
+    }
+    // An error at the very end may be a character cut by reading a prefix
+    if std::str::from_utf8(bytes).map_or_else(|e| e.error_len().is_none(), |_| true) {
+        return (UTF_8, 0);
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    (detector.guess(None, false), 0)
+}
+
+fn line_ending(text: &str) -> &'static str {
+    let crlf = text.matches("\r\n").count();
+    let cr = text.matches('\r').count() - crlf;
+    let lf = text.matches('\n').count() - crlf;
+    if crlf > lf && crlf >= cr {
+        "crlf"
+    } else if cr > lf {
+        "cr"
+    } else {
+        "lf"
+    }
+}
+
+/// Decodes a file's bytes into text with `\n` line endings, along with the
+/// format to save it back with.
+pub fn decode(bytes: &[u8]) -> (String, TextFormat) {
+    let (encoding, bom_len) = detect(bytes);
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+    let format = TextFormat {
+        encoding: encoding.name().to_string(),
+        bom: bom_len > 0,
+        line_ending: line_ending(&text).to_string(),
+    };
+    (text.replace("\r\n", "\n").replace('\r', "\n"), format)
+}
+
+pub fn encoding_of(format: &TextFormat) -> Result<&'static Encoding, String> {
+    Encoding::for_label(format.encoding.as_bytes()).ok_or_else(|| format!("Encodage inconnu: {}", format.encoding))
+}
+
+fn bom(encoding: &'static Encoding, with_bom: bool) -> &'static [u8] {
+    match encoding {
+        _ if !with_bom => b"",
+        e if e == UTF_16LE => b"\xFF\xFE",
+        e if e == UTF_16BE => b"\xFE\xFF",
+        e if e == UTF_8 => b"\xEF\xBB\xBF",
+        _ => b"",
+    }
+}
+
+/// Encodes editor text for disk in the given format. Fails rather than
+/// silently replacing characters the encoding cannot represent.
+pub fn encode(text: &str, format: &TextFormat) -> Result<Vec<u8>, String> {
+    let encoding = encoding_of(format)?;
+    let text = match format.line_ending.as_str() {
+        "crlf" => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+        "cr" => text.replace("\r\n", "\n").replace('\n', "\r"),
+        _ => text.to_string(),
+    };
+
+    // encoding_rs only decodes UTF-16; encode it by hand
+    let mut bytes = bom(encoding, format.bom).to_vec();
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        for unit in text.encode_utf16() {
+            bytes.extend_from_slice(&if encoding == UTF_16LE { unit.to_le_bytes() } else { unit.to_be_bytes() });
+        }
+        return Ok(bytes);
+    }
+    let (encoded, _, unmappable) = encoding.encode(&text);
+    if unmappable {
+        return Err(format!(
+            "Certains caractères ne peuvent pas être enregistrés en {}; enregistrez le document en UTF-8",
+            encoding.name()
+        ));
+    }
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(bytes: &[u8]) -> (String, TextFormat) {
+        let (text, format) = decode(bytes);
+        assert_eq!(encode(&text, &format).unwrap(), bytes);
+        (text, format)
+    }
+
+    #[test]
+    fn utf8_bom_is_kept() {
+        let (text, format) = round_trip(b"\xEF\xBB\xBFCaf\xC3\xA9\n");
+        assert_eq!(text, "Café\n");
+        assert_eq!(format.encoding, "UTF-8");
+        assert!(format.bom);
+    }
+
+    #[test]
+    fn utf16_with_bom() {
+        for (encoding, bom) in [(UTF_16LE, b"\xFF\xFE"), (UTF_16BE, b"\xFE\xFF")] {
+            let format = TextFormat { encoding: encoding.name().to_string(), bom: true, line_ending: "crlf".to_string() };
+            let bytes = encode("Été 😀\nfin", &format).unwrap();
+            assert!(bytes.starts_with(bom));
+            let (text, decoded) = round_trip(&bytes);
+            assert_eq!(text, "Été 😀\nfin");
+            assert_eq!(decoded.encoding, encoding.name());
+            assert_eq!(decoded.line_ending, "crlf");
+        }
+    }
+
+    #[test]
+    fn utf16_without_bom_is_sniffed() {
+        let format = TextFormat { encoding: UTF_16LE.name().to_string(), bom: false, line_ending: "lf".to_string() };
+        let bytes = encode("Bonjour à tous\n", &format).unwrap();
+        let (text, decoded) = round_trip(&bytes);
+        assert_eq!(text, "Bonjour à tous\n");
+        assert_eq!(decoded.encoding, "UTF-16LE");
+        assert!(!decoded.bom);
+    }
+
+    #[test]
+    fn unmappable_characters_are_refused() {
+        let format = TextFormat { encoding: "windows-1252".to_string(), bom: false, line_ending: "lf".to_string() };
+        assert_eq!(encode("café", &format).unwrap(), b"caf\xE9");
+        assert!(encode("😀", &format).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use age::secrecy::{ExposeSecret, SecretString};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Encrypted notes are standard age files (passphrase mode), so they can
+/// also be opened with the `age` command line tool.
+pub const ENCRYPTED_EXTENSION: &str = "age";
+
+const AGE_MAGIC: &[u8] = b"age-encryption.org/v1";
+const WRONG_PASSPHRASE: &str = "Phrase de passe incorrecte";
+const PASSPHRASE_REQUIRED: &str = "Phrase de passe requise";
+
+/// Passphrases of the encrypted documents unlocked during this session. They
+/// are never written to disk.
+#[derive(Default)]
+pub struct DocumentKeys {
+    passphrases: Mutex<HashMap<PathBuf, SecretString>>,
+}
+
+impl DocumentKeys {
+    fn get(&self, path: &Path) -> Option<SecretString> {
+        let passphrases = self.passphrases.lock().unwrap();
+        passphrases.get(path).map(|p| SecretString::from(p.expose_secret().to_string()))
+    }
+
+    fn set(&self, path: &Path, passphrase: &str) {
+        self.passphrases.lock().unwrap().insert(path.to_path_buf(), SecretString::from(passphrase.to_string()));
+    }
+}
+
+pub fn is_encrypted(path: &Path) -> bool {
+    let mut header = [0u8; AGE_MAGIC.len()];
+    std::fs::File::open(path).and_then(|mut f| f.read_exact(&mut header)).is_ok() && header == AGE_MAGIC
+}
+
+fn encrypt(content: &str, passphrase: SecretString) -> Result<Vec<u8>, String> {
+    age::encrypt(&age::scrypt::Recipient::new(passphrase), content.as_bytes())
+        .map_err(|e| format!("Erreur lors du chiffrement: {}", e))
+}
+
+fn decrypt(path: &Path, passphrase: SecretString) -> Result<String, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Impossible de lire le fichier: {}", e))?;
+    let plain = age::decrypt(&age::scrypt::Identity::new(passphrase), &data).map_err(|e| match e {
+        age::DecryptError::DecryptionFailed | age::DecryptError::NoMatchingKeys | age::DecryptError::KeyDecryptionFailed => {
+            WRONG_PASSPHRASE.to_string()
+        }
+        e => format!("Impossible de déchiffrer le fichier: {}", e),
+    })?;
+    String::from_utf8(plain).map_err(|_| "Le contenu déchiffré n'est pas du texte UTF-8".to_string())
+}
+
+/// The passphrase given by the user, or the one remembered for this session.
+fn passphrase_for(keys: &DocumentKeys, path: &Path, passphrase: Option<&str>) -> Result<SecretString, String> {
+    match passphrase {
+        Some(p) => Ok(SecretString::from(p.to_string())),
+        None => keys.get(path).ok_or_else(|| PASSPHRASE_REQUIRED.to_string()),
+    }
+}
+
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Erreur lors du chiffrement: {}", e))?
+}
+
+/// Encrypts a note into `<path>.age`, deletes the plain file and its version
+/// history, and returns the new path.
+#[tauri::command]
+pub async fn encrypt_document(app: AppHandle, path: String, passphrase: String) -> Result<String, String> {
+    if passphrase.is_empty() {
+        return Err(PASSPHRASE_REQUIRED.to_string());
+    }
+    blocking(move || {
+        let source = crate::scopes::check(&app, &path)?;
+        crate::locks::ensure_writable(&app, &source)?;
+        let target = PathBuf::from(format!("{}.{}", path, ENCRYPTED_EXTENSION));
+        if target.exists() {
+            return Err(format!("{} existe déjà", target.display()));
+        }
+        let content = std::fs::read_to_string(&source).map_err(|e| format!("Impossible de lire le fichier: {}", e))?;
+        crate::safe_save::write(&app, &target, &encrypt(&content, SecretString::from(passphrase.clone()))?)?;
+        std::fs::remove_file(&source).map_err(|e| format!("Impossible de supprimer {}: {}", source.display(), e))?;
+        // Backups and versions hold the note in clear
+        crate::safe_save::remove_backups(&source)?;
+        crate::history::clear(&app, &source)?;
+        app.state::<DocumentKeys>().set(&target, &passphrase);
+        Ok(target.to_string_lossy().to_string())
+    })
+    .await
+}
+
+/// Decrypts a note for editing. Without a passphrase, the one entered
+/// earlier in the session is used; errors are "Phrase de passe requise" or
+/// "Phrase de passe incorrecte" so the frontend can prompt again.
+#[tauri::command]
+pub async fn open_encrypted_document(app: AppHandle, path: String, passphrase: Option<String>) -> Result<String, String> {
+    blocking(move || {
+        let path = crate::scopes::check(&app, &path)?;
+        let keys = app.state::<DocumentKeys>();
+        let content = decrypt(&path, passphrase_for(&keys, &path, passphrase.as_deref())?)?;
+        if let Some(passphrase) = passphrase {
+            keys.set(&path, &passphrase);
+        }
+        Ok(content)
+    })
+    .await
+}
+
+/// Saves an unlocked encrypted note with the passphrase it was opened with.
+#[tauri::command]
+pub async fn save_encrypted_document(app: AppHandle, path: String, content: String) -> Result<(), String> {
+    blocking(move || {
+        let path = crate::scopes::check(&app, &path)?;
+        let passphrase = passphrase_for(&app.state::<DocumentKeys>(), &path, None)?;
+        crate::safe_save::write_document(&app, &path, &encrypt(&content, passphrase)?)
+    })
+    .await
+}
+
+/// Turns an encrypted note back into a plain file and returns its path.
+#[tauri::command]
+pub async fn decrypt_document(app: AppHandle, path: String, passphrase: Option<String>) -> Result<String, String> {
+    blocking(move || {
+        let source = crate::scopes::check(&app, &path)?;
+        let target = source.with_extension("");
+        if source.extension().is_none_or(|e| e != ENCRYPTED_EXTENSION) || target.exists() {
+            return Err(format!("Impossible de créer {}", target.display()));
+        }
+        let keys = app.state::<DocumentKeys>();
+        let content = decrypt(&source, passphrase_for(&keys, &source, passphrase.as_deref())?)?;
+        std::fs::write(&target, content).map_err(|e| format!("Impossible d'enregistrer le fichier: {}", e))?;
+        std::fs::remove_file(&source).map_err(|e| format!("Impossible de supprimer {}: {}", source.display(), e))?;
+        keys.passphrases.lock().unwrap().remove(&source);
+        Ok(target.to_string_lossy().to_string())
+    })
+    .await
+}
+
+#[tauri::command]
+pub fn is_encrypted_document(path: &str) -> bool {
+    is_encrypted(Path::new(path))
+}
+
+/// Forgets every passphrase entered during the session.
+#[tauri::command]
+pub fn lock_encrypted_documents(app: AppHandle) {
+    app.state::<DocumentKeys>().passphrases.lock().unwrap().clear();
+}
@@ -0,0 +1,122 @@
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+use crate::{assets, markdown, paths, settings};
+
+const CACHE_DIR: &str = "export-cache";
+/// Oldest outputs are evicted past this size.
+const MAX_CACHE_BYTES: u64 = 256 * 1024 * 1024;
+
+fn version(program: &str) -> String {
+    Command::new(settings::tool(program))
+        .arg("--version")
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).lines().next().unwrap_or_default().to_string())
+        .unwrap_or_default()
+}
+
+/// Hash of everything an export's output depends on: the markdown pandoc
+/// reads, its arguments (files they name by content, since temporary files
+/// get fresh names), the files the document links to, and the versions of
+/// the programs involved.
+pub fn key(md: &str, args: &[String], document_dir: Option<&Path>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(md.as_bytes());
+    let mut programs = vec!["pandoc".to_string()];
+    let mut skip_next = false;
+    for arg in args {
+        // The output path doesn't change the output
+        if std::mem::take(&mut skip_next) {
+            continue;
+        }
+        if arg == "-o" {
+            skip_next = true;
+            continue;
+        }
+        hasher.update([0]);
+        match arg.split_once('=') {
+            Some((option, value)) if Path::new(value).is_file() => {
+                hasher.update(option.as_bytes());
+                hasher.update(std::fs::read(value).unwrap_or_default());
+            }
+            _ => hasher.update(arg.as_bytes()),
+        }
+        if let Some(program) = arg.strip_prefix("--pdf-engine=").or_else(|| arg.strip_prefix("--filter=")) {
+            programs.push(program.to_string());
+        }
+    }
+    for link in markdown::links(md) {
+        let url = link.url(md);
+        if !assets::is_local_link(url) {
+            continue;
+        }
+        let path = assets::resolve_link(url, document_dir);
+        if let Ok(meta) = std::fs::metadata(&path) {
+            let modified = meta.modified().ok().and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok()).unwrap_or_default();
+            hasher.update(format!("\0{}\0{}\0{}", path.display(), meta.len(), modified.as_nanos()).as_bytes());
+        }
+    }
+    for program in programs {
+        hasher.update(format!("\0{}", version(&program)).as_bytes());
+    }
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn cache_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    paths::app_data_subdir(app, CACHE_DIR)
+}
+
+/// Copies the output cached under `key` to `output`; false when there is none.
+pub fn restore(app: &AppHandle, key: &str, output: &str) -> Result<bool, String> {
+    let cached = cache_dir(app)?.join(key);
+    if !cached.is_file() {
+        return Ok(false);
+    }
+    std::fs::copy(&cached, output).map_err(|e| format!("Impossible de copier l'export en cache vers {}: {}", output, e))?;
+    // Marks it recently used for eviction
+    let _ = std::fs::File::options().append(true).open(&cached).and_then(|f| f.set_modified(SystemTime::now()));
+    Ok(true)
+}
+
+/// Keeps a copy of `output` under `key`, then trims the cache to its size.
+pub fn store(app: &AppHandle, key: &str, output: &str) -> Result<(), String> {
+    let dir = cache_dir(app)?;
+    std::fs::copy(output, dir.join(key)).map_err(|e| format!("Impossible de mettre l'export en cache: {}", e))?;
+    let mut entries: Vec<(SystemTime, u64, PathBuf)> = std::fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some((meta.modified().ok()?, meta.len(), e.path()))
+        })
+        .collect();
+    entries.sort();
+    let mut total: u64 = entries.iter().map(|(_, len, _)| len).sum();
+    for (_, len, path) in entries {
+        if total <= MAX_CACHE_BYTES {
+            break;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            total -= len;
+        }
+    }
+    Ok(())
+}
+
+/// Deletes every cached export and returns the bytes freed.
+#[tauri::command]
+pub fn clear_export_cache(app: AppHandle) -> Result<u64, String> {
+    let dir = cache_dir(&app)?;
+    let mut freed = 0;
+    for entry in std::fs::read_dir(&dir).map_err(|e| e.to_string())?.filter_map(|e| e.ok()) {
+        let len = entry.metadata().map(|m| m.len()).unwrap_or_default();
+        if std::fs::remove_file(entry.path()).is_ok() {
+            freed += len;
+        }
+    }
+    Ok(freed)
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+
+/// Why an export's output can't be trusted even though pandoc succeeded,
+/// as happens when wkhtmltopdf crashes mid-write.
+pub enum OutputError {
+    Missing,
+    Empty,
+    /// The file doesn't start like a `format` file.
+    Corrupt { format: String },
+}
+
+impl fmt::Display for OutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputError::Missing => write!(f, "L'export n'a produit aucun fichier"),
+            OutputError::Empty => write!(f, "L'export a produit un fichier vide"),
+            OutputError::Corrupt { format } => write!(f, "Le fichier exporté n'est pas un {} valide", format),
+        }
+    }
+}
+
+/// Leading bytes files of `to_format` start with, None for formats without
+/// a fixed signature.
+fn signature(to_format: &str) -> Option<&'static [u8]> {
+    match to_format {
+        "pdf" | "beamer" => Some(b"%PDF-"),
+        // Zip containers
+        "docx" | "odt" | "pptx" | "epub" | "epub2" | "epub3" => Some(b"PK\x03\x04"),
+        "rtf" => Some(b"{\\rtf"),
+        _ => None,
+    }
+}
+
+/// Checks the file an export to `to_format` wrote at `path`.
+pub fn check(path: &Path, to_format: &str) -> Result<(), OutputError> {
+    let meta = std::fs::metadata(path).map_err(|_| OutputError::Missing)?;
+    if meta.len() == 0 {
+        return Err(OutputError::Empty);
+    }
+    let corrupt = || OutputError::Corrupt { format: to_format.to_string() };
+    let mut head = [0u8; 512];
+    let read = std::fs::File::open(path).and_then(|mut f| f.read(&mut head)).map_err(|_| OutputError::Missing)?;
+    let head = &head[..read];
+    match signature(to_format) {
+        Some(magic) if !head.starts_with(magic) => Err(corrupt()),
+        Some(_) => Ok(()),
+        // Text formats: a cut multi-byte character at the end of the sample is fine
+        None => match std::str::from_utf8(head) {
+            Ok(_) => Ok(()),
+            Err(e) if e.error_len().is_none() => Ok(()),
+            Err(_) => Err(corrupt()),
+        },
+    }
+}
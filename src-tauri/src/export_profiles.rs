@@ -0,0 +1,89 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::pandoc::ExportOptions;
+use crate::{paths, settings};
+
+const SETTINGS_FILE: &str = "export-profiles.json";
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Default export options by output format, as the partial JSON of an
+/// `ExportOptions`.
+type Profiles = BTreeMap<String, Value>;
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_config_dir(app)?.join(SETTINGS_FILE))
+}
+
+fn load(app: &AppHandle) -> Result<Profiles, String> {
+    let path = settings_path(app)?;
+    match std::fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Profils d'export invalides ({}): {}", path.display(), e)),
+        Err(_) => Ok(Profiles::new()),
+    }
+}
+
+/// Lays `over` onto `base`, key by key in nested objects.
+pub fn merge(base: &mut Value, over: Value) {
+    match (base, over) {
+        (Value::Object(base), Value::Object(over)) => {
+            for (key, value) in over {
+                match base.get_mut(&key) {
+                    Some(existing) if existing.is_object() && value.is_object() => merge(existing, value),
+                    _ => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
+
+fn parse(options: Value) -> Result<ExportOptions, String> {
+    serde_json::from_value(options).map_err(|e| format!("Options d'export invalides: {}", e))
+}
+
+/// The options of an export: the format's profile, overridden by the
+/// settings in effect (a workspace's, say), overridden by what was set for
+/// this export.
+pub fn resolve(app: &AppHandle, to_format: &str, options: Option<Value>) -> Result<ExportOptions, String> {
+    let mut resolved = load(app)?.remove(to_format).unwrap_or_else(|| Value::Object(Default::default()));
+    if let Some(overrides) = settings::load(app).export.profiles.remove(to_format) {
+        merge(&mut resolved, overrides);
+    }
+    if let Some(options) = options.filter(|o| !o.is_null()) {
+        merge(&mut resolved, options);
+    }
+    parse(resolved)
+}
+
+#[tauri::command]
+pub fn get_export_profiles(app: AppHandle) -> Result<Profiles, String> {
+    load(&app)
+}
+
+/// Saves the default options of exports to `format`, or removes them when
+/// `options` is None. They are checked as an export would check them.
+#[tauri::command]
+pub fn set_export_profile(app: AppHandle, format: String, options: Option<Value>) -> Result<(), String> {
+    if let Some(options) = &options {
+        parse(options.clone())?.pandoc_args(&app, &format)?;
+    }
+    let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+    let mut profiles = load(&app)?;
+    match options {
+        Some(options) => {
+            profiles.insert(format, options);
+        }
+        None => {
+            profiles.remove(&format);
+        }
+    }
+    let json = serde_json::to_string_pretty(&profiles).map_err(|e| e.to_string())?;
+    std::fs::write(settings_path(&app)?, json).map_err(|e| format!("Impossible d'enregistrer les profils d'export: {}", e))
+}
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::workspace::{self, TreeNode, Workspace};
+
+const LAYOUT_FILE: &str = "tree.json";
+
+// Serializes read-modify-write cycles on the layout file
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Starred entries and manual ordering of the tree, stored in the workspace
+/// with paths relative to it so they follow the folder around.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TreeLayout {
+    favorites: Vec<String>,
+    /// Child names of a folder ("" for the workspace itself), in the order
+    /// they were arranged. Children missing from the list come after them.
+    order: BTreeMap<String, Vec<String>>,
+}
+
+fn layout_path(root: &Path) -> PathBuf {
+    crate::paths::workspace_config_dir(root).join(LAYOUT_FILE)
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/")
+}
+
+pub fn load(root: &Path) -> TreeLayout {
+    std::fs::read_to_string(layout_path(root))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn update<R>(root: &Path, change: impl FnOnce(&mut TreeLayout) -> R) -> Result<R, String> {
+    let _guard = LOCK.lock().unwrap();
+    let mut layout = load(root);
+    let result = change(&mut layout);
+    let path = layout_path(root);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&layout).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Impossible d'enregistrer l'organisation du dossier: {}", e))?;
+    Ok(result)
+}
+
+impl TreeLayout {
+    pub fn is_favorite(&self, root: &Path, path: &Path) -> bool {
+        self.favorites.contains(&relative(root, path))
+    }
+
+    /// Position of `name` in its folder's manual order.
+    pub fn position(&self, root: &Path, folder: &Path, name: &str) -> Option<usize> {
+        self.order.get(&relative(root, folder))?.iter().position(|n| n == name)
+    }
+}
+
+/// Carries the favorites and ordering of an entry, and of everything under
+/// it, over to its new path.
+pub fn on_moved(root: &Path, from: &Path, to: &Path) -> Result<(), String> {
+    let (from, to) = (relative(root, from), relative(root, to));
+    let moved = |path: &str| -> Option<String> {
+        if path == from {
+            Some(to.clone())
+        } else {
+            path.strip_prefix(&format!("{}/", from)).map(|rest| format!("{}/{}", to, rest))
+        }
+    };
+    let split = |path: &str| match path.rsplit_once('/') {
+        Some((parent, name)) => (parent.to_string(), name.to_string()),
+        None => (String::new(), path.to_string()),
+    };
+    let ((old_parent, old_name), (new_parent, new_name)) = (split(&from), split(&to));
+
+    let layout = load(root);
+    let affected = layout.favorites.iter().chain(layout.order.keys()).any(|p| moved(p).is_some())
+        || layout.order.get(&old_parent).is_some_and(|names| names.contains(&old_name));
+    if !affected {
+        return Ok(());
+    }
+    update(root, |layout| {
+        for favorite in &mut layout.favorites {
+            if let Some(new) = moved(favorite) {
+                *favorite = new;
+            }
+        }
+        layout.order = std::mem::take(&mut layout.order)
+            .into_iter()
+            .map(|(folder, names)| (moved(&folder).unwrap_or(folder), names))
+            .collect();
+        // A rename keeps its place; a move to another folder loses it
+        if let Some(names) = layout.order.get_mut(&old_parent) {
+            if old_parent == new_parent {
+                names.iter_mut().filter(|n| **n == old_name).for_each(|n| *n = new_name.clone());
+            } else {
+                names.retain(|n| *n != old_name);
+            }
+        }
+    })
+}
+
+fn favorite_paths(root: &Path, layout: &TreeLayout) -> Vec<String> {
+    layout
+        .favorites
+        .iter()
+        .map(|f| root.join(f))
+        .filter(|p| p.exists())
+        .map(|p| p.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Starred files and folders of the workspace that still exist.
+#[tauri::command]
+pub fn list_favorites(workspace: State<'_, Workspace>) -> Result<Vec<String>, String> {
+    let root = workspace.root()?;
+    Ok(favorite_paths(&root, &load(&root)))
+}
+
+/// Stars or unstars an entry and returns the updated favorites.
+#[tauri::command]
+pub fn set_favorite(workspace: State<'_, Workspace>, path: &str, favorite: bool) -> Result<Vec<String>, String> {
+    let root = workspace.root()?;
+    let path = workspace.resolve(path)?;
+    if !path.exists() {
+        return Err(format!("Chemin introuvable: {}", path.display()));
+    }
+    let key = relative(&root, &path);
+    update(&root, |layout| {
+        layout.favorites.retain(|f| *f != key);
+        if favorite {
+            layout.favorites.push(key);
+        }
+        favorite_paths(&root, layout)
+    })
+}
+
+/// Sets the order of a folder's children from their names and returns the
+/// rearranged tree. An empty list goes back to alphabetical order.
+#[tauri::command]
+pub fn reorder_children(workspace: State<'_, Workspace>, folder: &str, names: Vec<String>) -> Result<TreeNode, String> {
+    let root = workspace.root()?;
+    let folder = workspace.resolve(folder)?;
+    if !folder.is_dir() {
+        return Err(format!("Dossier introuvable: {}", folder.display()));
+    }
+    if let Some(name) = names.iter().find(|n| n.contains(['/', '\\']) || !folder.join(n).exists()) {
+        return Err(format!("{} n'est pas dans {}", name, folder.display()));
+    }
+    let key = relative(&root, &folder);
+    update(&root, |layout| {
+        if names.is_empty() {
+            layout.order.remove(&key);
+        } else {
+            layout.order.insert(key, names);
+        }
+    })?;
+    Ok(workspace::build_tree(&root))
+}
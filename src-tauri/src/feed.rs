@@ -0,0 +1,258 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::frontmatter::{self, Frontmatter};
+use crate::{assets, markdown, scopes, site_export, workspace};
+
+const RSS_FILE: &str = "feed.xml";
+const ATOM_FILE: &str = "atom.xml";
+const DEFAULT_LIMIT: usize = 20;
+const DATE_KEYS: &[&str] = &["date", "published", "pubDate", "created"];
+const UPDATED_KEYS: &[&str] = &["updated", "lastmod", "modified"];
+const SUMMARY_KEYS: &[&str] = &["summary", "description", "excerpt"];
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedOptions {
+    /// Address the site is published at; feed readers need absolute links.
+    pub site_url: String,
+    /// Defaults to the folder's name.
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Used for posts without an `author` field.
+    pub author: Option<String>,
+    /// "rss", "atom" or "both" (the default).
+    pub format: Option<String>,
+    /// Most recent posts kept, 20 by default.
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedExport {
+    pub files: Vec<String>,
+    pub entries: usize,
+}
+
+struct Entry {
+    title: String,
+    url: String,
+    published: DateTime<Utc>,
+    updated: DateTime<Utc>,
+    summary: Option<String>,
+    author: Option<String>,
+    tags: Vec<String>,
+    html: String,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn cdata(text: &str) -> String {
+    format!("<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(date) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(date.and_utc());
+        }
+    }
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok().and_then(|d| d.and_hms_opt(0, 0, 0)).map(|d| d.and_utc())
+}
+
+/// `site_url` joined with a path of the site, each segment percent-encoded.
+fn absolute(site_url: &str, path: &Path) -> String {
+    let segments: Vec<String> = path
+        .to_string_lossy()
+        .replace('\\', "/")
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(|s| utf8_percent_encode(s, SEGMENT).to_string())
+        .collect();
+    format!("{}/{}", site_url.trim_end_matches('/'), segments.join("/"))
+}
+
+/// Points the relative links of a post at the published site, since a feed
+/// reader shows the post away from it.
+fn absolute_links(md: &str, note: &Path, root: &Path, site_url: &str) -> String {
+    let dir = note.parent();
+    let replacements = markdown::links(md)
+        .iter()
+        .filter(|link| assets::is_local_link(link.url(md)))
+        .filter_map(|link| {
+            let url = link.url(md);
+            let (path, fragment) = url.split_at(url.find('#').unwrap_or(url.len()));
+            let target = assets::resolve_link(path, dir);
+            let relative = target.strip_prefix(root).ok()?;
+            let href = if markdown::is_markdown_file(&target) {
+                absolute(site_url, &site_export::page_path(root, &target))
+            } else {
+                absolute(site_url, relative)
+            };
+            Some(assets::replacement(md, link, format!("{}{}", href, fragment)))
+        })
+        .collect();
+    markdown::replace_ranges(md, replacements)
+}
+
+fn entry(note: &Path, root: &Path, options: &FeedOptions) -> Option<Entry> {
+    let md = std::fs::read_to_string(note).ok()?;
+    let fields: Frontmatter = frontmatter::parse(&md);
+    if fields.flag("draft") == Some(true) || fields.flag("published") == Some(false) {
+        return None;
+    }
+    let page = site_export::page_path(root, note);
+    // Section intros list the posts rather than being one
+    if page.file_name().is_some_and(|n| n == "index.html") {
+        return None;
+    }
+    let modified = std::fs::metadata(note).and_then(|m| m.modified()).ok().map(DateTime::<Utc>::from).unwrap_or_else(Utc::now);
+    let published = fields.first_text(DATE_KEYS).and_then(parse_date).unwrap_or(modified);
+    let updated = fields.first_text(UPDATED_KEYS).and_then(parse_date).unwrap_or(published).max(published);
+    let body = absolute_links(frontmatter::body(&md), note, root, &options.site_url);
+    Some(Entry {
+        title: frontmatter::title(&md, Some(note)),
+        url: absolute(&options.site_url, &page),
+        published,
+        updated,
+        summary: fields.first_text(SUMMARY_KEYS).map(str::to_string),
+        author: fields.text("author").map(str::to_string),
+        tags: fields.list("tags"),
+        html: site_export::render(&body),
+    })
+}
+
+fn rss(entries: &[Entry], options: &FeedOptions, title: &str, link: &str, feed_url: &str) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" xmlns:content=\"http://purl.org/rss/1.0/modules/content/\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n<channel>\n",
+    );
+    xml.push_str(&format!("<title>{}</title>\n<link>{}</link>\n", escape(title), escape(link)));
+    xml.push_str(&format!("<description>{}</description>\n", escape(options.description.as_deref().unwrap_or(title))));
+    xml.push_str(&format!("<atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>\n", escape(feed_url)));
+    if let Some(updated) = entries.iter().map(|e| e.updated).max() {
+        xml.push_str(&format!("<lastBuildDate>{}</lastBuildDate>\n", updated.to_rfc2822()));
+    }
+    xml.push_str("<generator>OhMyMarkdown</generator>\n");
+    for entry in entries {
+        xml.push_str("<item>\n");
+        xml.push_str(&format!("<title>{}</title>\n<link>{}</link>\n", escape(&entry.title), escape(&entry.url)));
+        xml.push_str(&format!("<guid isPermaLink=\"true\">{}</guid>\n", escape(&entry.url)));
+        xml.push_str(&format!("<pubDate>{}</pubDate>\n", entry.published.to_rfc2822()));
+        // RSS authors must be e-mail addresses; Dublin Core takes names
+        if let Some(author) = entry.author.as_deref().or(options.author.as_deref()) {
+            xml.push_str(&format!("<dc:creator>{}</dc:creator>\n", escape(author)));
+        }
+        for tag in &entry.tags {
+            xml.push_str(&format!("<category>{}</category>\n", escape(tag)));
+        }
+        if let Some(summary) = &entry.summary {
+            xml.push_str(&format!("<description>{}</description>\n", escape(summary)));
+        }
+        xml.push_str(&format!("<content:encoded>{}</content:encoded>\n", cdata(&entry.html)));
+        xml.push_str("</item>\n");
+    }
+    xml.push_str("</channel>\n</rss>\n");
+    xml
+}
+
+fn atom(entries: &[Entry], options: &FeedOptions, title: &str, link: &str, feed_url: &str) -> String {
+    let updated = entries.iter().map(|e| e.updated).max().unwrap_or_else(Utc::now);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("<title>{}</title>\n<id>{}</id>\n", escape(title), escape(link)));
+    xml.push_str(&format!("<link href=\"{}\" rel=\"alternate\" type=\"text/html\"/>\n", escape(link)));
+    xml.push_str(&format!("<link href=\"{}\" rel=\"self\" type=\"application/atom+xml\"/>\n", escape(feed_url)));
+    xml.push_str(&format!("<updated>{}</updated>\n", updated.to_rfc3339()));
+    if let Some(description) = &options.description {
+        xml.push_str(&format!("<subtitle>{}</subtitle>\n", escape(description)));
+    }
+    // Atom wants an author on the feed or on every entry
+    xml.push_str(&format!("<author><name>{}</name></author>\n", escape(options.author.as_deref().unwrap_or(title))));
+    xml.push_str("<generator>OhMyMarkdown</generator>\n");
+    for entry in entries {
+        xml.push_str("<entry>\n");
+        xml.push_str(&format!("<title>{}</title>\n<id>{}</id>\n", escape(&entry.title), escape(&entry.url)));
+        xml.push_str(&format!("<link href=\"{}\" rel=\"alternate\" type=\"text/html\"/>\n", escape(&entry.url)));
+        xml.push_str(&format!("<published>{}</published>\n<updated>{}</updated>\n", entry.published.to_rfc3339(), entry.updated.to_rfc3339()));
+        if let Some(author) = &entry.author {
+            xml.push_str(&format!("<author><name>{}</name></author>\n", escape(author)));
+        }
+        for tag in &entry.tags {
+            xml.push_str(&format!("<category term=\"{}\"/>\n", escape(tag)));
+        }
+        if let Some(summary) = &entry.summary {
+            xml.push_str(&format!("<summary>{}</summary>\n", escape(summary)));
+        }
+        xml.push_str(&format!("<content type=\"html\">{}</content>\n", escape(&entry.html)));
+        xml.push_str("</entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn export(root: &Path, folder: &Path, output: &Path, options: &FeedOptions) -> Result<FeedExport, String> {
+    let format = options.format.as_deref().unwrap_or("both");
+    let (with_rss, with_atom) = match format {
+        "rss" => (true, false),
+        "atom" => (false, true),
+        "both" => (true, true),
+        other => return Err(format!("Format de flux inconnu: {} (rss, atom ou both)", other)),
+    };
+    let mut entries: Vec<Entry> = workspace::markdown_files(folder)
+        .iter()
+        .filter(|note| !note.starts_with(output))
+        .filter_map(|note| entry(note, root, options))
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.published));
+    entries.truncate(options.limit.unwrap_or(DEFAULT_LIMIT));
+
+    let relative = folder.strip_prefix(root).unwrap_or(Path::new(""));
+    let dir = output.join(relative);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+    let name = folder.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let title = options.title.clone().filter(|t| !t.trim().is_empty()).unwrap_or(name);
+    let link = format!("{}/", absolute(&options.site_url, relative).trim_end_matches('/'));
+
+    let mut feeds = Vec::new();
+    if with_rss {
+        feeds.push((RSS_FILE, rss(&entries, options, &title, &link, &absolute(&options.site_url, &relative.join(RSS_FILE)))));
+    }
+    if with_atom {
+        feeds.push((ATOM_FILE, atom(&entries, options, &title, &link, &absolute(&options.site_url, &relative.join(ATOM_FILE)))));
+    }
+    let mut files = Vec::new();
+    for (file, xml) in feeds {
+        let path = dir.join(file);
+        std::fs::write(&path, xml).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))?;
+        files.push(path.to_string_lossy().to_string());
+    }
+    Ok(FeedExport { files, entries: entries.len() })
+}
+
+/// Writes an RSS and/or Atom feed of the posts of a workspace folder into
+/// the static site export at `output_dir`, next to the folder's pages. Posts
+/// are dated by their frontmatter `date`; drafts and the folder's intro note
+/// are left out.
+#[tauri::command]
+pub async fn export_feed(app: AppHandle, folder: String, output_dir: String, options: FeedOptions) -> Result<FeedExport, String> {
+    let workspace = app.state::<workspace::Workspace>();
+    let root = workspace.root()?;
+    let folder: PathBuf = workspace.resolve(&folder)?;
+    let output_dir = scopes::check(&app, &output_dir)?;
+    let site_url = options.site_url.trim();
+    if !site_url.starts_with("https://") && !site_url.starts_with("http://") {
+        return Err(format!("Adresse du site invalide: {}", options.site_url));
+    }
+    tauri::async_runtime::spawn_blocking(move || export(&root, &folder, &output_dir, &options))
+        .await
+        .map_err(|e| format!("Erreur lors de la génération du flux: {}", e))?
+}
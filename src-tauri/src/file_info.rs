@@ -0,0 +1,55 @@
+use serde::Serialize;
+use std::time::SystemTime;
+use tauri::AppHandle;
+
+use crate::markdown;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStatus {
+    pub branch: Option<String>,
+    /// Whether the file has uncommitted changes, or is untracked.
+    pub dirty: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileInfo {
+    pub size: u64,
+    /// RFC 3339; None where the filesystem doesn't record it.
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    pub read_only: bool,
+    /// None for files that are not text, and for large files.
+    pub word_count: Option<usize>,
+    /// None outside a git repository.
+    pub git: Option<GitStatus>,
+}
+
+fn timestamp(time: std::io::Result<SystemTime>) -> Option<String> {
+    time.ok().map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339())
+}
+
+/// Details for the document info panel.
+#[tauri::command]
+pub async fn get_file_info(app: AppHandle, path: String) -> Result<FileInfo, String> {
+    let path = crate::scopes::check(&app, &path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = path.as_path();
+        let metadata = std::fs::metadata(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+        let word_count = (metadata.len() <= crate::documents::LARGE_FILE_BYTES)
+            .then(|| std::fs::read_to_string(path).ok())
+            .flatten()
+            .map(|md| markdown::word_count(&md));
+        Ok(FileInfo {
+            size: metadata.len(),
+            created: timestamp(metadata.created()),
+            modified: timestamp(metadata.modified()),
+            read_only: metadata.permissions().readonly(),
+            word_count,
+            git: crate::git::file_state(path).map(|(branch, dirty)| GitStatus { branch, dirty }),
+        })
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la lecture des informations: {}", e))?
+}
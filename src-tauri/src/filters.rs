@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::{paths, settings, workspace};
+
+const FILTERS_DIR: &str = "filters";
+const SETTINGS_FILE: &str = "lua-filters.json";
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Conversions a filter runs in.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Import,
+    Export,
+    Both,
+}
+
+impl Stage {
+    fn includes(self, stage: Stage) -> bool {
+        self == Stage::Both || self == stage
+    }
+}
+
+/// Enabled filters by file name. Workspace filters are enabled here, per
+/// workspace, rather than in the workspace itself: opening a folder must not
+/// run code it ships.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Settings {
+    #[serde(default)]
+    app: BTreeMap<String, Stage>,
+    #[serde(default)]
+    workspaces: BTreeMap<String, BTreeMap<String, Stage>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LuaFilter {
+    pub name: String,
+    pub path: String,
+    /// "app" or "workspace".
+    pub source: &'static str,
+    /// None when disabled.
+    pub stage: Option<Stage>,
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_config_dir(app)?.join(SETTINGS_FILE))
+}
+
+fn load(app: &AppHandle) -> Result<Settings, String> {
+    let path = settings_path(app)?;
+    match std::fs::read_to_string(&path) {
+        Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Réglages des filtres invalides ({}): {}", path.display(), e)),
+        Err(_) => Ok(Settings::default()),
+    }
+}
+
+fn filter_dirs(app: &AppHandle) -> Result<Vec<(&'static str, PathBuf)>, String> {
+    let mut dirs = vec![("app", paths::app_data_subdir(app, FILTERS_DIR)?)];
+    if let Ok(root) = app.state::<workspace::Workspace>().root() {
+        dirs.push(("workspace", paths::workspace_config_dir(&root).join(FILTERS_DIR)));
+    }
+    Ok(dirs)
+}
+
+fn workspace_key(app: &AppHandle) -> Option<String> {
+    app.state::<workspace::Workspace>().root().ok().map(|root| root.to_string_lossy().to_string())
+}
+
+/// `.lua` files of `dir`, by name so a numeric prefix sets the order.
+fn lua_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|e| e.eq_ignore_ascii_case("lua")))
+        .collect();
+    files.sort();
+    files
+}
+
+fn filters(app: &AppHandle) -> Result<Vec<LuaFilter>, String> {
+    let settings = load(app)?;
+    let workspace = workspace_key(app).and_then(|key| settings.workspaces.get(&key));
+    // Settings can change which of the app's filters run, never enable the
+    // workspace's own
+    let overrides = settings::load(app).filters;
+    let mut out = Vec::new();
+    for (source, dir) in filter_dirs(app)? {
+        let enabled = if source == "app" { Some(&settings.app) } else { workspace };
+        for path in lua_files(&dir) {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let stage = match overrides.get(&name) {
+                Some(stage) if source == "app" => *stage,
+                _ => enabled.and_then(|e| e.get(&name)).copied(),
+            };
+            out.push(LuaFilter { name, path: path.to_string_lossy().to_string(), source, stage });
+        }
+    }
+    Ok(out)
+}
+
+/// `--lua-filter` arguments for the filters enabled for `stage`: the app's
+/// first, then the workspace's.
+pub fn pandoc_args(app: &AppHandle, stage: Stage) -> Result<Vec<String>, String> {
+    Ok(filters(app)?
+        .into_iter()
+        .filter(|f| f.stage.is_some_and(|s| s.includes(stage)))
+        .map(|f| format!("--lua-filter={}", f.path))
+        .collect())
+}
+
+/// Lua filters found in the app's filters folder and the workspace's
+/// `.ohmymarkdown/filters`.
+#[tauri::command]
+pub fn list_lua_filters(app: AppHandle) -> Result<Vec<LuaFilter>, String> {
+    filters(&app)
+}
+
+/// Folder holding the filters of `source` ("app" or "workspace"), created if
+/// needed so the user can drop files into it.
+#[tauri::command]
+pub fn get_lua_filters_dir(app: AppHandle, source: String) -> Result<String, String> {
+    let dir = filter_dirs(&app)?
+        .into_iter()
+        .find(|(s, _)| *s == source)
+        .map(|(_, dir)| dir)
+        .ok_or_else(|| format!("Source de filtres inconnue: {}", source))?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+    Ok(dir.to_string_lossy().to_string())
+}
+
+/// Enables the filter `name` of `source` for `stage`, or disables it when
+/// `stage` is None.
+#[tauri::command]
+pub fn set_lua_filter(app: AppHandle, source: String, name: String, stage: Option<Stage>) -> Result<(), String> {
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("Nom de filtre invalide: {}", name));
+    }
+    let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+    let mut settings = load(&app)?;
+    let enabled = match source.as_str() {
+        "app" => &mut settings.app,
+        "workspace" => {
+            let key = workspace_key(&app).ok_or("Aucun espace de travail ouvert")?;
+            settings.workspaces.entry(key).or_default()
+        }
+        _ => return Err(format!("Source de filtres inconnue: {}", source)),
+    };
+    match stage {
+        Some(stage) => {
+            enabled.insert(name, stage);
+        }
+        None => {
+            enabled.remove(&name);
+        }
+    }
+    settings.workspaces.retain(|_, enabled| !enabled.is_empty());
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    let path = settings_path(&app)?;
+    std::fs::write(&path, json).map_err(|e| format!("Impossible d'enregistrer les réglages des filtres: {}", e))
+}
@@ -0,0 +1,211 @@
+use std::path::Path;
+
+use crate::markdown;
+
+/// The subset of YAML found in note frontmatter: top-level keys holding a
+/// scalar or a list of scalars. Nested mappings are skipped.
+pub enum Value {
+    Text(String),
+    List(Vec<String>),
+}
+
+#[derive(Default)]
+pub struct Frontmatter {
+    fields: Vec<(String, Value)>,
+}
+
+impl Frontmatter {
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// A non-empty scalar field.
+    pub fn text(&self, key: &str) -> Option<&str> {
+        match self.get(key) {
+            Some(Value::Text(text)) if !text.is_empty() => Some(text),
+            _ => None,
+        }
+    }
+
+    /// The first of `keys` holding a non-empty scalar.
+    pub fn first_text(&self, keys: &[&str]) -> Option<&str> {
+        keys.iter().find_map(|k| self.text(k))
+    }
+
+    /// A list field; a scalar is read as comma-separated items.
+    pub fn list(&self, key: &str) -> Vec<String> {
+        match self.get(key) {
+            Some(Value::List(items)) => items.clone(),
+            Some(Value::Text(text)) => text.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn flag(&self, key: &str) -> Option<bool> {
+        match self.text(key)?.to_lowercase().as_str() {
+            "true" | "yes" | "on" => Some(true),
+            "false" | "no" | "off" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        return value[1..value.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\");
+    }
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        return value[1..value.len() - 1].replace("''", "'");
+    }
+    // Unquoted scalars may end with a comment
+    value.split(" #").next().unwrap_or(value).trim().to_string()
+}
+
+/// Items of an inline `[a, "b, c"]` list.
+fn inline_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    for c in value.chars() {
+        match quote {
+            None if c == ',' => {
+                items.push(std::mem::take(&mut current));
+                continue;
+            }
+            None if c == '"' || c == '\'' => quote = Some(c),
+            Some(q) if q == c => quote = None,
+            _ => {}
+        }
+        current.push(c);
+    }
+    items.push(current);
+    items.iter().map(|item| unquote(item)).filter(|item| !item.is_empty()).collect()
+}
+
+/// Top-level `key:` name of a frontmatter line.
+fn key_of(line: &str) -> Option<&str> {
+    if line.starts_with([' ', '\t', '-', '#']) {
+        return None;
+    }
+    let (key, _) = line.split_once(':')?;
+    let key = key.trim();
+    (!key.is_empty() && !key.contains(' ')).then_some(key)
+}
+
+/// Lines of the frontmatter block, without its `---` markers.
+fn block(md: &str) -> Option<&str> {
+    let len = markdown::frontmatter_len(md);
+    if len == 0 {
+        return None;
+    }
+    let start = md.find('\n')? + 1;
+    let inner = &md[start..len];
+    let end = inner.trim_end_matches('\n').rfind('\n').map(|i| i + 1).unwrap_or(0);
+    Some(&inner[..end])
+}
+
+pub fn parse(md: &str) -> Frontmatter {
+    let Some(block) = block(md) else { return Frontmatter::default() };
+    let lines: Vec<&str> = block.lines().collect();
+    let mut fields = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+        let Some(key) = key_of(line) else { continue };
+        let rest = line[line.find(':').unwrap_or(0) + 1..].trim();
+        let value = if rest.starts_with('[') && rest.ends_with(']') {
+            Value::List(inline_list(&rest[1..rest.len() - 1]))
+        } else if rest.is_empty() {
+            // A block list, or a nested mapping which is skipped
+            let mut items = Vec::new();
+            while i < lines.len() && key_of(lines[i]).is_none() {
+                if let Some(item) = lines[i].trim_start().strip_prefix('-') {
+                    items.push(unquote(item));
+                }
+                i += 1;
+            }
+            Value::List(items)
+        } else {
+            Value::Text(unquote(rest))
+        };
+        fields.push((key.to_string(), value));
+    }
+    Frontmatter { fields }
+}
+
+/// The document without its frontmatter.
+pub fn body(md: &str) -> &str {
+    md[markdown::frontmatter_len(md)..].trim_start_matches('\n')
+}
+
+/// The first heading, for documents without a title field.
+fn first_heading(body: &str) -> Option<String> {
+    let mut fences = markdown::FenceState::default();
+    body.lines()
+        .filter(|line| !fences.is_code(line))
+        .find_map(|line| line.strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+}
+
+/// The document's title: its title field, else its first heading, else the
+/// file name.
+pub fn title(md: &str, path: Option<&Path>) -> String {
+    parse(md)
+        .text("title")
+        .map(str::to_string)
+        .or_else(|| first_heading(body(md)))
+        .or_else(|| path.and_then(|p| p.file_stem()).map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or_default()
+}
+
+/// The value as a YAML scalar, double-quoted when it would not read back as
+/// the same string.
+pub fn quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && !value.contains(": ")
+        && !value.contains(" #")
+        && !value.starts_with(|c: char| c.is_whitespace() || "-?:,[]{}#&*!|>'\"%@`".contains(c))
+        && !value.ends_with(|c: char| c.is_whitespace() || c == ':');
+    if plain {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Sets a scalar field, replacing the existing one in place or adding it at
+/// the end of the frontmatter, which is created if needed.
+pub fn set(md: &str, key: &str, value: &str) -> String {
+    let line = format!("{}: {}\n", key, quote(value));
+    let Some(block) = block(md) else {
+        return format!("---\n{}---\n\n{}", line, md);
+    };
+    let start = md.find('\n').map(|i| i + 1).unwrap_or(0);
+    let mut out = String::from(&md[..start]);
+    let mut lines = block.split_inclusive('\n').peekable();
+    let mut replaced = false;
+    while let Some(current) = lines.next() {
+        if key_of(current) != Some(key) {
+            out.push_str(current);
+            continue;
+        }
+        // Drop the old value along with its list items or nested lines
+        while lines.peek().is_some_and(|next| key_of(next).is_none() && !next.trim().is_empty()) {
+            lines.next();
+        }
+        if !replaced {
+            out.push_str(&line);
+            replaced = true;
+        }
+    }
+    if !replaced {
+        if !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(&line);
+    }
+    out.push_str(&md[start + block.len()..]);
+    out
+}
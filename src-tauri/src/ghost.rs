@@ -0,0 +1,190 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::publish::{self, Published};
+use crate::{http, pandoc};
+
+const TARGET: &str = "ghost";
+const ID_KEY: &str = "ghost_id";
+/// Tokens are only accepted for five minutes.
+const TOKEN_LIFETIME_SECS: i64 = 5 * 60;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GhostSite {
+    /// Address of the site, e.g. "https://blog.example.com".
+    url: String,
+    /// Admin API key of a custom integration, "<id>:<secret>".
+    admin_api_key: String,
+}
+
+fn split_key(key: &str) -> Result<(&str, Vec<u8>), String> {
+    let invalid = || "Clé d'API Admin invalide: attendu <id>:<secret>".to_string();
+    let (id, secret) = key.trim().split_once(':').ok_or_else(invalid)?;
+    if id.is_empty() || secret.len() % 2 != 0 {
+        return Err(invalid());
+    }
+    let secret = (0..secret.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&secret[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| invalid())?;
+    Ok((id, secret))
+}
+
+pub fn validate(account: &Value) -> Result<(), String> {
+    let site: GhostSite = serde_json::from_value(account.clone()).map_err(|e| format!("Compte Ghost invalide: {}", e))?;
+    if !site.url.starts_with("https://") && !site.url.starts_with("http://") {
+        return Err(format!("Adresse du site invalide: {}", site.url));
+    }
+    split_key(&site.admin_api_key).map(|_| ())
+}
+
+/// Short-lived JWT signed with the integration's secret, as the Admin API
+/// expects.
+fn token(key: &str) -> Result<String, String> {
+    let (id, secret) = split_key(key)?;
+    let now = chrono::Utc::now().timestamp();
+    let header = json!({ "alg": "HS256", "typ": "JWT", "kid": id });
+    let claims = json!({ "iat": now, "exp": now + TOKEN_LIFETIME_SECS, "aud": "/admin/" });
+    let unsigned = format!("{}.{}", URL_SAFE_NO_PAD.encode(header.to_string()), URL_SAFE_NO_PAD.encode(claims.to_string()));
+    let mut mac = Hmac::<Sha256>::new_from_slice(&secret).map_err(|e| e.to_string())?;
+    mac.update(unsigned.as_bytes());
+    Ok(format!("{}.{}", unsigned, URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes())))
+}
+
+#[derive(Deserialize)]
+struct Images {
+    images: Vec<Image>,
+}
+
+#[derive(Deserialize)]
+struct Image {
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct Posts {
+    posts: Vec<Post>,
+}
+
+#[derive(Deserialize)]
+struct Post {
+    id: String,
+    url: String,
+    updated_at: String,
+}
+
+struct Client {
+    agent: ureq::Agent,
+    base: String,
+    auth: String,
+}
+
+impl Client {
+    fn new(site: &GhostSite) -> Result<Self, String> {
+        Ok(Client {
+            agent: http::agent(),
+            base: format!("{}/ghost/api/admin", site.url.trim_end_matches('/')),
+            auth: format!("Ghost {}", token(&site.admin_api_key)?),
+        })
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        self.agent
+            .request(method, &format!("{}/{}", self.base, path))
+            .set("Authorization", &self.auth)
+            .set("Accept-Version", "v5.0")
+    }
+
+    fn upload(&self, file: &Path) -> Result<String, String> {
+        let bytes = std::fs::read(file).map_err(|e| format!("Impossible de lire {}: {}", file.display(), e))?;
+        let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let (content_type, body) = http::multipart(&[("purpose", "image")], "file", &name, &bytes, publish::image_mime(file));
+        let images: Images = self
+            .request("POST", "images/upload/")
+            .set("Content-Type", &content_type)
+            .send_bytes(&body)
+            .map_err(http::error_message)?
+            .into_json()
+            .map_err(|e| format!("Réponse inattendue de Ghost: {}", e))?;
+        images.images.into_iter().next().map(|i| i.url).ok_or_else(|| "Ghost n'a pas renvoyé l'image".to_string())
+    }
+
+    fn post(&self, response: Result<ureq::Response, ureq::Error>) -> Result<Post, String> {
+        let posts: Posts = response
+            .map_err(http::error_message)?
+            .into_json()
+            .map_err(|e| format!("Réponse inattendue de Ghost: {}", e))?;
+        posts.posts.into_iter().next().ok_or_else(|| "Ghost n'a pas renvoyé l'article".to_string())
+    }
+}
+
+/// Publishes the document to a Ghost site through the Admin API, or updates
+/// the post it was published as before. The HTML is converted to Ghost's
+/// editor format on the server; local images, the cover image included, are
+/// uploaded first.
+#[tauri::command]
+pub async fn publish_to_ghost(app: AppHandle, account: String, content: String, path: Option<String>) -> Result<Published, String> {
+    publish::blocking(move || {
+        let site: GhostSite = publish::account(&app, TARGET, &account)?;
+        let client = Client::new(&site)?;
+        let document_path = crate::scopes::check_optional(&app, path.as_deref())?;
+        let document_dir = document_path.as_deref().and_then(Path::parent);
+        let post = publish::post(&content, document_path.as_deref(), ID_KEY);
+
+        let (body, images_uploaded) = publish::upload_images(&app, &post.body, document_dir, |file| client.upload(file))?;
+        let mut fields = json!({
+            "title": post.title,
+            "html": pandoc::to_html_fragment(&body, document_dir)?,
+            "status": if post.draft { "draft" } else { "published" },
+            "tags": post.tags.iter().map(|name| json!({ "name": name })).collect::<Vec<_>>(),
+        });
+        if let Some(summary) = &post.summary {
+            fields["custom_excerpt"] = json!(summary);
+        }
+        if let Some(url) = &post.canonical_url {
+            fields["canonical_url"] = json!(url);
+        }
+        if let Some(image) = &post.feature_image {
+            fields["feature_image"] = json!(match publish::local_image(&app, image, document_dir)? {
+                Some(file) => client.upload(&file)?,
+                None => image.clone(),
+            });
+        }
+
+        // Updates must carry the post's current updated_at
+        let existing = match &post.id {
+            Some(id) => match client.request("GET", &format!("posts/{}/", publish::checked_id(id, |c| c.is_ascii_hexdigit())?)).call() {
+                Err(ureq::Error::Status(404, _)) => None,
+                response => Some(client.post(response)?),
+            },
+            None => None,
+        };
+        let saved = match existing {
+            Some(existing) => {
+                fields["updated_at"] = json!(existing.updated_at);
+                client.post(
+                    client
+                        .request("PUT", &format!("posts/{}/", existing.id))
+                        .query("source", "html")
+                        .send_json(json!({ "posts": [fields] })),
+                )?
+            }
+            None => client.post(client.request("POST", "posts/").query("source", "html").send_json(json!({ "posts": [fields] })))?,
+        };
+        Ok(Published {
+            markdown: publish::record_id(&content, ID_KEY, &saved.id),
+            id: saved.id,
+            url: saved.url,
+            images_uploaded,
+        })
+    })
+    .await
+}
@@ -0,0 +1,185 @@
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+
+use crate::publish::{self, Published};
+use crate::workspace::Workspace;
+use crate::{assets, http};
+
+const TARGET: &str = "github";
+const ID_KEY: &str = "gist_id";
+const API_URL: &str = "https://api.github.com/gists";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitHubAccount {
+    /// Personal access token with the "gist" scope.
+    token: String,
+}
+
+pub fn validate(account: &Value) -> Result<(), String> {
+    let account: GitHubAccount = serde_json::from_value(account.clone()).map_err(|e| format!("Compte GitHub invalide: {}", e))?;
+    if account.token.trim().is_empty() {
+        return Err("Jeton d'accès GitHub requis".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct Gist {
+    id: String,
+    html_url: String,
+    #[serde(default)]
+    files: BTreeMap<String, Option<GistFile>>,
+}
+
+#[derive(Deserialize)]
+struct GistFile {
+    filename: String,
+    content: Option<String>,
+    #[serde(default)]
+    truncated: bool,
+    raw_url: Option<String>,
+}
+
+fn request(agent: &ureq::Agent, method: &str, url: &str, token: Option<&str>) -> ureq::Request {
+    let request = agent
+        .request(method, url)
+        .set("Accept", "application/vnd.github+json")
+        .set("X-GitHub-Api-Version", "2022-11-28");
+    match token {
+        Some(token) => request.set("Authorization", &format!("Bearer {}", token.trim())),
+        None => request,
+    }
+}
+
+fn file_name(path: &Path) -> Result<String, String> {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).ok_or_else(|| format!("Chemin invalide: {}", path.display()))
+}
+
+/// Shares the document as a GitHub gist along with `companions` (other text
+/// files), or updates the gist it was shared as before. Gists are flat, so
+/// files are sent under their names only.
+#[tauri::command]
+pub async fn push_gist(
+    app: AppHandle,
+    account: String,
+    content: String,
+    path: Option<String>,
+    companions: Option<Vec<String>>,
+    public: Option<bool>,
+) -> Result<Published, String> {
+    publish::blocking(move || {
+        let settings: GitHubAccount = publish::account(&app, TARGET, &account)?;
+        let document_path = crate::scopes::check_optional(&app, path.as_deref())?;
+        let post = publish::post(&content, document_path.as_deref(), ID_KEY);
+        let name = match &document_path {
+            Some(path) => file_name(path)?,
+            None if post.title.is_empty() => "document.md".to_string(),
+            None => format!("{}.md", post.title.replace(['/', '\\'], "-")),
+        };
+
+        let mut files = Map::new();
+        files.insert(name.clone(), json!({ "content": content }));
+        for companion in companions.unwrap_or_default() {
+            let companion = crate::scopes::check(&app, &companion)?;
+            let text = std::fs::read_to_string(&companion)
+                .map_err(|_| format!("{} n'est pas un fichier texte lisible; les gists ne contiennent que du texte", companion.display()))?;
+            let companion_name = file_name(&companion)?;
+            if files.contains_key(&companion_name) {
+                return Err(format!("Deux fichiers s'appellent {}", companion_name));
+            }
+            files.insert(companion_name, json!({ "content": text }));
+        }
+        let description = post.summary.clone().unwrap_or_else(|| post.title.clone());
+
+        let agent = http::agent();
+        let token = Some(settings.token.as_str());
+        let response = match &post.id {
+            Some(id) => {
+                let body = json!({ "description": description, "files": files });
+                match request(&agent, "PATCH", &format!("{}/{}", API_URL, publish::checked_id(id, |c| c.is_ascii_hexdigit())?), token).send_json(&body) {
+                    Err(ureq::Error::Status(404, _)) => None,
+                    result => Some(result),
+                }
+            }
+            None => None,
+        };
+        let response = match response {
+            Some(response) => response,
+            None => request(&agent, "POST", API_URL, token).send_json(json!({
+                "description": description,
+                "public": public.unwrap_or(false),
+                "files": files,
+            })),
+        };
+        let gist: Gist = response
+            .map_err(http::error_message)?
+            .into_json()
+            .map_err(|e| format!("Réponse inattendue de GitHub: {}", e))?;
+        Ok(Published {
+            markdown: publish::record_id(&content, ID_KEY, &gist.id),
+            id: gist.id,
+            url: gist.html_url,
+            images_uploaded: 0,
+        })
+    })
+    .await
+}
+
+/// The gist id in a gist URL, or the id itself.
+fn gist_id(url: &str) -> Result<String, String> {
+    let url = url.trim().split(['#', '?']).next().unwrap_or_default().trim_end_matches('/');
+    let id = url.rsplit('/').next().unwrap_or_default().trim_end_matches(".git");
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Adresse de gist invalide: {}", url));
+    }
+    Ok(id.to_string())
+}
+
+/// Downloads the files of a gist into a folder of the workspace (its root by
+/// default) and returns their paths. Existing files are never overwritten;
+/// an incoming file with a taken name gets a numbered one.
+#[tauri::command]
+pub async fn import_gist(app: AppHandle, url: String, dest_dir: Option<String>, account: Option<String>) -> Result<Vec<String>, String> {
+    publish::blocking(move || {
+        let workspace = app.state::<Workspace>();
+        let dir = workspace.resolve(dest_dir.as_deref().unwrap_or(""))?;
+        // A token raises the rate limit and gives access to secret gists
+        let token = match &account {
+            Some(name) => Some(publish::account::<GitHubAccount>(&app, TARGET, name)?.token),
+            None => None,
+        };
+
+        let agent = http::agent();
+        let gist: Gist = request(&agent, "GET", &format!("{}/{}", API_URL, gist_id(&url)?), token.as_deref())
+            .call()
+            .map_err(http::error_message)?
+            .into_json()
+            .map_err(|e| format!("Réponse inattendue de GitHub: {}", e))?;
+
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+        let mut written = Vec::new();
+        for file in gist.files.into_values().flatten() {
+            let content = match (file.content, file.truncated, &file.raw_url) {
+                (Some(content), false, _) => content,
+                (_, _, Some(raw_url)) => agent
+                    .get(raw_url)
+                    .call()
+                    .map_err(http::error_message)?
+                    .into_string()
+                    .map_err(|e| format!("Erreur pendant le téléchargement de {}: {}", file.filename, e))?,
+                _ => continue,
+            };
+            // Names come from the server; keep only the last component
+            let name = file_name(Path::new(&file.filename))?;
+            let target = assets::unique_path(&dir, &name);
+            std::fs::write(&target, content).map_err(|e| format!("Impossible d'enregistrer {}: {}", target.display(), e))?;
+            written.push(target.to_string_lossy().to_string());
+        }
+        Ok(written)
+    })
+    .await
+}
@@ -0,0 +1,295 @@
+use git2::{IndexAddOption, Repository, Signature, Sort, Status, StatusOptions};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::history::{self, VersionDiff};
+use crate::workspace::Workspace;
+
+const DEFAULT_LOG_LIMIT: usize = 100;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitFile {
+    pub path: String,
+    /// "new", "modified", "deleted", "renamed" or "conflicted".
+    pub status: &'static str,
+    /// Whether the change is already in the index.
+    pub staged: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitStatusReport {
+    /// None on a detached HEAD.
+    pub branch: Option<String>,
+    pub files: Vec<GitFile>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitCommit {
+    pub id: String,
+    pub short_id: String,
+    pub summary: String,
+    pub author: String,
+    /// RFC 3339.
+    pub time: String,
+}
+
+fn git_error(e: git2::Error) -> String {
+    format!("Erreur git: {}", e.message())
+}
+
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Erreur lors de l'opération git: {}", e))?
+}
+
+/// Resolves symlinks in the existing part of `path`, so it can be compared
+/// with the repository's work directory. Deleted files keep their name.
+fn canonical(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => canonical(parent).join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+struct Repo {
+    repo: Repository,
+    workdir: PathBuf,
+}
+
+impl Repo {
+    fn open(path: &Path) -> Result<Repo, String> {
+        let repo = Repository::discover(path).map_err(|_| "Le dossier de travail n'est pas un dépôt git".to_string())?;
+        let workdir = repo.workdir().map(canonical).ok_or("Le dépôt git n'a pas de copie de travail")?;
+        Ok(Repo { repo, workdir })
+    }
+
+    /// `path` relative to the work directory, as git expects it.
+    fn relative(&self, path: &Path) -> Result<PathBuf, String> {
+        canonical(path)
+            .strip_prefix(&self.workdir)
+            .map(Path::to_path_buf)
+            .map_err(|_| format!("{} n'est pas dans le dépôt git", path.display()))
+    }
+
+    fn branch(&self) -> Option<String> {
+        let head = self.repo.head().ok()?;
+        head.is_branch().then(|| head.shorthand().map(str::to_string)).flatten()
+    }
+
+    fn statuses(&self, pathspec: &Path) -> Result<git2::Statuses<'_>, String> {
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true).renames_head_to_index(true);
+        if !pathspec.as_os_str().is_empty() {
+            options.pathspec(pathspec);
+        }
+        self.repo.statuses(Some(&mut options)).map_err(git_error)
+    }
+
+    /// Content of `path` (relative) in the commit `rev` points to.
+    fn content_at(&self, rev: &str, path: &Path) -> Result<String, String> {
+        let tree = self.repo.revparse_single(rev).and_then(|o| o.peel_to_tree()).map_err(git_error)?;
+        let entry = tree
+            .get_path(path)
+            .map_err(|_| format!("{} n'existe pas dans la révision {}", path.display(), rev))?;
+        let blob = entry.to_object(&self.repo).and_then(|o| o.peel_to_blob()).map_err(git_error)?;
+        Ok(String::from_utf8_lossy(blob.content()).to_string())
+    }
+}
+
+fn status_name(status: Status) -> &'static str {
+    if status.is_conflicted() {
+        "conflicted"
+    } else if status.intersects(Status::INDEX_NEW | Status::WT_NEW) {
+        "new"
+    } else if status.intersects(Status::INDEX_DELETED | Status::WT_DELETED) {
+        "deleted"
+    } else if status.intersects(Status::INDEX_RENAMED | Status::WT_RENAMED) {
+        "renamed"
+    } else {
+        "modified"
+    }
+}
+
+/// Branch of the repository containing `path` and whether the file has
+/// uncommitted changes, or None outside a repository.
+pub fn file_state(path: &Path) -> Option<(Option<String>, bool)> {
+    let repo = Repo::open(path.parent()?).ok()?;
+    let relative = repo.relative(path).ok()?;
+    let dirty = repo.repo.status_file(&relative).is_ok_and(|s| !s.is_empty() && !s.is_ignored());
+    Some((repo.branch(), dirty))
+}
+
+/// Resolves a path given by the frontend and checks it is in the workspace.
+fn workspace_path(app: &AppHandle, path: &str) -> Result<(PathBuf, Repo), String> {
+    let workspace = app.state::<Workspace>();
+    let path = workspace.resolve(path)?;
+    Ok((path, Repo::open(&workspace.root()?)?))
+}
+
+/// Changed files of the workspace, sorted by path.
+#[tauri::command]
+pub async fn git_status(app: AppHandle) -> Result<GitStatusReport, String> {
+    blocking(move || {
+        let root = app.state::<Workspace>().root()?;
+        let repo = Repo::open(&root)?;
+        let statuses = repo.statuses(&repo.relative(&root)?)?;
+        let index_changes = Status::INDEX_NEW
+            | Status::INDEX_MODIFIED
+            | Status::INDEX_DELETED
+            | Status::INDEX_RENAMED
+            | Status::INDEX_TYPECHANGE;
+        let mut files: Vec<GitFile> = statuses
+            .iter()
+            .filter(|entry| !entry.status().is_ignored())
+            .filter_map(|entry| {
+                let path = entry.path()?;
+                Some(GitFile {
+                    path: repo.workdir.join(path).to_string_lossy().to_string(),
+                    status: status_name(entry.status()),
+                    staged: entry.status().intersects(index_changes),
+                })
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(GitStatusReport { branch: repo.branch(), files })
+    })
+    .await
+}
+
+/// Commits the given files, or every change in the workspace when `paths`
+/// is empty, and returns the new commit's id.
+#[tauri::command]
+pub async fn git_commit(app: AppHandle, message: String, paths: Option<Vec<String>>) -> Result<String, String> {
+    if message.trim().is_empty() {
+        return Err("Le message de commit est vide".to_string());
+    }
+    blocking(move || {
+        let workspace = app.state::<Workspace>();
+        let root = workspace.root()?;
+        let repo = Repo::open(&root)?;
+        let mut index = repo.repo.index().map_err(git_error)?;
+        match paths.filter(|p| !p.is_empty()) {
+            Some(paths) => {
+                for path in paths {
+                    let path = workspace.resolve(&path)?;
+                    let relative = repo.relative(&path)?;
+                    if path.exists() {
+                        index.add_path(&relative).map_err(git_error)?;
+                    } else {
+                        index.remove_path(&relative).map_err(git_error)?;
+                    }
+                }
+            }
+            None => {
+                let scope = repo.relative(&root)?.join("*");
+                index.add_all([&scope], IndexAddOption::DEFAULT, None).map_err(git_error)?;
+                // add_all skips deletions
+                index.update_all([&scope], None).map_err(git_error)?;
+            }
+        }
+        index.write().map_err(git_error)?;
+
+        let tree = index.write_tree().and_then(|id| repo.repo.find_tree(id)).map_err(git_error)?;
+        let parent = repo.repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        if parent.as_ref().is_some_and(|p| p.tree_id() == tree.id()) {
+            return Err("Aucune modification à enregistrer".to_string());
+        }
+        // Writers may never have configured git
+        let signature = repo
+            .repo
+            .signature()
+            .or_else(|_| Signature::now("OhMyMarkdown", "ohmymarkdown@localhost"))
+            .map_err(git_error)?;
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let id = repo.repo.commit(Some("HEAD"), &signature, &signature, &message, &tree, &parents).map_err(git_error)?;
+        Ok(id.to_string())
+    })
+    .await
+}
+
+/// Commits of the current branch, newest first, limited to those changing
+/// `path` when one is given.
+#[tauri::command]
+pub async fn git_log(app: AppHandle, path: Option<String>, limit: Option<usize>) -> Result<Vec<GitCommit>, String> {
+    blocking(move || {
+        let (repo, relative) = match path {
+            Some(path) => {
+                let (path, repo) = workspace_path(&app, &path)?;
+                let relative = repo.relative(&path)?;
+                (repo, Some(relative))
+            }
+            None => (Repo::open(&app.state::<Workspace>().root()?)?, None),
+        };
+        if repo.repo.head().is_err() {
+            return Ok(Vec::new());
+        }
+        let mut walk = repo.repo.revwalk().map_err(git_error)?;
+        walk.push_head().map_err(git_error)?;
+        walk.set_sorting(Sort::TIME).map_err(git_error)?;
+
+        let blob_at = |commit: &git2::Commit, path: &Path| commit.tree().ok()?.get_path(path).ok().map(|e| e.id());
+        let mut commits = Vec::new();
+        for id in walk {
+            if commits.len() >= limit.unwrap_or(DEFAULT_LOG_LIMIT) {
+                break;
+            }
+            let commit = id.and_then(|id| repo.repo.find_commit(id)).map_err(git_error)?;
+            if let Some(relative) = &relative {
+                let before = commit.parent(0).ok().and_then(|p| blob_at(&p, relative));
+                if blob_at(&commit, relative) == before {
+                    continue;
+                }
+            }
+            let time = chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+                .map(|t| t.with_timezone(&chrono::Local).to_rfc3339())
+                .unwrap_or_default();
+            let id = commit.id().to_string();
+            commits.push(GitCommit {
+                short_id: id[..7.min(id.len())].to_string(),
+                id,
+                summary: commit.summary().unwrap_or_default().to_string(),
+                author: commit.author().name().unwrap_or_default().to_string(),
+                time,
+            });
+        }
+        Ok(commits)
+    })
+    .await
+}
+
+/// Changes of the file on disk since the revision (HEAD by default).
+#[tauri::command]
+pub async fn git_diff(app: AppHandle, path: String, rev: Option<String>) -> Result<VersionDiff, String> {
+    blocking(move || {
+        let (path, repo) = workspace_path(&app, &path)?;
+        let old = repo.content_at(rev.as_deref().unwrap_or("HEAD"), &repo.relative(&path)?)?;
+        let current = std::fs::read_to_string(&path).unwrap_or_default();
+        Ok(history::diff(&old, &current))
+    })
+    .await
+}
+
+/// Puts the file back as it was at the revision (HEAD by default) and
+/// returns that content.
+#[tauri::command]
+pub async fn git_restore(app: AppHandle, path: String, rev: Option<String>) -> Result<String, String> {
+    blocking(move || {
+        let (path, repo) = workspace_path(&app, &path)?;
+        let content = repo.content_at(rev.as_deref().unwrap_or("HEAD"), &repo.relative(&path)?)?;
+        crate::locks::ensure_writable(&app, &path)?;
+        if let Some(current) = std::fs::read_to_string(&path).ok().filter(|_| history::keeps_history(&path)) {
+            history::record(&app, &path, &current)?;
+        }
+        crate::safe_save::write_document(&app, &path, content.as_bytes())?;
+        Ok(content)
+    })
+    .await
+}
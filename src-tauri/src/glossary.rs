@@ -0,0 +1,177 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::{markdown, scopes};
+
+const GLOSSARY_FILE: &str = "glossary.json";
+
+pub type Glossary = BTreeMap<String, String>;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TermOccurrence {
+    /// UTF-16 offset in the markdown source.
+    pub offset: usize,
+    pub length: usize,
+    pub line: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlossaryMatch {
+    pub term: String,
+    pub definition: String,
+    pub occurrences: Vec<TermOccurrence>,
+}
+
+fn glossary_path(workspace_dir: &Path) -> PathBuf {
+    crate::paths::workspace_config_dir(workspace_dir).join(GLOSSARY_FILE)
+}
+
+pub fn load(workspace_dir: &Path) -> Result<Glossary, String> {
+    let path = glossary_path(workspace_dir);
+    if !path.exists() {
+        return Ok(Glossary::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Impossible de lire le glossaire: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Glossaire invalide ({}): {}", path.display(), e))
+}
+
+fn save(workspace_dir: &Path, glossary: &Glossary) -> Result<(), String> {
+    let path = glossary_path(workspace_dir);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Impossible de créer le dossier {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(glossary).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json)
+        .map_err(|e| format!("Impossible d'enregistrer le glossaire: {}", e))
+}
+
+/// Source byte ranges of whole-word occurrences of `term` in the document's
+/// prose (code, URLs and frontmatter excluded).
+fn find_term(md: &str, plain: &markdown::PlainText, term: &str) -> Vec<(usize, usize)> {
+    let text = &plain.text;
+    let is_word_char = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+
+    text.match_indices(term)
+        .filter(|&(i, _)| {
+            !is_word_char(text[..i].chars().next_back()) && !is_word_char(text[i + term.len()..].chars().next())
+        })
+        .map(|(i, _)| plain.source_range(i, i + term.len()))
+        // Skip occurrences split by inline markup, since they can't be wrapped as-is
+        .filter(|&(start, end)| &md[start..end] == term)
+        .collect()
+}
+
+fn escape_attr(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn is_html_format(to_format: &str) -> bool {
+    // PDF goes through wkhtmltopdf, so it renders HTML too
+    matches!(to_format, "html" | "html4" | "html5" | "epub" | "epub2" | "epub3" | "pdf" | "revealjs" | "slidy" | "s5" | "dzslides")
+}
+
+/// Prepares a document for export: wraps glossary terms in `<abbr>` tags for
+/// HTML-based formats and appends an abbreviations appendix listing the terms
+/// the document actually uses.
+pub fn apply_to_export(md: &str, glossary: &Glossary, to_format: &str) -> String {
+    if glossary.is_empty() {
+        return md.to_string();
+    }
+    let plain = markdown::to_plain_text(md);
+
+    let mut used: Vec<(&String, &String)> = Vec::new();
+    let mut wraps: Vec<(usize, usize, &String)> = Vec::new();
+    for (term, definition) in glossary {
+        let ranges = find_term(md, &plain, term);
+        if ranges.is_empty() {
+            continue;
+        }
+        used.push((term, definition));
+        wraps.extend(ranges.into_iter().map(|(s, e)| (s, e, definition)));
+    }
+    if used.is_empty() {
+        return md.to_string();
+    }
+
+    let mut result = String::with_capacity(md.len());
+    if is_html_format(to_format) {
+        // Longer terms win when two glossary entries overlap ("API" in "REST API")
+        wraps.sort_by_key(|&(s, e, _)| (s, std::cmp::Reverse(e)));
+        let mut pos = 0;
+        for (start, end, definition) in wraps {
+            if start < pos {
+                continue;
+            }
+            result.push_str(&md[pos..start]);
+            result.push_str(&format!("<abbr title=\"{}\">{}</abbr>", escape_attr(definition), &md[start..end]));
+            pos = end;
+        }
+        result.push_str(&md[pos..]);
+    } else {
+        result.push_str(md);
+    }
+
+    result.truncate(result.trim_end().len());
+    result.push_str("\n\n## Abréviations\n\n");
+    for (term, definition) in used {
+        result.push_str(&format!("{}\n:   {}\n\n", term, definition));
+    }
+    result
+}
+
+#[tauri::command]
+pub fn get_glossary(app: AppHandle, workspace_dir: &str) -> Result<Glossary, String> {
+    load(&scopes::check(&app, workspace_dir)?)
+}
+
+#[tauri::command]
+pub fn set_glossary_entry(app: AppHandle, workspace_dir: &str, term: &str, definition: &str) -> Result<(), String> {
+    let workspace_dir = &scopes::check(&app, workspace_dir)?;
+    let term = term.trim();
+    if term.is_empty() {
+        return Err("Le terme ne peut pas être vide".to_string());
+    }
+    let mut glossary = load(workspace_dir)?;
+    glossary.insert(term.to_string(), definition.trim().to_string());
+    save(workspace_dir, &glossary)
+}
+
+#[tauri::command]
+pub fn remove_glossary_entry(app: AppHandle, workspace_dir: &str, term: &str) -> Result<(), String> {
+    let workspace_dir = &scopes::check(&app, workspace_dir)?;
+    let mut glossary = load(workspace_dir)?;
+    glossary.remove(term);
+    save(workspace_dir, &glossary)
+}
+
+/// Lists the glossary terms used in a document, with their positions.
+#[tauri::command]
+pub fn scan_glossary_terms(app: AppHandle, workspace_dir: &str, markdown: &str) -> Result<Vec<GlossaryMatch>, String> {
+    let glossary = load(&scopes::check(&app, workspace_dir)?)?;
+    let plain = markdown::to_plain_text(markdown);
+
+    Ok(glossary.into_iter().filter_map(|(term, definition)| {
+        let occurrences: Vec<TermOccurrence> = find_term(markdown, &plain, &term)
+            .into_iter()
+            .map(|(start, end)| {
+                let offset = markdown::utf16_offset(markdown, start);
+                TermOccurrence {
+                    offset,
+                    length: markdown::utf16_offset(markdown, end) - offset,
+                    line: markdown::line_at(markdown, start),
+                }
+            })
+            .collect();
+        (!occurrences.is_empty()).then_some(GlossaryMatch { term, definition, occurrences })
+    }).collect())
+}
@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use crate::{downloads, markdown};
+
+/// A fixed release, so the archive can be checked against its checksum.
+const LANGUAGETOOL_VERSION: &str = "6.6";
+const LANGUAGETOOL_SERVER_JAR: &str = "languagetool-server.jar";
+const DEFAULT_PORT: u16 = 8081;
+
+/// Local LanguageTool server spawned by the app, if any.
+#[derive(Default)]
+pub struct LanguageToolServer {
+    process: Mutex<Option<(Child, u16)>>,
+}
+
+impl Drop for LanguageToolServer {
+    fn drop(&mut self) {
+        if let Ok(mut guard) = self.process.lock() {
+            if let Some((mut child, _)) = guard.take() {
+                let _ = child.kill();
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageToolStatus {
+    pub java_installed: bool,
+    pub installed: bool,
+    /// False while this build can't download LanguageTool.
+    pub installable: bool,
+    /// False for installs made before downloads were verified, which must
+    /// be reinstalled before the server can start.
+    pub verified: bool,
+    pub running: bool,
+    pub url: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrammarIssue {
+    pub message: String,
+    pub short_message: String,
+    pub rule_id: String,
+    pub category: String,
+    /// UTF-16 offset in the markdown source.
+    pub offset: usize,
+    /// UTF-16 length in the markdown source.
+    pub length: usize,
+    pub line: usize,
+    pub replacements: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CheckResponse {
+    matches: Vec<CheckMatch>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckMatch {
+    message: String,
+    #[serde(default)]
+    short_message: String,
+    offset: usize,
+    length: usize,
+    #[serde(default)]
+    replacements: Vec<Replacement>,
+    rule: Rule,
+}
+
+#[derive(Deserialize)]
+struct Replacement {
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct Rule {
+    id: String,
+    category: Category,
+}
+
+#[derive(Deserialize)]
+struct Category {
+    name: String,
+}
+
+fn java_installed() -> bool {
+    Command::new("java")
+        .arg("-version")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Finds the directory holding the server jar; the release zip wraps
+/// everything in a versioned `LanguageTool-x.y` folder.
+fn find_server_jar(install_dir: &Path) -> Option<PathBuf> {
+    let direct = install_dir.join(LANGUAGETOOL_SERVER_JAR);
+    if direct.is_file() {
+        return Some(direct);
+    }
+    std::fs::read_dir(install_dir).ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path().join(LANGUAGETOOL_SERVER_JAR))
+        .find(|p| p.is_file())
+}
+
+fn archive_url() -> String {
+    format!("https://languagetool.org/download/LanguageTool-{}.zip", LANGUAGETOOL_VERSION)
+}
+
+/// Whether `command` is served: installing is left out until the archive of
+/// `LANGUAGETOOL_VERSION` has a pinned checksum.
+pub fn is_available(command: &str) -> bool {
+    command != "install_languagetool" || downloads::is_pinned(&archive_url())
+}
+
+fn install_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::paths::app_data_subdir(app, "languagetool")
+}
+
+#[tauri::command]
+pub fn get_languagetool_status(app: AppHandle, server: State<'_, LanguageToolServer>) -> Result<LanguageToolStatus, String> {
+    let dir = install_dir(&app)?;
+    let installed = find_server_jar(&dir).is_some();
+    let mut guard = server.process.lock().map_err(|e| e.to_string())?;
+
+    // Forget the process if it exited on its own
+    if let Some((child, _)) = guard.as_mut() {
+        if !matches!(child.try_wait(), Ok(None)) {
+            *guard = None;
+        }
+    }
+
+    Ok(LanguageToolStatus {
+        java_installed: java_installed(),
+        installed,
+        installable: is_available("install_languagetool"),
+        verified: installed && downloads::is_verified(&dir),
+        running: guard.is_some(),
+        url: guard.as_ref().map(|(_, port)| format!("http://localhost:{}", port)),
+    })
+}
+
+/// Downloads and verifies LanguageTool. An install made without
+/// verification is replaced.
+#[tauri::command]
+pub async fn install_languagetool(app: AppHandle) -> Result<(), String> {
+    let dir = install_dir(&app)?;
+    if find_server_jar(&dir).is_some() && downloads::is_verified(&dir) {
+        return Ok(());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        // Next to the install, which is only replaced once the archive is verified
+        let archive_path = crate::paths::app_data_dir(&app)?.join(format!("LanguageTool-{}.zip", LANGUAGETOOL_VERSION));
+        downloads::download_verified(&app, &archive_url(), &archive_path)?;
+        if find_server_jar(&dir).is_some() {
+            std::fs::remove_dir_all(&dir).and_then(|_| std::fs::create_dir_all(&dir))
+                .map_err(|e| format!("Impossible de remplacer l'installation de LanguageTool: {}", e))?;
+        }
+
+        let file = std::fs::File::open(&archive_path)
+            .map_err(|e| format!("Impossible d'ouvrir l'archive LanguageTool: {}", e))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| format!("Archive LanguageTool invalide: {}", e))?;
+        archive.extract(&dir)
+            .map_err(|e| format!("Erreur d'extraction de LanguageTool: {}", e))?;
+        let _ = std::fs::remove_file(&archive_path);
+
+        if find_server_jar(&dir).is_some() {
+            downloads::record_manifest(&dir, "jar")
+        } else {
+            Err("L'archive LanguageTool ne contient pas le serveur attendu".to_string())
+        }
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de l'installation de LanguageTool: {}", e))?
+}
+
+#[tauri::command]
+pub fn start_languagetool_server(app: AppHandle, server: State<'_, LanguageToolServer>, port: Option<u16>) -> Result<String, String> {
+    let mut guard = server.process.lock().map_err(|e| e.to_string())?;
+    if let Some((child, port)) = guard.as_mut() {
+        if matches!(child.try_wait(), Ok(None)) {
+            return Ok(format!("http://localhost:{}", port));
+        }
+    }
+
+    if !java_installed() {
+        return Err("Java est requis pour lancer LanguageTool. Installez un JRE puis réessayez.".to_string());
+    }
+    let dir = install_dir(&app)?;
+    let jar = find_server_jar(&dir)
+        .ok_or("LanguageTool n'est pas installé")?;
+    downloads::verify_manifest(&app, &dir, "jar")?;
+
+    let port = port.unwrap_or(DEFAULT_PORT);
+    // No --allow-origin: only the backend queries the server, and a CORS
+    // header would let any page open in a browser read the text checked
+    let child = Command::new("java")
+        .arg("-cp")
+        .arg(&jar)
+        .args(["org.languagetool.server.HTTPServer", "--port", &port.to_string()])
+        .current_dir(jar.parent().unwrap_or(&jar))
+        .spawn()
+        .map_err(|e| format!("Erreur lors du lancement de LanguageTool: {}", e))?;
+
+    *guard = Some((child, port));
+    Ok(format!("http://localhost:{}", port))
+}
+
+#[tauri::command]
+pub fn stop_languagetool_server(server: State<'_, LanguageToolServer>) -> Result<(), String> {
+    let mut guard = server.process.lock().map_err(|e| e.to_string())?;
+    if let Some((mut child, _)) = guard.take() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    Ok(())
+}
+
+/// Checks the prose of a markdown document against a LanguageTool server.
+/// Markdown syntax and code are stripped before sending, and issue positions
+/// are mapped back to UTF-16 offsets in the original source.
+#[tauri::command]
+pub async fn check_grammar(markdown: String, server_url: String, language: Option<String>) -> Result<Vec<GrammarIssue>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let plain = markdown::to_plain_text(&markdown);
+        if plain.text.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let url = format!("{}/v2/check", server_url.trim_end_matches('/'));
+        let language = language.unwrap_or_else(|| "auto".to_string());
+        let response: CheckResponse = crate::http::agent()
+            .post(&url)
+            .send_form(&[("text", plain.text.as_str()), ("language", language.as_str())])
+            .map_err(crate::http::error_message)?
+            .into_json()
+            .map_err(|e| format!("Réponse LanguageTool invalide: {}", e))?;
+
+        let issues = response.matches.into_iter().map(|m| {
+            let start = markdown::byte_offset_from_utf16(&plain.text, m.offset);
+            let end = markdown::byte_offset_from_utf16(&plain.text, m.offset + m.length);
+            let (src_start, src_end) = plain.source_range(start, end);
+            let offset = markdown::utf16_offset(&markdown, src_start);
+            GrammarIssue {
+                message: m.message,
+                short_message: m.short_message,
+                rule_id: m.rule.id,
+                category: m.rule.category.name,
+                offset,
+                length: markdown::utf16_offset(&markdown, src_end) - offset,
+                line: markdown::line_at(&markdown, src_start),
+                replacements: m.replacements.into_iter().take(5).map(|r| r.value).collect(),
+            }
+        }).collect();
+
+        Ok(issues)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la vérification grammaticale: {}", e))?
+}
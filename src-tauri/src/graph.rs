@@ -0,0 +1,112 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::search::SearchIndex;
+use crate::{backlinks, tags, workspace};
+
+const TAG_PREFIX: &str = "tag:";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNode {
+    /// The note's path, or "tag:" followed by the lowercased tag.
+    pub id: String,
+    /// "note" or "tag".
+    pub kind: &'static str,
+    pub label: String,
+    /// Notes linking to this note, or notes having this tag.
+    pub in_degree: usize,
+    /// Notes this note links to.
+    pub out_degree: usize,
+    /// Distinct neighbours, tags included.
+    pub degree: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    /// "link" or "tag".
+    pub kind: &'static str,
+    /// Number of links, or of occurrences of the tag in the note.
+    pub weight: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+fn node(id: String, kind: &'static str, label: String) -> GraphNode {
+    GraphNode { id, kind, label, in_degree: 0, out_degree: 0, degree: 0 }
+}
+
+/// Notes and tags of the workspace with the links between them. Built from
+/// the search and backlink indexes, which follow the files as they change,
+/// so no note is read again here.
+#[tauri::command]
+pub fn get_graph(app: AppHandle, include_tags: Option<bool>) -> Result<Graph, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    let include_tags = include_tags.unwrap_or(true);
+
+    let mut nodes: BTreeMap<String, GraphNode> = BTreeMap::new();
+    let mut weights: HashMap<(String, String, &'static str), usize> = HashMap::new();
+    let note_tags: Vec<(PathBuf, Vec<String>)> = app.state::<SearchIndex>().with_docs(&root, |docs| {
+        docs.iter()
+            .map(|(path, doc)| (path.clone(), doc.tags().iter().map(|t| t.name.to_lowercase()).collect()))
+            .collect()
+    });
+    for (path, _) in &note_tags {
+        let label = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let id = path.to_string_lossy().to_string();
+        nodes.insert(id.clone(), node(id, "note", label));
+    }
+
+    for (source, target) in backlinks::resolved_links(&app)? {
+        let key = (source.to_string_lossy().to_string(), target.to_string_lossy().to_string(), "link");
+        if nodes.contains_key(&key.0) && nodes.contains_key(&key.1) {
+            *weights.entry(key).or_default() += 1;
+        }
+    }
+    if include_tags {
+        for tag in tags::list_tags(app.clone())? {
+            let id = format!("{}{}", TAG_PREFIX, tag.name.to_lowercase());
+            nodes.insert(id.clone(), node(id, "tag", tag.name));
+        }
+        for (path, tags) in note_tags {
+            let source = path.to_string_lossy().to_string();
+            for tag in tags {
+                *weights.entry((source.clone(), format!("{}{}", TAG_PREFIX, tag), "tag")).or_default() += 1;
+            }
+        }
+    }
+
+    let mut neighbours: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for (source, target, kind) in weights.keys() {
+        neighbours.entry(source).or_default().insert(target);
+        neighbours.entry(target).or_default().insert(source);
+        if *kind == "link" {
+            if let Some(n) = nodes.get_mut(source) {
+                n.out_degree += 1;
+            }
+        }
+        if let Some(n) = nodes.get_mut(target) {
+            n.in_degree += 1;
+        }
+    }
+    for (id, n) in &mut nodes {
+        n.degree = neighbours.get(id.as_str()).map_or(0, |s| s.len());
+    }
+
+    let mut edges: Vec<GraphEdge> = weights
+        .into_iter()
+        .map(|((source, target, kind), weight)| GraphEdge { source, target, kind, weight })
+        .collect();
+    edges.sort_by(|a, b| a.source.cmp(&b.source).then_with(|| a.target.cmp(&b.target)));
+    Ok(Graph { nodes: nodes.into_values().collect(), edges })
+}
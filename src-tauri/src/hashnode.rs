@@ -0,0 +1,122 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::{http, markdown};
+use crate::publish::{self, Published};
+
+const TARGET: &str = "hashnode";
+const ID_KEY: &str = "hashnode_id";
+const API_URL: &str = "https://gql.hashnode.com";
+
+const SERIES_QUERY: &str = "query($id: ObjectId!) { publication(id: $id) { seriesList(first: 50) { edges { node { id name slug } } } } }";
+const PUBLISH_POST: &str = "mutation($input: PublishPostInput!) { publishPost(input: $input) { post { id url } } }";
+const UPDATE_POST: &str = "mutation($input: UpdatePostInput!) { updatePost(input: $input) { post { id url } } }";
+const CREATE_DRAFT: &str = "mutation($input: CreateDraftInput!) { createDraft(input: $input) { draft { id } } }";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HashnodeAccount {
+    /// Personal access token, from Settings › Developer.
+    token: String,
+    /// The blog posts go to, shown in its dashboard URL.
+    publication_id: String,
+}
+
+pub fn validate(account: &Value) -> Result<(), String> {
+    let account: HashnodeAccount = serde_json::from_value(account.clone()).map_err(|e| format!("Compte Hashnode invalide: {}", e))?;
+    if account.token.trim().is_empty() || account.publication_id.trim().is_empty() {
+        return Err("Jeton d'accès et identifiant de publication requis".to_string());
+    }
+    Ok(())
+}
+
+fn graphql(account: &HashnodeAccount, query: &str, variables: Value) -> Result<Value, String> {
+    let mut response: Value = http::agent()
+        .post(API_URL)
+        .set("Authorization", account.token.trim())
+        .send_json(json!({ "query": query, "variables": variables }))
+        .map_err(http::error_message)?
+        .into_json()
+        .map_err(|e| format!("Réponse inattendue de Hashnode: {}", e))?;
+    if let Some(errors) = response["errors"].as_array().filter(|e| !e.is_empty()) {
+        let messages: Vec<&str> = errors.iter().filter_map(|e| e["message"].as_str()).collect();
+        return Err(format!("Hashnode a refusé la requête: {}", messages.join("; ")));
+    }
+    Ok(response["data"].take())
+}
+
+/// Id of the publication's series with this name or slug.
+fn series_id(account: &HashnodeAccount, series: &str) -> Result<String, String> {
+    let data = graphql(account, SERIES_QUERY, json!({ "id": account.publication_id.trim() }))?;
+    data["publication"]["seriesList"]["edges"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|edge| &edge["node"])
+        .find(|node| node["name"].as_str().is_some_and(|n| n.eq_ignore_ascii_case(series)) || node["slug"] == series)
+        .and_then(|node| node["id"].as_str().map(str::to_string))
+        .ok_or_else(|| format!("Série introuvable sur Hashnode: {}", series))
+}
+
+/// Publishes the document's markdown to a Hashnode blog, or updates the post
+/// it was published as before. Drafts are created as Hashnode drafts, which
+/// get published from its dashboard. Images must already be online.
+#[tauri::command]
+pub async fn publish_to_hashnode(app: AppHandle, account: String, content: String, path: Option<String>) -> Result<Published, String> {
+    publish::blocking(move || {
+        let settings: HashnodeAccount = publish::account(&app, TARGET, &account)?;
+        let document_path = crate::scopes::check_optional(&app, path.as_deref())?;
+        let post = publish::post(&content, document_path.as_deref(), ID_KEY);
+        publish::ensure_remote_images(&post.body, post.feature_image.as_deref())?;
+
+        let mut input = json!({
+            "title": post.title,
+            "contentMarkdown": post.body,
+            "tags": post.tags.iter().map(|name| json!({ "slug": markdown::slug(name), "name": name })).collect::<Vec<_>>(),
+        });
+        if let Some(summary) = &post.summary {
+            input["subtitle"] = json!(summary);
+        }
+        if let Some(url) = &post.canonical_url {
+            input["originalArticleURL"] = json!(url);
+        }
+        if let Some(image) = &post.feature_image {
+            input["coverImageOptions"] = json!({ "coverImageURL": image });
+        }
+        if let Some(series) = &post.series {
+            input["seriesId"] = json!(series_id(&settings, series)?);
+        }
+
+        if let Some(id) = &post.id {
+            input["id"] = json!(id);
+            let data = graphql(&settings, UPDATE_POST, json!({ "input": input }))?;
+            return published(&content, &data["updatePost"]["post"]);
+        }
+        input["publicationId"] = json!(settings.publication_id.trim());
+        if post.draft {
+            let data = graphql(&settings, CREATE_DRAFT, json!({ "input": input }))?;
+            let id = data["createDraft"]["draft"]["id"].as_str().unwrap_or_default().to_string();
+            // Nothing is recorded: publishing the draft creates the post
+            return Ok(Published {
+                url: format!("https://hashnode.com/draft/{}", id),
+                id,
+                markdown: content,
+                images_uploaded: 0,
+            });
+        }
+        let data = graphql(&settings, PUBLISH_POST, json!({ "input": input }))?;
+        published(&content, &data["publishPost"]["post"])
+    })
+    .await
+}
+
+fn published(content: &str, post: &Value) -> Result<Published, String> {
+    let id = post["id"].as_str().ok_or("Hashnode n'a pas renvoyé l'article")?.to_string();
+    Ok(Published {
+        markdown: publish::record_id(content, ID_KEY, &id),
+        url: post["url"].as_str().unwrap_or_default().to_string(),
+        id,
+        images_uploaded: 0,
+    })
+}
@@ -0,0 +1,91 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::{paths, scopes};
+
+/// Pandoc's built-in highlighting styles.
+const BUILTIN_STYLES: &[&str] = &["pygments", "tango", "espresso", "zenburn", "kate", "monochrome", "breezedark", "haddock"];
+const THEMES_DIR: &str = "highlight-themes";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightStyle {
+    pub name: String,
+    /// "builtin" or "app" for imported `.theme` files.
+    pub source: &'static str,
+    pub path: Option<String>,
+}
+
+fn themes_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    paths::app_data_subdir(app, THEMES_DIR)
+}
+
+/// Theme files are KDE syntax-highlighting JSON; reading them here gives a
+/// clearer error than pandoc's.
+fn check_theme(path: &Path) -> Result<(), String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+    match serde_json::from_str::<serde_json::Value>(&json) {
+        Ok(value) if value.get("text-styles").is_some() => Ok(()),
+        _ => Err(format!("Thème de coloration invalide: {}", path.display())),
+    }
+}
+
+/// The `--highlight-style` or `--no-highlight` argument for `style`: a
+/// built-in style, an imported theme by name, a `.theme` file, or "none".
+pub fn pandoc_arg(app: &AppHandle, style: &str) -> Result<String, String> {
+    let style = style.trim();
+    if style == "none" {
+        return Ok("--no-highlight".to_string());
+    }
+    if BUILTIN_STYLES.contains(&style) {
+        return Ok(format!("--highlight-style={}", style));
+    }
+    let path = if style.ends_with(".theme") && style.contains(['/', '\\']) {
+        PathBuf::from(style)
+    } else {
+        themes_dir(app)?.join(format!("{}.theme", style.trim_end_matches(".theme")))
+    };
+    if !path.is_file() {
+        return Err(format!("Style de coloration inconnu: {}", style));
+    }
+    check_theme(&path)?;
+    Ok(format!("--highlight-style={}", path.display()))
+}
+
+/// Built-in styles, then the themes imported into the app.
+#[tauri::command]
+pub fn list_highlight_styles(app: AppHandle) -> Result<Vec<HighlightStyle>, String> {
+    let mut styles: Vec<HighlightStyle> =
+        BUILTIN_STYLES.iter().map(|name| HighlightStyle { name: name.to_string(), source: "builtin", path: None }).collect();
+    let mut themes: Vec<PathBuf> = std::fs::read_dir(themes_dir(&app)?)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "theme"))
+        .collect();
+    themes.sort();
+    styles.extend(themes.into_iter().map(|path| HighlightStyle {
+        name: path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+        source: "app",
+        path: Some(path.to_string_lossy().to_string()),
+    }));
+    Ok(styles)
+}
+
+/// Copies a `.theme` file into the app so exports can use it by name, e.g.
+/// one written by `pandoc --print-highlight-style` and edited to match the
+/// editor.
+#[tauri::command]
+pub fn add_highlight_theme(app: AppHandle, file: String) -> Result<HighlightStyle, String> {
+    let source = &scopes::check(&app, &file)?;
+    check_theme(source)?;
+    let name = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    if BUILTIN_STYLES.contains(&name.as_str()) || name == "none" {
+        return Err(format!("Le nom {} est réservé à un style intégré", name));
+    }
+    let target = themes_dir(&app)?.join(format!("{}.theme", name));
+    std::fs::copy(source, &target).map_err(|e| format!("Impossible de copier {}: {}", file, e))?;
+    Ok(HighlightStyle { name, source: "app", path: Some(target.to_string_lossy().to_string()) })
+}
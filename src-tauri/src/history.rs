@@ -0,0 +1,222 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use similar::{ChangeTag, TextDiff};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// Versions kept per document; the oldest are dropped first.
+const MAX_VERSIONS: usize = 200;
+const DIFF_CONTEXT: usize = 3;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Version {
+    /// Milliseconds since the epoch at which the version was saved.
+    pub id: String,
+    pub created_at: String,
+    /// Compressed size on disk.
+    pub size: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    /// "equal", "insert" or "delete".
+    pub kind: &'static str,
+    pub text: String,
+    /// 1-based line in the old version, None for inserted lines.
+    pub old_line: Option<usize>,
+    /// 1-based line in the current text, None for deleted lines.
+    pub new_line: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionDiff {
+    /// Changed lines with a few lines of context around them.
+    pub hunks: Vec<Vec<DiffLine>>,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// One directory per document, named after a hash of its path.
+fn history_dir(app: &AppHandle, document: &Path) -> Result<PathBuf, String> {
+    let document = crate::assets::normalize(document);
+    let hex: String = Sha256::digest(document.to_string_lossy().as_bytes())
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok(crate::paths::app_data_subdir(app, "history")?.join(hex))
+}
+
+fn version_ids(dir: &Path) -> Vec<u64> {
+    let mut ids: Vec<u64> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|e| e.file_name().to_str()?.strip_suffix(".md.gz")?.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    ids.sort_unstable();
+    ids
+}
+
+fn version_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{}.md.gz", id))
+}
+
+fn read_version(dir: &Path, id: u64) -> Result<String, String> {
+    let file = std::fs::File::open(version_path(dir, id))
+        .map_err(|_| format!("Version introuvable: {}", id))?;
+    let mut content = String::new();
+    GzDecoder::new(file)
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Version illisible: {}", e))?;
+    Ok(content)
+}
+
+fn parse_id(id: &str) -> Result<u64, String> {
+    id.parse().map_err(|_| format!("Version invalide: {}", id))
+}
+
+/// Whether versions of `document` are kept. Encrypted notes get no history:
+/// versions are stored in clear.
+pub fn keeps_history(document: &Path) -> bool {
+    !(document.extension().is_some_and(|e| e == crate::encryption::ENCRYPTED_EXTENSION) || crate::encryption::is_encrypted(document))
+}
+
+/// Stores `content` as a new version of `document`, unless it matches the
+/// latest one.
+pub fn record(app: &AppHandle, document: &Path, content: &str) -> Result<(), String> {
+    if !keeps_history(document) {
+        return Err(format!("{} est chiffré: son historique n'est pas conservé", document.display()));
+    }
+    let dir = history_dir(app, document)?;
+    let ids = version_ids(&dir);
+    if let Some(&latest) = ids.last() {
+        if read_version(&dir, latest).is_ok_and(|previous| previous == content) {
+            return Ok(());
+        }
+    }
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+
+    let now = chrono::Local::now().timestamp_millis().max(0) as u64;
+    let id = ids.last().map_or(now, |&latest| now.max(latest + 1));
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(content.as_bytes()).map_err(|e| e.to_string())?;
+    let compressed = encoder.finish().map_err(|e| e.to_string())?;
+    std::fs::write(version_path(&dir, id), compressed)
+        .map_err(|e| format!("Impossible d'enregistrer la version: {}", e))?;
+
+    for &old in ids.iter().rev().skip(MAX_VERSIONS - 1) {
+        let _ = std::fs::remove_file(version_path(&dir, old));
+    }
+    Ok(())
+}
+
+/// Deletes every saved version of a document.
+pub fn clear(app: &AppHandle, document: &Path) -> Result<(), String> {
+    let dir = history_dir(app, document)?;
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).map_err(|e| format!("Impossible de supprimer l'historique: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Line diff from `old` to `new`, grouped into hunks with some context.
+pub fn diff(old: &str, new: &str) -> VersionDiff {
+    let diff = TextDiff::from_lines(old, new);
+    let mut result = VersionDiff { hunks: Vec::new(), insertions: 0, deletions: 0 };
+    for group in diff.grouped_ops(DIFF_CONTEXT) {
+        let mut hunk = Vec::new();
+        for change in group.iter().flat_map(|op| diff.iter_changes(op)) {
+            let kind = match change.tag() {
+                ChangeTag::Equal => "equal",
+                ChangeTag::Insert => {
+                    result.insertions += 1;
+                    "insert"
+                }
+                ChangeTag::Delete => {
+                    result.deletions += 1;
+                    "delete"
+                }
+            };
+            hunk.push(DiffLine {
+                kind,
+                text: change.value().trim_end_matches(['\n', '\r']).to_string(),
+                old_line: change.old_index().map(|i| i + 1),
+                new_line: change.new_index().map(|i| i + 1),
+            });
+        }
+        result.hunks.push(hunk);
+    }
+    result
+}
+
+/// Called by the frontend after each save.
+#[tauri::command]
+pub fn record_version(app: AppHandle, path: &str, content: &str) -> Result<(), String> {
+    record(&app, &crate::scopes::check(&app, path)?, content)
+}
+
+/// Saved versions of a document, newest first.
+#[tauri::command]
+pub fn list_versions(app: AppHandle, path: &str) -> Result<Vec<Version>, String> {
+    let dir = history_dir(&app, &crate::scopes::check(&app, path)?)?;
+    Ok(version_ids(&dir)
+        .into_iter()
+        .rev()
+        .map(|id| Version {
+            id: id.to_string(),
+            created_at: chrono::DateTime::from_timestamp_millis(id as i64)
+                .map(|t| t.with_timezone(&chrono::Local).to_rfc3339())
+                .unwrap_or_default(),
+            size: std::fs::metadata(version_path(&dir, id)).map(|m| m.len()).unwrap_or(0),
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn get_version(app: AppHandle, path: &str, id: &str) -> Result<String, String> {
+    read_version(&history_dir(&app, &crate::scopes::check(&app, path)?)?, parse_id(id)?)
+}
+
+/// Changes from a saved version to `current`, the text in the editor.
+#[tauri::command]
+pub fn diff_version(app: AppHandle, path: &str, id: &str, current: &str) -> Result<VersionDiff, String> {
+    let old = read_version(&history_dir(&app, &crate::scopes::check(&app, path)?)?, parse_id(id)?)?;
+    Ok(diff(&old, current))
+}
+
+/// Writes a saved version back to the document and returns its content. The
+/// text being replaced is recorded first, so the restore can be undone.
+#[tauri::command]
+pub fn restore_version(app: AppHandle, path: &str, id: &str) -> Result<String, String> {
+    let document = &crate::scopes::check(&app, path)?;
+    let content = read_version(&history_dir(&app, document)?, parse_id(id)?)?;
+    // Versions are stored as UTF-8 with `\n`; the document keeps its format
+    let format = match crate::safe_save::read_text(document) {
+        Ok((current, format)) => {
+            record(&app, document, &current)?;
+            format
+        }
+        Err(_) => Default::default(),
+    };
+    crate::safe_save::write_text(&app, document, &content, &format)?;
+    record(&app, document, &content)?;
+    Ok(content)
+}
+
+#[tauri::command]
+pub fn export_version(app: AppHandle, path: &str, id: &str, output_path: &str) -> Result<(), String> {
+    let output_path = crate::scopes::check(&app, output_path)?;
+    let content = read_version(&history_dir(&app, &crate::scopes::check(&app, path)?)?, parse_id(id)?)?;
+    std::fs::write(output_path, content)
+        .map_err(|e| format!("Impossible d'exporter la version: {}", e))
+}
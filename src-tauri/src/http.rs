@@ -0,0 +1,60 @@
+use std::path::Path;
+use std::time::Duration;
+
+/// Shared HTTP agent with sane timeouts for calls to local and remote services.
+pub fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(10))
+        .timeout(Duration::from_secs(120))
+        .user_agent(concat!("ohmymarkdown/", env!("CARGO_PKG_VERSION")))
+        .build()
+}
+
+/// Turns a ureq error into a user-facing message, including the response body
+/// for HTTP errors since APIs usually explain what went wrong there.
+pub fn error_message(err: ureq::Error) -> String {
+    match err {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            format!("Le serveur a répondu {}: {}", code, body.trim())
+        }
+        ureq::Error::Transport(t) => format!("Erreur réseau: {}", t),
+    }
+}
+
+/// Downloads `url` to `dest`, writing to a temporary file first so an
+/// interrupted download never leaves a truncated file behind.
+pub fn download_to_file(url: &str, dest: &Path) -> Result<(), String> {
+    let response = agent().get(url).call().map_err(error_message)?;
+
+    let partial = dest.with_extension("part");
+    let mut file = std::fs::File::create(&partial)
+        .map_err(|e| format!("Impossible de créer {}: {}", partial.display(), e))?;
+    std::io::copy(&mut response.into_reader(), &mut file)
+        .map_err(|e| format!("Erreur pendant le téléchargement de {}: {}", url, e))?;
+    drop(file);
+
+    std::fs::rename(&partial, dest)
+        .map_err(|e| format!("Impossible de finaliser {}: {}", dest.display(), e))
+}
+
+/// A `multipart/form-data` body with text fields and one file, returned with
+/// the matching content type.
+pub fn multipart(fields: &[(&str, &str)], file_field: &str, file_name: &str, bytes: &[u8], mime: &str) -> (String, Vec<u8>) {
+    let name = file_name.replace('"', "");
+    let boundary = format!("ohmymarkdown-{:x}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_nanos());
+    let mut body = Vec::with_capacity(bytes.len() + 512);
+    for (key, value) in fields {
+        body.extend_from_slice(format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n", boundary, key, value).as_bytes());
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary, file_field, name, mime
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    (format!("multipart/form-data; boundary={}", boundary), body)
+}
@@ -0,0 +1,273 @@
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageFormat, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::AppHandle;
+
+use crate::{assets, markdown};
+
+const DEFAULT_JPEG_QUALITY: u8 = 85;
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImageOptimizeOptions {
+    pub max_width: Option<u32>,
+    pub max_height: Option<u32>,
+    pub jpeg_quality: Option<u8>,
+    /// Re-encode PNGs as (lossless) WebP.
+    pub png_to_webp: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizedImage {
+    pub original_path: String,
+    pub path: String,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizeReport {
+    /// Markdown with links updated for images whose extension changed.
+    pub markdown: String,
+    pub images: Vec<OptimizedImage>,
+    pub bytes_saved: u64,
+}
+
+fn extension_for(format: ImageFormat) -> &'static str {
+    format.extensions_str().first().copied().unwrap_or("img")
+}
+
+/// Encodes an image in memory, as JPEG (alpha dropped) or any other format
+/// the image crate can write.
+pub fn encode_image(img: &DynamicImage, format: ImageFormat, jpeg_quality: u8) -> Result<Vec<u8>, String> {
+    let mut buffer = Cursor::new(Vec::new());
+    let result = match format {
+        ImageFormat::Jpeg => {
+            let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buffer, jpeg_quality);
+            img.to_rgb8().write_with_encoder(encoder)
+        }
+        _ => img.write_to(&mut buffer, format),
+    };
+    result.map_err(|e| format!("Erreur d'encodage de l'image: {}", e))?;
+    Ok(buffer.into_inner())
+}
+
+pub fn write_image(img: &DynamicImage, path: &Path, format: ImageFormat, jpeg_quality: u8) -> Result<(), String> {
+    let bytes = encode_image(img, format, jpeg_quality)?;
+    std::fs::write(path, bytes)
+        .map_err(|e| format!("Impossible d'enregistrer l'image {}: {}", path.display(), e))
+}
+
+fn fit_within(img: DynamicImage, options: &ImageOptimizeOptions) -> (DynamicImage, bool) {
+    let (width, height) = img.dimensions();
+    let max_width = options.max_width.unwrap_or(u32::MAX);
+    let max_height = options.max_height.unwrap_or(u32::MAX);
+    if width <= max_width && height <= max_height {
+        return (img, false);
+    }
+    (img.resize(max_width.min(width), max_height.min(height), FilterType::Lanczos3), true)
+}
+
+/// Downsizes and recompresses a PNG or JPEG file in place (or next to it when
+/// the format changes). Returns None when the file was left untouched because
+/// the result would not be smaller.
+pub fn optimize_file(path: &Path, options: &ImageOptimizeOptions) -> Result<Option<OptimizedImage>, String> {
+    let format = match ImageFormat::from_path(path) {
+        Ok(f @ (ImageFormat::Png | ImageFormat::Jpeg)) => f,
+        _ => return Ok(None),
+    };
+    let bytes_before = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let img = image::open(path)
+        .map_err(|e| format!("Impossible de lire l'image {}: {}", path.display(), e))?;
+    let (img, resized) = fit_within(img, options);
+
+    let target_format = if format == ImageFormat::Png && options.png_to_webp {
+        ImageFormat::WebP
+    } else {
+        format
+    };
+    let encoded = encode_image(&img, target_format, options.jpeg_quality.unwrap_or(DEFAULT_JPEG_QUALITY))?;
+    if !resized && encoded.len() as u64 >= bytes_before {
+        return Ok(None);
+    }
+
+    let dest: PathBuf = if target_format == format {
+        path.to_path_buf()
+    } else {
+        let file_name = path.with_extension(extension_for(target_format));
+        let file_name = file_name.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        assets::unique_path(path.parent().unwrap_or(Path::new(".")), &file_name)
+    };
+    std::fs::write(&dest, &encoded)
+        .map_err(|e| format!("Impossible d'enregistrer l'image {}: {}", dest.display(), e))?;
+    if dest != path {
+        let _ = std::fs::remove_file(path);
+    }
+
+    Ok(Some(OptimizedImage {
+        original_path: path.to_string_lossy().to_string(),
+        path: dest.to_string_lossy().to_string(),
+        bytes_before,
+        bytes_after: encoded.len() as u64,
+    }))
+}
+
+/// Applies `process` to every local file a document links to inside its
+/// managed assets folders (user files elsewhere on disk are never rewritten),
+/// and updates the links of files that `process` moved.
+fn process_managed_files<F>(app: &AppHandle, md: &str, document_path: Option<&Path>, mut process: F) -> Result<String, String>
+where
+    F: FnMut(&Path) -> Result<Option<PathBuf>, String>,
+{
+    let document_dir = document_path.and_then(Path::parent);
+    let managed_roots: Vec<PathBuf> = std::iter::once(assets::unsaved_assets_root(app)?)
+        .chain(document_path.map(assets::assets_dir_for))
+        .collect();
+
+    let mut moved: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut replacements = Vec::new();
+    for link in markdown::links(md) {
+        let url = link.url(md);
+        if !assets::is_local_link(url) {
+            continue;
+        }
+        let target = assets::resolve_link(url, document_dir);
+        if !moved.contains_key(&target) {
+            if !target.is_file() || !managed_roots.iter().any(|root| target.starts_with(root)) {
+                continue;
+            }
+            if let Some(new_path) = process(&target)? {
+                moved.insert(target.clone(), new_path);
+            }
+        }
+        if let Some(new_path) = moved.get(&target).filter(|p| **p != target) {
+            replacements.push(assets::replacement(md, &link, assets::link_to(new_path, document_dir)));
+        }
+    }
+
+    Ok(markdown::replace_ranges(md, replacements))
+}
+
+/// Optimizes every image a document keeps in its managed assets folders.
+pub fn optimize_document(app: &AppHandle, md: &str, document_path: Option<&Path>, options: &ImageOptimizeOptions) -> Result<OptimizeReport, String> {
+    let mut images = Vec::new();
+    let markdown = process_managed_files(app, md, document_path, |path| {
+        Ok(optimize_file(path, options)?.map(|optimized| {
+            let new_path = PathBuf::from(&optimized.path);
+            images.push(optimized);
+            new_path
+        }))
+    })?;
+
+    let bytes_saved = images.iter().map(|i| i.bytes_before.saturating_sub(i.bytes_after)).sum();
+    Ok(OptimizeReport { markdown, images, bytes_saved })
+}
+
+/// Converts a format browsers can't display (HEIC, TIFF, BMP) to PNG, or to
+/// JPEG for HEIC photos. Returns the new file, or None if no conversion was
+/// needed.
+pub fn convert_for_web(path: &Path) -> Result<Option<PathBuf>, String> {
+    let ext = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+
+    let dest = match ext.as_str() {
+        "tif" | "tiff" | "bmp" => {
+            let img = image::open(path)
+                .map_err(|e| format!("Impossible de lire l'image {}: {}", path.display(), e))?;
+            let dest = assets::unique_path(dir, &format!("{}.png", stem));
+            write_image(&img, &dest, ImageFormat::Png, DEFAULT_JPEG_QUALITY)?;
+            dest
+        }
+        "heic" | "heif" => {
+            let dest = assets::unique_path(dir, &format!("{}.jpg", stem));
+            convert_heic(path, &dest)?;
+            dest
+        }
+        _ => return Ok(None),
+    };
+
+    let _ = std::fs::remove_file(path);
+    Ok(Some(dest))
+}
+
+/// HEIC has no pure-Rust decoder, so use whichever converter the platform
+/// provides.
+fn convert_heic(src: &Path, dest: &Path) -> Result<(), String> {
+    let candidates: [(&str, Vec<&std::ffi::OsStr>); 3] = [
+        ("magick", vec![src.as_os_str(), dest.as_os_str()]),
+        ("heif-convert", vec![src.as_os_str(), dest.as_os_str()]),
+        ("sips", vec!["-s".as_ref(), "format".as_ref(), "jpeg".as_ref(), src.as_os_str(), "--out".as_ref(), dest.as_os_str()]),
+    ];
+    for (program, args) in candidates {
+        let converted = Command::new(program)
+            .args(args)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false);
+        if converted && dest.is_file() {
+            return Ok(());
+        }
+    }
+    Err(format!(
+        "Impossible de convertir {}: installez ImageMagick ou libheif pour prendre en charge les images HEIC",
+        src.display()
+    ))
+}
+
+/// Converts the document's HEIC/TIFF/BMP assets and rewrites their links.
+pub fn convert_document_images(app: &AppHandle, md: &str, document_path: Option<&Path>) -> Result<String, String> {
+    process_managed_files(app, md, document_path, convert_for_web)
+}
+
+#[tauri::command]
+pub fn convert_unsupported_images(app: AppHandle, markdown: &str, document_path: Option<&str>) -> Result<String, String> {
+    let document_path = crate::scopes::check_optional(&app, document_path)?;
+    convert_document_images(&app, markdown, document_path.as_deref())
+}
+
+#[tauri::command]
+pub fn optimize_images(app: AppHandle, markdown: &str, document_path: Option<&str>, options: ImageOptimizeOptions) -> Result<OptimizeReport, String> {
+    let document_path = crate::scopes::check_optional(&app, document_path)?;
+    optimize_document(&app, markdown, document_path.as_deref(), &options)
+}
+
+pub fn clipboard_image() -> Result<DynamicImage, String> {
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| format!("Impossible d'accéder au presse-papiers: {}", e))?;
+    let data = clipboard.get_image()
+        .map_err(|_| "Le presse-papiers ne contient pas d'image".to_string())?;
+    let rgba = RgbaImage::from_raw(data.width as u32, data.height as u32, data.bytes.into_owned())
+        .ok_or("Image du presse-papiers invalide")?;
+    Ok(DynamicImage::ImageRgba8(rgba))
+}
+
+/// Saves the image currently on the clipboard into the document's assets
+/// folder and returns the link to insert in the markdown.
+#[tauri::command]
+pub fn save_clipboard_image(app: AppHandle, document_path: Option<&str>, format: Option<&str>, optimize: Option<ImageOptimizeOptions>) -> Result<String, String> {
+    let img = clipboard_image()?;
+
+    let (format, ext) = match format {
+        Some("jpeg") | Some("jpg") => (ImageFormat::Jpeg, "jpg"),
+        _ => (ImageFormat::Png, "png"),
+    };
+    let dir = assets::target_assets_dir(&app, document_path)?;
+    let name = format!("pasted-{}.{}", chrono::Local::now().format("%Y%m%d-%H%M%S"), ext);
+    let mut path = assets::unique_path(&dir, &name);
+    write_image(&img, &path, format, DEFAULT_JPEG_QUALITY)?;
+
+    if let Some(options) = optimize {
+        if let Some(optimized) = optimize_file(&path, &options)? {
+            path = PathBuf::from(optimized.path);
+        }
+    }
+
+    Ok(assets::document_link(&path, document_path))
+}
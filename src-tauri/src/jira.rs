@@ -0,0 +1,518 @@
+use base64::Engine;
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::publish::{self, Published};
+use crate::{frontmatter, http};
+
+const TARGET: &str = "jira";
+const ID_KEY: &str = "jira_issue";
+const DEFAULT_ISSUE_TYPE: &str = "Task";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JiraAccount {
+    /// "https://example.atlassian.net" for Cloud, the server's address
+    /// otherwise.
+    url: String,
+    /// Cloud only: the Atlassian account email, used with an API token.
+    email: Option<String>,
+    /// API token (Cloud) or personal access token (Server/Data Center).
+    token: String,
+}
+
+impl JiraAccount {
+    /// Cloud speaks ADF through API v3; Server and Data Center only have v2
+    /// and wiki markup.
+    fn is_cloud(&self) -> bool {
+        self.email.as_deref().is_some_and(|e| !e.trim().is_empty())
+    }
+}
+
+pub fn validate(account: &Value) -> Result<(), String> {
+    let account: JiraAccount = serde_json::from_value(account.clone()).map_err(|e| format!("Compte Jira invalide: {}", e))?;
+    if !account.url.starts_with("https://") && !account.url.starts_with("http://") {
+        return Err(format!("Adresse Jira invalide: {}", account.url));
+    }
+    if account.token.trim().is_empty() {
+        return Err("Jeton d'accès Jira requis".to_string());
+    }
+    Ok(())
+}
+
+fn parser(md: &str) -> Parser<'_> {
+    Parser::new_ext(md, Options::ENABLE_TABLES | Options::ENABLE_FOOTNOTES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS)
+}
+
+/// Renders markdown events to Jira wiki markup.
+#[derive(Default)]
+struct WikiRenderer {
+    out: String,
+    /// `*` or `#` for each open list.
+    lists: Vec<char>,
+    in_code: bool,
+    /// Header cells are `||`, body cells `|`.
+    in_head: bool,
+    /// Destination and start in `out` of each open link or image.
+    links: Vec<(String, usize)>,
+    /// A footnote label or `{quote}` was just written; the next paragraph
+    /// follows it directly.
+    attached: bool,
+}
+
+impl WikiRenderer {
+    fn block(&mut self) {
+        if std::mem::take(&mut self.attached) {
+            return;
+        }
+        let trimmed = self.out.trim_end_matches([' ', '\n']).len();
+        self.out.truncate(trimmed);
+        if !self.out.is_empty() {
+            self.out.push_str(if self.lists.is_empty() { "\n\n" } else { "\n" });
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.in_code {
+            self.out.push_str(text);
+            return;
+        }
+        for c in text.chars() {
+            if matches!(c, '\\' | '{' | '}' | '[' | ']' | '|' | '*' | '_' | '!' | '^' | '~') {
+                self.out.push('\\');
+            }
+            self.out.push(c);
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            // Paragraphs of a loose list item stay on the item's line
+            Tag::Paragraph if self.lists.is_empty() => self.block(),
+            Tag::Heading { level, .. } => {
+                self.block();
+                self.out.push_str(&format!("h{}. ", level as usize));
+            }
+            Tag::BlockQuote(_) => {
+                self.block();
+                self.out.push_str("{quote}\n");
+                self.attached = true;
+            }
+            Tag::CodeBlock(kind) => {
+                self.block();
+                match kind {
+                    CodeBlockKind::Fenced(lang) if !lang.trim().is_empty() => {
+                        self.out.push_str(&format!("{{code:{}}}\n", lang.split_whitespace().next().unwrap_or_default()))
+                    }
+                    _ => self.out.push_str("{code}\n"),
+                }
+                self.in_code = true;
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.block();
+                }
+                self.lists.push(if start.is_some() { '#' } else { '*' });
+            }
+            Tag::Item => {
+                if !self.out.is_empty() && !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+                let markers: String = self.lists.iter().collect();
+                self.out.push_str(&format!("{} ", markers));
+            }
+            Tag::FootnoteDefinition(label) => {
+                self.block();
+                self.out.push_str(&format!("[{}] ", label));
+                self.attached = true;
+            }
+            Tag::Table(_) => self.block(),
+            Tag::TableHead => self.in_head = true,
+            Tag::TableCell => self.out.push_str(if self.in_head { "||" } else { "|" }),
+            Tag::Emphasis => self.out.push('_'),
+            Tag::Strong => self.out.push('*'),
+            Tag::Strikethrough => self.out.push('-'),
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => self.links.push((dest_url.to_string(), self.out.len())),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::BlockQuote(_) => {
+                let trimmed = self.out.trim_end().len();
+                self.out.truncate(trimmed);
+                self.out.push_str("\n{quote}");
+            }
+            TagEnd::CodeBlock => {
+                if !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+                self.out.push_str("{code}");
+                self.in_code = false;
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+            }
+            TagEnd::Paragraph if !self.lists.is_empty() => self.out.push(' '),
+            TagEnd::TableHead => {
+                self.out.push_str("||\n");
+                self.in_head = false;
+            }
+            TagEnd::TableRow => self.out.push_str("|\n"),
+            TagEnd::Table => {
+                let trimmed = self.out.trim_end().len();
+                self.out.truncate(trimmed);
+            }
+            TagEnd::Emphasis => self.out.push('_'),
+            TagEnd::Strong => self.out.push('*'),
+            TagEnd::Strikethrough => self.out.push('-'),
+            TagEnd::Link => {
+                let (url, start) = self.links.pop().unwrap_or_default();
+                let text = self.out.split_off(start.min(self.out.len()));
+                if text.is_empty() || text == url {
+                    self.out.push_str(&format!("[{}]", url));
+                } else {
+                    self.out.push_str(&format!("[{}|{}]", text, url));
+                }
+            }
+            TagEnd::Image => {
+                let (url, start) = self.links.pop().unwrap_or_default();
+                let alt = self.out.split_off(start.min(self.out.len()));
+                let alt = alt.replace(['\\', '|', '!', ','], "");
+                if alt.is_empty() {
+                    self.out.push_str(&format!("!{}!", url));
+                } else {
+                    self.out.push_str(&format!("!{}|alt={}!", url, alt));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Converts markdown to Jira wiki markup, for Jira Server and Data Center
+/// or pasting into a text field.
+fn to_wiki(md: &str) -> String {
+    let mut renderer = WikiRenderer::default();
+    for event in parser(frontmatter::body(md)) {
+        match event {
+            Event::Start(tag) => renderer.start(tag),
+            Event::End(tag) => renderer.end(tag),
+            Event::Text(text) => renderer.text(&text),
+            Event::Code(code) => renderer.out.push_str(&format!("{{{{{}}}}}", code.replace('}', "\\}"))),
+            Event::SoftBreak => renderer.out.push(' '),
+            Event::HardBreak => renderer.out.push_str("\\\\ "),
+            Event::Rule => {
+                renderer.block();
+                renderer.out.push_str("----");
+            }
+            Event::TaskListMarker(done) => renderer.out.push_str(if done { "☑ " } else { "☐ " }),
+            Event::FootnoteReference(label) => renderer.out.push_str(&format!("[{}]", label)),
+            _ => {}
+        }
+    }
+    renderer.out.trim().to_string()
+}
+
+/// Builds an Atlassian Document Format tree from markdown events.
+struct AdfBuilder {
+    /// Open nodes, the document at the bottom.
+    nodes: Vec<Value>,
+    /// Whether each open node was opened to hold inline content where ADF
+    /// only accepts blocks.
+    implicit: Vec<bool>,
+    marks: Vec<Value>,
+    in_head: bool,
+    /// Text that starts the next paragraph: a footnote's label.
+    prefix: Option<String>,
+}
+
+impl AdfBuilder {
+    fn open(&mut self, node: Value) {
+        self.nodes.push(node);
+        self.implicit.push(false);
+    }
+
+    fn close(&mut self) {
+        if self.nodes.len() < 2 {
+            return;
+        }
+        let node = self.nodes.pop().unwrap();
+        self.implicit.pop();
+        // ADF rejects empty paragraphs and containers
+        let empty = node.get("content").and_then(Value::as_array).is_some_and(|c| c.is_empty());
+        if empty && node["type"] != "tableCell" && node["type"] != "tableHeader" && node["type"] != "codeBlock" {
+            return;
+        }
+        let parent = self.nodes.last_mut().unwrap();
+        parent["content"].as_array_mut().unwrap().push(node);
+    }
+
+    fn close_implicit(&mut self) {
+        while self.implicit.last() == Some(&true) {
+            self.close();
+        }
+    }
+
+    fn kind(&self) -> &str {
+        self.nodes.last().and_then(|n| n["type"].as_str()).unwrap_or_default()
+    }
+
+    /// Makes sure inline content has a paragraph to go in.
+    fn inline(&mut self) {
+        if matches!(self.kind(), "doc" | "listItem" | "blockquote" | "tableCell" | "tableHeader") {
+            self.nodes.push(json!({ "type": "paragraph", "content": [] }));
+            self.implicit.push(true);
+        }
+    }
+
+    fn push_inline(&mut self, node: Value) {
+        self.inline();
+        self.nodes.last_mut().unwrap()["content"].as_array_mut().unwrap().push(node);
+    }
+
+    fn text(&mut self, text: &str, extra_marks: &[Value]) {
+        if text.is_empty() {
+            return;
+        }
+        let mut node = json!({ "type": "text", "text": text });
+        let mut marks: Vec<Value> = if self.kind() == "codeBlock" { Vec::new() } else { self.marks.clone() };
+        if !extra_marks.is_empty() {
+            // Code only combines with links
+            marks.retain(|m| m["type"] == "link");
+            marks.extend_from_slice(extra_marks);
+        }
+        if !marks.is_empty() {
+            node["marks"] = Value::Array(marks);
+        }
+        self.push_inline(node);
+    }
+
+    fn start(&mut self, tag: Tag) {
+        if !matches!(tag, Tag::Emphasis | Tag::Strong | Tag::Strikethrough | Tag::Link { .. } | Tag::Image { .. }) {
+            self.close_implicit();
+        }
+        match tag {
+            Tag::Paragraph => {
+                self.open(json!({ "type": "paragraph", "content": [] }));
+                if let Some(prefix) = self.prefix.take() {
+                    self.text(&prefix, &[]);
+                }
+            }
+            Tag::Heading { level, .. } => self.open(json!({ "type": "heading", "attrs": { "level": level as usize }, "content": [] })),
+            Tag::BlockQuote(_) => self.open(json!({ "type": "blockquote", "content": [] })),
+            Tag::CodeBlock(kind) => {
+                let mut node = json!({ "type": "codeBlock", "content": [] });
+                if let CodeBlockKind::Fenced(lang) = kind {
+                    if let Some(lang) = lang.split_whitespace().next() {
+                        node["attrs"] = json!({ "language": lang });
+                    }
+                }
+                self.open(node);
+            }
+            Tag::List(Some(start)) => self.open(json!({ "type": "orderedList", "attrs": { "order": start }, "content": [] })),
+            Tag::List(None) => self.open(json!({ "type": "bulletList", "content": [] })),
+            Tag::Item => self.open(json!({ "type": "listItem", "content": [] })),
+            Tag::FootnoteDefinition(label) => self.prefix = Some(format!("[{}] ", label)),
+            Tag::Table(_) => self.open(json!({ "type": "table", "content": [] })),
+            Tag::TableHead => {
+                self.in_head = true;
+                self.open(json!({ "type": "tableRow", "content": [] }));
+            }
+            Tag::TableRow => self.open(json!({ "type": "tableRow", "content": [] })),
+            Tag::TableCell => self.open(json!({ "type": if self.in_head { "tableHeader" } else { "tableCell" }, "content": [] })),
+            Tag::Emphasis => self.marks.push(json!({ "type": "em" })),
+            Tag::Strong => self.marks.push(json!({ "type": "strong" })),
+            Tag::Strikethrough => self.marks.push(json!({ "type": "strike" })),
+            Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => self.marks.push(json!({ "type": "link", "attrs": { "href": dest_url.to_string() } })),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough | TagEnd::Link | TagEnd::Image => {
+                self.marks.pop();
+            }
+            TagEnd::FootnoteDefinition | TagEnd::HtmlBlock | TagEnd::MetadataBlock(_) => {}
+            TagEnd::TableHead => {
+                self.close_implicit();
+                self.close();
+                self.in_head = false;
+            }
+            _ => {
+                self.close_implicit();
+                if tag == TagEnd::CodeBlock {
+                    // pulldown-cmark ends code with the last line's newline
+                    if let Some(last) = self.nodes.last_mut().unwrap()["content"].as_array_mut().unwrap().last_mut() {
+                        let text = last["text"].as_str().unwrap_or_default().trim_end_matches('\n').to_string();
+                        last["text"] = json!(text);
+                    }
+                }
+                self.close();
+            }
+        }
+    }
+}
+
+/// Converts markdown to an Atlassian Document Format document, what Jira
+/// Cloud stores descriptions and comments as.
+fn to_adf(md: &str) -> Value {
+    let mut builder = AdfBuilder {
+        nodes: vec![json!({ "type": "doc", "version": 1, "content": [] })],
+        implicit: vec![false],
+        marks: Vec::new(),
+        in_head: false,
+        prefix: None,
+    };
+    for event in parser(frontmatter::body(md)) {
+        match event {
+            Event::Start(tag) => builder.start(tag),
+            Event::End(tag) => builder.end(tag),
+            Event::Text(text) => builder.text(&text, &[]),
+            Event::Code(code) => builder.text(&code, &[json!({ "type": "code" })]),
+            Event::SoftBreak => builder.text(" ", &[]),
+            Event::HardBreak => builder.push_inline(json!({ "type": "hardBreak" })),
+            Event::Rule => {
+                builder.close_implicit();
+                builder.open(json!({ "type": "rule" }));
+                let rule = builder.nodes.pop().unwrap();
+                builder.implicit.pop();
+                builder.nodes.last_mut().unwrap()["content"].as_array_mut().unwrap().push(rule);
+            }
+            Event::TaskListMarker(done) => builder.text(if done { "☑ " } else { "☐ " }, &[]),
+            Event::FootnoteReference(label) => builder.text(&format!("[{}]", label), &[]),
+            _ => {}
+        }
+    }
+    builder.close_implicit();
+    while builder.nodes.len() > 1 {
+        builder.close();
+    }
+    builder.nodes.pop().unwrap()
+}
+
+#[derive(Deserialize)]
+struct CreatedIssue {
+    key: String,
+}
+
+#[tauri::command]
+pub fn markdown_to_jira(markdown: &str) -> String {
+    to_wiki(markdown)
+}
+
+#[tauri::command]
+pub fn markdown_to_adf(markdown: &str) -> Value {
+    to_adf(markdown)
+}
+
+/// Sends the document to Jira: as the description of `issue` (or of the
+/// issue recorded in the frontmatter), as a comment on it with `comment`, or
+/// as a new issue of `project` when there is none yet. Cloud gets ADF,
+/// Server and Data Center wiki markup. Images must already be online.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn publish_to_jira(
+    app: AppHandle,
+    account: String,
+    content: String,
+    path: Option<String>,
+    issue: Option<String>,
+    project: Option<String>,
+    issue_type: Option<String>,
+    comment: Option<bool>,
+) -> Result<Published, String> {
+    publish::blocking(move || {
+        let settings: JiraAccount = publish::account(&app, TARGET, &account)?;
+        let document_path = crate::scopes::check_optional(&app, path.as_deref())?;
+        let post = publish::post(&content, document_path.as_deref(), ID_KEY);
+        publish::ensure_remote_images(&post.body, None)?;
+
+        let base = settings.url.trim_end_matches('/').to_string();
+        let (api, body) = if settings.is_cloud() {
+            (format!("{}/rest/api/3", base), to_adf(&post.body))
+        } else {
+            (format!("{}/rest/api/2", base), Value::String(to_wiki(&post.body)))
+        };
+        let auth = match settings.email.as_deref().filter(|e| !e.trim().is_empty()) {
+            Some(email) => {
+                let credentials = format!("{}:{}", email.trim(), settings.token.trim());
+                format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(credentials))
+            }
+            None => format!("Bearer {}", settings.token.trim()),
+        };
+        let agent = http::agent();
+        let request = |method: &str, path: &str| agent.request(method, &format!("{}/{}", api, path)).set("Authorization", &auth);
+
+        let key = issue.filter(|i| !i.trim().is_empty()).map(|i| i.trim().to_uppercase()).or(post.id);
+        if let Some(key) = &key {
+            publish::checked_id(key, |c| c.is_ascii_alphanumeric() || c == '-' || c == '_')?;
+        }
+        let key = match key {
+            Some(key) if comment.unwrap_or(false) => {
+                request("POST", &format!("issue/{}/comment", key)).send_json(json!({ "body": body })).map_err(http::error_message)?;
+                key
+            }
+            Some(key) => {
+                request("PUT", &format!("issue/{}", key))
+                    .send_json(json!({ "fields": { "description": body } }))
+                    .map_err(http::error_message)?;
+                key
+            }
+            None => {
+                let project = project.filter(|p| !p.trim().is_empty()).ok_or("Choisissez le projet Jira du ticket")?;
+                let issue_type = issue_type.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| DEFAULT_ISSUE_TYPE.to_string());
+                let created: CreatedIssue = request("POST", "issue")
+                    .send_json(json!({ "fields": {
+                        "project": { "key": project.trim() },
+                        "summary": post.title,
+                        "issuetype": { "name": issue_type.trim() },
+                        "description": body,
+                    }}))
+                    .map_err(http::error_message)?
+                    .into_json()
+                    .map_err(|e| format!("Réponse inattendue de Jira: {}", e))?;
+                created.key
+            }
+        };
+
+        let markdown = publish::record_id(&content, ID_KEY, &key);
+        Ok(Published { markdown, url: format!("{}/browse/{}", base, key), id: key, images_uploaded: 0 })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adf_of_headings_marks_and_code() {
+        let adf = to_adf("---\ntitle: x\n---\n# Titre\n\nDu **gras** et `code`.\n\n```rust\nfn main() {}\n```\n");
+        assert_eq!(adf["type"], "doc");
+        let content = adf["content"].as_array().unwrap();
+        assert_eq!(content[0]["type"], "heading");
+        assert_eq!(content[0]["attrs"]["level"], 1);
+        assert_eq!(content[0]["content"][0]["text"], "Titre");
+        let paragraph = &content[1]["content"];
+        assert_eq!(paragraph[1]["text"], "gras");
+        assert_eq!(paragraph[1]["marks"][0]["type"], "strong");
+        assert_eq!(paragraph[3]["marks"][0]["type"], "code");
+        assert_eq!(content[2]["type"], "codeBlock");
+        assert_eq!(content[2]["attrs"]["language"], "rust");
+    }
+
+    #[test]
+    fn adf_lists_wrap_items_in_paragraphs() {
+        let adf = to_adf("- un\n- deux\n");
+        let list = &adf["content"][0];
+        assert_eq!(list["type"], "bulletList");
+        assert_eq!(list["content"].as_array().unwrap().len(), 2);
+        assert_eq!(list["content"][1]["content"][0]["type"], "paragraph");
+        assert_eq!(list["content"][1]["content"][0]["content"][0]["text"], "deux");
+    }
+}
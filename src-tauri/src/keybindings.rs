@@ -0,0 +1,215 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::settings;
+
+/// Editor shortcuts by action. `Mod` is Cmd on macOS and Ctrl elsewhere, so
+/// bindings carry over between platforms.
+const DEFAULT_BINDINGS: &[(&str, &[&str])] = &[
+    ("undo", &["Mod+Z"]),
+    ("redo", &["Mod+Shift+Z", "Mod+Y"]),
+    ("open", &["Mod+O"]),
+    ("save", &["Mod+S"]),
+    ("saveAs", &["Mod+Shift+S"]),
+    ("bold", &["Mod+B"]),
+    ("italic", &["Mod+I"]),
+    ("underline", &["Mod+U"]),
+    ("strikethrough", &["Mod+D"]),
+    ("code", &["Mod+E"]),
+    ("link", &["Mod+K"]),
+];
+
+/// Clipboard and selection shortcuts, which the webview handles itself.
+const RESERVED: &[&str] = &["Mod+A", "Mod+C", "Mod+V", "Mod+X"];
+#[cfg(target_os = "macos")]
+const SYSTEM_RESERVED: &[&str] = &["Mod+Q", "Mod+H", "Mod+M", "Mod+W", "Mod+Tab"];
+#[cfg(not(target_os = "macos"))]
+const SYSTEM_RESERVED: &[&str] = &["Alt+F4", "Alt+Tab"];
+
+const MODIFIERS: &[&str] = &["Mod", "Ctrl", "Meta", "Alt", "Shift"];
+const NAMED_KEYS: &[(&str, &[&str])] = &[
+    ("Enter", &["enter", "return"]),
+    ("Tab", &["tab"]),
+    ("Escape", &["escape", "esc"]),
+    ("Space", &["space"]),
+    ("Backspace", &["backspace"]),
+    ("Delete", &["delete", "del"]),
+    ("Insert", &["insert", "ins"]),
+    ("Home", &["home"]),
+    ("End", &["end"]),
+    ("PageUp", &["pageup", "pgup"]),
+    ("PageDown", &["pagedown", "pgdn"]),
+    ("ArrowUp", &["arrowup", "up"]),
+    ("ArrowDown", &["arrowdown", "down"]),
+    ("ArrowLeft", &["arrowleft", "left"]),
+    ("ArrowRight", &["arrowright", "right"]),
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Keybinding {
+    pub action: &'static str,
+    /// Normalized chords, e.g. "Mod+Shift+S".
+    pub chords: Vec<String>,
+    /// The chords as this platform writes them, e.g. "Cmd+Shift+S".
+    pub labels: Vec<String>,
+    pub default_chords: Vec<&'static str>,
+    pub customized: bool,
+}
+
+fn modifier(token: &str) -> Option<&'static str> {
+    let mac = cfg!(target_os = "macos");
+    Some(match token.to_lowercase().as_str() {
+        "mod" | "cmdorctrl" | "commandorcontrol" => "Mod",
+        "ctrl" | "control" => if mac { "Ctrl" } else { "Mod" },
+        "cmd" | "command" | "meta" | "super" | "win" | "⌘" => if mac { "Mod" } else { "Meta" },
+        "alt" | "option" | "opt" | "⌥" => "Alt",
+        "shift" | "⇧" => "Shift",
+        _ => return None,
+    })
+}
+
+fn key(token: &str) -> Option<String> {
+    let mut chars = token.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return (!c.is_whitespace()).then(|| c.to_uppercase().to_string());
+    }
+    let lower = token.to_lowercase();
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()).filter(|n| (1..=24).contains(n)) {
+        return Some(format!("F{}", n));
+    }
+    NAMED_KEYS.iter().find(|(_, names)| names.contains(&lower.as_str())).map(|(name, _)| name.to_string())
+}
+
+/// `chord` written the one way this module stores it: modifiers in a fixed
+/// order, then the key, with Ctrl (or Cmd on macOS) as `Mod`.
+pub fn normalize(chord: &str) -> Result<String, String> {
+    let chord = chord.trim();
+    let (modifiers, key_token) = match chord.strip_suffix("++") {
+        // The plus key itself
+        Some(modifiers) => (modifiers, "+"),
+        None => chord.rsplit_once('+').unwrap_or(("", chord)),
+    };
+    let key = key(key_token.trim()).ok_or_else(|| format!("Touche inconnue dans le raccourci {}: {}", chord, key_token))?;
+    let mut found = Vec::new();
+    for token in modifiers.split('+').map(str::trim).filter(|t| !t.is_empty()) {
+        let modifier = modifier(token).ok_or_else(|| format!("Modificateur inconnu dans le raccourci {}: {}", chord, token))?;
+        if !found.contains(&modifier) {
+            found.push(modifier);
+        }
+    }
+    let is_function_key = key.len() > 1 && key.starts_with('F');
+    if !is_function_key && found.iter().all(|m| *m == "Shift") {
+        return Err(format!("Le raccourci {} doit utiliser Ctrl, Cmd ou Alt: sans eux, il tape du texte", chord));
+    }
+    let mut parts: Vec<&str> = MODIFIERS.iter().copied().filter(|m| found.contains(m)).collect();
+    parts.push(&key);
+    Ok(parts.join("+"))
+}
+
+/// `chord` as this platform labels it.
+fn label(chord: &str) -> String {
+    let primary = if cfg!(target_os = "macos") { "Cmd" } else { "Ctrl" };
+    chord.split('+').map(|part| if part == "Mod" { primary } else { part }).collect::<Vec<_>>().join("+")
+}
+
+/// Every action's chords: the user's where they changed them, the defaults
+/// otherwise.
+fn bindings(custom: &BTreeMap<String, Vec<String>>) -> Vec<(&'static str, Vec<String>, bool)> {
+    DEFAULT_BINDINGS
+        .iter()
+        .map(|(action, defaults)| match custom.get(*action) {
+            Some(chords) => (*action, chords.iter().filter_map(|c| normalize(c).ok()).collect(), true),
+            None => (*action, defaults.iter().map(|c| c.to_string()).collect(), false),
+        })
+        .collect()
+}
+
+fn list(custom: &BTreeMap<String, Vec<String>>) -> Vec<Keybinding> {
+    bindings(custom)
+        .into_iter()
+        .map(|(action, chords, customized)| Keybinding {
+            action,
+            labels: chords.iter().map(|c| label(c)).collect(),
+            chords,
+            default_chords: DEFAULT_BINDINGS.iter().find(|(a, _)| *a == action).map(|(_, d)| d.to_vec()).unwrap_or_default(),
+            customized,
+        })
+        .collect()
+}
+
+/// What makes the chords of `action` unusable: chords the system or another
+/// action already has.
+fn conflicts(custom: &BTreeMap<String, Vec<String>>, action: &str) -> Vec<String> {
+    let all = bindings(custom);
+    let Some((_, chords, _)) = all.iter().find(|(a, _, _)| *a == action) else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    for chord in chords {
+        if RESERVED.contains(&chord.as_str()) || SYSTEM_RESERVED.contains(&chord.as_str()) {
+            out.push(format!("{} est réservé par le système", label(chord)));
+        }
+        for (other, other_chords, _) in &all {
+            if *other != action && other_chords.contains(chord) {
+                out.push(format!("{} est déjà utilisé par {}", label(chord), other));
+            }
+        }
+    }
+    out
+}
+
+fn save(app: &AppHandle, custom: &BTreeMap<String, Vec<String>>) -> Result<Vec<Keybinding>, String> {
+    let value = serde_json::to_value(custom).map_err(|e| e.to_string())?;
+    let settings = settings::replace(app, "keybindings", value)?;
+    Ok(list(&settings.keybindings))
+}
+
+#[tauri::command]
+pub fn get_keybindings(app: AppHandle) -> Vec<Keybinding> {
+    list(&settings::load(&app).keybindings)
+}
+
+/// Binds `action` to `chords`, refusing chords the system or another action
+/// has. An empty list leaves the action without a shortcut.
+#[tauri::command]
+pub fn set_keybinding(app: AppHandle, action: String, chords: Vec<String>) -> Result<Vec<Keybinding>, String> {
+    let (_, defaults) = DEFAULT_BINDINGS.iter().find(|(a, _)| *a == action).ok_or_else(|| format!("Action inconnue: {}", action))?;
+    let mut normalized: Vec<String> = Vec::new();
+    for chord in &chords {
+        let chord = normalize(chord)?;
+        if !normalized.contains(&chord) {
+            normalized.push(chord);
+        }
+    }
+    let mut custom = settings::load(&app).keybindings;
+    if normalized.iter().map(String::as_str).eq(defaults.iter().copied()) {
+        custom.remove(&action);
+    } else {
+        custom.insert(action.clone(), normalized);
+    }
+    let conflicts = conflicts(&custom, &action);
+    if !conflicts.is_empty() {
+        return Err(conflicts.join("; "));
+    }
+    save(&app, &custom)
+}
+
+/// Puts back the default shortcuts of `action`, or of every action when None.
+#[tauri::command]
+pub fn reset_keybindings(app: AppHandle, action: Option<String>) -> Result<Vec<Keybinding>, String> {
+    let mut custom = settings::load(&app).keybindings;
+    match action {
+        Some(action) => {
+            custom.remove(&action);
+            // Another action may have taken a default chord of this one
+            let conflicts = conflicts(&custom, &action);
+            if !conflicts.is_empty() {
+                return Err(conflicts.join("; "));
+            }
+        }
+        None => custom.clear(),
+    }
+    save(&app, &custom)
+}
@@ -0,0 +1,33 @@
+use std::process::Command;
+
+/// LaTeX engines pandoc can make PDFs with, preferred first: xelatex and
+/// lualatex handle Unicode text and system fonts.
+const ENGINES: &[&str] = &["xelatex", "lualatex", "pdflatex"];
+
+fn installed(engine: &str) -> bool {
+    Command::new(engine).arg("--version").output().map(|o| o.status.success()).unwrap_or(false)
+}
+
+/// The `--pdf-engine` for LaTeX output: `preferred` when given, otherwise
+/// the first installed engine.
+pub fn pdf_engine_arg(preferred: Option<&str>) -> Result<String, String> {
+    let engine = match preferred.map(str::trim).filter(|e| !e.is_empty()) {
+        Some(engine) if !ENGINES.contains(&engine) => return Err(format!("Moteur LaTeX inconnu: {} ({})", engine, ENGINES.join(", "))),
+        Some(engine) if !installed(engine) => return Err(format!("Le moteur LaTeX {} est introuvable", engine)),
+        Some(engine) => engine,
+        None => ENGINES
+            .iter()
+            .copied()
+            .find(|e| installed(e))
+            .ok_or("Aucun moteur LaTeX trouvé. Installez TeX Live ou MiKTeX pour exporter en beamer.")?,
+    };
+    Ok(format!("--pdf-engine={}", engine))
+}
+
+/// LaTeX engines installed on this machine.
+#[tauri::command]
+pub async fn list_latex_engines() -> Result<Vec<&'static str>, String> {
+    tauri::async_runtime::spawn_blocking(|| ENGINES.iter().copied().filter(|e| installed(e)).collect())
+        .await
+        .map_err(|e| format!("Erreur lors de la recherche des moteurs LaTeX: {}", e))
+}
@@ -1,8 +1,99 @@
+mod accessibility;
+mod anki;
+mod archive;
+mod assets;
+mod backlinks;
+mod calendar;
+mod citations;
+mod confluence;
+mod criticmarkup;
+mod crossref;
+mod devto;
+mod diagnostics;
+mod diagrams;
+mod directories;
+mod documents;
+mod docx_styles;
+mod downloads;
+mod dropped_files;
+mod duplicates;
+mod email;
+mod encoding;
+mod encryption;
+mod export_cache;
+mod export_check;
+mod export_profiles;
+mod favorites;
+mod feed;
+mod file_info;
+mod filters;
+mod frontmatter;
+mod ghost;
+mod gist;
+mod git;
+mod glossary;
+mod grammar;
+mod graph;
+mod hashnode;
+mod highlight_styles;
+mod history;
+mod http;
+mod images;
+mod jira;
+mod keybindings;
+mod latex;
+mod locks;
+mod markdown;
+mod math;
+mod obsidian;
+mod offline;
+mod pandoc;
+mod pandoc_ast;
+mod pandoc_defaults;
+mod pandoc_templates;
+mod paths;
+mod preview_server;
+mod publish;
+mod read_later;
+mod readability;
+mod recent;
+mod recovery;
+mod redact;
+mod s3_backup;
+mod safe_save;
+mod sanitize;
+mod scopes;
+mod screenshot;
+mod search;
+mod session;
+mod settings;
+mod settings_bundle;
+mod setup;
+mod site_export;
+mod slides;
+mod static_site;
+mod stats;
+mod svg;
+mod tags;
+mod templates;
+mod track_changes;
+mod trash;
+mod updates;
+mod usage;
+mod watcher;
+mod webdav;
+mod webhooks;
+mod wordpress;
+mod workspace;
+mod zotero;
+
+use std::path::Path;
 use std::process::Command;
+use tauri::AppHandle;
 
 #[tauri::command]
 fn check_wkhtmltopdf_installed() -> bool {
-    Command::new("wkhtmltopdf")
+    Command::new(settings::tool("wkhtmltopdf"))
         .arg("--version")
         .output()
         .map(|o| o.status.success())
@@ -33,25 +124,63 @@ async fn install_wkhtmltopdf_winget() -> Result<(), String> {
     }
 }
 
+/// `track_changes` is "accept" (the default), "reject", or "all" to keep
+/// revisions as CriticMarkup; comments are kept as CriticMarkup either way.
 #[tauri::command]
-fn convert_word_to_markdown(file_path: &str) -> Result<String, String> {
-    convert_to_markdown(file_path, "docx")
+#[allow(clippy::too_many_arguments)]
+fn convert_word_to_markdown(app: AppHandle, file_path: &str, document_path: Option<&str>, image_options: Option<images::ImageOptimizeOptions>, flavor: Option<&str>, track_changes: Option<&str>, shift_heading_level: Option<i8>, sandbox: Option<bool>) -> Result<String, String> {
+    let track_changes = track_changes.map(track_changes::TrackChanges::parse).transpose()?.unwrap_or(track_changes::TrackChanges::Accept);
+    let options = pandoc::ImportOptions { flavor, track_changes: Some(track_changes), shift_heading_level, sandbox };
+    let result = convert_to_markdown(&app, file_path, "docx", document_path, image_options, &options).inspect_err(|e| diagnostics::record_error("import", e));
+    usage::record_conversion(&app, "import", "docx", &result);
+    result
 }
 
 #[tauri::command]
-fn convert_to_markdown_via_pandoc(file_path: &str, from_format: &str) -> Result<String, String> {
-    convert_to_markdown(file_path, from_format)
+#[allow(clippy::too_many_arguments)]
+fn convert_to_markdown_via_pandoc(app: AppHandle, file_path: &str, from_format: &str, document_path: Option<&str>, image_options: Option<images::ImageOptimizeOptions>, flavor: Option<&str>, shift_heading_level: Option<i8>, sandbox: Option<bool>) -> Result<String, String> {
+    let options = pandoc::ImportOptions { flavor, shift_heading_level, sandbox, ..Default::default() };
+    let result = convert_to_markdown(&app, file_path, from_format, document_path, image_options, &options).inspect_err(|e| diagnostics::record_error("import", e));
+    usage::record_conversion(&app, "import", from_format, &result);
+    result
 }
 
-fn convert_to_markdown(file_path: &str, from_format: &str) -> Result<String, String> {
-    let output = Command::new("pandoc")
+/// Converts a file to markdown with pandoc. Embedded media is extracted into
+/// a fresh assets folder and, when the destination document is known, moved
+/// into that document's own assets folder, optionally optimizing images on
+/// the way.
+fn convert_to_markdown(app: &AppHandle, file_path: &str, from_format: &str, document_path: Option<&str>, image_options: Option<images::ImageOptimizeOptions>, options: &pandoc::ImportOptions) -> Result<String, String> {
+    let file = scopes::check(app, file_path)?;
+    let document_path = scopes::check_optional(app, document_path)?;
+    let writer = pandoc::import_writer(options.flavor)?;
+    let shift_arg = pandoc::shift_heading_arg(options.shift_heading_level)?;
+    let (track_args, _track_filter) = match options.track_changes {
+        Some(mode) => {
+            let (args, filter) = mode.pandoc_args()?;
+            (args, Some(filter))
+        }
+        None => (Vec::new(), None),
+    };
+    // Word styles are only visible to the filter with +styles
+    let style_filter = if from_format == "docx" { docx_styles::lua_filter(app)? } else { None };
+    let reader = match &style_filter {
+        Some(_) => format!("{}+styles", from_format),
+        None => from_format.to_string(),
+    };
+    let extract_dir = assets::new_unsaved_assets_dir(app)?;
+    let output = Command::new(settings::tool("pandoc"))
         .args([
-            "-f", from_format,
-            "-t", "markdown-raw_html-native_spans-native_divs",
+            "-f", &reader,
+            "-t", &writer,
             "--wrap=none",
-            "--extract-media=.",
-            file_path
         ])
+        .args(track_args)
+        .args(shift_arg)
+        .args(pandoc::sandbox_arg(app, &file, options.sandbox))
+        .args(style_filter.iter().map(|filter| format!("--lua-filter={}", filter.path().display())))
+        .args(filters::pandoc_args(app, filters::Stage::Import)?)
+        .arg(format!("--extract-media={}", extract_dir.display()))
+        .arg(&file)
         .output()
         .map_err(|e| format!("Erreur lors de l'exécution de pandoc: {}. Assurez-vous que pandoc est installé.", e))?;
 
@@ -66,6 +195,19 @@ fn convert_to_markdown(file_path: &str, from_format: &str) -> Result<String, Str
             .replace("<sub>", "~")
             .replace("</sub>", "~");
 
+        let content = assets::rewrite_extracted_links(&content, &extract_dir, None);
+        let content = images::convert_document_images(app, &content, None)?;
+        let content = match image_options {
+            Some(options) => images::optimize_document(app, &content, None, &options)?.markdown,
+            None => content,
+        };
+        let content = match &document_path {
+            Some(path) => assets::migrate(app, &content, None, path)?,
+            None => content,
+        };
+        // Nothing was extracted: don't leave an empty folder behind
+        let _ = std::fs::remove_dir(&extract_dir);
+
         Ok(content)
     } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -73,20 +215,131 @@ fn convert_to_markdown(file_path: &str, from_format: &str) -> Result<String, Str
     }
 }
 
+/// `options` are those of an `ExportOptions`, laid over the format's saved
+/// profile. Raw HTML of HTML exports is sanitized unless the document is
+/// `trusted`.
 #[tauri::command]
-fn export_markdown_via_pandoc(markdown_content: &str, output_path: &str, to_format: &str) -> Result<(), String> {
-    let mut args = vec![
-        "-f".to_string(), "markdown".to_string(),
+fn export_markdown_via_pandoc(app: AppHandle, markdown_content: &str, output_path: &str, to_format: &str, workspace_dir: Option<&str>, options: Option<serde_json::Value>, trusted: Option<bool>) -> Result<(), String> {
+    let _busy = updates::busy()?;
+    let result = export_via_pandoc(&app, markdown_content, output_path, to_format, workspace_dir, options, trusted.unwrap_or(false))
+        .inspect_err(|e| diagnostics::record_error("export", e));
+    usage::record_conversion(&app, "export", to_format, &result);
+    result
+}
+
+fn export_via_pandoc(app: &AppHandle, markdown_content: &str, output_path: &str, to_format: &str, workspace_dir: Option<&str>, options: Option<serde_json::Value>, trusted: bool) -> Result<(), String> {
+    let settings = settings::load(app);
+    let html_output = sanitize::HTML_FORMATS.contains(&to_format);
+    // Relative paths land in the exports folder, which the user chose
+    let checked_output;
+    let output_path = if Path::new(output_path).is_absolute() {
+        checked_output = scopes::check(app, output_path)?.to_string_lossy().to_string();
+        checked_output.as_str()
+    } else if Path::new(output_path).components().any(|c| !matches!(c, std::path::Component::Normal(_))) {
+        return Err(scopes::PathError::Invalid { path: output_path.to_string() }.into());
+    } else {
+        output_path
+    };
+    let output_path = &directories::export_path(app, output_path)?;
+    let workspace_dir = scopes::check_optional(app, workspace_dir)?.map(|dir| dir.to_string_lossy().to_string());
+    let workspace_dir = workspace_dir.as_deref();
+    let mut options = export_profiles::resolve(app, to_format, options)?;
+    if options.slides.pdf_engine.is_none() {
+        options.slides.pdf_engine = settings.export.latex_engine.clone();
+    }
+    if options.citations.document_path.is_none() {
+        options.citations.document_path = options.document_path.clone();
+    }
+    let math_mode = match options.math.as_deref() {
+        Some(mode) => math::MathMode::parse(mode)?,
+        None => math::MathMode::default_for(to_format, options.offline),
+    };
+    if options.offline {
+        offline::check(to_format, math_mode)?;
+        options.diagrams.plantuml_server = None;
+    }
+    let reader = options.reader(markdown_content)?;
+    let option_args = options.pandoc_args(app, to_format)?;
+    let metadata_file = options.metadata_file()?;
+    let crossref_args = crossref::pandoc_args(markdown_content, &options.crossref)?;
+    let citation_args = citations::pandoc_args(app, markdown_content, &options.citations)?;
+    let defaults_args = pandoc_defaults::pandoc_args(app, options.defaults.as_deref())?;
+    let resource_dirs = options.resource_dirs(app, workspace_dir)?;
+
+    let markdown_content = match workspace_dir {
+        Some(dir) => glossary::apply_to_export(markdown_content, &glossary::load(Path::new(dir))?, to_format),
+        None => markdown_content.to_string(),
+    };
+    let markdown_content = match workspace_dir.map(Path::new).filter(|dir| obsidian::is_vault(dir)) {
+        Some(dir) => obsidian::prepare(&markdown_content, None, &obsidian::Vault::load(dir)),
+        None => markdown_content,
+    };
+    let markdown_content = diagrams::render_for_export(app, &markdown_content, to_format, &options.diagrams);
+    let markdown_content = math::prepare_for_export(app, &markdown_content, math_mode)?;
+    let markdown_content = svg::sanitize_linked(app, &markdown_content, &resource_dirs)?;
+    let markdown_content = if options.offline { offline::strip_remote_images(&markdown_content) } else { markdown_content };
+    // HTML links its images instead of embedding them
+    let markdown_content = if options.redact_metadata && !html_output {
+        redact::strip_linked_images(app, &markdown_content, &resource_dirs)?
+    } else {
+        markdown_content
+    };
+    let markdown_content = options.slides.prepare(&markdown_content, to_format);
+    let missing = pandoc::missing_resources(&markdown_content, &resource_dirs);
+    if !missing.is_empty() {
+        return Err(format!("Ressources introuvables: {}", missing.join(", ")));
+    }
+
+    let mut args = defaults_args;
+    args.extend([
+        "-f".to_string(), reader,
         "-t".to_string(), to_format.to_string(),
         "--wrap=none".to_string(),
         "-o".to_string(), output_path.to_string(),
-    ];
+    ]);
 
     if to_format == "pdf" {
-        args.push("--pdf-engine=wkhtmltopdf".to_string());
+        args.push(format!("--pdf-engine={}", settings::tool("wkhtmltopdf")));
+        if options.offline {
+            args.extend(offline::pdf_engine_args());
+        }
     }
+    args.extend(option_args);
+    args.extend(pandoc::resource_path_arg(&resource_dirs)?);
+    args.extend(options.slides.pandoc_args(to_format)?);
+    if let Some(file) = &metadata_file {
+        args.push(format!("--metadata-file={}", file.path().display()));
+    }
+    args.extend(math_mode.pandoc_args());
+    // Before citeproc, so filters can add or rewrite citations
+    args.extend(filters::pandoc_args(app, filters::Stage::Export)?);
+    args.extend(crossref_args);
+    args.extend(citation_args);
 
-    let output = Command::new("pandoc")
+    let document_dir = options.document_path.as_deref().map(Path::new).and_then(Path::parent);
+    // The cache keeps pandoc's output, cleaned on the way out
+    let sanitize = !trusted && html_output;
+    let strip_remote = options.offline && html_output;
+    let finish = |path: &Path| -> Result<(), String> {
+        if sanitize {
+            sanitize::clean_file(path)?;
+        }
+        if strip_remote {
+            offline::clean_file(path)?;
+        }
+        if options.redact_metadata {
+            redact::redact_file(path, to_format)?;
+        }
+        Ok(())
+    };
+    let cache_key = settings.export.cache.then(|| export_cache::key(&markdown_content, &args, document_dir));
+    if let Some(key) = &cache_key {
+        if export_cache::restore(app, key, output_path)? {
+            return finish(Path::new(output_path));
+        }
+    }
+
+    let output = Command::new(settings::tool("pandoc"))
         .args(&args)
         .stdin(std::process::Stdio::piped())
         .stdout(std::process::Stdio::piped())
@@ -105,7 +358,16 @@ fn export_markdown_via_pandoc(markdown_content: &str, output_path: &str, to_form
         .map_err(|e| format!("Erreur lors de l'attente de pandoc: {}", e))?;
 
     if result.status.success() {
-        Ok(())
+        export_check::check(Path::new(output_path), to_format).map_err(|e| {
+            // The engine's warnings usually say what went wrong
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            if stderr.trim().is_empty() { e.to_string() } else { format!("{}: {}", e, stderr.trim()) }
+        })?;
+        // A cache failure shouldn't fail an export that worked
+        if let Some(key) = &cache_key {
+            let _ = export_cache::store(app, key, output_path);
+        }
+        finish(Path::new(output_path))
     } else {
         let stderr = String::from_utf8_lossy(&result.stderr);
         Err(format!("Pandoc a échoué: {}", stderr))
@@ -113,17 +375,21 @@ fn export_markdown_via_pandoc(markdown_content: &str, output_path: &str, to_form
 }
 
 #[tauri::command]
-fn export_html_to_temp(html_content: &str) -> Result<String, String> {
-    let temp_dir = std::env::temp_dir();
+fn export_html_to_temp(html_content: &str, trusted: Option<bool>, offline: Option<bool>) -> Result<String, String> {
+    let temp_dir = settings::temp_dir();
     let path = temp_dir.join("ohmymarkdown_export.html");
-    std::fs::write(&path, html_content)
+    let html = svg::sanitize_embedded(html_content);
+    let html = if trusted.unwrap_or(false) { html } else { sanitize::clean_document(&html) };
+    let html = if offline.unwrap_or(false) { offline::strip_remote_resources(&html) } else { html };
+    std::fs::write(&path, html)
         .map_err(|e| format!("Erreur d'écriture du fichier temporaire: {}", e))?;
     Ok(path.to_string_lossy().to_string())
 }
 
 #[tauri::command]
-fn convert_pdf_to_markdown(file_path: &str) -> Result<String, String> {
-    let bytes = std::fs::read(file_path)
+fn convert_pdf_to_markdown(app: AppHandle, file_path: &str) -> Result<String, String> {
+    let file = scopes::check(&app, file_path)?;
+    let bytes = std::fs::read(&file)
         .map_err(|e| format!("Impossible de lire le fichier: {}", e))?;
 
     let text = pdf_extract::extract_text_from_mem(&bytes)
@@ -181,13 +447,260 @@ fn convert_pdf_to_markdown(file_path: &str) -> Result<String, String> {
     Ok(result)
 }
 
+/// `handler` answering "command not found" for the commands this build
+/// can't serve.
+fn available_only(handler: impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static) -> impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let command = invoke.message.command();
+        updates::is_available(command) && grammar::is_available(command) && handler(invoke)
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
-        .invoke_handler(tauri::generate_handler![convert_word_to_markdown, convert_to_markdown_via_pandoc, export_markdown_via_pandoc, export_html_to_temp, check_wkhtmltopdf_installed, install_wkhtmltopdf_winget, convert_pdf_to_markdown])
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .manage(grammar::LanguageToolServer::default())
+        .manage(recovery::AutosaveQueue::default())
+        .manage(watcher::FileWatcher::default())
+        .manage(workspace::Workspace::default())
+        .manage(search::SearchIndex::default())
+        .manage(backlinks::BacklinkIndex::default())
+        .manage(encryption::DocumentKeys::default())
+        .manage(documents::LargeFiles::default())
+        .manage(static_site::SiteProcess::default())
+        .manage(preview_server::PreviewServer::default())
+        .manage(updates::Updates::default())
+        .setup(|app| {
+            recovery::start(app.handle().clone());
+            s3_backup::start(app.handle().clone());
+            safe_save::migrate_settings(app.handle());
+            settings::load(app.handle());
+            updates::init(app.handle());
+            session::restore_window(app.handle());
+            Ok(())
+        })
+        .invoke_handler(available_only(tauri::generate_handler![
+            convert_word_to_markdown,
+            convert_to_markdown_via_pandoc,
+            export_markdown_via_pandoc,
+            export_html_to_temp,
+            check_wkhtmltopdf_installed,
+            install_wkhtmltopdf_winget,
+            convert_pdf_to_markdown,
+            grammar::get_languagetool_status,
+            grammar::install_languagetool,
+            grammar::start_languagetool_server,
+            grammar::stop_languagetool_server,
+            grammar::check_grammar,
+            readability::analyze_readability,
+            accessibility::audit_images,
+            assets::migrate_document_assets,
+            assets::get_assets_dir,
+            assets::localize_remote_images,
+            assets::find_unused_assets,
+            assets::trash_assets,
+            images::save_clipboard_image,
+            images::optimize_images,
+            images::convert_unsupported_images,
+            screenshot::capture_screenshot,
+            dropped_files::handle_dropped_files,
+            diagrams::get_diagram_renderers,
+            diagrams::render_diagram,
+            svg::sanitize_svg,
+            glossary::get_glossary,
+            glossary::set_glossary_entry,
+            glossary::remove_glossary_entry,
+            glossary::scan_glossary_terms,
+            recovery::push_autosave,
+            recovery::discard_autosave,
+            recovery::list_recoverable_documents,
+            recovery::get_recovered_document,
+            recent::add_recent_file,
+            recent::get_recent_files,
+            recent::pin_recent_file,
+            recent::remove_recent_file,
+            session::save_session,
+            session::restore_session,
+            session::get_session_restore_enabled,
+            session::set_session_restore_enabled,
+            session::clear_session,
+            watcher::watch_path,
+            watcher::unwatch_path,
+            workspace::open_workspace,
+            workspace::close_workspace,
+            workspace::get_workspace_tree,
+            workspace::create_workspace_file,
+            workspace::create_workspace_folder,
+            workspace::rename_workspace_entry,
+            workspace::move_workspace_entry,
+            workspace::delete_workspace_entry,
+            search::search_workspace,
+            backlinks::get_backlinks,
+            backlinks::update_backlinks,
+            backlinks::rename_note,
+            assets::move_document,
+            duplicates::find_duplicate_notes,
+            archive::export_workspace_zip,
+            stats::get_workspace_stats,
+            graph::get_graph,
+            favorites::list_favorites,
+            favorites::set_favorite,
+            favorites::reorder_children,
+            locks::get_document_lock,
+            locks::set_document_locked,
+            tags::list_tags,
+            tags::get_files_by_tag,
+            tags::rename_tag,
+            templates::list_templates,
+            templates::create_from_template,
+            history::record_version,
+            history::list_versions,
+            history::get_version,
+            history::diff_version,
+            history::restore_version,
+            history::export_version,
+            trash::list_trash,
+            trash::restore_from_trash,
+            trash::empty_trash,
+            encryption::encrypt_document,
+            encryption::open_encrypted_document,
+            encryption::save_encrypted_document,
+            encryption::decrypt_document,
+            encryption::is_encrypted_document,
+            encryption::lock_encrypted_documents,
+            documents::open_document,
+            documents::save_document,
+            documents::read_document_lines,
+            documents::begin_document_write,
+            documents::write_document_chunk,
+            documents::finish_document_write,
+            safe_save::get_backup_retention,
+            safe_save::set_backup_retention,
+            file_info::get_file_info,
+            git::git_status,
+            git::git_commit,
+            git::git_log,
+            git::git_diff,
+            git::git_restore,
+            publish::list_publish_accounts,
+            publish::set_publish_account,
+            publish::remove_publish_account,
+            wordpress::publish_to_wordpress,
+            ghost::publish_to_ghost,
+            devto::publish_to_devto,
+            hashnode::publish_to_hashnode,
+            gist::push_gist,
+            gist::import_gist,
+            confluence::publish_to_confluence,
+            static_site::detect_site_project,
+            static_site::create_site_post,
+            static_site::run_site_command,
+            static_site::stop_site_command,
+            site_export::export_site,
+            preview_server::start_preview_server,
+            preview_server::stop_preview_server,
+            preview_server::get_preview_server,
+            preview_server::update_preview_server,
+            webdav::get_sync_remote,
+            webdav::set_sync_remote,
+            webdav::remove_sync_remote,
+            webdav::sync_workspace,
+            s3_backup::get_s3_backup_settings,
+            s3_backup::set_s3_backup_settings,
+            s3_backup::remove_s3_backup_settings,
+            s3_backup::backup_workspace_to_s3,
+            s3_backup::list_s3_snapshots,
+            s3_backup::restore_s3_snapshot,
+            obsidian::get_obsidian_vault,
+            obsidian::prepare_obsidian_markdown,
+            obsidian::resolve_wikilink,
+            obsidian::open_daily_note,
+            zotero::search_citations,
+            zotero::pick_citations,
+            zotero::export_bibliography,
+            citations::check_citations,
+            citations::list_csl_styles,
+            citations::download_csl_style,
+            citations::remove_csl_style,
+            email::get_smtp_settings,
+            email::set_smtp_settings,
+            email::remove_smtp_settings,
+            email::email_document,
+            webhooks::list_webhooks,
+            webhooks::set_webhook,
+            webhooks::remove_webhook,
+            webhooks::preview_webhook_messages,
+            webhooks::share_to_webhook,
+            anki::preview_flashcards,
+            anki::export_anki,
+            jira::markdown_to_jira,
+            jira::markdown_to_adf,
+            jira::publish_to_jira,
+            read_later::list_read_later_accounts,
+            read_later::set_readwise_token,
+            read_later::connect_instapaper,
+            read_later::remove_read_later_account,
+            read_later::import_read_later,
+            read_later::import_pocket_export,
+            feed::export_feed,
+            calendar::list_due_tasks,
+            calendar::export_tasks_ics,
+            filters::list_lua_filters,
+            filters::get_lua_filters_dir,
+            filters::set_lua_filter,
+            pandoc_defaults::list_pandoc_defaults,
+            pandoc_defaults::validate_pandoc_defaults,
+            pandoc_defaults::add_pandoc_defaults,
+            highlight_styles::list_highlight_styles,
+            highlight_styles::add_highlight_theme,
+            pandoc_templates::list_pandoc_templates,
+            pandoc_templates::import_pandoc_template,
+            pandoc_templates::remove_pandoc_template,
+            pandoc_templates::preview_pandoc_template,
+            crossref::check_pandoc_crossref,
+            criticmarkup::list_annotations,
+            docx_styles::get_docx_style_map,
+            docx_styles::set_docx_style_map,
+            pandoc_ast::markdown_to_ast,
+            pandoc_ast::ast_to_markdown,
+            export_cache::clear_export_cache,
+            export_profiles::get_export_profiles,
+            export_profiles::set_export_profile,
+            slides::list_slide_themes,
+            slides::list_beamer_color_themes,
+            latex::list_latex_engines,
+            settings::get_settings,
+            settings::get_workspace_settings,
+            settings::update_settings,
+            settings_bundle::export_settings_bundle,
+            settings_bundle::import_settings_bundle,
+            directories::get_directories,
+            directories::set_directory,
+            directories::relocate_media_links,
+            diagnostics::get_diagnostics,
+            diagnostics::report_error,
+            setup::get_setup_status,
+            setup::install_setup_tool,
+            setup::complete_setup,
+            paths::get_portable_dir,
+            keybindings::get_keybindings,
+            keybindings::set_keybinding,
+            keybindings::reset_keybindings,
+            usage::get_usage_stats,
+            usage::record_feature_use,
+            usage::set_usage_stats_enabled,
+            usage::clear_usage_stats,
+            updates::check_for_updates,
+            updates::install_update,
+            updates::set_update_channel,
+            sanitize::sanitize_html,
+            scopes::get_path_scopes,
+            scopes::revoke_path_scope,
+        ]))
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,82 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+const LOCKS_FILE: &str = "locked-files.json";
+
+// Serializes read-modify-write cycles on the locked files list
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentLock {
+    /// Locked from the app.
+    pub locked: bool,
+    /// Read-only on disk.
+    pub read_only: bool,
+}
+
+fn locks_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::paths::app_config_dir(app)?.join(LOCKS_FILE))
+}
+
+/// The same for every spelling of a path and the symlinks to it; files not
+/// created yet fall back to the normalized path.
+fn key(path: &Path) -> String {
+    path.canonicalize().unwrap_or_else(|_| crate::assets::normalize(path)).to_string_lossy().to_string()
+}
+
+fn load(app: &AppHandle) -> BTreeSet<String> {
+    locks_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn is_read_only(path: &Path) -> bool {
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().readonly())
+}
+
+/// Fails when `path` must not be overwritten: locked in the app or read-only
+/// on disk. Called by the save commands before touching the file.
+pub fn ensure_writable(app: &AppHandle, path: &Path) -> Result<(), String> {
+    if load(app).contains(&key(path)) {
+        return Err(format!("{} est verrouillé; déverrouillez-le pour l'enregistrer", path.display()));
+    }
+    if is_read_only(path) {
+        return Err(format!("{} est en lecture seule", path.display()));
+    }
+    Ok(())
+}
+
+/// `ensure_writable` for every note an operation rewrites, checked before it
+/// starts so it doesn't stop halfway.
+pub fn ensure_all_writable<'a>(app: &AppHandle, paths: impl IntoIterator<Item = &'a Path>) -> Result<(), String> {
+    paths.into_iter().try_for_each(|path| ensure_writable(app, path))
+}
+
+#[tauri::command]
+pub fn get_document_lock(app: AppHandle, path: &str) -> Result<DocumentLock, String> {
+    let path = &crate::scopes::check(&app, path)?;
+    Ok(DocumentLock { locked: load(&app).contains(&key(path)), read_only: is_read_only(path) })
+}
+
+/// Locks or unlocks a document so it cannot be saved by mistake.
+#[tauri::command]
+pub fn set_document_locked(app: AppHandle, path: &str, locked: bool) -> Result<DocumentLock, String> {
+    let file = &crate::scopes::check(&app, path)?;
+    let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+    let mut locks = load(&app);
+    if locked {
+        locks.insert(key(file));
+    } else {
+        locks.remove(&key(file));
+    }
+    let json = serde_json::to_string_pretty(&locks).map_err(|e| e.to_string())?;
+    std::fs::write(locks_path(&app)?, json)
+        .map_err(|e| format!("Impossible d'enregistrer les fichiers verrouillés: {}", e))?;
+    Ok(DocumentLock { locked, read_only: is_read_only(file) })
+}
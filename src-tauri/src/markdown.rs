@@ -0,0 +1,772 @@
+// Helpers for reading markdown source without a full parser. Positions handed
+// back to the frontend are UTF-16 offsets so they can be used directly with
+// textarea.setSelectionRange.
+
+/// Plain-text projection of a markdown document, with each byte of `text`
+/// mapped back to the byte of the source it came from.
+pub struct PlainText {
+    pub text: String,
+    offsets: Vec<usize>,
+    source_len: usize,
+}
+
+impl PlainText {
+    fn new(source_len: usize) -> Self {
+        PlainText { text: String::new(), offsets: Vec::new(), source_len }
+    }
+
+    fn push(&mut self, c: char, source: usize) {
+        self.text.push(c);
+        for _ in 0..c.len_utf8() {
+            self.offsets.push(source);
+        }
+    }
+
+    /// Maps a byte range of `text` to a byte range of the source.
+    pub fn source_range(&self, start: usize, end: usize) -> (usize, usize) {
+        let src_start = self.offsets.get(start).copied().unwrap_or(self.source_len);
+        if end <= start {
+            return (src_start, src_start);
+        }
+        let last = end - 1;
+        let src_end = match self.offsets.get(last) {
+            Some(&o) => {
+                let c_len = self.text[..end]
+                    .chars()
+                    .next_back()
+                    .map(|c| c.len_utf8())
+                    .unwrap_or(1);
+                o + c_len
+            }
+            None => self.source_len,
+        };
+        (src_start, src_end.max(src_start))
+    }
+}
+
+/// Byte length of a leading `---` YAML frontmatter block, or 0 if there is none.
+pub fn frontmatter_len(md: &str) -> usize {
+    let first_line_len = match md.find('\n') {
+        Some(i) => i + 1,
+        None => return 0,
+    };
+    if md[..first_line_len].trim_end() != "---" {
+        return 0;
+    }
+    let mut pos = first_line_len;
+    for line in md[first_line_len..].split_inclusive('\n') {
+        pos += line.len();
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            return pos;
+        }
+    }
+    0
+}
+
+/// Returns the fence character and run length if the line opens or closes a
+/// fenced code block.
+pub fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let c = trimmed.chars().next()?;
+    if c != '`' && c != '~' {
+        return None;
+    }
+    let run = trimmed.chars().take_while(|&x| x == c).count();
+    if run >= 3 {
+        Some((c, run))
+    } else {
+        None
+    }
+}
+
+/// Tracks whether successive lines are inside a fenced code block.
+#[derive(Default)]
+pub struct FenceState {
+    open: Option<(char, usize)>,
+}
+
+impl FenceState {
+    /// Feeds one line and returns true if it belongs to a code block
+    /// (fence lines included).
+    pub fn is_code(&mut self, line: &str) -> bool {
+        match (self.open, fence_marker(line)) {
+            (None, Some(marker)) => {
+                self.open = Some(marker);
+                true
+            }
+            (Some((c, n)), Some((mc, mn))) if c == mc && mn >= n && line.trim().chars().all(|x| x == c) => {
+                self.open = None;
+                true
+            }
+            (Some(_), _) => true,
+            (None, None) => false,
+        }
+    }
+}
+
+/// Length of the block-level prefix of a line (headings, quotes, list
+/// markers, task boxes).
+fn block_prefix_len(line: &str) -> usize {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    loop {
+        while i < bytes.len() && bytes[i] == b' ' {
+            i += 1;
+        }
+        let rest = &bytes[i..];
+        if rest.first() == Some(&b'>') {
+            i += 1;
+            continue;
+        }
+        let hashes = rest.iter().take_while(|&&b| b == b'#').count();
+        if (1..=6).contains(&hashes) && rest.get(hashes) == Some(&b' ') {
+            i += hashes + 1;
+            continue;
+        }
+        if matches!(rest.first(), Some(b'-' | b'*' | b'+')) && rest.get(1) == Some(&b' ') {
+            i += 2;
+            continue;
+        }
+        let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+        if digits > 0 && matches!(rest.get(digits), Some(b'.' | b')')) && rest.get(digits + 1) == Some(&b' ') {
+            i += digits + 2;
+            continue;
+        }
+        if rest.len() >= 4 && rest[0] == b'[' && matches!(rest[1], b' ' | b'x' | b'X') && rest[2] == b']' && rest[3] == b' ' {
+            i += 4;
+            continue;
+        }
+        return i;
+    }
+}
+
+/// Returns the end of a bracketed span starting at `start` (which must be
+/// `open`), honouring nesting.
+fn matching_close(s: &str, start: usize, open: u8, close: u8) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b if b == open => depth += 1,
+            b if b == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn skip_span(out: &mut PlainText, s: &str, next: usize) -> usize {
+    // Avoid leaving a double space where the span used to be
+    if out.text.ends_with(' ') && s[next..].starts_with(' ') {
+        next + 1
+    } else {
+        next
+    }
+}
+
+fn push_inline(s: &str, base: usize, out: &mut PlainText) {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < s.len() {
+        let c = s[i..].chars().next().unwrap_or(' ');
+        match c {
+            '\\' if i + 1 < s.len() => {
+                let escaped = s[i + 1..].chars().next().unwrap_or(' ');
+                out.push(escaped, base + i + 1);
+                i += 1 + escaped.len_utf8();
+            }
+            '`' => {
+                let run = s[i..].chars().take_while(|&x| x == '`').count();
+                let fence = &s[i..i + run];
+                match s[i + run..].find(fence) {
+                    Some(close) => i = skip_span(out, s, i + run + close + run),
+                    None => i += run,
+                }
+            }
+            '!' if bytes.get(i + 1) == Some(&b'[') => {
+                let end = matching_close(s, i + 1, b'[', b']')
+                    .filter(|&e| bytes.get(e + 1) == Some(&b'('))
+                    .and_then(|e| matching_close(s, e + 1, b'(', b')'));
+                match end {
+                    Some(e) => i = skip_span(out, s, e + 1),
+                    None => {
+                        out.push('!', base + i);
+                        i += 1;
+                    }
+                }
+            }
+            '[' if bytes.get(i + 1) == Some(&b'[') => {
+                // Wikilink: keep the alias if there is one, otherwise the target
+                match s[i + 2..].find("]]") {
+                    Some(close) => {
+                        let inner_start = i + 2;
+                        let inner = &s[inner_start..inner_start + close];
+                        let (text_start, text) = match inner.find('|') {
+                            Some(p) => (inner_start + p + 1, &inner[p + 1..]),
+                            None => (inner_start, inner),
+                        };
+                        for (k, ch) in text.char_indices() {
+                            out.push(ch, base + text_start + k);
+                        }
+                        i = inner_start + close + 2;
+                    }
+                    None => {
+                        out.push('[', base + i);
+                        i += 1;
+                    }
+                }
+            }
+            '[' => match matching_close(s, i, b'[', b']') {
+                Some(close) => {
+                    push_inline(&s[i + 1..close], base + i + 1, out);
+                    let mut next = close + 1;
+                    match bytes.get(next) {
+                        Some(b'(') => {
+                            if let Some(e) = matching_close(s, next, b'(', b')') {
+                                next = e + 1;
+                            }
+                        }
+                        Some(b'[') => {
+                            if let Some(e) = matching_close(s, next, b'[', b']') {
+                                next = e + 1;
+                            }
+                        }
+                        _ => {}
+                    }
+                    i = next;
+                }
+                None => {
+                    out.push('[', base + i);
+                    i += 1;
+                }
+            },
+            '<' if bytes.get(i + 1).is_some_and(|b| b.is_ascii_alphabetic() || *b == b'/' || *b == b'!') => {
+                match s[i..].find('>') {
+                    Some(close) => i = skip_span(out, s, i + close + 1),
+                    None => {
+                        out.push('<', base + i);
+                        i += 1;
+                    }
+                }
+            }
+            '*' | '_' | '~' | '^' => i += 1,
+            _ => {
+                out.push(c, base + i);
+                i += c.len_utf8();
+            }
+        }
+    }
+}
+
+/// Strips markdown syntax, frontmatter and code blocks, keeping a mapping back
+/// to source positions. Newlines are preserved so paragraphs stay separated.
+pub fn to_plain_text(md: &str) -> PlainText {
+    let mut out = PlainText::new(md.len());
+    let start = frontmatter_len(md);
+    let mut fences = FenceState::default();
+    let mut pos = start;
+
+    for line in md[start..].split_inclusive('\n') {
+        let line_start = pos;
+        pos += line.len();
+        let content = line.trim_end_matches(['\n', '\r']);
+
+        let is_rule = content.len() >= 3
+            && content.trim().chars().all(|c| c == '-' || c == '*' || c == '_' || c == ' ');
+        let is_table_separator = content.trim_start().starts_with('|')
+            && content.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '));
+
+        if !fences.is_code(content) && !is_rule && !is_table_separator {
+            let prefix = block_prefix_len(content);
+            push_inline(&content[prefix..], line_start + prefix, &mut out);
+        }
+        if line.ends_with('\n') {
+            out.push('\n', line_start + content.len());
+        }
+    }
+
+    out
+}
+
+/// Words of the rendered text, code blocks and frontmatter excluded.
+pub fn word_count(md: &str) -> usize {
+    to_plain_text(md).text.split_whitespace().filter(|w| w.chars().any(char::is_alphanumeric)).count()
+}
+
+/// Converts a byte offset into a UTF-16 offset (what JavaScript strings use).
+pub fn utf16_offset(s: &str, byte: usize) -> usize {
+    let byte = byte.min(s.len());
+    s[..byte].chars().map(char::len_utf16).sum()
+}
+
+/// Converts a UTF-16 offset into a byte offset, clamping to the string length.
+pub fn byte_offset_from_utf16(s: &str, utf16: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in s.char_indices() {
+        if units >= utf16 {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    s.len()
+}
+
+/// 1-based line number of a byte offset.
+pub fn line_at(s: &str, byte: usize) -> usize {
+    s[..byte.min(s.len())].matches('\n').count() + 1
+}
+
+/// Lines outside frontmatter and fenced code, with their byte offset and
+/// without line endings.
+pub fn prose_lines(md: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut fences = FenceState::default();
+    let start = frontmatter_len(md);
+    let mut pos = start;
+    md[start..].split_inclusive('\n').filter_map(move |line| {
+        let line_start = pos;
+        pos += line.len();
+        let content = line.trim_end_matches(['\n', '\r']);
+        (!fences.is_code(content)).then_some((line_start, content))
+    })
+}
+
+/// End of the inline code span opening at `i`, or just past its backticks
+/// when it is never closed.
+fn code_span_end(line: &str, i: usize) -> usize {
+    let run = line.as_bytes()[i..].iter().take_while(|&&b| b == b'`').count();
+    let fence = &line[i..i + run];
+    match line[i + run..].find(fence) {
+        Some(close) => i + run + close + run,
+        None => i + run,
+    }
+}
+
+/// A link or image destination found in the source.
+pub struct LinkRef {
+    /// Byte range of the destination (without angle brackets).
+    pub start: usize,
+    pub end: usize,
+    pub is_image: bool,
+    /// Byte range of the link text or image alt; None for reference
+    /// definitions and `<img>` tags without an `alt` attribute.
+    pub text: Option<(usize, usize)>,
+}
+
+impl LinkRef {
+    pub fn url<'a>(&self, md: &'a str) -> &'a str {
+        &md[self.start..self.end]
+    }
+}
+
+/// Parses an inline link destination starting at `i`, returning its range.
+fn link_destination(line: &str, mut i: usize) -> Option<(usize, usize)> {
+    let bytes = line.as_bytes();
+    while bytes.get(i) == Some(&b' ') {
+        i += 1;
+    }
+    if bytes.get(i) == Some(&b'<') {
+        let close = line[i + 1..].find('>')?;
+        return Some((i + 1, i + 1 + close));
+    }
+    let start = i;
+    let mut depth = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'(' => depth += 1,
+            b')' if depth == 0 => break,
+            b')' => depth -= 1,
+            b' ' | b'\t' => break,
+            _ => {}
+        }
+        i += 1;
+    }
+    (i > start).then_some((start, i.min(bytes.len())))
+}
+
+/// Range of a quoted attribute value inside an HTML tag.
+fn html_attribute(tag: &str, name: &str) -> Option<(usize, usize)> {
+    let bytes = tag.as_bytes();
+    let pattern = format!("{}=", name);
+    let mut from = 0;
+    while let Some(found) = tag[from..].find(&pattern) {
+        let at = from + found;
+        from = at + pattern.len();
+        if at > 0 && !bytes[at - 1].is_ascii_whitespace() {
+            continue;
+        }
+        let q = *bytes.get(from)?;
+        if q != b'"' && q != b'\'' {
+            return None;
+        }
+        let len = tag[from + 1..].find(q as char)?;
+        return Some((from + 1, from + 1 + len));
+    }
+    None
+}
+
+fn links_in_line(line: &str, base: usize, out: &mut Vec<LinkRef>) {
+    let bytes = line.as_bytes();
+
+    // Reference definition: [id]: destination
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('[') && !trimmed.starts_with("[^") {
+        if let Some(close) = trimmed.find("]:") {
+            let offset = line.len() - trimmed.len() + close + 2;
+            if let Some((s, e)) = link_destination(line, offset) {
+                out.push(LinkRef { start: base + s, end: base + e, is_image: false, text: None });
+            }
+            return;
+        }
+    }
+
+    let mut opens: Vec<usize> = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'`' => i = code_span_end(line, i) - 1,
+            b'[' => opens.push(i),
+            b']' if !opens.is_empty() => {
+                let open = opens.pop().unwrap_or(0);
+                let destination = match bytes.get(i + 1) {
+                    Some(b'(') => link_destination(line, i + 2),
+                    _ => None,
+                };
+                if let Some((s, e)) = destination {
+                    let is_image = open > 0 && bytes[open - 1] == b'!';
+                    let text = Some((base + open + 1, base + i));
+                    out.push(LinkRef { start: base + s, end: base + e, is_image, text });
+                    i = e;
+                }
+            }
+            b'<' if line[i..].starts_with("<img") => {
+                if let Some(tag_end) = line[i..].find('>') {
+                    let tag = &line[i..i + tag_end];
+                    if let Some(src) = tag.find("src=") {
+                        let quote = tag.as_bytes().get(src + 4).copied();
+                        if let Some(q @ (b'"' | b'\'')) = quote {
+                            let s = src + 5;
+                            if let Some(len) = tag[s..].find(q as char) {
+                                let text = html_attribute(tag, "alt").map(|(s, e)| (base + i + s, base + i + e));
+                                out.push(LinkRef { start: base + i + s, end: base + i + s + len, is_image: true, text });
+                            }
+                        }
+                    }
+                    i += tag_end;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Finds link, image and reference-definition destinations outside code.
+pub fn links(md: &str) -> Vec<LinkRef> {
+    let mut out = Vec::new();
+    for (line_start, line) in prose_lines(md) {
+        links_in_line(line, line_start, &mut out);
+    }
+    out
+}
+
+/// A `#tag` in the text or an entry of the frontmatter `tags:` key.
+pub struct TagRef {
+    /// Tag name, without `#`.
+    pub name: String,
+    /// Byte range of the name in the source.
+    pub start: usize,
+    pub end: usize,
+}
+
+pub fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '/'
+}
+
+/// Pushes a frontmatter tag found at `start..start + item.len()`, trimming
+/// quotes and a leading `#`.
+fn push_frontmatter_tag(item: &str, start: usize, out: &mut Vec<TagRef>) {
+    let leading = item.len() - item.trim_start().len();
+    let name = item.trim().trim_matches(['"', '\'']);
+    let quote = item.trim().len() - item.trim().trim_start_matches(['"', '\'']).len();
+    let hash = usize::from(name.starts_with('#'));
+    let name = &name[hash..];
+    if !name.is_empty() {
+        let name_start = start + leading + quote + hash;
+        out.push(TagRef { name: name.to_string(), start: name_start, end: name_start + name.len() });
+    }
+}
+
+fn frontmatter_tags(md: &str, out: &mut Vec<TagRef>) {
+    let end = frontmatter_len(md);
+    if end == 0 {
+        return;
+    }
+    let mut in_list = false;
+    let mut pos = md.find('\n').map(|i| i + 1).unwrap_or(0);
+    for line in md[pos..end].split_inclusive('\n') {
+        let line_start = pos;
+        pos += line.len();
+        let content = line.trim_end_matches(['\n', '\r']);
+
+        if content.get(..5).is_some_and(|p| p.eq_ignore_ascii_case("tags:")) {
+            let value = &content[5..];
+            let trimmed = value.trim();
+            in_list = trimmed.is_empty();
+            // Inline list: `tags: [a, b]`, or a comma-separated string
+            let inner_start = 5 + (value.len() - value.trim_start().len()) + usize::from(trimmed.starts_with('['));
+            let inner = trimmed.trim_start_matches('[').trim_end_matches(']');
+            let mut offset = line_start + inner_start;
+            for item in inner.split(',') {
+                push_frontmatter_tag(item, offset, out);
+                offset += item.len() + 1;
+            }
+        } else if in_list && content.trim_start().starts_with("- ") {
+            let item_start = content.len() - content.trim_start().len() + 2;
+            push_frontmatter_tag(&content[item_start..], line_start + item_start, out);
+        } else if !content.starts_with([' ', '\t']) && !content.trim().is_empty() {
+            in_list = false;
+        }
+    }
+}
+
+fn inline_tags(line: &str, base: usize, out: &mut Vec<TagRef>) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'`' => i = code_span_end(line, i) - 1,
+            b'#' if i == 0 || bytes[i - 1].is_ascii_whitespace() => {
+                let name_len: usize = line[i + 1..].chars().take_while(|&c| is_tag_char(c)).map(char::len_utf8).sum();
+                let name = line[i + 1..i + 1 + name_len].trim_end_matches(['/', '-']);
+                // "#1" is an issue number, not a tag
+                if name.chars().any(|c| !c.is_ascii_digit()) {
+                    out.push(TagRef { name: name.to_string(), start: base + i + 1, end: base + i + 1 + name.len() });
+                }
+                i += name_len;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Finds frontmatter tags and `#tags` in the text, outside code.
+pub fn tags(md: &str) -> Vec<TagRef> {
+    let mut out = Vec::new();
+    frontmatter_tags(md, &mut out);
+    for (line_start, line) in prose_lines(md) {
+        inline_tags(line, line_start, &mut out);
+    }
+    out
+}
+
+/// A `[[wikilink]]` (or `![[embed]]`).
+pub struct WikiLink {
+    /// Byte range of the target, without any `#heading` or `|alias`.
+    pub start: usize,
+    pub end: usize,
+}
+
+impl WikiLink {
+    pub fn target<'a>(&self, md: &'a str) -> &'a str {
+        &md[self.start..self.end]
+    }
+}
+
+fn wikilinks_in_line(line: &str, base: usize, same_note: bool, out: &mut Vec<WikiLink>) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'`' => i = code_span_end(line, i) - 1,
+            b'[' if line[i..].starts_with("[[") => {
+                if let Some(close) = line[i + 2..].find("]]") {
+                    let inner = &line[i + 2..i + 2 + close];
+                    let target = inner.split(['|', '#']).next().unwrap_or("").trim_end();
+                    let has_target = !target.trim().is_empty() || (same_note && inner.starts_with('#'));
+                    if has_target && !inner.contains('[') {
+                        let start = base + i + 2;
+                        out.push(WikiLink { start, end: start + target.len() });
+                    }
+                    i += close + 3;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Finds wikilinks outside code.
+pub fn wikilinks(md: &str) -> Vec<WikiLink> {
+    let mut out = Vec::new();
+    for (line_start, line) in prose_lines(md) {
+        wikilinks_in_line(line, line_start, false, &mut out);
+    }
+    out
+}
+
+/// Like `wikilinks`, with `[[#heading]]` links to the same note too; their
+/// target is empty.
+pub fn wikilinks_with_anchors(md: &str) -> Vec<WikiLink> {
+    let mut out = Vec::new();
+    for (line_start, line) in prose_lines(md) {
+        wikilinks_in_line(line, line_start, true, &mut out);
+    }
+    out
+}
+
+/// A pandoc citation, `@key` alone or in `[@key, p. 3]`.
+pub struct CitationRef {
+    /// Byte range of the key, without the `@`.
+    pub start: usize,
+    pub end: usize,
+}
+
+impl CitationRef {
+    pub fn key<'a>(&self, md: &'a str) -> &'a str {
+        &md[self.start..self.end]
+    }
+}
+
+fn is_citekey_char(c: char) -> bool {
+    c.is_alphanumeric() || "_:.#$%&-+?<>~/".contains(c)
+}
+
+fn citations_in_line(line: &str, base: usize, out: &mut Vec<CitationRef>) {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'`' => i = code_span_end(line, i) - 1,
+            // Not in e-mail addresses
+            b'@' if !line[..i].ends_with(|c: char| c.is_alphanumeric() || c == '.') => {
+                let rest = &line[i + 1..];
+                let len = rest.find(|c: char| !is_citekey_char(c)).unwrap_or(rest.len());
+                // Keys end with a letter, digit or underscore; the rest is punctuation
+                let key = rest[..len].trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_');
+                if key.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+                    out.push(CitationRef { start: base + i + 1, end: base + i + 1 + key.len() });
+                }
+                i += key.len();
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Finds citations outside code.
+pub fn citations(md: &str) -> Vec<CitationRef> {
+    let mut out = Vec::new();
+    for (line_start, line) in prose_lines(md) {
+        citations_in_line(line, line_start, &mut out);
+    }
+    out
+}
+
+/// Applies non-overlapping byte-range replacements to the source.
+pub fn replace_ranges(md: &str, mut replacements: Vec<(usize, usize, String)>) -> String {
+    replacements.sort_by_key(|r| r.0);
+    let mut result = String::with_capacity(md.len());
+    let mut pos = 0;
+    for (start, end, text) in replacements {
+        if start < pos {
+            continue;
+        }
+        result.push_str(&md[pos..start]);
+        result.push_str(&text);
+        pos = end;
+    }
+    result.push_str(&md[pos..]);
+    result
+}
+
+/// Lowercase words joined by dashes, for file names and URLs.
+pub fn slug(text: &str) -> String {
+    let slug: String = text.to_lowercase().chars().map(|c| if c.is_alphanumeric() { c } else { '-' }).collect();
+    slug.split('-').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("-")
+}
+
+pub const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown", "mdown", "mkd", "mkdn"];
+
+pub fn is_markdown_file(path: &std::path::Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| MARKDOWN_EXTENSIONS.contains(&e.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frontmatter_tags_with_non_ascii_lines() {
+        let md = "---\nauthors:\n  - Émile Zola\ntags: [roman, été]\n---\nTexte #naturalisme\n";
+        let names: Vec<String> = tags(md).into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["roman", "été", "naturalisme"]);
+        for tag in tags(md) {
+            assert_eq!(&md[tag.start..tag.end], tag.name);
+        }
+    }
+
+    #[test]
+    fn utf16_offsets_round_trip_over_multi_byte_text() {
+        let s = "é😀a";
+        assert_eq!(utf16_offset(s, 2), 1);
+        assert_eq!(utf16_offset(s, 6), 3);
+        assert_eq!(utf16_offset(s, s.len() + 10), 4);
+        for (byte, _) in s.char_indices() {
+            assert_eq!(byte_offset_from_utf16(s, utf16_offset(s, byte)), byte);
+        }
+        assert_eq!(byte_offset_from_utf16(s, 99), s.len());
+    }
+
+    #[test]
+    fn plain_text_maps_multi_byte_characters_back_to_the_source() {
+        let md = "# Été **très** 😀\n";
+        let plain = to_plain_text(md);
+        assert_eq!(plain.text, "Été très 😀\n");
+        let start = plain.text.find("très").unwrap();
+        let (src_start, src_end) = plain.source_range(start, start + "très".len());
+        assert_eq!(&md[src_start..src_end], "très");
+        let emoji = plain.text.find('😀').unwrap();
+        let (src_start, src_end) = plain.source_range(emoji, emoji + '😀'.len_utf8());
+        assert_eq!(&md[src_start..src_end], "😀");
+    }
+
+    #[test]
+    fn unclosed_fence_runs_to_the_end() {
+        let md = "Avant\n```rust\nlet x = [lien](a.md);\n";
+        let lines: Vec<&str> = prose_lines(md).map(|(_, line)| line).collect();
+        assert_eq!(lines, ["Avant"]);
+        assert_eq!(word_count(md), 1);
+        assert!(links(md).is_empty());
+    }
+
+    #[test]
+    fn shorter_fence_does_not_close_a_longer_one() {
+        let mut fences = FenceState::default();
+        assert!(fences.is_code("````"));
+        assert!(fences.is_code("```"));
+        assert!(fences.is_code("````"));
+        assert!(!fences.is_code("texte"));
+    }
+}
@@ -0,0 +1,227 @@
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::AppHandle;
+
+use crate::markdown;
+
+/// How TeX math is rendered in an export.
+#[derive(Clone, Copy, PartialEq)]
+pub enum MathMode {
+    /// Leave it to pandoc's writer (OMML equations in DOCX).
+    Native,
+    MathMl,
+    /// SVG images rendered locally with latex + dvisvgm.
+    Svg,
+    /// SVG images from an online LaTeX renderer.
+    WebTex,
+}
+
+const WEBTEX_URL: &str = "https://latex.codecogs.com/svg.image?";
+
+impl MathMode {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "native" => Ok(MathMode::Native),
+            "mathml" => Ok(MathMode::MathMl),
+            "svg" => Ok(MathMode::Svg),
+            "webtex" => Ok(MathMode::WebTex),
+            other => Err(format!("Mode de rendu des formules inconnu: {}", other)),
+        }
+    }
+
+    /// What works best for a format when the user didn't choose. Offline
+    /// exports never fall back to the online renderer.
+    pub fn default_for(to_format: &str, offline: bool) -> Self {
+        match to_format {
+            "epub" | "epub2" | "epub3" | "html" | "html4" | "html5" => MathMode::MathMl,
+            // wkhtmltopdf's webkit has no MathML support; without latex and
+            // dvisvgm, SVG fails saying they are needed
+            "pdf" if local_renderer_available() || offline => MathMode::Svg,
+            "pdf" => MathMode::WebTex,
+            _ => MathMode::Native,
+        }
+    }
+
+    pub fn pandoc_args(self) -> Vec<String> {
+        match self {
+            MathMode::MathMl => vec!["--mathml".to_string()],
+            MathMode::WebTex => vec![format!("--webtex={}", WEBTEX_URL)],
+            MathMode::Native | MathMode::Svg => Vec::new(),
+        }
+    }
+}
+
+/// A `$...$` or `$$...$$` span in the source.
+pub struct MathSpan {
+    pub start: usize,
+    pub end: usize,
+    pub tex: String,
+    pub display: bool,
+}
+
+/// Finds TeX math using pandoc's `tex_math_dollars` rules, outside code.
+pub fn find_math(md: &str) -> Vec<MathSpan> {
+    let mut spans = Vec::new();
+    let mut fences = markdown::FenceState::default();
+    let mut code_lines: Vec<(usize, usize)> = Vec::new();
+    let mut pos = 0;
+    for line in md.split_inclusive('\n') {
+        if fences.is_code(line.trim_end_matches(['\n', '\r'])) {
+            code_lines.push((pos, pos + line.len()));
+        }
+        pos += line.len();
+    }
+    let in_code_block = |i: usize| code_lines.iter().any(|&(s, e)| i >= s && i < e);
+
+    let bytes = md.as_bytes();
+    let mut i = markdown::frontmatter_len(md);
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' if !in_code_block(i) => {
+                let run = bytes[i..].iter().take_while(|&&b| b == b'`').count();
+                let fence = &md[i..i + run];
+                i += run + md[i + run..].find(fence).map(|c| c + run).unwrap_or(0);
+            }
+            b'$' if !in_code_block(i) => {
+                let display = bytes.get(i + 1) == Some(&b'$');
+                let open_len = if display { 2 } else { 1 };
+                let body_start = i + open_len;
+                let close = if display {
+                    md[body_start..].find("$$").map(|c| body_start + c)
+                } else {
+                    inline_close(md, body_start)
+                };
+                match close {
+                    Some(close) if close > body_start => {
+                        spans.push(MathSpan {
+                            start: i,
+                            end: close + open_len,
+                            tex: md[body_start..close].trim().to_string(),
+                            display,
+                        });
+                        i = close + open_len;
+                    }
+                    _ => i += open_len,
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    spans
+}
+
+/// Closing `$` of inline math: the opener must not be followed by a space,
+/// the closer not preceded by one nor followed by a digit ("$5 and $10").
+fn inline_close(md: &str, body_start: usize) -> Option<usize> {
+    let bytes = md.as_bytes();
+    if matches!(bytes.get(body_start), None | Some(b' ' | b'\t' | b'\n')) {
+        return None;
+    }
+    let mut j = body_start;
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => j += 1,
+            b'\n' if bytes.get(j + 1) == Some(&b'\n') => return None,
+            b'$' if !matches!(bytes[j - 1], b' ' | b'\t') && !bytes.get(j + 1).is_some_and(u8::is_ascii_digit) => {
+                return Some(j);
+            }
+            _ => {}
+        }
+        j += 1;
+    }
+    None
+}
+
+fn tool_works(program: &str, arg: &str) -> bool {
+    Command::new(program)
+        .arg(arg)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+pub fn local_renderer_available() -> bool {
+    tool_works("latex", "--version") && tool_works("dvisvgm", "--version")
+}
+
+fn render_svg(tex: &str, display: bool, dir: &Path, output: &Path) -> Result<(), String> {
+    let name = output.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let math = if display { format!("\\[{}\\]", tex) } else { format!("${}$", tex) };
+    let document = format!(
+        "\\documentclass[preview]{{standalone}}\n\\usepackage{{amsmath,amssymb}}\n\\begin{{document}}\n{}\n\\end{{document}}\n",
+        math
+    );
+    let tex_path = dir.join(format!("{}.tex", name));
+    std::fs::write(&tex_path, document)
+        .map_err(|e| format!("Impossible d'écrire la formule: {}", e))?;
+
+    let latex = Command::new("latex")
+        .args(["-interaction=nonstopmode", "-halt-on-error"])
+        .arg(format!("-output-directory={}", dir.display()))
+        .arg(&tex_path)
+        .output()
+        .map_err(|e| format!("Erreur lors de l'exécution de latex: {}", e))?;
+    if !latex.status.success() {
+        let log = String::from_utf8_lossy(&latex.stdout);
+        let error = log.lines().find(|l| l.starts_with('!')).unwrap_or("erreur inconnue");
+        return Err(format!("Formule invalide ({}): {}", tex, error));
+    }
+
+    let dvisvgm = Command::new("dvisvgm")
+        .args(["--no-fonts", "--exact", "-o"])
+        .arg(output)
+        .arg(dir.join(format!("{}.dvi", name)))
+        .output()
+        .map_err(|e| format!("Erreur lors de l'exécution de dvisvgm: {}", e))?;
+
+    for ext in ["tex", "dvi", "aux", "log"] {
+        let _ = std::fs::remove_file(dir.join(format!("{}.{}", name, ext)));
+    }
+    if dvisvgm.status.success() && output.is_file() {
+        Ok(())
+    } else {
+        Err(format!("La conversion SVG de la formule a échoué: {}", String::from_utf8_lossy(&dvisvgm.stderr).trim()))
+    }
+}
+
+/// Renders a formula to SVG, reusing a cached render of the same TeX.
+fn render_cached(app: &AppHandle, tex: &str, display: bool) -> Result<PathBuf, String> {
+    let dir = crate::paths::app_data_subdir(app, "math-cache")?;
+    let mut hasher = Sha256::new();
+    hasher.update([display as u8]);
+    hasher.update(tex.as_bytes());
+    let hex: String = hasher.finalize().iter().take(16).map(|b| format!("{:02x}", b)).collect();
+    let output = dir.join(format!("math-{}.svg", hex));
+    if !output.is_file() {
+        render_svg(tex, display, &dir, &output)?;
+    }
+    Ok(output)
+}
+
+/// Prepares math for export. In SVG mode, formulas are replaced by links to
+/// locally rendered images; formulas that fail to render are left as TeX.
+pub fn prepare_for_export(app: &AppHandle, md: &str, mode: MathMode) -> Result<String, String> {
+    if mode != MathMode::Svg {
+        return Ok(md.to_string());
+    }
+    let spans = find_math(md);
+    if spans.is_empty() {
+        return Ok(md.to_string());
+    }
+    if !local_renderer_available() {
+        return Err("Le rendu SVG des formules nécessite latex et dvisvgm (TeX Live ou MiKTeX)".to_string());
+    }
+
+    let replacements = spans
+        .into_iter()
+        .filter_map(|span| {
+            let path = render_cached(app, &span.tex, span.display).ok()?;
+            let attributes = if span.display { "{.math .display}" } else { "{.math .inline}" };
+            let link = crate::assets::link_to(&path, None);
+            Some((span.start, span.end, format!("![]({}){}", link, attributes)))
+        })
+        .collect();
+    Ok(markdown::replace_ranges(md, replacements))
+}
@@ -0,0 +1,577 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::workspace::{self, Workspace};
+use crate::{assets, backlinks, frontmatter, markdown, publish};
+
+/// Obsidian's settings folder, at the root of a vault.
+pub const CONFIG_DIR: &str = ".obsidian";
+const DAILY_NOTES_FILE: &str = "daily-notes.json";
+const DEFAULT_DAILY_FORMAT: &str = "YYYY-MM-DD";
+/// Embedded notes can embed notes in turn, down to this depth.
+const MAX_EMBED_DEPTH: usize = 3;
+
+/// Settings of Obsidian's daily notes plugin.
+#[derive(Serialize, Deserialize, Default)]
+pub struct DailyNotes {
+    /// Folder of the daily notes, relative to the vault.
+    #[serde(default)]
+    pub folder: String,
+    /// Moment.js format of their names, e.g. "YYYY-MM-DD" or "YYYY/MM/DD".
+    #[serde(default)]
+    pub format: String,
+    /// Note new daily notes start from, relative to the vault.
+    #[serde(default)]
+    pub template: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObsidianVault {
+    pub root: String,
+    pub daily_notes: DailyNotes,
+}
+
+#[derive(Serialize)]
+pub struct WikilinkTarget {
+    pub path: String,
+    /// Line of the heading or block the link points at.
+    pub line: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct DailyNote {
+    pub path: String,
+    pub created: bool,
+}
+
+pub fn is_vault(root: &Path) -> bool {
+    root.join(CONFIG_DIR).is_dir()
+}
+
+fn daily_notes(root: &Path) -> DailyNotes {
+    let mut settings: DailyNotes = std::fs::read_to_string(root.join(CONFIG_DIR).join(DAILY_NOTES_FILE))
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    if settings.format.trim().is_empty() {
+        settings.format = DEFAULT_DAILY_FORMAT.to_string();
+    }
+    settings
+}
+
+/// Notes and attachments of a vault, to resolve links the way Obsidian does.
+pub struct Vault {
+    root: PathBuf,
+    /// Notes by lowercased file stem.
+    notes: HashMap<String, Vec<PathBuf>>,
+    /// Other files by lowercased file name.
+    files: HashMap<String, Vec<PathBuf>>,
+}
+
+impl Vault {
+    pub fn load(root: &Path) -> Self {
+        let mut vault = Vault { root: root.to_path_buf(), notes: HashMap::new(), files: HashMap::new() };
+        for entry in workspace::walk(root).filter(|e| e.file_type().is_some_and(|t| t.is_file())) {
+            let path = entry.into_path();
+            let (map, key) = if markdown::is_markdown_file(&path) {
+                (&mut vault.notes, path.file_stem())
+            } else {
+                (&mut vault.files, path.file_name())
+            };
+            if let Some(key) = key.map(|k| k.to_string_lossy().to_lowercase()) {
+                map.entry(key).or_default().push(path);
+            }
+        }
+        vault
+    }
+
+    /// The note a wikilink target names: the one next to the linking note
+    /// when several match, otherwise the one closest to the vault root.
+    fn note(&self, target: &str, from: Option<&Path>) -> Option<&PathBuf> {
+        let last = target.trim().replace('\\', "/").rsplit('/').next().unwrap_or_default().to_string();
+        let stem = if markdown::is_markdown_file(Path::new(&last)) {
+            Path::new(&last).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+        } else {
+            last
+        };
+        let from_dir = from.and_then(Path::parent);
+        self.notes
+            .get(&stem.to_lowercase())?
+            .iter()
+            .filter(|note| backlinks::wikilink_points_to(target, note, &self.root))
+            .min_by_key(|note| (note.parent() != from_dir, note.components().count()))
+    }
+
+    /// A file named by an embed: relative to the note, to the vault, or by its
+    /// name anywhere in the vault.
+    fn attachment(&self, target: &str, from: Option<&Path>) -> Option<PathBuf> {
+        let target = target.trim();
+        from.and_then(Path::parent)
+            .map(|dir| assets::normalize(&dir.join(target)))
+            .into_iter()
+            .chain([assets::normalize(&self.root.join(target))])
+            .find(|path| path.starts_with(&self.root) && path.is_file())
+            .or_else(|| {
+                let name = target.replace('\\', "/").rsplit('/').next()?.to_lowercase();
+                self.files.get(&name)?.iter().min_by_key(|f| f.components().count()).cloned()
+            })
+    }
+}
+
+/// Splits the inside of `[[...]]` into target, `#heading` or `#^block`
+/// subpath and alias.
+fn parts(inner: &str) -> (&str, Option<&str>, Option<&str>) {
+    let (link, alias) = match inner.split_once('|') {
+        // In tables the pipe is escaped
+        Some((link, alias)) => (link.trim_end_matches('\\'), Some(alias.trim())),
+        None => (inner, None),
+    };
+    match link.split_once('#') {
+        Some((target, subpath)) => (target.trim(), Some(subpath.trim()).filter(|s| !s.is_empty()), alias),
+        None => (link.trim(), None, alias),
+    }
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.bytes().take_while(|&b| b == b'#').count();
+    let rest = &line[level..];
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' '))).then(|| (level, rest.trim().trim_end_matches('#').trim_end()))
+}
+
+/// The `^id` ending a line, if any.
+fn block_id(line: &str) -> Option<&str> {
+    let line = line.trim_end();
+    let start = line.rfind('^')?;
+    let id = &line[start + 1..];
+    let valid = !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    (valid && (start == 0 || line[..start].ends_with(char::is_whitespace))).then_some(id)
+}
+
+/// Lines of `md` and whether each is code (or frontmatter).
+fn prose_flags(md: &str) -> (Vec<&str>, Vec<bool>) {
+    let lines: Vec<&str> = md.lines().collect();
+    let frontmatter_lines = md[..markdown::frontmatter_len(md)].lines().count();
+    let mut fences = markdown::FenceState::default();
+    let code = lines.iter().enumerate().map(|(i, line)| i < frontmatter_lines || fences.is_code(line)).collect();
+    (lines, code)
+}
+
+/// Line range of the section under a heading (the last one of `a#b`) or of
+/// a `^block`.
+fn section(md: &str, subpath: &str) -> Option<(usize, usize)> {
+    let (lines, code) = prose_flags(md);
+    if let Some(id) = subpath.strip_prefix('^') {
+        let marker = (0..lines.len()).find(|&i| !code[i] && block_id(lines[i]) == Some(id))?;
+        let mut last = marker;
+        if lines[marker].trim_start().starts_with('^') {
+            // A marker on its own line names the block above it
+            last = (0..marker).rev().find(|&i| !lines[i].trim().is_empty())?;
+        }
+        let start = (0..last).rev().find(|&i| lines[i].trim().is_empty()).map_or(0, |i| i + 1);
+        return Some((start, marker + 1));
+    }
+    let name = subpath.rsplit('#').next()?.trim();
+    let (start, level) = (0..lines.len()).find_map(|i| {
+        let (level, text) = heading(lines[i]).filter(|_| !code[i])?;
+        (text.eq_ignore_ascii_case(name) || markdown::slug(text) == markdown::slug(name)).then_some((i, level))
+    })?;
+    let end = (start + 1..lines.len())
+        .find(|&i| !code[i] && heading(lines[i]).is_some_and(|(l, _)| l <= level))
+        .unwrap_or(lines.len());
+    Some((start, end))
+}
+
+/// `link` with a `#fragment`, inside the angle brackets if any.
+fn with_fragment(link: String, fragment: &str) -> String {
+    match link.strip_suffix('>') {
+        Some(link) => format!("{}{}>", link, fragment),
+        None => link + fragment,
+    }
+}
+
+fn fragment(subpath: &str) -> String {
+    match subpath.strip_prefix('^') {
+        Some(_) => format!("#{}", subpath),
+        None => format!("#{}", markdown::slug(subpath.rsplit('#').next().unwrap_or(subpath))),
+    }
+}
+
+fn escape_label(label: &str) -> String {
+    label.replace('[', "\\[").replace(']', "\\]")
+}
+
+/// Points the relative links of `md`, written from `from_dir`, at the same
+/// files from `to_dir`.
+fn rebase_links(md: &str, from_dir: Option<&Path>, to_dir: Option<&Path>) -> String {
+    if from_dir == to_dir {
+        return md.to_string();
+    }
+    let replacements = markdown::links(md)
+        .iter()
+        .filter(|link| assets::is_local_link(link.url(md)) && !Path::new(link.url(md)).is_absolute())
+        .map(|link| {
+            let url = link.url(md);
+            let fragment = url.find('#').map(|i| &url[i..]).unwrap_or("");
+            let target = assets::resolve_link(url, from_dir);
+            assets::replacement(md, link, with_fragment(assets::link_to(&target, to_dir), fragment))
+        })
+        .collect();
+    markdown::replace_ranges(md, replacements)
+}
+
+struct Rewriter<'a> {
+    vault: &'a Vault,
+    /// Folder the links are written relative to: the rendered document's.
+    base: Option<&'a Path>,
+}
+
+impl Rewriter<'_> {
+    fn link(&self, target: &str, subpath: Option<&str>, alias: Option<&str>, note: Option<&Path>) -> Option<String> {
+        let label = match (alias, subpath) {
+            (Some(alias), _) => alias.to_string(),
+            (None, Some(subpath)) if target.is_empty() => subpath.to_string(),
+            (None, Some(subpath)) => format!("{} > {}", target, subpath),
+            (None, None) => target.to_string(),
+        };
+        let fragment = subpath.map(fragment).unwrap_or_default();
+        let url = if target.is_empty() {
+            // Same note, which may be embedded into another one
+            match note {
+                Some(note) if note.parent() != self.base => with_fragment(assets::link_to(note, self.base), &fragment),
+                _ => fragment,
+            }
+        } else {
+            let path = self.vault.note(target, note).cloned().or_else(|| self.vault.attachment(target, note))?;
+            with_fragment(assets::link_to(&path, self.base), &fragment)
+        };
+        Some(format!("[{}]({})", escape_label(&label), url))
+    }
+
+    fn embed(&self, target: &str, subpath: Option<&str>, alias: Option<&str>, note: Option<&Path>, depth: usize) -> Option<String> {
+        if let Some(embedded) = self.vault.note(target, note).filter(|_| !target.is_empty()) {
+            if depth >= MAX_EMBED_DEPTH || Some(embedded.as_path()) == note {
+                return None;
+            }
+            let md = std::fs::read_to_string(embedded).ok()?;
+            let body = frontmatter::body(&md);
+            let content = match subpath {
+                Some(subpath) => {
+                    let (start, end) = section(body, subpath)?;
+                    body.lines().skip(start).take(end - start).collect::<Vec<_>>().join("\n")
+                }
+                None => body.trim().to_string(),
+            };
+            let content = rebase_links(&content, embedded.parent(), self.base);
+            return Some(self.rewrite(&content, Some(embedded), depth + 1));
+        }
+        let file = self.vault.attachment(target, note)?;
+        let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        // `|300` and `|300x200` give a size, not a caption
+        let alt = alias.filter(|a| !a.chars().all(|c| c.is_ascii_digit() || c == 'x')).unwrap_or(&name);
+        let link = assets::link_to(&file, self.base);
+        let is_image = publish::image_mime(&file) != "application/octet-stream";
+        Some(if is_image { format!("![{}]({})", escape_label(alt), link) } else { format!("[{}]({})", escape_label(alt), link) })
+    }
+
+    /// Turns wikilinks into markdown links and embeds into images or the
+    /// embedded note's content.
+    fn rewrite(&self, md: &str, note: Option<&Path>, depth: usize) -> String {
+        let mut replacements = Vec::new();
+        for link in markdown::wikilinks_with_anchors(md) {
+            let Some(close) = md[link.end..].find("]]") else { continue };
+            let embed = link.start >= 3 && md.as_bytes()[link.start - 3] == b'!';
+            let start = link.start - if embed { 3 } else { 2 };
+            let end = link.end + close + 2;
+            let (target, subpath, alias) = parts(&md[link.start..end - 2]);
+            let text = if embed { self.embed(target, subpath, alias, note, depth) } else { self.link(target, subpath, alias, note) };
+            let Some(mut text) = text else { continue };
+            // Embeds inside a quote or callout stay inside it
+            let line_start = md[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+            let prefix = &md[line_start..start];
+            if embed && !prefix.is_empty() && prefix.chars().all(|c| c == '>' || c == ' ') {
+                text = text.replace('\n', &format!("\n{}", prefix));
+            }
+            replacements.push((start, end, text));
+        }
+        markdown::replace_ranges(md, replacements)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Content of a quote line, without its `>` marker.
+fn quote_content(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches(' ').strip_prefix('>')?;
+    Some(rest.strip_prefix(' ').unwrap_or(rest))
+}
+
+/// Type, fold state (Some(open) for `+` and `-`) and title of a callout's
+/// first line, `> [!type]± title`.
+fn callout_header(line: &str) -> Option<(&str, Option<bool>, &str)> {
+    let rest = quote_content(line)?.strip_prefix("[!")?;
+    let (kind, rest) = rest.split_once(']')?;
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return None;
+    }
+    let (fold, title) = match rest.chars().next() {
+        Some('+') => (Some(true), &rest[1..]),
+        Some('-') => (Some(false), &rest[1..]),
+        _ => (None, rest),
+    };
+    Some((kind, fold, title.trim()))
+}
+
+fn callout(kind: &str, fold: Option<bool>, title: &str, content: &str) -> String {
+    let kind = kind.to_lowercase();
+    let title = if title.is_empty() {
+        let mut chars = kind.chars();
+        chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+    } else {
+        escape_html(title)
+    };
+    match fold {
+        Some(open) => format!(
+            "<details class=\"callout\" data-callout=\"{}\"{}>\n<summary class=\"callout-title\">{}</summary>\n\n{}\n\n</details>\n",
+            kind,
+            if open { " open" } else { "" },
+            title,
+            content
+        ),
+        None => format!("<div class=\"callout\" data-callout=\"{}\">\n<p class=\"callout-title\">{}</p>\n\n{}\n\n</div>\n", kind, title, content),
+    }
+}
+
+/// A line with its `^id` marker turned into an anchor links can point at.
+fn anchor(line: &str) -> String {
+    match block_id(line) {
+        Some(id) => {
+            let text = line.trim_end();
+            let text = text[..text.len() - id.len() - 1].trim_end();
+            let anchor = format!("<span id=\"^{}\"></span>", id);
+            if text.is_empty() {
+                anchor
+            } else {
+                format!("{} {}", text, anchor)
+            }
+        }
+        None => line.to_string(),
+    }
+}
+
+fn blocks(md: &str) -> String {
+    let lines: Vec<&str> = md.lines().collect();
+    let mut fences = markdown::FenceState::default();
+    let mut out = String::with_capacity(md.len());
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+        if fences.is_code(line) {
+            out.push_str(line);
+        } else if let Some((kind, fold, title)) = callout_header(line) {
+            let mut content = Vec::new();
+            while let Some(inner) = lines.get(i).and_then(|l| quote_content(l)) {
+                content.push(inner);
+                i += 1;
+            }
+            if !out.is_empty() && !out.ends_with("\n\n") {
+                out.push('\n');
+            }
+            // Nested callouts are quotes inside the content
+            out.push_str(callout(kind, fold, title, &blocks(&content.join("\n"))).trim_end());
+        } else {
+            out.push_str(&anchor(line));
+        }
+        out.push('\n');
+    }
+    if !md.ends_with('\n') {
+        out.pop();
+    }
+    out
+}
+
+/// Converts callouts (`> [!note] Title`) into HTML blocks, foldable ones
+/// into `<details>`, and `^block` markers into anchors.
+pub fn convert_blocks(md: &str) -> String {
+    let frontmatter = markdown::frontmatter_len(md);
+    format!("{}{}", &md[..frontmatter], blocks(&md[frontmatter..]))
+}
+
+/// Rewrites a note of `vault` in plain markdown for preview and export:
+/// wikilinks (aliases, headings and block references included) become
+/// markdown links, `![[...]]` embeds become images or the embedded note's
+/// content, and callouts and block markers are converted.
+pub fn prepare(md: &str, note: Option<&Path>, vault: &Vault) -> String {
+    let rewriter = Rewriter { vault, base: note.and_then(Path::parent) };
+    convert_blocks(&rewriter.rewrite(md, note, 0))
+}
+
+/// Converts a Moment.js date format, as Obsidian uses, to a chrono one.
+fn chrono_format(moment: &str) -> String {
+    const TOKENS: &[(&str, &str)] = &[
+        ("YYYY", "%Y"),
+        ("YY", "%y"),
+        ("MMMM", "%B"),
+        ("MMM", "%b"),
+        ("MM", "%m"),
+        ("M", "%-m"),
+        ("DDDD", "%j"),
+        ("Do", "%-d"),
+        ("DD", "%d"),
+        ("D", "%-d"),
+        ("dddd", "%A"),
+        ("ddd", "%a"),
+        ("d", "%w"),
+        ("GGGG", "%G"),
+        ("gggg", "%G"),
+        ("WW", "%V"),
+        ("ww", "%V"),
+        ("W", "%-V"),
+        ("w", "%-V"),
+        ("HH", "%H"),
+        ("H", "%-H"),
+        ("hh", "%I"),
+        ("h", "%-I"),
+        ("mm", "%M"),
+        ("m", "%-M"),
+        ("ss", "%S"),
+        ("s", "%-S"),
+        ("A", "%p"),
+        ("a", "%P"),
+    ];
+    let mut out = String::new();
+    let mut rest = moment;
+    while let Some(c) = rest.chars().next() {
+        if c == '[' {
+            // Literal text
+            let end = rest.find(']').unwrap_or(rest.len());
+            out.push_str(&rest[1..end].replace('%', "%%"));
+            rest = rest.get(end + 1..).unwrap_or_default();
+        } else if let Some((token, format)) = TOKENS.iter().find(|(token, _)| rest.starts_with(token)) {
+            out.push_str(format);
+            rest = &rest[token.len()..];
+        } else {
+            if c == '%' {
+                out.push('%');
+            }
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+fn format_date(date: chrono::NaiveDateTime, moment: &str) -> String {
+    use std::fmt::Write;
+    let mut value = String::new();
+    // chrono reports invalid formats through fmt::Error
+    match write!(value, "{}", date.format(&chrono_format(moment))) {
+        Ok(()) => value,
+        Err(_) => date.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// Fills the `{{title}}`, `{{date}}` and `{{time}}` placeholders of Obsidian
+/// templates, each with an optional `:format`.
+fn render_template(template: &str, title: &str, date: chrono::NaiveDateTime) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        let Some(close) = rest[open..].find("}}").map(|i| open + i) else { break };
+        out.push_str(&rest[..open]);
+        let inner = rest[open + 2..close].trim();
+        let (name, format) = inner.split_once(':').map(|(n, f)| (n.trim(), Some(f.trim()))).unwrap_or((inner, None));
+        match name {
+            "title" => out.push_str(title),
+            "date" => out.push_str(&format_date(date, format.unwrap_or(DEFAULT_DAILY_FORMAT))),
+            "time" => out.push_str(&format_date(date, format.unwrap_or("HH:mm"))),
+            _ => out.push_str(&rest[open..close + 2]),
+        }
+        rest = &rest[close + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The open workspace's Obsidian settings, None when it isn't a vault.
+#[tauri::command]
+pub fn get_obsidian_vault(app: AppHandle) -> Option<ObsidianVault> {
+    let root = app.state::<Workspace>().root().ok().filter(|root| is_vault(root))?;
+    Some(ObsidianVault { root: root.to_string_lossy().to_string(), daily_notes: daily_notes(&root) })
+}
+
+/// The markdown of a vault note as the preview should render it, see
+/// `prepare`.
+#[tauri::command]
+pub fn prepare_obsidian_markdown(app: AppHandle, markdown: String, document_path: Option<String>) -> Result<String, String> {
+    let root = app.state::<Workspace>().root()?;
+    let document_path = crate::scopes::check_optional(&app, document_path.as_deref())?;
+    let vault = Vault::load(&root);
+    Ok(prepare(&markdown, document_path.as_deref(), &vault))
+}
+
+/// Where a wikilink (the text between `[[` and `]]`) leads from the document
+/// at `document_path`, down to its heading or block.
+#[tauri::command]
+pub fn resolve_wikilink(app: AppHandle, link: &str, document_path: Option<&str>) -> Result<Option<WikilinkTarget>, String> {
+    let root = app.state::<Workspace>().root()?;
+    let document_path = crate::scopes::check_optional(&app, document_path)?;
+    let vault = Vault::load(&root);
+    let note = document_path.as_deref();
+    let (target, subpath, _) = parts(link.trim_start_matches('!'));
+    let path = if target.is_empty() {
+        note.map(Path::to_path_buf)
+    } else {
+        vault.note(target, note).cloned().or_else(|| vault.attachment(target, note))
+    };
+    let Some(path) = path else { return Ok(None) };
+    let line = subpath.and_then(|subpath| {
+        let md = std::fs::read_to_string(&path).ok()?;
+        section(&md, subpath).map(|(start, _)| start + 1)
+    });
+    Ok(Some(WikilinkTarget { path: path.to_string_lossy().to_string(), line }))
+}
+
+/// Opens the daily note of `date` (YYYY-MM-DD, today by default) where
+/// Obsidian's daily notes settings put it, creating it from their template
+/// the first time.
+#[tauri::command]
+pub fn open_daily_note(app: AppHandle, date: Option<&str>) -> Result<DailyNote, String> {
+    let root = app.state::<Workspace>().root()?;
+    let settings = daily_notes(&root);
+    let now = chrono::Local::now().naive_local();
+    let date = match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("Date invalide: {}", date))?
+            .and_time(now.time()),
+        None => now,
+    };
+    let name = format_date(date, &settings.format);
+    let path = root.join(settings.folder.trim_matches('/')).join(format!("{}.md", name));
+    if path.exists() {
+        return Ok(DailyNote { path: path.to_string_lossy().to_string(), created: false });
+    }
+
+    let template = settings.template.trim();
+    let content = if template.is_empty() {
+        String::new()
+    } else {
+        let template_path = root.join(template);
+        let template_path =
+            if markdown::is_markdown_file(&template_path) { template_path } else { template_path.with_extension("md") };
+        let template = std::fs::read_to_string(&template_path)
+            .map_err(|e| format!("Impossible de lire le modèle {}: {}", template_path.display(), e))?;
+        let title = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        render_template(&template, &title, date)
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer le dossier {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&path, content).map_err(|e| format!("Impossible de créer le fichier: {}", e))?;
+    Ok(DailyNote { path: path.to_string_lossy().to_string(), created: true })
+}
@@ -0,0 +1,197 @@
+use std::path::Path;
+
+use crate::{markdown, math, svg};
+
+/// Elements dropped with their content when they load something remote.
+/// Scripts are always dropped: an inline one can fetch as well.
+const EMBEDDING_ELEMENTS: &[&str] = &["iframe", "frame", "object", "embed"];
+
+/// Policy the page carries, for viewers that would still load something:
+/// only the file's own folder and inline data, and no scripts.
+const CONTENT_SECURITY_POLICY: &str = "default-src 'self' data:; style-src 'self' 'unsafe-inline' data:; script-src 'none'";
+
+/// Attributes through which an element loads a resource. `href` only loads
+/// something outside of links.
+const RESOURCE_ATTRIBUTES: &[&str] = &["src", "srcset", "href", "xlink:href", "poster", "data", "background"];
+
+/// Slide formats whose player pandoc links from a CDN.
+const HTML_SLIDE_FORMATS: &[&str] = &["revealjs", "slidy", "slideous", "dzslides", "s5"];
+
+/// wkhtmltopdf through a proxy nobody listens on, so nothing of the page
+/// reaches the network, and with what fails to load left out.
+const PDF_ENGINE_OPTIONS: &[&str] = &[
+    "--disable-javascript",
+    "--proxy", "http://127.0.0.1:9",
+    "--load-error-handling", "ignore",
+    "--load-media-error-handling", "ignore",
+];
+
+/// Whether `url` is fetched from the network, protocol-relative URLs
+/// included.
+pub fn is_remote(url: &str) -> bool {
+    let url = url.trim().to_ascii_lowercase();
+    ["http://", "https://", "ftp://", "//"].iter().any(|prefix| url.starts_with(prefix))
+}
+
+/// Refuses an offline export that can't be made without the network.
+pub fn check(to_format: &str, math_mode: math::MathMode) -> Result<(), String> {
+    if math_mode == math::MathMode::WebTex {
+        return Err("Les formules WebTeX sont rendues par un service en ligne: choisissez un autre rendu pour un export hors ligne".to_string());
+    }
+    if HTML_SLIDE_FORMATS.contains(&to_format) {
+        return Err(format!("Le format {} charge son lecteur depuis le web et ne peut pas être exporté hors ligne", to_format));
+    }
+    Ok(())
+}
+
+/// Flags for the wkhtmltopdf run of a PDF export.
+pub fn pdf_engine_args() -> Vec<String> {
+    PDF_ENGINE_OPTIONS.iter().map(|option| format!("--pdf-engine-opt={}", option)).collect()
+}
+
+/// Replaces remote images, in markdown or `<img>` tags, with their alt text.
+pub fn strip_remote_images(md: &str) -> String {
+    let replacements = markdown::links(md)
+        .into_iter()
+        .filter(|link| link.is_image && is_remote(link.url(md)))
+        .filter_map(|link| {
+            let alt = link.text.map(|(s, e)| md[s..e].to_string()).unwrap_or_default();
+            match link.text {
+                Some((text_start, _)) if md[..text_start].ends_with("![") => {
+                    let close = md[link.end..].find(')')?;
+                    Some((text_start - 2, link.end + close + 1, alt))
+                }
+                _ => {
+                    let start = md[..link.start].rfind("<img")?;
+                    Some((start, svg::tag_end(md, start), alt))
+                }
+            }
+        })
+        .collect();
+    markdown::replace_ranges(md, replacements)
+}
+
+/// `css` without `@import`s or `url()`s of remote files, fonts included.
+fn strip_css(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    loop {
+        let lower = rest.to_ascii_lowercase();
+        let import = lower.find("@import");
+        let url = lower.find("url(");
+        let Some(at) = [import, url].into_iter().flatten().min() else { break };
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        if Some(at) == import {
+            let end = rest.find(';').map(|e| e + 1).unwrap_or(rest.len());
+            let statement = &rest[..end];
+            let target = statement["@import".len()..].trim_start().trim_start_matches("url(").trim_start_matches(['"', '\'']);
+            if !is_remote(target) {
+                out.push_str(statement);
+            }
+            rest = &rest[end..];
+        } else {
+            let end = rest.find(')').map(|e| e + 1).unwrap_or(rest.len());
+            let value = rest[4..end].trim_end_matches(')').trim().trim_matches(['"', '\'']);
+            out.push_str(if is_remote(value) { "none" } else { &rest[..end] });
+            rest = &rest[end..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn loads_remote(name: &str, value: &str) -> bool {
+    if name == "srcset" {
+        return value.split(',').any(|candidate| is_remote(candidate.trim_start()));
+    }
+    is_remote(value)
+}
+
+/// End of the element opening at `end`, whose raw content runs to its
+/// close tag.
+fn close_tag_end(html: &str, lower: &str, end: usize, name: &str) -> usize {
+    lower[end..].find(&format!("</{}", name)).map(|e| svg::tag_end(html, end + e)).unwrap_or(html.len())
+}
+
+/// `html` with its content security policy, at the top of its head.
+fn with_policy(html: &str) -> String {
+    let meta = format!("<meta http-equiv=\"Content-Security-Policy\" content=\"{}\">", CONTENT_SECURITY_POLICY);
+    let lower = html.to_ascii_lowercase();
+    let head = lower.match_indices("<head").find(|(at, _)| matches!(lower[at + 5..].chars().next(), Some('>' | ' ' | '\t' | '\r' | '\n')));
+    let at = head.map(|(at, _)| svg::tag_end(html, at)).unwrap_or(0);
+    format!("{}{}{}", &html[..at], meta, &html[at..])
+}
+
+/// Removes from an HTML page everything that would load from the network
+/// when it is opened: scripts, remote frames, remote sources of images,
+/// media and stylesheets, and remote fonts and imports in its CSS, then
+/// adds a policy forbidding the rest. Links are kept; they load nothing
+/// until followed.
+pub fn strip_remote_resources(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut out = String::with_capacity(html.len());
+    let mut pos = 0;
+    while let Some(offset) = html[pos..].find('<') {
+        let start = pos + offset;
+        out.push_str(&html[pos..start]);
+        let rest = &html[start..];
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map(|e| start + e + 3).unwrap_or(html.len());
+            out.push_str(&html[start..end]);
+            pos = end;
+            continue;
+        }
+        let end = svg::tag_end(html, start);
+        let tag = svg::parse_tag(&html[start..end]);
+        let name = tag.name.to_ascii_lowercase();
+        if tag.closing || name.starts_with(['!', '?']) {
+            out.push_str(&html[start..end]);
+            pos = end;
+            continue;
+        }
+        let remote = tag.attributes.iter().any(|(attr, value, _)| {
+            let attr = attr.to_ascii_lowercase();
+            RESOURCE_ATTRIBUTES.contains(&attr.as_str()) && loads_remote(&attr, value)
+        });
+        if name == "script" {
+            pos = if tag.self_closing { end } else { close_tag_end(html, &lower, end, &name) };
+            continue;
+        }
+        if remote && EMBEDDING_ELEMENTS.contains(&name.as_str()) {
+            pos = if tag.self_closing || name == "embed" { end } else { close_tag_end(html, &lower, end, &name) };
+            continue;
+        }
+        out.push('<');
+        out.push_str(tag.name);
+        for (attr, value, text) in &tag.attributes {
+            let attr = attr.to_ascii_lowercase();
+            let loads = RESOURCE_ATTRIBUTES.contains(&attr.as_str()) && !(attr == "href" && name == "a");
+            if loads && loads_remote(&attr, value) {
+                continue;
+            }
+            out.push(' ');
+            if attr == "style" {
+                out.push_str(&text.replacen(value, &strip_css(value), 1));
+            } else {
+                out.push_str(text);
+            }
+        }
+        out.push_str(if tag.self_closing { "/>" } else { ">" });
+        pos = end;
+        // Raw text, to clean as CSS rather than as markup
+        if !tag.self_closing && name == "style" {
+            let close = lower[end..].find("</style").map(|e| end + e).unwrap_or(html.len());
+            out.push_str(&strip_css(&html[end..close]));
+            pos = close;
+        }
+    }
+    out.push_str(&html[pos..]);
+    with_policy(&out)
+}
+
+/// Runs `strip_remote_resources` over the HTML file at `path`.
+pub fn clean_file(path: &Path) -> Result<(), String> {
+    let html = std::fs::read_to_string(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+    std::fs::write(path, strip_remote_resources(&html)).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))
+}
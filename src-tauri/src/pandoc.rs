@@ -0,0 +1,320 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tauri::{AppHandle, Manager};
+
+use crate::frontmatter::{self, Frontmatter};
+use crate::track_changes::TrackChanges;
+use crate::{assets, markdown};
+use crate::{citations, crossref, diagrams, highlight_styles, pandoc_templates, scopes, settings, slides, workspace};
+
+/// Frontmatter field choosing the dialect a document is written in.
+const FLAVOR_FIELD: &str = "markdown_flavor";
+
+/// What the export dialog sets, validated before being turned into pandoc
+/// flags.
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportOptions {
+    /// Forces a standalone document; pandoc already makes one for binary
+    /// formats.
+    pub standalone: bool,
+    /// Table of contents, placed after the title block.
+    pub toc: bool,
+    /// Heading levels in the table of contents, 1 to 6; implies `toc`.
+    pub toc_depth: Option<u8>,
+    /// Heading of the table of contents, instead of the one for the
+    /// document's `lang`.
+    pub toc_title: Option<String>,
+    pub number_sections: bool,
+    /// Moves every heading this many levels, e.g. -1 to promote a chapter's
+    /// h2 sections to h1.
+    pub shift_heading_level: Option<i8>,
+    /// A built-in style, an imported theme, a `.theme` file, or "none" to
+    /// disable highlighting.
+    pub highlight_style: Option<String>,
+    /// Template or reference document: a name from the app's templates
+    /// folder or a path.
+    pub template: Option<String>,
+    /// Title, author, date, lang, abstract, keywords… Text, numbers and
+    /// booleans override the frontmatter; lists and maps (several authors,
+    /// keywords) go through a metadata file, which it overrides.
+    pub metadata: BTreeMap<String, serde_json::Value>,
+    /// Folders where images and other resources are looked up, before the
+    /// document's own.
+    pub resource_path: Vec<String>,
+    /// Document being exported, whose relative links start from its folder.
+    pub document_path: Option<String>,
+    /// Markdown dialect of the document, instead of its frontmatter's
+    /// `markdown_flavor`.
+    pub flavor: Option<String>,
+    /// Markdown reader extensions, e.g. "+smart" or "-raw_html".
+    pub extensions: Vec<String>,
+    pub diagrams: diagrams::DiagramOptions,
+    /// Formula rendering ("native", "mathml", "svg", "webtex"); defaults to
+    /// what suits the format.
+    pub math: Option<String>,
+    pub citations: citations::CitationOptions,
+    pub crossref: crossref::CrossrefOptions,
+    pub slides: slides::SlideOptions,
+    /// Export preset: a defaults file name or path.
+    pub defaults: Option<String>,
+    /// Leaves out remote images, scripts, stylesheets and fonts, so that
+    /// neither the export nor opening it reaches the network.
+    pub offline: bool,
+    /// Strips who made the export from it: author and producer of PDFs,
+    /// properties and revision marks of office documents, EXIF of images.
+    pub redact_metadata: bool,
+}
+
+impl ExportOptions {
+    /// The `-f` argument for `md`: its dialect with the requested extensions.
+    pub fn reader(&self, md: &str) -> Result<String, String> {
+        let mut reader = match &self.flavor {
+            Some(name) => flavor(name)?.to_string(),
+            None => document_flavor(md)?,
+        };
+        for extension in &self.extensions {
+            let name = extension.strip_prefix(['+', '-']).unwrap_or_default();
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+                return Err(format!("Extension pandoc invalide: {}", extension));
+            }
+            reader.push_str(extension);
+        }
+        Ok(reader)
+    }
+
+    /// Flags for the document options (not formats, math or citations) of
+    /// an export to `to_format`.
+    pub fn pandoc_args(&self, app: &AppHandle, to_format: &str) -> Result<Vec<String>, String> {
+        let mut args = Vec::new();
+        let toc = self.toc || self.toc_depth.is_some();
+        // Text formats only get a table of contents or numbered sections in a
+        // full document; binary ones are always standalone
+        let text_output = !matches!(to_format, "docx" | "odt" | "pptx" | "epub" | "epub2" | "epub3" | "pdf");
+        if self.standalone || (text_output && (toc || self.number_sections)) {
+            args.push("--standalone".to_string());
+        }
+        if toc {
+            args.push("--toc".to_string());
+        }
+        if let Some(depth) = self.toc_depth {
+            if !(1..=6).contains(&depth) {
+                return Err(format!("Profondeur de table des matières invalide: {} (1 à 6)", depth));
+            }
+            args.push(format!("--toc-depth={}", depth));
+        }
+        if let Some(title) = self.toc_title.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            args.push(format!("--metadata=toc-title:{}", title));
+        }
+        if self.number_sections {
+            args.push("--number-sections".to_string());
+        }
+        args.extend(shift_heading_arg(self.shift_heading_level)?);
+        if let Some(template) = self.template.as_deref().filter(|t| !t.trim().is_empty()) {
+            args.push(pandoc_templates::pandoc_arg(app, template)?);
+        }
+        if let Some(style) = self.highlight_style.as_deref().filter(|s| !s.trim().is_empty()) {
+            args.push(highlight_styles::pandoc_arg(app, style)?);
+        }
+        for (key, value) in &self.metadata {
+            if key.is_empty() || key.contains([':', '=']) || key.chars().any(char::is_whitespace) {
+                return Err(format!("Clé de métadonnée invalide: {}", key));
+            }
+            match value {
+                serde_json::Value::String(text) => args.push(format!("--metadata={}:{}", key, text)),
+                serde_json::Value::Bool(_) | serde_json::Value::Number(_) => args.push(format!("--metadata={}:{}", key, value)),
+                _ => {}
+            }
+        }
+        Ok(args)
+    }
+
+    /// Folders pandoc looks resources up in, since it doesn't run from the
+    /// document's folder: the user's, then the document's folder and assets
+    /// folder, then the workspace.
+    pub fn resource_dirs(&self, app: &AppHandle, workspace_dir: Option<&str>) -> Result<Vec<PathBuf>, String> {
+        let mut dirs = Vec::new();
+        for dir in &self.resource_path {
+            let dir = scopes::check(app, dir)?;
+            if !dir.is_dir() {
+                return Err(format!("Dossier de ressources introuvable: {}", dir.display()));
+            }
+            dirs.push(dir);
+        }
+        if let Some(document) = scopes::check_optional(app, self.document_path.as_deref())? {
+            dirs.extend(document.parent().map(Path::to_path_buf));
+            dirs.push(assets::assets_dir_for(&document));
+        }
+        dirs.extend(workspace_dir.map(PathBuf::from));
+        let mut unique: Vec<PathBuf> = Vec::new();
+        for dir in dirs.into_iter().filter(|d| d.is_dir()) {
+            if !unique.contains(&dir) {
+                unique.push(dir);
+            }
+        }
+        Ok(unique)
+    }
+
+    /// Metadata pandoc can't take on the command line, as a JSON file for
+    /// `--metadata-file`.
+    pub fn metadata_file(&self) -> Result<Option<TempFile>, String> {
+        let structured: BTreeMap<&String, &serde_json::Value> =
+            self.metadata.iter().filter(|(_, v)| v.is_array() || v.is_object()).collect();
+        if structured.is_empty() {
+            return Ok(None);
+        }
+        let json = serde_json::to_string(&structured).map_err(|e| e.to_string())?;
+        TempFile::write("metadata", "json", &json).map(Some)
+    }
+}
+
+/// `--resource-path` for `dirs`, which pandoc splits on the platform's
+/// separator, like PATH.
+pub fn resource_path_arg(dirs: &[PathBuf]) -> Result<Option<String>, String> {
+    if dirs.is_empty() {
+        return Ok(None);
+    }
+    let joined = std::env::join_paths(dirs).map_err(|e| format!("Chemin de ressources invalide: {}", e))?;
+    Ok(Some(format!("--resource-path={}", joined.to_string_lossy())))
+}
+
+/// Local images of `md` that none of `dirs` holds, as written in the source.
+/// Without folders there is nothing to resolve relative links against.
+pub fn missing_resources(md: &str, dirs: &[PathBuf]) -> Vec<String> {
+    let mut missing: Vec<String> = Vec::new();
+    if dirs.is_empty() {
+        return missing;
+    }
+    for link in markdown::links(md).iter().filter(|l| l.is_image) {
+        let url = link.url(md);
+        if !assets::is_local_link(url) || missing.iter().any(|m| m == url) {
+            continue;
+        }
+        let path = assets::resolve_link(url, None);
+        let found = if path.is_absolute() { path.exists() } else { dirs.iter().any(|dir| dir.join(&path).exists()) };
+        if !found {
+            missing.push(url.to_string());
+        }
+    }
+    missing
+}
+
+/// How an import writes markdown.
+#[derive(Default)]
+pub struct ImportOptions<'a> {
+    /// Markdown dialect, pandoc's by default.
+    pub flavor: Option<&'a str>,
+    /// Only for docx.
+    pub track_changes: Option<TrackChanges>,
+    /// Moves every heading this many levels, e.g. 1 to demote a chapter
+    /// whose top level is h1 before merging it into a larger document.
+    pub shift_heading_level: Option<i8>,
+    /// Overrides the sandbox setting, for files of the open workspace only.
+    pub sandbox: Option<bool>,
+}
+
+/// `--sandbox` when the import of `file` runs sandboxed: as set in the
+/// settings, unless `requested` says otherwise for a file of the workspace,
+/// which the user trusts. Files from elsewhere can only ask for more.
+pub fn sandbox_arg(app: &AppHandle, file: &Path, requested: Option<bool>) -> Option<&'static str> {
+    let in_workspace = app.state::<workspace::Workspace>().root().is_ok_and(|root| assets::normalize(file).starts_with(root));
+    let sandbox = match requested {
+        Some(sandbox) if in_workspace => sandbox,
+        Some(true) => true,
+        _ => settings::load(app).import.sandbox,
+    };
+    sandbox.then_some("--sandbox")
+}
+
+/// `--shift-heading-level-by`, within the six levels markdown has.
+pub fn shift_heading_arg(shift: Option<i8>) -> Result<Option<String>, String> {
+    match shift {
+        None | Some(0) => Ok(None),
+        Some(by) if (-5..=5).contains(&by) => Ok(Some(format!("--shift-heading-level-by={}", by))),
+        Some(by) => Err(format!("Décalage des titres invalide: {} (de -5 à 5)", by)),
+    }
+}
+
+/// File removed when dropped, for what pandoc reads from disk during a run.
+pub struct TempFile(PathBuf);
+
+impl TempFile {
+    /// Writes `content` to a fresh file of the temp directory.
+    pub fn write(prefix: &str, extension: &str, content: &str) -> Result<Self, String> {
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+        let path = settings::temp_dir().join(format!("ohmymarkdown-{}-{}-{}.{}", prefix, std::process::id(), nanos, extension));
+        std::fs::write(&path, content).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))?;
+        Ok(TempFile(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Pandoc format of a markdown dialect: "markdown" (pandoc's own, the
+/// default), "commonmark", "gfm", "markdown_strict" or "multimarkdown".
+pub fn flavor(name: &str) -> Result<&'static str, String> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "" | "markdown" | "pandoc" => Ok("markdown"),
+        "commonmark" => Ok("commonmark"),
+        "commonmark_x" => Ok("commonmark_x"),
+        "gfm" | "github" => Ok("gfm"),
+        "markdown_strict" | "strict" => Ok("markdown_strict"),
+        "multimarkdown" | "markdown_mmd" | "mmd" => Ok("markdown_mmd"),
+        other => Err(format!("Variante de markdown inconnue: {}", other)),
+    }
+}
+
+/// The dialect named in the document's frontmatter, pandoc's by default.
+pub fn document_flavor(md: &str) -> Result<String, String> {
+    let fields: Frontmatter = frontmatter::parse(md);
+    Ok(flavor(fields.text(FLAVOR_FIELD).unwrap_or_default())?.to_string())
+}
+
+/// The `-t` argument for imports: the dialect without raw HTML, which the
+/// editor doesn't show. Pandoc's spans and divs are dropped where they exist.
+pub fn import_writer(name: Option<&str>) -> Result<String, String> {
+    Ok(match flavor(name.unwrap_or_default())? {
+        format @ ("commonmark" | "commonmark_x" | "gfm") => format!("{}-raw_html", format),
+        format => format!("{}-raw_html-native_spans-native_divs", format),
+    })
+}
+
+/// Runs pandoc with `args` on `input` and returns what it wrote to stdout.
+/// Relative paths in the document resolve against `dir`.
+pub fn convert(input: &str, args: &[&str], dir: Option<&Path>) -> Result<Vec<u8>, String> {
+    let mut command = Command::new(settings::tool("pandoc"));
+    command.args(args).stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    if let Some(dir) = dir.filter(|d| d.is_dir()) {
+        command.current_dir(dir);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Erreur lors de l'exécution de pandoc: {}. Assurez-vous que pandoc est installé.", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes()).map_err(|e| format!("Erreur d'écriture vers pandoc: {}", e))?;
+    }
+    let output = child.wait_with_output().map_err(|e| format!("Erreur lors de l'attente de pandoc: {}", e))?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(format!("Pandoc a échoué: {}", String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+/// Markdown to an HTML fragment, for services that take the body of a page.
+pub fn to_html_fragment(md: &str, dir: Option<&Path>) -> Result<String, String> {
+    let reader = document_flavor(md)?;
+    let html = convert(md, &["-f", &reader, "-t", "html5", "--wrap=none"], dir)?;
+    Ok(String::from_utf8_lossy(&html).to_string())
+}
@@ -0,0 +1,30 @@
+use crate::pandoc;
+
+/// Pandoc's JSON AST of a markdown document, read in its frontmatter's
+/// dialect.
+#[tauri::command]
+pub async fn markdown_to_ast(content: String) -> Result<serde_json::Value, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let reader = pandoc::document_flavor(&content)?;
+        let json = pandoc::convert(&content, &["-f", &reader, "-t", "json"], None)?;
+        serde_json::from_slice(&json).map_err(|e| format!("AST pandoc invalide: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la conversion en AST: {}", e))?
+}
+
+/// Markdown, with its metadata as frontmatter, from a pandoc JSON AST.
+/// `flavor` is the dialect written, pandoc's by default.
+#[tauri::command]
+pub async fn ast_to_markdown(ast: serde_json::Value, flavor: Option<String>) -> Result<String, String> {
+    if ast.get("pandoc-api-version").is_none() || !ast.get("blocks").is_some_and(|b| b.is_array()) {
+        return Err("AST pandoc invalide: \"pandoc-api-version\" et \"blocks\" sont attendus".to_string());
+    }
+    let writer = pandoc::flavor(flavor.as_deref().unwrap_or_default())?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let markdown = pandoc::convert(&ast.to_string(), &["-f", "json", "-t", writer, "--standalone", "--wrap=none"], None)?;
+        String::from_utf8(markdown).map_err(|e| format!("Erreur de conversion UTF-8: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la conversion depuis l'AST: {}", e))?
+}
@@ -0,0 +1,166 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::{paths, scopes, workspace};
+
+const PRESETS_DIR: &str = "pandoc-defaults";
+/// Applied to every export from the workspace, before the preset.
+const WORKSPACE_FILE: &str = "pandoc-defaults.yaml";
+
+/// Options the app sets itself (formats, files) or that would run programs
+/// named by the file.
+const RESERVED_KEYS: &[&str] = &[
+    "from",
+    "reader",
+    "to",
+    "writer",
+    "input-file",
+    "input-files",
+    "output-file",
+    "filters",
+    "lua-filter",
+    "pdf-engine",
+    "pdf-engine-opt",
+    "pdf-engine-opts",
+    "extract-media",
+    "data-dir",
+    "log-file",
+    "defaults",
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultsFile {
+    pub name: String,
+    pub path: String,
+    /// "app" for presets, "workspace" for the workspace's file.
+    pub source: &'static str,
+    /// Top-level options it sets.
+    pub keys: Vec<String>,
+    /// Why it would be refused at export.
+    pub error: Option<String>,
+}
+
+/// Top-level keys of a defaults file. Only block mappings are read: flow
+/// style, anchors merged at the top and other constructs that could hide a
+/// key are refused rather than guessed at.
+fn top_level_keys(yaml: &str) -> Result<Vec<String>, String> {
+    let mut keys = Vec::new();
+    for line in yaml.lines() {
+        let trimmed = line.trim_end();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" || trimmed == "..." || line.starts_with([' ', '\t']) {
+            continue;
+        }
+        let key = trimmed
+            .split_once(':')
+            .map(|(key, _)| key.trim().trim_matches(|c| c == '"' || c == '\''))
+            .filter(|key| !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .ok_or_else(|| format!("Ligne non prise en charge dans le fichier de défauts: {}", trimmed))?;
+        keys.push(key.to_string());
+    }
+    Ok(keys)
+}
+
+/// Validates the defaults file at `path` and returns the options it sets.
+pub fn validate(path: &Path) -> Result<Vec<String>, String> {
+    let yaml = std::fs::read_to_string(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+    let keys = top_level_keys(&yaml)?;
+    let reserved: Vec<&str> = keys
+        .iter()
+        .map(|k| k.as_str())
+        .filter(|k| RESERVED_KEYS.contains(&k.to_ascii_lowercase().replace('_', "-").as_str()))
+        .collect();
+    if !reserved.is_empty() {
+        return Err(format!("Options réservées à l'application dans {}: {}", path.display(), reserved.join(", ")));
+    }
+    Ok(keys)
+}
+
+fn presets_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    paths::app_data_subdir(app, PRESETS_DIR)
+}
+
+fn workspace_file(app: &AppHandle) -> Option<PathBuf> {
+    let root = app.state::<workspace::Workspace>().root().ok()?;
+    Some(paths::workspace_config_dir(&root).join(WORKSPACE_FILE)).filter(|p| p.is_file())
+}
+
+/// A preset of the app's defaults folder by name, or a defaults file by path.
+fn preset_path(app: &AppHandle, preset: &str) -> Result<PathBuf, String> {
+    if preset.contains(['/', '\\']) {
+        return Ok(PathBuf::from(preset));
+    }
+    let dir = presets_dir(app)?;
+    ["yaml", "yml"]
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", preset, ext)))
+        .find(|p| p.is_file())
+        .ok_or_else(|| format!("Préréglage pandoc introuvable: {}", preset))
+}
+
+/// `--defaults` arguments for an export: the workspace's file, then the
+/// preset. They go before the app's own arguments, which pandoc lets win.
+pub fn pandoc_args(app: &AppHandle, preset: Option<&str>) -> Result<Vec<String>, String> {
+    let mut files: Vec<PathBuf> = workspace_file(app).into_iter().collect();
+    if let Some(preset) = preset.filter(|p| !p.trim().is_empty()) {
+        files.push(preset_path(app, preset)?);
+    }
+    files
+        .into_iter()
+        .map(|file| {
+            validate(&file)?;
+            Ok(format!("--defaults={}", file.display()))
+        })
+        .collect()
+}
+
+fn describe(path: PathBuf, source: &'static str) -> DefaultsFile {
+    let (keys, error) = match validate(&path) {
+        Ok(keys) => (keys, None),
+        Err(e) => (Vec::new(), Some(e)),
+    };
+    DefaultsFile {
+        name: path.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        path: path.to_string_lossy().to_string(),
+        source,
+        keys,
+        error,
+    }
+}
+
+/// Export presets (defaults files in the app's `pandoc-defaults` folder) and
+/// the workspace's `.ohmymarkdown/pandoc-defaults.yaml`.
+#[tauri::command]
+pub fn list_pandoc_defaults(app: AppHandle) -> Result<Vec<DefaultsFile>, String> {
+    let mut presets: Vec<PathBuf> = std::fs::read_dir(presets_dir(&app)?)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "yaml" || e == "yml"))
+        .collect();
+    presets.sort();
+    let mut files: Vec<DefaultsFile> = presets.into_iter().map(|p| describe(p, "app")).collect();
+    files.extend(workspace_file(&app).map(|p| describe(p, "workspace")));
+    Ok(files)
+}
+
+#[tauri::command]
+pub fn validate_pandoc_defaults(app: AppHandle, path: String) -> Result<Vec<String>, String> {
+    validate(&scopes::check(&app, &path)?)
+}
+
+/// Copies the defaults file at `file` into the presets folder as `name`.
+#[tauri::command]
+pub fn add_pandoc_defaults(app: AppHandle, file: String, name: String) -> Result<DefaultsFile, String> {
+    let name = name.trim();
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("Nom de préréglage invalide: {}", name));
+    }
+    let file = scopes::check(&app, &file)?;
+    validate(&file)?;
+    let target = presets_dir(&app)?.join(format!("{}.yaml", name));
+    std::fs::copy(&file, &target).map_err(|e| format!("Impossible de copier {}: {}", file.display(), e))?;
+    Ok(describe(target, "app"))
+}
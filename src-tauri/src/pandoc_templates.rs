@@ -0,0 +1,163 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::{pandoc, paths, scopes, settings};
+
+const TEMPLATES_DIR: &str = "pandoc-templates";
+
+/// Rendered with a template to check it before exporting real documents.
+const SAMPLE_DOCUMENT: &str = r#"---
+title: Document d'exemple
+subtitle: Aperçu du modèle
+author: [Ada Lovelace, Alan Turing]
+date: 2024-01-01
+abstract: Un court résumé pour vérifier la page de titre.
+lang: fr
+---
+
+# Premier niveau
+
+Un paragraphe avec de l'*italique*, du **gras**, du `code`, un [lien](https://pandoc.org) et une note[^1].
+
+## Deuxième niveau
+
+> Une citation, pour voir les marges.
+
+- Un élément
+- Un autre élément
+  1. Imbriqué
+
+| Colonne | Valeur |
+|---------|-------:|
+| Un      |     1  |
+| Deux    |     2  |
+
+```rust
+fn main() {
+    println!("Bonjour");
+}
+```
+
+$$E = mc^2$$
+
+[^1]: Le texte de la note.
+"#;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PandocTemplate {
+    pub name: String,
+    pub path: String,
+    /// Pandoc format the template is for.
+    pub format: &'static str,
+}
+
+fn templates_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    paths::app_data_subdir(app, TEMPLATES_DIR)
+}
+
+/// Output format of a template file, from its extension.
+fn template_format(path: &Path) -> Option<&'static str> {
+    match path.extension()?.to_string_lossy().to_lowercase().as_str() {
+        "html" | "htm" | "html5" => Some("html5"),
+        "tex" | "latex" => Some("latex"),
+        "typ" | "typst" => Some("typst"),
+        "docx" => Some("docx"),
+        "odt" => Some("odt"),
+        "pptx" => Some("pptx"),
+        _ => None,
+    }
+}
+
+/// Office formats are styled by a reference document rather than a template.
+fn is_reference_doc(format: &str) -> bool {
+    matches!(format, "docx" | "odt" | "pptx")
+}
+
+fn describe(path: PathBuf) -> Option<PandocTemplate> {
+    Some(PandocTemplate {
+        name: path.file_name()?.to_string_lossy().to_string(),
+        format: template_format(&path)?,
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+/// The template named `template` in the app's folder, or a template file by
+/// path.
+fn resolve(app: &AppHandle, template: &str) -> Result<(PathBuf, &'static str), String> {
+    let path = if template.contains(['/', '\\']) { scopes::check(app, template)? } else { templates_dir(app)?.join(template) };
+    if !path.is_file() {
+        return Err(format!("Modèle pandoc introuvable: {}", template));
+    }
+    let format = template_format(&path).ok_or_else(|| format!("Type de modèle pandoc non pris en charge: {}", path.display()))?;
+    Ok((path, format))
+}
+
+/// `--template` or, for office formats, `--reference-doc`.
+pub fn pandoc_arg(app: &AppHandle, template: &str) -> Result<String, String> {
+    let (path, format) = resolve(app, template)?;
+    let option = if is_reference_doc(format) { "--reference-doc" } else { "--template" };
+    Ok(format!("{}={}", option, path.display()))
+}
+
+#[tauri::command]
+pub fn list_pandoc_templates(app: AppHandle) -> Result<Vec<PandocTemplate>, String> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(templates_dir(&app)?)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .collect();
+    paths.sort();
+    Ok(paths.into_iter().filter_map(describe).collect())
+}
+
+/// Copies a template (HTML, LaTeX, Typst) or reference document (DOCX, ODT,
+/// PPTX) into the app's templates folder, under `name` when given.
+#[tauri::command]
+pub fn import_pandoc_template(app: AppHandle, file: String, name: Option<String>) -> Result<PandocTemplate, String> {
+    let source = &scopes::check(&app, &file)?;
+    let extension = source.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    template_format(source).ok_or_else(|| format!("Type de modèle pandoc non pris en charge: {}", file))?;
+    let stem = match name.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) if name.contains(['/', '\\']) || name.starts_with('.') => return Err(format!("Nom de modèle invalide: {}", name)),
+        Some(name) => name.to_string(),
+        None => source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+    };
+    let target = templates_dir(&app)?.join(format!("{}.{}", stem, extension));
+    std::fs::copy(source, &target).map_err(|e| format!("Impossible de copier {}: {}", file, e))?;
+    describe(target).ok_or_else(|| format!("Type de modèle pandoc non pris en charge: {}", file))
+}
+
+#[tauri::command]
+pub fn remove_pandoc_template(app: AppHandle, name: String) -> Result<(), String> {
+    if name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("Nom de modèle invalide: {}", name));
+    }
+    let path = templates_dir(&app)?.join(&name);
+    std::fs::remove_file(&path).map_err(|e| format!("Impossible de supprimer {}: {}", path.display(), e))
+}
+
+/// Renders a sample document with `template` and returns the path of the
+/// result, so a broken template shows up before a real export.
+#[tauri::command]
+pub async fn preview_pandoc_template(app: AppHandle, template: String) -> Result<String, String> {
+    let (path, format) = resolve(&app, &template)?;
+    let argument = pandoc_arg(&app, &template)?;
+    let extension = match format {
+        "html5" => "html",
+        "latex" => "tex",
+        "typst" => "typ",
+        other => other,
+    };
+    let output = settings::temp_dir().join(format!("ohmymarkdown-template-preview.{}", extension));
+    tauri::async_runtime::spawn_blocking(move || {
+        let output_arg = output.to_string_lossy().to_string();
+        pandoc::convert(SAMPLE_DOCUMENT, &["-f", "markdown", "-t", format, "--standalone", &argument, "-o", &output_arg], path.parent())?;
+        Ok(output_arg)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de l'aperçu du modèle: {}", e))?
+}
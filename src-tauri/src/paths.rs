@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+
+/// File next to the executable that turns on portable mode.
+const PORTABLE_MARKER: &str = "portable";
+/// Sibling folder holding everything the app keeps in portable mode.
+const PORTABLE_DIR: &str = "ohmymarkdown-data";
+
+/// The portable data folder, when a `portable` file sits next to the
+/// executable, e.g. on a USB stick.
+pub fn portable_dir() -> Option<&'static Path> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+        exe_dir.join(PORTABLE_MARKER).is_file().then(|| exe_dir.join(PORTABLE_DIR))
+    })
+    .as_deref()
+}
+
+fn create(dir: PathBuf) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// App data directory (downloaded tools, caches), created on first use.
+pub fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    if let Some(portable) = portable_dir() {
+        return create(portable.join("data"));
+    }
+    let dir = app.path().app_data_dir()
+        .map_err(|e| format!("Impossible de déterminer le dossier de données: {}", e))?;
+    create(dir)
+}
+
+/// Subdirectory of the app data directory, created on first use.
+pub fn app_data_subdir(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    create(app_data_dir(app)?.join(name))
+}
+
+/// App config directory (settings, recent files), created on first use.
+pub fn app_config_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    if let Some(portable) = portable_dir() {
+        return create(portable.join("config"));
+    }
+    let dir = app.path().app_config_dir()
+        .map_err(|e| format!("Impossible de déterminer le dossier de configuration: {}", e))?;
+    create(dir)
+}
+
+/// Writes a file only its owner may read, for settings holding passwords
+/// or tokens. New files are created that way, so their content is never
+/// readable by others, even briefly; older ones are restricted first.
+pub fn write_private(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(bytes)
+}
+
+/// Per-workspace configuration directory (`.ohmymarkdown/`), not created here.
+pub fn workspace_config_dir(workspace: &Path) -> PathBuf {
+    workspace.join(".ohmymarkdown")
+}
+
+/// Bytes available to the user on the volume holding `dir`, when the OS
+/// tells.
+pub fn free_space(dir: &Path) -> Option<u64> {
+    #[cfg(target_os = "windows")]
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", "[System.IO.DriveInfo]::new($env:OHMYMARKDOWN_DIR).AvailableFreeSpace"])
+        .env("OHMYMARKDOWN_DIR", dir)
+        .output()
+        .ok()?;
+    #[cfg(not(target_os = "windows"))]
+    let output = std::process::Command::new("df").arg("-Pk").arg(dir).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if cfg!(target_os = "windows") {
+        return stdout.trim().parse().ok();
+    }
+    // POSIX format: a header, then "filesystem blocks used available ..."
+    let kib: u64 = stdout.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// The portable data folder when the app runs in portable mode.
+#[tauri::command]
+pub fn get_portable_dir() -> Option<String> {
+    portable_dir().map(|dir| dir.to_string_lossy().to_string())
+}
@@ -0,0 +1,362 @@
+use base64::Engine;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use std::io::Write;
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use tauri::{AppHandle, Manager, State};
+use tiny_http::{Header, ReadWrite, Request, Response, Server};
+
+use crate::workspace::{self, Workspace};
+use crate::{calendar, publish, sanitize, site_export, svg};
+
+const LIVE_SCRIPT: &str = include_str!("preview_server/live.js");
+/// Messages a page may fall behind by before it is dropped.
+const CLIENT_QUEUE: usize = 8;
+const DEFAULT_PORT: u16 = 8765;
+/// WebSocket endpoint pushing updates to open pages.
+const LIVE_PATH: &str = "/live";
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// Files are served under this prefix, relative to the workspace.
+const FILES_PREFIX: &str = "/files/";
+/// Subscribable calendar of the workspace's tasks with a due date.
+const CALENDAR_PATH: &str = "/calendar.ics";
+const PATH_SEGMENT: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'#').add(b'%').add(b'?').add(b'<').add(b'>');
+
+/// What the preview server shows: the frontend's rendering of the current
+/// document.
+#[derive(Default)]
+struct Preview {
+    html: String,
+    title: String,
+    /// Folder files are served from: the workspace, or the document's folder
+    /// when it lives outside.
+    root: Option<PathBuf>,
+    /// The document's folder relative to `root`, where its links start.
+    base: String,
+}
+
+struct Running {
+    server: Arc<Server>,
+    thread: JoinHandle<()>,
+    info: PreviewServerInfo,
+}
+
+/// Random first segment of every path the server answers, so only those
+/// given the address can read the preview.
+fn session_token() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Impossible de générer le jeton du serveur d'aperçu: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Compares in constant time, so the token can't be guessed byte by byte.
+fn same_token(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Embedded HTTP server showing the preview to other devices.
+impl Preview {
+    fn body(&self) -> &str {
+        if self.html.is_empty() {
+            "<p>Aucun document n'est ouvert.</p>"
+        } else {
+            &self.html
+        }
+    }
+}
+
+/// Live-reload connections of the pages showing the preview. Each page has
+/// its own writer thread, so a stalled one doesn't hold up the others.
+type Clients = Arc<Mutex<Vec<SyncSender<Arc<Vec<u8>>>>>>;
+
+fn add_client(clients: &Clients, mut stream: Box<dyn ReadWrite + Send>) {
+    let (sender, receiver) = mpsc::sync_channel::<Arc<Vec<u8>>>(CLIENT_QUEUE);
+    std::thread::spawn(move || {
+        for frame in receiver {
+            if stream.write_all(&frame).and_then(|_| stream.flush()).is_err() {
+                break;
+            }
+        }
+    });
+    if let Ok(mut clients) = clients.lock() {
+        clients.push(sender);
+    }
+}
+
+#[derive(Default)]
+pub struct PreviewServer {
+    preview: Arc<Mutex<Preview>>,
+    clients: Clients,
+    running: Mutex<Option<Running>>,
+}
+
+impl PreviewServer {
+    fn stop(&self) {
+        if let Some(running) = self.running.lock().ok().and_then(|mut r| r.take()) {
+            running.server.unblock();
+            let _ = running.thread.join();
+        }
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.clear();
+        }
+    }
+
+    /// Queues a text message for every page, dropping the ones that are gone
+    /// or too far behind.
+    fn broadcast(&self, message: &str) {
+        let payload = message.as_bytes();
+        // Final text frame; servers don't mask
+        let mut frame = vec![0x81];
+        match payload.len() {
+            len if len < 126 => frame.push(len as u8),
+            len if len <= u16::MAX as usize => {
+                frame.push(126);
+                frame.extend((len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(127);
+                frame.extend((len as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(payload);
+        let frame = Arc::new(frame);
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.retain(|client| !matches!(client.try_send(frame.clone()), Err(TrySendError::Full(_) | TrySendError::Disconnected(_))));
+        }
+    }
+}
+
+impl Drop for PreviewServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewServerInfo {
+    pub port: u16,
+    /// Address on this machine.
+    pub local_url: String,
+    /// Address on the local network, short enough for a QR code. None when
+    /// the server only listens locally or no network is up.
+    pub lan_url: Option<String>,
+    /// Address to subscribe to the tasks calendar, when it is served.
+    pub calendar_url: Option<String>,
+}
+
+/// Address of the interface that reaches the network. Connecting a UDP
+/// socket sends nothing.
+fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect(("192.0.2.1", 80)).ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name, value).expect("valid header")
+}
+
+fn page(preview: &Preview, token: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    let base: String = preview.base.split('/').filter(|s| !s.is_empty()).map(|s| format!("{}/", utf8_percent_encode(s, PATH_SEGMENT))).collect();
+    let html = format!(
+        "<!DOCTYPE html>\n<html lang=\"fr\" data-theme=\"auto\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n\
+         <base href=\"/{}{}{}\">\n<style>\n{}</style>\n</head>\n<body>\n<main>\n{}\n</main>\n<script>\n{}</script>\n</body>\n</html>\n",
+        preview.title.replace('&', "&amp;").replace('<', "&lt;"),
+        token,
+        FILES_PREFIX,
+        base,
+        site_export::STYLE,
+        preview.body(),
+        LIVE_SCRIPT
+    );
+    Response::from_data(html)
+        .with_header(header("Content-Type", "text/html; charset=utf-8"))
+        .with_header(header("Cache-Control", "no-store"))
+}
+
+/// A file linked from the preview. Only assets (images, PDFs) are served, and
+/// nothing hidden, so the rest of the workspace stays private.
+fn file(preview: &Preview, relative: &str) -> Option<Response<std::io::Cursor<Vec<u8>>>> {
+    let root = preview.root.as_ref()?;
+    let relative = percent_encoding::percent_decode_str(relative).decode_utf8_lossy().to_string();
+    let relative = Path::new(&relative);
+    let safe = relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(name) if !name.to_string_lossy().starts_with('.')));
+    let ext = relative.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if !safe || !workspace::ASSET_EXTENSIONS.contains(&ext.as_str()) {
+        return None;
+    }
+    let path = root.join(relative);
+    let data = std::fs::read(&path).ok()?;
+    let (data, mime) = match ext.as_str() {
+        "svg" => (svg::sanitize(&String::from_utf8_lossy(&data)).into_bytes(), "image/svg+xml"),
+        "pdf" => (data, "application/pdf"),
+        _ => (data, publish::image_mime(&path)),
+    };
+    Some(Response::from_data(data).with_header(header("Content-Type", mime)))
+}
+
+/// The calendar is built on each request so subscribers see current tasks.
+fn calendar(app: &AppHandle) -> Option<Response<std::io::Cursor<Vec<u8>>>> {
+    let root = app.state::<Workspace>().root().ok()?;
+    let ics = calendar::ics(&calendar::due_tasks(&root), &calendar::calendar_name(&root), false);
+    Some(
+        Response::from_data(ics)
+            .with_header(header("Content-Type", "text/calendar; charset=utf-8"))
+            .with_header(header("Cache-Control", "no-store")),
+    )
+}
+
+fn accept_key(key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(key.trim().as_bytes());
+    hasher.update(WEBSOCKET_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(hasher.finalize())
+}
+
+fn handle(request: Request, preview: &Mutex<Preview>, clients: &Clients, calendar_app: Option<&AppHandle>, token: &str) {
+    let url = request.url().split(['?', '#']).next().unwrap_or_default().to_string();
+    let session = url.trim_start_matches('/').split('/').next().unwrap_or_default();
+    if !same_token(session, token) {
+        let _ = request.respond(Response::from_data("Introuvable").with_status_code(404));
+        return;
+    }
+    let path = url.trim_start_matches('/')[session.len()..].to_string();
+    let path = if path.is_empty() { "/".to_string() } else { path };
+    if path == LIVE_PATH {
+        let key = request.headers().iter().find(|h| h.field.equiv("Sec-WebSocket-Key")).map(|h| h.value.to_string());
+        if let Some(key) = key {
+            let response = Response::empty(101).with_header(header("Sec-WebSocket-Accept", &accept_key(&key)));
+            add_client(clients, request.upgrade("websocket", response));
+            return;
+        }
+    }
+    if path == CALENDAR_PATH {
+        if let Some(app) = calendar_app {
+            let response = calendar(app).unwrap_or_else(|| Response::from_data("Aucun espace de travail").with_status_code(404));
+            let _ = request.respond(response);
+            return;
+        }
+    }
+    let response = {
+        let preview = preview.lock().unwrap();
+        match path.as_str() {
+            "/" | "/index.html" => Some(page(&preview, token)),
+            _ => path.strip_prefix(FILES_PREFIX).and_then(|relative| file(&preview, relative)),
+        }
+    };
+    let response = response.unwrap_or_else(|| Response::from_data("Introuvable").with_status_code(404));
+    let _ = request.respond(response);
+}
+
+/// Starts the preview server, on the local network only with `lan`, and
+/// returns its addresses, which hold a token new to each start. The port defaults to 8765, or any free one when
+/// it is taken. With `calendar`, it also serves the tasks calendar.
+#[tauri::command]
+pub fn start_preview_server(
+    app: AppHandle,
+    server: State<'_, PreviewServer>,
+    port: Option<u16>,
+    lan: Option<bool>,
+    calendar: Option<bool>,
+) -> Result<PreviewServerInfo, String> {
+    let mut running = server.running.lock().map_err(|e| e.to_string())?;
+    if let Some(running) = running.as_ref() {
+        return Ok(running.info.clone());
+    }
+
+    let lan = lan.unwrap_or(false);
+    let token = session_token()?;
+    let host = if lan { Ipv4Addr::UNSPECIFIED } else { Ipv4Addr::LOCALHOST };
+    let http = match port {
+        Some(port) => Server::http((host, port)),
+        None => Server::http((host, DEFAULT_PORT)).or_else(|_| Server::http((host, 0))),
+    }
+    .map_err(|e| format!("Impossible de démarrer le serveur d'aperçu: {}", e))?;
+    let port = http.server_addr().to_ip().map(|addr| addr.port()).unwrap_or_default();
+    let local_url = format!("http://localhost:{}/{}/", port, token);
+    let lan_url = lan.then(lan_address).flatten().map(|ip| format!("http://{}:{}/{}/", ip, port, token));
+    let calendar = calendar.unwrap_or(false);
+    // Calendar apps often run on another device, hence the network address
+    let calendar_url = calendar.then(|| format!("{}{}", lan_url.as_ref().unwrap_or(&local_url).trim_end_matches('/'), CALENDAR_PATH));
+    let info = PreviewServerInfo { port, local_url, lan_url, calendar_url };
+
+    let http = Arc::new(http);
+    let thread = {
+        let http = http.clone();
+        let preview = server.preview.clone();
+        let clients = server.clients.clone();
+        let calendar_app = calendar.then_some(app);
+        std::thread::spawn(move || {
+            for request in http.incoming_requests() {
+                let preview = preview.clone();
+                let clients = clients.clone();
+                let calendar_app = calendar_app.clone();
+                let token = token.clone();
+                std::thread::spawn(move || handle(request, &preview, &clients, calendar_app.as_ref(), &token));
+            }
+        })
+    };
+    *running = Some(Running { server: http, thread, info: info.clone() });
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn stop_preview_server(server: State<'_, PreviewServer>) {
+    server.stop();
+}
+
+#[tauri::command]
+pub fn get_preview_server(server: State<'_, PreviewServer>) -> Result<Option<PreviewServerInfo>, String> {
+    Ok(server.running.lock().map_err(|e| e.to_string())?.as_ref().map(|r| r.info.clone()))
+}
+
+/// Replaces what the preview server shows with the rendered `html` of the
+/// document at `document_path`, whose relative links it resolves. Open pages
+/// are patched in place, or reloaded when the document's folder changed.
+/// The HTML is sanitized unless the document is `trusted`.
+#[tauri::command]
+pub fn update_preview_server(
+    app: AppHandle,
+    server: State<'_, PreviewServer>,
+    html: String,
+    title: Option<String>,
+    document_path: Option<String>,
+    trusted: Option<bool>,
+) -> Result<(), String> {
+    let html = sanitize::sanitize_html(html, trusted);
+    let document_dir = document_path.as_deref().map(Path::new).and_then(Path::parent).map(Path::to_path_buf);
+    let workspace_root = app.state::<Workspace>().root().ok();
+    let root = match (&document_dir, workspace_root) {
+        (Some(dir), Some(root)) if dir.starts_with(&root) => Some(root),
+        (dir, _) => dir.clone(),
+    };
+    let base = match (&document_dir, &root) {
+        (Some(dir), Some(root)) => dir.strip_prefix(root).unwrap_or(Path::new("")).to_string_lossy().replace('\\', "/"),
+        _ => String::new(),
+    };
+    let title = title
+        .or_else(|| document_path.as_deref().map(Path::new).and_then(Path::file_stem).map(|s| s.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "Aperçu".to_string());
+    let message = {
+        let mut preview = server.preview.lock().map_err(|e| e.to_string())?;
+        let moved = preview.root != root || preview.base != base;
+        *preview = Preview { html, title, root, base };
+        if moved {
+            serde_json::json!({ "type": "reload" })
+        } else {
+            serde_json::json!({ "type": "patch", "html": preview.body(), "title": preview.title })
+        }
+    };
+    server.broadcast(&message.to_string());
+    Ok(())
+}
@@ -0,0 +1,30 @@
+(function () {
+  var connected = false;
+
+  function connect() {
+    // The first path segment is the server's session token
+    var session = location.pathname.split("/")[1];
+    var socket = new WebSocket((location.protocol === "https:" ? "wss://" : "ws://") + location.host + "/" + session + "/live");
+    socket.onopen = function () {
+      // Back after the server restarted: the page may be stale
+      if (connected) {
+        location.reload();
+      }
+      connected = true;
+    };
+    socket.onmessage = function (event) {
+      var message = JSON.parse(event.data);
+      if (message.type === "patch") {
+        document.querySelector("main").innerHTML = message.html;
+        document.title = message.title;
+      } else {
+        location.reload();
+      }
+    };
+    socket.onclose = function () {
+      setTimeout(connect, 1000);
+    };
+  }
+
+  connect();
+})();
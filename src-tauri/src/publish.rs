@@ -0,0 +1,243 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::frontmatter::{self, Frontmatter};
+use crate::{assets, markdown, scopes};
+
+const ACCOUNTS_FILE: &str = "publishing.json";
+const FEATURE_IMAGE_KEYS: &[&str] = &["feature_image", "cover_image", "image", "cover"];
+
+// Serializes read-modify-write cycles on the accounts file
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Accounts of every target, by target then by the name the user gave them.
+type Accounts = BTreeMap<String, BTreeMap<String, serde_json::Value>>;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Published {
+    pub id: String,
+    pub url: String,
+    /// The document with the post id recorded in its frontmatter, so the next
+    /// publication updates the post instead of creating another one.
+    pub markdown: String,
+    pub images_uploaded: usize,
+}
+
+/// What a document says about the post it becomes.
+pub struct Post {
+    pub title: String,
+    /// Markdown without the frontmatter.
+    pub body: String,
+    pub tags: Vec<String>,
+    pub categories: Vec<String>,
+    /// `status` as written ("draft", "publish", "private"…), if any.
+    pub status: Option<String>,
+    /// True unless the frontmatter asks for publication; nothing goes live
+    /// by accident.
+    pub draft: bool,
+    pub summary: Option<String>,
+    pub canonical_url: Option<String>,
+    pub series: Option<String>,
+    /// Local path or URL of the cover image.
+    pub feature_image: Option<String>,
+    /// Id recorded by a previous publication to this target.
+    pub id: Option<String>,
+}
+
+fn accounts_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::paths::app_config_dir(app)?.join(ACCOUNTS_FILE))
+}
+
+fn load(app: &AppHandle) -> Accounts {
+    accounts_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn update(app: &AppHandle, change: impl FnOnce(&mut Accounts)) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap();
+    let mut accounts = load(app);
+    change(&mut accounts);
+    let json = serde_json::to_string_pretty(&accounts).map_err(|e| e.to_string())?;
+    let path = accounts_path(app)?;
+    // The file holds passwords and tokens
+    crate::paths::write_private(&path, json.as_bytes()).map_err(|e| format!("Impossible d'enregistrer les comptes de publication: {}", e))
+}
+
+/// Settings of a saved account of `target`.
+pub fn account<T: DeserializeOwned>(app: &AppHandle, target: &str, name: &str) -> Result<T, String> {
+    let value = load(app)
+        .remove(target)
+        .and_then(|mut accounts| accounts.remove(name))
+        .ok_or_else(|| format!("Compte de publication inconnu: {}", name))?;
+    serde_json::from_value(value).map_err(|e| format!("Compte de publication invalide ({}): {}", name, e))
+}
+
+/// Checks the settings of an account before saving them.
+fn validate(target: &str, account: &serde_json::Value) -> Result<(), String> {
+    match target {
+        "wordpress" => crate::wordpress::validate(account),
+        "ghost" => crate::ghost::validate(account),
+        "devto" => crate::devto::validate(account),
+        "hashnode" => crate::hashnode::validate(account),
+        "github" => crate::gist::validate(account),
+        "confluence" => crate::confluence::validate(account),
+        "jira" => crate::jira::validate(account),
+        _ => Err(format!("Cible de publication inconnue: {}", target)),
+    }
+}
+
+/// Names of the saved accounts of a target; their secrets stay in the
+/// backend.
+#[tauri::command]
+pub fn list_publish_accounts(app: AppHandle, target: &str) -> Vec<String> {
+    load(&app).remove(target).map(|a| a.into_keys().collect()).unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn set_publish_account(app: AppHandle, target: &str, name: &str, account: serde_json::Value) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Le nom du compte est vide".to_string());
+    }
+    validate(target, &account)?;
+    update(&app, |accounts| {
+        accounts.entry(target.to_string()).or_default().insert(name.trim().to_string(), account);
+    })
+}
+
+#[tauri::command]
+pub fn remove_publish_account(app: AppHandle, target: &str, name: &str) -> Result<(), String> {
+    update(&app, |accounts| {
+        if let Some(target_accounts) = accounts.get_mut(target) {
+            target_accounts.remove(name);
+        }
+    })
+}
+
+/// Reads the post fields from the frontmatter. `id_key` is the field the
+/// target records its post id in.
+pub fn post(md: &str, document_path: Option<&Path>, id_key: &str) -> Post {
+    let fields: Frontmatter = frontmatter::parse(md);
+    let body = frontmatter::body(md).to_string();
+    let status = fields.text("status").map(|s| s.to_lowercase());
+    let draft = match (fields.flag("draft"), fields.flag("published"), status.as_deref()) {
+        (Some(draft), _, _) => draft,
+        (_, Some(published), _) => !published,
+        (_, _, Some(status)) => matches!(status, "draft" | "pending"),
+        _ => true,
+    };
+    let text = |keys: &[&str]| fields.first_text(keys).map(str::to_string);
+    Post {
+        title: frontmatter::title(md, document_path),
+        tags: fields.list("tags"),
+        categories: fields.list("categories"),
+        status,
+        draft,
+        summary: text(&["summary", "description", "excerpt"]),
+        canonical_url: text(&["canonical_url", "canonical"]),
+        series: text(&["series"]),
+        feature_image: text(FEATURE_IMAGE_KEYS),
+        id: text(&[id_key]),
+        body,
+    }
+}
+
+/// Uploads the local images of `md` with `upload`, which returns the remote
+/// URL of a file, and points the links at them. Each file is sent once, and
+/// only from the folders the app may read.
+pub fn upload_images(
+    app: &AppHandle,
+    md: &str,
+    document_dir: Option<&Path>,
+    mut upload: impl FnMut(&Path) -> Result<String, String>,
+) -> Result<(String, usize), String> {
+    let mut uploaded: HashMap<PathBuf, String> = HashMap::new();
+    let mut replacements = Vec::new();
+    for link in markdown::links(md).iter().filter(|l| l.is_image) {
+        let url = link.url(md);
+        if !assets::is_local_link(url) {
+            continue;
+        }
+        let file = scopes::check(app, &assets::resolve_link(url, document_dir).to_string_lossy())?;
+        if !file.is_file() {
+            return Err(format!("Image introuvable: {}", file.display()));
+        }
+        let remote = match uploaded.get(&file) {
+            Some(remote) => remote.clone(),
+            None => {
+                let remote = upload(&file)?;
+                uploaded.insert(file, remote.clone());
+                remote
+            }
+        };
+        replacements.push(assets::replacement(md, link, remote));
+    }
+    Ok((markdown::replace_ranges(md, replacements), uploaded.len()))
+}
+
+/// For targets without an upload API: images must already be online.
+pub fn ensure_remote_images(md: &str, feature_image: Option<&str>) -> Result<(), String> {
+    let local: Vec<&str> = markdown::links(md)
+        .iter()
+        .filter(|l| l.is_image)
+        .map(|l| l.url(md))
+        .chain(feature_image)
+        .filter(|url| assets::is_local_link(url))
+        .collect();
+    if local.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Ce service n'accepte que des images en ligne; images locales: {}", local.join(", ")))
+    }
+}
+
+/// `id` from a note's frontmatter, refused unless every character is
+/// `allowed`: ids go into API paths, where `/` or `..` would reach another
+/// route.
+pub fn checked_id(id: &str, allowed: fn(char) -> bool) -> Result<&str, String> {
+    if !id.is_empty() && id.chars().all(allowed) {
+        Ok(id)
+    } else {
+        Err(format!("Identifiant de publication invalide: {:?}", id))
+    }
+}
+
+/// The local file a frontmatter image refers to, None for a URL.
+pub fn local_image(app: &AppHandle, image: &str, document_dir: Option<&Path>) -> Result<Option<PathBuf>, String> {
+    if !assets::is_local_link(image) {
+        return Ok(None);
+    }
+    Ok(Some(scopes::check(app, &assets::resolve_link(image, document_dir).to_string_lossy())?))
+}
+
+/// MIME type of an image, from its extension.
+pub fn image_mime(path: &Path) -> &'static str {
+    match path.extension().map(|e| e.to_string_lossy().to_lowercase()).as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        Some("avif") => "image/avif",
+        Some("bmp") => "image/bmp",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Records the target's post id in the document's frontmatter.
+pub fn record_id(md: &str, id_key: &str, id: &str) -> String {
+    frontmatter::set(md, id_key, id)
+}
+
+pub async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Erreur lors de la publication: {}", e))?
+}
@@ -0,0 +1,644 @@
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::frontmatter;
+use crate::{assets, http, paths, scopes};
+
+const ACCOUNTS_FILE: &str = "read-later.json";
+const READWISE_API: &str = "https://readwise.io/api/v2";
+const INSTAPAPER_API: &str = "https://www.instapaper.com/api";
+/// Instapaper's largest page; it has no paging beyond it.
+const INSTAPAPER_LIMIT: &str = "500";
+const INSTAPAPER_FOLDERS: &[&str] = &["unread", "archive", "starred"];
+const MAX_RETRIES: usize = 3;
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(60);
+const MAX_NAME_CHARS: usize = 100;
+/// Words of a highlight kept at each end of its `#:~:text=` link.
+const FRAGMENT_WORDS: usize = 4;
+/// RFC 3986 unreserved characters stay as they are; OAuth encodes the rest.
+const OAUTH_ENCODE: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+const FRAGMENT_ENCODE: &AsciiSet = &OAUTH_ENCODE.add(b'-');
+
+// Serializes read-modify-write cycles on the accounts file
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ReadLaterAccounts {
+    readwise: Option<ReadwiseAccount>,
+    instapaper: Option<InstapaperAccount>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ReadwiseAccount {
+    token: String,
+}
+
+/// OAuth credentials from an xAuth exchange; the password is not kept.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct InstapaperAccount {
+    consumer_key: String,
+    consumer_secret: String,
+    token: String,
+    token_secret: String,
+}
+
+/// What was imported into a folder from one service, to only add what is
+/// new on the next sync.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct SyncState {
+    /// Start time of the last complete sync, for services that filter by
+    /// date.
+    last_sync: Option<String>,
+    items: BTreeMap<String, ItemState>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct ItemState {
+    /// Note file name in the folder.
+    note: String,
+    highlights: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadLaterImport {
+    /// Notes created for new articles.
+    pub created: Vec<String>,
+    /// Notes that got new highlights.
+    pub updated: Vec<String>,
+    pub highlights: usize,
+}
+
+/// An article as every service describes it.
+#[derive(Default)]
+struct Article {
+    id: String,
+    title: String,
+    url: Option<String>,
+    author: Option<String>,
+    saved: Option<String>,
+    summary: Option<String>,
+    tags: Vec<String>,
+    highlights: Vec<Highlight>,
+}
+
+struct Highlight {
+    id: String,
+    text: String,
+    note: Option<String>,
+    /// Link to the passage, when the service has one.
+    url: Option<String>,
+}
+
+fn accounts_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_config_dir(app)?.join(ACCOUNTS_FILE))
+}
+
+fn load(app: &AppHandle) -> ReadLaterAccounts {
+    accounts_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn update(app: &AppHandle, change: impl FnOnce(&mut ReadLaterAccounts)) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap();
+    let mut accounts = load(app);
+    change(&mut accounts);
+    let json = serde_json::to_string_pretty(&accounts).map_err(|e| e.to_string())?;
+    let path = accounts_path(app)?;
+    // The file holds access tokens
+    crate::paths::write_private(&path, json.as_bytes()).map_err(|e| format!("Impossible d'enregistrer les comptes de lecture: {}", e))
+}
+
+fn state_path(app: &AppHandle, service: &str, dir: &Path) -> Result<PathBuf, String> {
+    let mut hasher = Sha256::new();
+    hasher.update(dir.to_string_lossy().as_bytes());
+    let key: String = hasher.finalize().iter().take(16).map(|b| format!("{:02x}", b)).collect();
+    Ok(paths::app_data_subdir(app, "read-later")?.join(format!("{}-{}.json", service, key)))
+}
+
+fn load_state(path: &Path) -> SyncState {
+    std::fs::read_to_string(path).ok().and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default()
+}
+
+fn save_state(path: &Path, state: &SyncState) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("Impossible d'enregistrer l'état de la synchronisation: {}", e))
+}
+
+/// Waits out rate limits, which both services apply to their export calls.
+fn call(request: impl Fn() -> Result<ureq::Response, Box<ureq::Error>>) -> Result<ureq::Response, String> {
+    let mut attempt = 0;
+    loop {
+        match request().map_err(|e| *e) {
+            Err(ureq::Error::Status(429, response)) if attempt < MAX_RETRIES => {
+                attempt += 1;
+                let seconds = response.header("Retry-After").and_then(|s| s.trim().parse::<u64>().ok()).unwrap_or(10);
+                std::thread::sleep(Duration::from_secs(seconds).min(MAX_RETRY_WAIT));
+            }
+            response => return response.map_err(http::error_message),
+        }
+    }
+}
+
+fn names(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.get("name").and_then(Value::as_str).or_else(|| t.as_str()))
+        .map(str::to_string)
+        .collect()
+}
+
+fn text(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::trim).filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// Books, articles and tweets with highlights changed since `since`.
+fn readwise_articles(account: &ReadwiseAccount, since: Option<&str>) -> Result<Vec<Article>, String> {
+    let agent = http::agent();
+    let auth = format!("Token {}", account.token.trim());
+    let mut articles = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page: Value = call(|| {
+            let mut request = agent.get(&format!("{}/export/", READWISE_API)).set("Authorization", &auth);
+            if let Some(since) = since {
+                request = request.query("updatedAfter", since);
+            }
+            if let Some(cursor) = &cursor {
+                request = request.query("pageCursor", cursor);
+            }
+            request.call().map_err(Box::new)
+        })?
+        .into_json()
+        .map_err(|e| format!("Réponse inattendue de Readwise: {}", e))?;
+        for book in page.get("results").and_then(Value::as_array).into_iter().flatten() {
+            let highlights = book
+                .get("highlights")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter(|h| !h.get("is_deleted").and_then(Value::as_bool).unwrap_or(false))
+                .filter_map(|h| {
+                    Some(Highlight {
+                        id: h.get("id")?.to_string(),
+                        text: text(h, "text")?,
+                        note: text(h, "note"),
+                        url: text(h, "url").or_else(|| text(h, "readwise_url")),
+                    })
+                })
+                .collect();
+            articles.push(Article {
+                id: book.get("user_book_id").map(Value::to_string).unwrap_or_default(),
+                title: text(book, "readable_title").or_else(|| text(book, "title")).unwrap_or_default(),
+                url: text(book, "source_url").or_else(|| text(book, "unique_url")),
+                author: text(book, "author"),
+                saved: None,
+                summary: text(book, "summary").or_else(|| text(book, "document_note")),
+                tags: names(book.get("book_tags").unwrap_or(&Value::Null)),
+                highlights,
+            });
+        }
+        cursor = page.get("nextPageCursor").and_then(|c| if c.is_null() { None } else { Some(c.to_string().trim_matches('"').to_string()) });
+        if cursor.is_none() {
+            return Ok(articles);
+        }
+    }
+}
+
+fn oauth_encode(value: &str) -> String {
+    utf8_percent_encode(value, OAUTH_ENCODE).to_string()
+}
+
+/// Signs an Instapaper call with OAuth 1.0a (HMAC-SHA1) and posts its form.
+fn instapaper_post(
+    consumer: (&str, &str),
+    token: Option<(&str, &str)>,
+    path: &str,
+    form: &[(&str, &str)],
+) -> Result<ureq::Response, String> {
+    let url = format!("{}/{}", INSTAPAPER_API, path);
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+    let nonce = format!("{:x}", Sha256::digest(format!("{}{}", now.as_nanos(), path).as_bytes()))[..32].to_string();
+    let timestamp = now.as_secs().to_string();
+    let mut oauth: Vec<(&str, &str)> = vec![
+        ("oauth_consumer_key", consumer.0),
+        ("oauth_nonce", &nonce),
+        ("oauth_signature_method", "HMAC-SHA1"),
+        ("oauth_timestamp", &timestamp),
+        ("oauth_version", "1.0"),
+    ];
+    if let Some((token, _)) = token {
+        oauth.push(("oauth_token", token));
+    }
+    let mut signed: Vec<(String, String)> = oauth.iter().chain(form).map(|(k, v)| (oauth_encode(k), oauth_encode(v))).collect();
+    signed.sort();
+    let parameters: Vec<String> = signed.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    let base = format!("POST&{}&{}", oauth_encode(&url), oauth_encode(&parameters.join("&")));
+    let key = format!("{}&{}", oauth_encode(consumer.1), oauth_encode(token.map(|t| t.1).unwrap_or_default()));
+    let mut mac = Hmac::<Sha1>::new_from_slice(key.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(base.as_bytes());
+    let signature = base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes());
+
+    let header: Vec<String> = oauth
+        .iter()
+        .map(|(k, v)| (*k, *v))
+        .chain([("oauth_signature", signature.as_str())])
+        .map(|(k, v)| format!("{}=\"{}\"", k, oauth_encode(v)))
+        .collect();
+    let agent = http::agent();
+    call(|| agent.post(&url).set("Authorization", &format!("OAuth {}", header.join(", "))).send_form(form).map_err(Box::new))
+}
+
+/// Bookmarks of the usual folders with their highlights, which Instapaper
+/// lists along with them.
+fn instapaper_articles(account: &InstapaperAccount) -> Result<Vec<Article>, String> {
+    let consumer = (account.consumer_key.as_str(), account.consumer_secret.as_str());
+    let token = Some((account.token.as_str(), account.token_secret.as_str()));
+    let mut articles: Vec<Article> = Vec::new();
+    let mut highlights: HashMap<String, Vec<Highlight>> = HashMap::new();
+    for folder in INSTAPAPER_FOLDERS {
+        let list: Value = instapaper_post(consumer, token, "1.1/bookmarks/list", &[("limit", INSTAPAPER_LIMIT), ("folder_id", folder)])?
+            .into_json()
+            .map_err(|e| format!("Réponse inattendue d'Instapaper: {}", e))?;
+        for bookmark in list.get("bookmarks").and_then(Value::as_array).into_iter().flatten() {
+            let Some(id) = bookmark.get("bookmark_id").map(Value::to_string) else { continue };
+            if articles.iter().any(|a| a.id == id) {
+                continue;
+            }
+            let saved = bookmark.get("time").and_then(Value::as_i64).and_then(|t| chrono::DateTime::from_timestamp(t, 0));
+            articles.push(Article {
+                id,
+                title: text(bookmark, "title").unwrap_or_default(),
+                url: text(bookmark, "url"),
+                author: None,
+                saved: saved.map(|t| t.format("%Y-%m-%d").to_string()),
+                summary: text(bookmark, "description"),
+                tags: names(bookmark.get("tags").unwrap_or(&Value::Null)),
+                highlights: Vec::new(),
+            });
+        }
+        for highlight in list.get("highlights").and_then(Value::as_array).into_iter().flatten() {
+            let (Some(id), Some(bookmark), Some(quote)) =
+                (highlight.get("highlight_id"), highlight.get("bookmark_id"), text(highlight, "text"))
+            else {
+                continue;
+            };
+            let entry = highlights.entry(bookmark.to_string()).or_default();
+            let id = id.to_string();
+            if !entry.iter().any(|h| h.id == id) {
+                entry.push(Highlight { id, text: quote, note: text(highlight, "note"), url: None });
+            }
+        }
+    }
+    for article in &mut articles {
+        article.highlights = highlights.remove(&article.id).unwrap_or_default();
+    }
+    Ok(articles)
+}
+
+/// Splits one CSV line per record, honoring quoted fields.
+fn csv_records(csv: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            '\r' if !quoted => {}
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+fn html_attribute(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let end = tag[start..].find('"')? + start;
+    Some(tag[start..end].replace("&amp;", "&").replace("&quot;", "\"")).filter(|v| !v.is_empty())
+}
+
+/// Articles of a Pocket export: the CSV of the last exports, or the HTML
+/// list of older ones. Pocket's API closed in 2025 along with the service.
+fn pocket_articles(export: &str) -> Vec<Article> {
+    let saved = |time: &str| time.trim().parse::<i64>().ok().and_then(|t| chrono::DateTime::from_timestamp(t, 0)).map(|t| t.format("%Y-%m-%d").to_string());
+    let mut articles = Vec::new();
+    if export.trim_start().starts_with('<') {
+        let mut rest = export;
+        while let Some(start) = rest.find("<a ") {
+            let Some(end) = rest[start..].find('>').map(|e| start + e) else { break };
+            let tag = &rest[start..end];
+            let close = rest[end..].find("</a>").map(|c| end + c).unwrap_or(end + 1);
+            let title = rest.get(end + 1..close).unwrap_or_default().trim().replace("&amp;", "&");
+            if let Some(url) = html_attribute(tag, "href") {
+                articles.push(Article {
+                    id: url.clone(),
+                    title: if title.is_empty() { url.clone() } else { title },
+                    saved: html_attribute(tag, "time_added").as_deref().and_then(saved),
+                    tags: html_attribute(tag, "tags").map(|t| t.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()).unwrap_or_default(),
+                    url: Some(url),
+                    ..Default::default()
+                });
+            }
+            rest = &rest[close..];
+        }
+        return articles;
+    }
+    let records = csv_records(export);
+    let Some(header) = records.first() else { return articles };
+    let column = |name: &str| header.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+    let (Some(url_col), title_col, time_col, tags_col) = (column("url"), column("title"), column("time_added"), column("tags")) else {
+        return articles;
+    };
+    for record in &records[1..] {
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c)).map(|s| s.trim()).filter(|s| !s.is_empty());
+        let Some(url) = field(Some(url_col)) else { continue };
+        articles.push(Article {
+            id: url.to_string(),
+            title: field(title_col).unwrap_or(url).to_string(),
+            saved: field(time_col).and_then(saved),
+            tags: field(tags_col).map(|t| t.split('|').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()).unwrap_or_default(),
+            url: Some(url.to_string()),
+            ..Default::default()
+        });
+    }
+    articles
+}
+
+fn note_name(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .map(|c| if c.is_control() || "<>:\"/\\|?*".contains(c) { ' ' } else { c })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let name: String = name.chars().take(MAX_NAME_CHARS).collect();
+    let name = name.trim_matches(['.', ' ']);
+    if name.is_empty() {
+        "Article".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Link to the passage in the page, with a text fragment browsers scroll
+/// to.
+fn passage_link(url: &str, quote: &str) -> String {
+    let words: Vec<&str> = quote.split_whitespace().collect();
+    let encode = |words: &[&str]| utf8_percent_encode(&words.join(" "), FRAGMENT_ENCODE).to_string();
+    let fragment = if words.len() <= FRAGMENT_WORDS * 2 {
+        encode(&words)
+    } else {
+        format!("{},{}", encode(&words[..FRAGMENT_WORDS]), encode(&words[words.len() - FRAGMENT_WORDS..]))
+    };
+    let base = url.split('#').next().unwrap_or(url);
+    format!("{}#:~:text={}", base, fragment)
+}
+
+fn render_highlight(highlight: &Highlight, article_url: Option<&str>) -> String {
+    let mut quote: Vec<String> = highlight.text.lines().map(|l| format!("> {}", l).trim_end().to_string()).collect();
+    let link = highlight.url.clone().or_else(|| article_url.map(|url| passage_link(url, &highlight.text)));
+    if let Some(link) = link {
+        quote.push(">".to_string());
+        quote.push(format!("> — [Source]({})", link));
+    }
+    let mut block = quote.join("\n");
+    if let Some(note) = &highlight.note {
+        block.push_str(&format!("\n\n{}", note));
+    }
+    block
+}
+
+fn render_note(article: &Article, service: &str) -> String {
+    let mut md = String::from("---\n");
+    md.push_str(&format!("title: {}\n", frontmatter::quote(&article.title)));
+    for (key, value) in [("source", &article.url), ("author", &article.author), ("saved", &article.saved)] {
+        if let Some(value) = value {
+            md.push_str(&format!("{}: {}\n", key, frontmatter::quote(value)));
+        }
+    }
+    md.push_str(&format!("imported_from: {}\n", service));
+    if !article.tags.is_empty() {
+        md.push_str("tags:\n");
+        for tag in &article.tags {
+            md.push_str(&format!("  - {}\n", frontmatter::quote(tag)));
+        }
+    }
+    md.push_str("---\n\n");
+    md.push_str(&format!("# {}\n\n", article.title));
+    if let Some(url) = &article.url {
+        md.push_str(&format!("<{}>\n\n", url));
+    }
+    if let Some(summary) = &article.summary {
+        md.push_str(&format!("{}\n\n", summary));
+    }
+    if !article.highlights.is_empty() {
+        md.push_str("## Passages surlignés\n\n");
+        let blocks: Vec<String> = article.highlights.iter().map(|h| render_highlight(h, article.url.as_deref())).collect();
+        md.push_str(&blocks.join("\n\n"));
+        md.push('\n');
+    }
+    md
+}
+
+/// Writes a note per new article and appends new highlights to the notes of
+/// articles already imported.
+fn import(dir: &Path, service: &str, articles: Vec<Article>, state: &mut SyncState) -> Result<ReadLaterImport, String> {
+    std::fs::create_dir_all(dir).map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+    let mut report = ReadLaterImport { created: Vec::new(), updated: Vec::new(), highlights: 0 };
+    for mut article in articles {
+        if article.title.trim().is_empty() {
+            article.title = article.url.clone().unwrap_or_else(|| "Article".to_string());
+        }
+        match state.items.get_mut(&article.id) {
+            None => {
+                let path = assets::unique_path(dir, &format!("{}.md", note_name(&article.title)));
+                std::fs::write(&path, render_note(&article, service)).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))?;
+                report.highlights += article.highlights.len();
+                report.created.push(path.to_string_lossy().to_string());
+                let note = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                state.items.insert(article.id.clone(), ItemState { note, highlights: article.highlights.iter().map(|h| h.id.clone()).collect() });
+            }
+            Some(item) => {
+                let new: Vec<&Highlight> = article.highlights.iter().filter(|h| !item.highlights.contains(&h.id)).collect();
+                let path = dir.join(&item.note);
+                // A note deleted since stays deleted
+                if new.is_empty() || !path.is_file() {
+                    continue;
+                }
+                let mut md = std::fs::read_to_string(&path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+                if !md.contains("## Passages surlignés") {
+                    md = format!("{}\n\n## Passages surlignés", md.trim_end());
+                }
+                for highlight in &new {
+                    md = format!("{}\n\n{}", md.trim_end(), render_highlight(highlight, article.url.as_deref()));
+                    item.highlights.push(highlight.id.clone());
+                }
+                md.push('\n');
+                std::fs::write(&path, md).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))?;
+                report.highlights += new.len();
+                report.updated.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Services with saved credentials.
+#[tauri::command]
+pub fn list_read_later_accounts(app: AppHandle) -> Vec<String> {
+    let accounts = load(&app);
+    [("readwise", accounts.readwise.is_some()), ("instapaper", accounts.instapaper.is_some())]
+        .into_iter()
+        .filter(|(_, set)| *set)
+        .map(|(service, _)| service.to_string())
+        .collect()
+}
+
+/// Saves a Readwise access token after checking it.
+#[tauri::command]
+pub async fn set_readwise_token(app: AppHandle, token: String) -> Result<(), String> {
+    let token = token.trim().to_string();
+    let checked = token.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        http::agent()
+            .get(&format!("{}/auth/", READWISE_API))
+            .set("Authorization", &format!("Token {}", checked))
+            .call()
+            .map_err(|e| match e {
+                ureq::Error::Status(401, _) => "Jeton Readwise refusé".to_string(),
+                e => http::error_message(e),
+            })
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la connexion à Readwise: {}", e))??;
+    update(&app, |accounts| accounts.readwise = Some(ReadwiseAccount { token }))
+}
+
+/// Signs in to Instapaper with xAuth. The consumer key and secret are those
+/// Instapaper grants to an API application.
+#[tauri::command]
+pub async fn connect_instapaper(
+    app: AppHandle,
+    consumer_key: String,
+    consumer_secret: String,
+    username: String,
+    password: String,
+) -> Result<(), String> {
+    let account = tauri::async_runtime::spawn_blocking(move || {
+        let response = instapaper_post(
+            (consumer_key.trim(), consumer_secret.trim()),
+            None,
+            "1/oauth/access_token",
+            &[("x_auth_username", username.trim()), ("x_auth_password", &password), ("x_auth_mode", "client_auth")],
+        )
+        .map_err(|e| format!("Connexion à Instapaper impossible: {}", e))?
+        .into_string()
+        .map_err(|e| format!("Réponse inattendue d'Instapaper: {}", e))?;
+        let field = |name: &str| {
+            response.split('&').find_map(|pair| pair.strip_prefix(&format!("{}=", name))).map(str::to_string).ok_or("Réponse inattendue d'Instapaper")
+        };
+        Ok::<_, String>(InstapaperAccount {
+            token: field("oauth_token")?,
+            token_secret: field("oauth_token_secret")?,
+            consumer_key: consumer_key.trim().to_string(),
+            consumer_secret: consumer_secret.trim().to_string(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la connexion à Instapaper: {}", e))??;
+    update(&app, |accounts| accounts.instapaper = Some(account))
+}
+
+#[tauri::command]
+pub fn remove_read_later_account(app: AppHandle, service: &str) -> Result<(), String> {
+    update(&app, |accounts| match service {
+        "readwise" => accounts.readwise = None,
+        "instapaper" => accounts.instapaper = None,
+        _ => {}
+    })
+}
+
+/// Imports the saved articles and highlights of `service` ("readwise" or
+/// "instapaper") into `target_dir`, one note per article. Only what the last
+/// sync didn't bring is added, unless `full` starts over.
+#[tauri::command]
+pub async fn import_read_later(app: AppHandle, service: String, target_dir: String, full: Option<bool>) -> Result<ReadLaterImport, String> {
+    let accounts = load(&app);
+    let dir = scopes::check(&app, &target_dir)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let state_file = state_path(&app, &service, &dir)?;
+        let mut state = if full.unwrap_or(false) { SyncState::default() } else { load_state(&state_file) };
+        let started = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let articles = match service.as_str() {
+            "readwise" => {
+                let account = accounts.readwise.ok_or("Aucun compte Readwise n'est configuré")?;
+                readwise_articles(&account, state.last_sync.as_deref())?
+            }
+            "instapaper" => instapaper_articles(&accounts.instapaper.ok_or("Aucun compte Instapaper n'est configuré")?)?,
+            other => return Err(format!("Service inconnu: {}", other)),
+        };
+        let report = import(&dir, &service, articles, &mut state)?;
+        state.last_sync = Some(started);
+        save_state(&state_file, &state)?;
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de l'import: {}", e))?
+}
+
+/// Imports a Pocket export file into `target_dir`; articles already imported
+/// from an earlier export are skipped.
+#[tauri::command]
+pub async fn import_pocket_export(app: AppHandle, file: String, target_dir: String) -> Result<ReadLaterImport, String> {
+    let file = scopes::check(&app, &file)?;
+    let dir = scopes::check(&app, &target_dir)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let export = std::fs::read_to_string(&file).map_err(|e| format!("Impossible de lire {}: {}", file.display(), e))?;
+        let articles = pocket_articles(&export);
+        if articles.is_empty() {
+            return Err("Aucun article trouvé dans l'export Pocket".to_string());
+        }
+        let state_file = state_path(&app, "pocket", &dir)?;
+        let mut state = load_state(&state_file);
+        let report = import(&dir, "pocket", articles, &mut state)?;
+        save_state(&state_file, &state)?;
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de l'import: {}", e))?
+}
@@ -0,0 +1,243 @@
+use serde::Serialize;
+
+use crate::markdown::{self, PlainText};
+
+const DEFAULT_LONG_SENTENCE_WORDS: usize = 25;
+
+// Forms of "to be" / "être" that introduce a passive construction
+const PASSIVE_AUXILIARIES: &[&str] = &[
+    "am", "is", "are", "was", "were", "be", "been", "being",
+    "est", "sont", "était", "étaient", "été", "sera", "seront", "serait", "seraient", "soit", "soient", "fut", "furent",
+];
+
+const IRREGULAR_PARTICIPLES: &[&str] = &[
+    "built", "done", "found", "given", "held", "known", "made", "kept", "left", "lost", "paid", "put",
+    "read", "said", "seen", "sent", "set", "shown", "sold", "taken", "taught", "told", "thought", "written",
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SentenceSpan {
+    /// UTF-16 offset in the markdown source.
+    pub offset: usize,
+    /// UTF-16 length in the markdown source.
+    pub length: usize,
+    pub line: usize,
+    pub word_count: usize,
+    pub text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadabilityReport {
+    pub word_count: usize,
+    pub sentence_count: usize,
+    pub syllable_count: usize,
+    pub complex_word_count: usize,
+    pub average_sentence_length: f64,
+    pub flesch_reading_ease: f64,
+    pub flesch_kincaid_grade: f64,
+    pub gunning_fog: f64,
+    pub passive_sentences: Vec<SentenceSpan>,
+    pub long_sentences: Vec<SentenceSpan>,
+}
+
+fn words(sentence: &str) -> Vec<&str> {
+    sentence
+        .split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '’' || c == '-'))
+        .map(|w| w.trim_matches(|c: char| c == '\'' || c == '’' || c == '-'))
+        .filter(|w| w.chars().any(char::is_alphabetic))
+        .collect()
+}
+
+/// Vowel-group heuristic; good enough for English and French prose.
+fn syllables(word: &str) -> usize {
+    let lower = word.to_lowercase();
+    let is_vowel = |c: char| "aeiouyàâäéèêëîïôöùûüÿæœ".contains(c);
+    let mut count = 0;
+    let mut prev_vowel = false;
+    for c in lower.chars() {
+        let vowel = is_vowel(c);
+        if vowel && !prev_vowel {
+            count += 1;
+        }
+        prev_vowel = vowel;
+    }
+    // Silent trailing "e" ("make", "table")
+    if count > 1 && lower.ends_with('e') && !lower.ends_with("le") {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+fn is_participle(word: &str) -> bool {
+    let lower = word.to_lowercase();
+    IRREGULAR_PARTICIPLES.contains(&lower.as_str())
+        || (lower.len() > 3 && lower.ends_with("ed"))
+        || (lower.chars().count() > 3 && ["é", "ée", "és", "ées"].iter().any(|s| lower.ends_with(s)))
+}
+
+fn is_passive(words: &[&str]) -> bool {
+    (0..words.len()).any(|i| {
+        if !PASSIVE_AUXILIARIES.contains(&words[i].to_lowercase().as_str()) {
+            return false;
+        }
+        // Allow one adverb between the auxiliary and the participle
+        match (words.get(i + 1), words.get(i + 2)) {
+            (Some(w), _) if is_participle(w) => true,
+            (Some(adverb), Some(w)) => adverb.to_lowercase().ends_with("ly") && is_participle(w),
+            _ => false,
+        }
+    })
+}
+
+/// Splits plain text into sentences, as byte ranges. Sentences end at
+/// terminal punctuation or at paragraph breaks.
+fn sentence_ranges(text: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start: Option<usize> = None;
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        if start.is_none() {
+            if !c.is_whitespace() {
+                start = Some(pos);
+            }
+            i += 1;
+            continue;
+        }
+
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+        let paragraph_break = c == '\n'
+            && chars[i + 1..].iter().map(|&(_, c)| c).find(|&c| c == '\n' || !c.is_whitespace()) == Some('\n');
+
+        if matches!(c, '.' | '!' | '?' | '…') && next.is_none_or(char::is_whitespace) {
+            let end = pos + c.len_utf8();
+            ranges.push((start.take().unwrap_or(pos), end));
+        } else if paragraph_break {
+            ranges.push((start.take().unwrap_or(pos), pos));
+        }
+        i += 1;
+    }
+    if let Some(s) = start {
+        let end = text.trim_end().len();
+        if end > s {
+            ranges.push((s, end));
+        }
+    }
+    ranges
+}
+
+fn span(markdown: &str, plain: &PlainText, start: usize, end: usize, word_count: usize) -> SentenceSpan {
+    let (src_start, src_end) = plain.source_range(start, end);
+    let offset = markdown::utf16_offset(markdown, src_start);
+    SentenceSpan {
+        offset,
+        length: markdown::utf16_offset(markdown, src_end) - offset,
+        line: markdown::line_at(markdown, src_start),
+        word_count,
+        text: plain.text[start..end].split_whitespace().collect::<Vec<_>>().join(" "),
+    }
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// Computes readability scores for the prose of a markdown document, ignoring
+/// frontmatter and code.
+#[tauri::command]
+pub fn analyze_readability(markdown: &str, long_sentence_words: Option<usize>) -> ReadabilityReport {
+    let plain = markdown::to_plain_text(markdown);
+    let long_threshold = long_sentence_words.unwrap_or(DEFAULT_LONG_SENTENCE_WORDS);
+
+    let mut word_count = 0;
+    let mut sentence_count = 0;
+    let mut syllable_count = 0;
+    let mut complex_word_count = 0;
+    let mut passive_sentences = Vec::new();
+    let mut long_sentences = Vec::new();
+
+    for (start, end) in sentence_ranges(&plain.text) {
+        let sentence_words = words(&plain.text[start..end]);
+        if sentence_words.is_empty() {
+            continue;
+        }
+        sentence_count += 1;
+        word_count += sentence_words.len();
+        for w in &sentence_words {
+            let s = syllables(w);
+            syllable_count += s;
+            if s >= 3 {
+                complex_word_count += 1;
+            }
+        }
+
+        if is_passive(&sentence_words) {
+            passive_sentences.push(span(markdown, &plain, start, end, sentence_words.len()));
+        }
+        if sentence_words.len() > long_threshold {
+            long_sentences.push(span(markdown, &plain, start, end, sentence_words.len()));
+        }
+    }
+
+    let (words_per_sentence, syllables_per_word, complex_ratio) = if word_count == 0 {
+        (0.0, 0.0, 0.0)
+    } else {
+        (
+            word_count as f64 / sentence_count as f64,
+            syllable_count as f64 / word_count as f64,
+            complex_word_count as f64 / word_count as f64,
+        )
+    };
+
+    let (flesch_reading_ease, flesch_kincaid_grade, gunning_fog) = if word_count == 0 {
+        (0.0, 0.0, 0.0)
+    } else {
+        (
+            206.835 - 1.015 * words_per_sentence - 84.6 * syllables_per_word,
+            0.39 * words_per_sentence + 11.8 * syllables_per_word - 15.59,
+            0.4 * (words_per_sentence + 100.0 * complex_ratio),
+        )
+    };
+
+    ReadabilityReport {
+        word_count,
+        sentence_count,
+        syllable_count,
+        complex_word_count,
+        average_sentence_length: round(words_per_sentence),
+        flesch_reading_ease: round(flesch_reading_ease),
+        flesch_kincaid_grade: round(flesch_kincaid_grade),
+        gunning_fog: round(gunning_fog),
+        passive_sentences,
+        long_sentences,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_sentence_offsets_are_utf16() {
+        let md = "# Été 😀\n\nCourte. Une phrase beaucoup trop longue pour le seuil choisi ici.\n";
+        let report = analyze_readability(md, Some(5));
+        assert_eq!(report.sentence_count, 3);
+        assert_eq!(report.long_sentences.len(), 1);
+        let span = &report.long_sentences[0];
+        let start = markdown::byte_offset_from_utf16(md, span.offset);
+        let end = markdown::byte_offset_from_utf16(md, span.offset + span.length);
+        assert_eq!(&md[start..end], "Une phrase beaucoup trop longue pour le seuil choisi ici.");
+        assert_eq!(span.line, 3);
+    }
+
+    #[test]
+    fn code_is_not_counted() {
+        let report = analyze_readability("Deux mots.\n\n```\nbeaucoup de code ici\n```\n", None);
+        assert_eq!(report.word_count, 2);
+        assert_eq!(report.sentence_count, 1);
+    }
+}
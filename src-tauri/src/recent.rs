@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+const RECENT_FILE: &str = "recent-files.json";
+/// Unpinned entries kept; pinned ones are never dropped.
+const MAX_RECENT: usize = 20;
+
+// Serializes read-modify-write cycles on the recent files list
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentFile {
+    pub path: String,
+    /// Cursor position (UTF-16 offset) when the file was last left.
+    pub position: Option<usize>,
+    /// RFC 3339 timestamp of the last opening.
+    pub opened_at: String,
+    pub pinned: bool,
+}
+
+fn recent_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::paths::app_config_dir(app)?.join(RECENT_FILE))
+}
+
+fn load(app: &AppHandle) -> Result<Vec<RecentFile>, String> {
+    let path = recent_path(app)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Impossible de lire les fichiers récents: {}", e))?;
+    // A corrupt list isn't worth failing over
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+fn save(app: &AppHandle, files: &[RecentFile]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(files).map_err(|e| e.to_string())?;
+    std::fs::write(recent_path(app)?, json)
+        .map_err(|e| format!("Impossible d'enregistrer les fichiers récents: {}", e))
+}
+
+/// Pinned files first, then most recently opened.
+fn sort(files: &mut [RecentFile]) {
+    files.sort_by(|a, b| b.pinned.cmp(&a.pinned).then_with(|| b.opened_at.cmp(&a.opened_at)));
+}
+
+fn update<F>(app: &AppHandle, change: F) -> Result<Vec<RecentFile>, String>
+where
+    F: FnOnce(&mut Vec<RecentFile>),
+{
+    let _guard = LOCK.lock().unwrap();
+    let mut files = load(app)?;
+    change(&mut files);
+    sort(&mut files);
+    let mut unpinned = 0;
+    files.retain(|f| {
+        unpinned += usize::from(!f.pinned);
+        f.pinned || unpinned <= MAX_RECENT
+    });
+    save(app, &files)?;
+    Ok(files)
+}
+
+/// Records that a file was opened, or updates its last cursor position.
+#[tauri::command]
+pub fn add_recent_file(app: AppHandle, path: String, position: Option<usize>) -> Result<Vec<RecentFile>, String> {
+    // Reopening from the list must not need the dialog again
+    crate::scopes::grant(&app, &crate::scopes::check(&app, &path)?)?;
+    update(&app, |files| {
+        let pinned = files.iter().any(|f| f.path == path && f.pinned);
+        let previous = files.iter().find(|f| f.path == path).and_then(|f| f.position);
+        files.retain(|f| f.path != path);
+        files.push(RecentFile {
+            path,
+            position: position.or(previous),
+            opened_at: chrono::Local::now().to_rfc3339(),
+            pinned,
+        });
+    })
+}
+
+/// Returns the recent files, pruning the ones that no longer exist.
+#[tauri::command]
+pub fn get_recent_files(app: AppHandle) -> Result<Vec<RecentFile>, String> {
+    update(&app, |files| files.retain(|f| Path::new(&f.path).is_file()))
+}
+
+#[tauri::command]
+pub fn pin_recent_file(app: AppHandle, path: String, pinned: bool) -> Result<Vec<RecentFile>, String> {
+    update(&app, |files| {
+        if let Some(file) = files.iter_mut().find(|f| f.path == path) {
+            file.pinned = pinned;
+        }
+    })
+}
+
+#[tauri::command]
+pub fn remove_recent_file(app: AppHandle, path: String) -> Result<Vec<RecentFile>, String> {
+    update(&app, |files| files.retain(|f| f.path != path))
+}
@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+const PREVIEW_CHARS: usize = 120;
+
+/// Unsaved content of an open document.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    /// Frontend identifier of the document (tab id).
+    pub id: String,
+    /// File the document belongs to, None for a new document.
+    pub document_path: Option<String>,
+    pub content: String,
+    /// RFC 3339 timestamp of the last edit pushed.
+    pub updated_at: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverableDocument {
+    pub id: String,
+    pub document_path: Option<String>,
+    pub updated_at: String,
+    /// Start of the content, to help tell snapshots apart.
+    pub preview: String,
+    pub size: usize,
+}
+
+/// Content pushed by the frontend since the last snapshot was written.
+#[derive(Default)]
+pub struct AutosaveQueue {
+    pending: Mutex<HashMap<String, Snapshot>>,
+}
+
+fn recovery_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::paths::app_data_subdir(app, "recovery")
+}
+
+/// Tab ids come from the frontend: hash them into safe file names.
+fn snapshot_path(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    let hex: String = Sha256::digest(id.as_bytes()).iter().take(16).map(|b| format!("{:02x}", b)).collect();
+    Ok(recovery_dir(app)?.join(format!("{}.json", hex)))
+}
+
+fn write_snapshot(app: &AppHandle, snapshot: &Snapshot) -> Result<(), String> {
+    let path = snapshot_path(app, &snapshot.id)?;
+    let json = serde_json::to_string(snapshot)
+        .map_err(|e| format!("Erreur de sérialisation de la sauvegarde: {}", e))?;
+    // Write then rename so a crash mid-write never leaves a truncated snapshot
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json)
+        .map_err(|e| format!("Impossible d'écrire la sauvegarde automatique: {}", e))?;
+    std::fs::rename(&tmp, &path)
+        .map_err(|e| format!("Impossible d'écrire la sauvegarde automatique: {}", e))
+}
+
+fn read_snapshots(app: &AppHandle) -> Result<Vec<(PathBuf, Snapshot)>, String> {
+    let dir = recovery_dir(app)?;
+    let entries = std::fs::read_dir(&dir)
+        .map_err(|e| format!("Impossible de lire le dossier de récupération: {}", e))?;
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .filter_map(|path| {
+            let json = std::fs::read_to_string(&path).ok()?;
+            let snapshot = serde_json::from_str(&json).ok()?;
+            Some((path, snapshot))
+        })
+        .collect())
+}
+
+/// Starts the thread that periodically writes pending content to disk.
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SNAPSHOT_INTERVAL);
+        // Holding the lock while writing keeps a concurrent discard from
+        // being undone by a snapshot taken just before it
+        let queue = app.state::<AutosaveQueue>();
+        let mut pending = queue.pending.lock().unwrap();
+        for (_, snapshot) in pending.drain() {
+            let _ = write_snapshot(&app, &snapshot);
+        }
+    });
+}
+
+/// Queues the current content of a modified document for the next snapshot.
+#[tauri::command]
+pub fn push_autosave(queue: State<'_, AutosaveQueue>, id: String, document_path: Option<String>, content: String) {
+    let snapshot = Snapshot {
+        id: id.clone(),
+        document_path,
+        content,
+        updated_at: chrono::Local::now().to_rfc3339(),
+    };
+    queue.pending.lock().unwrap().insert(id, snapshot);
+}
+
+/// Drops a document's snapshot, once it has been saved or deliberately closed
+/// without saving.
+#[tauri::command]
+pub fn discard_autosave(app: AppHandle, queue: State<'_, AutosaveQueue>, id: &str) -> Result<(), String> {
+    let mut pending = queue.pending.lock().unwrap();
+    pending.remove(id);
+    let path = snapshot_path(&app, id)?;
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Impossible de supprimer la sauvegarde automatique: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Snapshots left over from a previous run, newest first. Snapshots whose
+/// content matches the file on disk hold nothing to recover and are removed.
+#[tauri::command]
+pub fn list_recoverable_documents(app: AppHandle) -> Result<Vec<RecoverableDocument>, String> {
+    let mut documents = Vec::new();
+    for (path, snapshot) in read_snapshots(&app)? {
+        let saved = snapshot.document_path.as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .is_some_and(|on_disk| on_disk == snapshot.content);
+        if saved {
+            let _ = std::fs::remove_file(&path);
+            continue;
+        }
+        documents.push(RecoverableDocument {
+            preview: snapshot.content.chars().take(PREVIEW_CHARS).collect(),
+            size: snapshot.content.len(),
+            id: snapshot.id,
+            document_path: snapshot.document_path,
+            updated_at: snapshot.updated_at,
+        });
+    }
+    documents.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(documents)
+}
+
+#[tauri::command]
+pub fn get_recovered_document(app: AppHandle, id: &str) -> Result<Snapshot, String> {
+    let json = std::fs::read_to_string(snapshot_path(&app, id)?)
+        .map_err(|_| "Aucune sauvegarde automatique pour ce document".to_string())?;
+    serde_json::from_str(&json)
+        .map_err(|e| format!("Sauvegarde automatique illisible: {}", e))
+}
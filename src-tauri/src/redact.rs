@@ -0,0 +1,218 @@
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::{assets, markdown};
+
+/// JPEG segments holding EXIF, XMP, IPTC and comments.
+const JPEG_METADATA_MARKERS: &[u8] = &[0xE1, 0xED, 0xFE];
+/// PNG chunks holding EXIF, text fields and the last edit time.
+const PNG_METADATA_CHUNKS: &[&[u8; 4]] = &[b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+/// Elements of the document properties that say who made a file, and how
+/// long they worked on it.
+const PROPERTY_ELEMENTS: &[&str] = &[
+    "dc:creator", "cp:lastModifiedBy", "cp:revision", "Company", "Manager", "TotalTime", "Template",
+    "meta:initial-creator", "meta:editing-cycles", "meta:editing-duration", "meta:generator", "w:rsids",
+];
+const PROPERTY_FILES: &[&str] = &["docProps/core.xml", "docProps/app.xml", "meta.xml", "word/settings.xml"];
+
+/// Formats saved as zip packages, whose properties and media are redacted.
+const PACKAGE_FORMATS: &[&str] = &["docx", "pptx", "odt", "epub", "epub2", "epub3"];
+
+/// `bytes` of a JPEG or PNG image without its metadata, or None when the
+/// image has none or is in another format. The pixels are left untouched.
+pub fn strip_image(bytes: &[u8]) -> Option<Vec<u8>> {
+    let stripped = if bytes.starts_with(&[0xFF, 0xD8]) {
+        strip_jpeg(bytes)?
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        strip_png(bytes)?
+    } else {
+        return None;
+    };
+    (stripped.len() != bytes.len()).then_some(stripped)
+}
+
+fn strip_jpeg(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut out = bytes[..2].to_vec();
+    let mut i = 2;
+    while i + 4 <= bytes.len() {
+        if bytes[i] != 0xFF {
+            return None;
+        }
+        let marker = bytes[i + 1];
+        // Start of scan: the compressed image runs to the end
+        if marker == 0xDA {
+            break;
+        }
+        let len = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+        let end = (i + 2 + len).min(bytes.len());
+        if !JPEG_METADATA_MARKERS.contains(&marker) {
+            out.extend_from_slice(&bytes[i..end]);
+        }
+        i = end;
+    }
+    out.extend_from_slice(&bytes[i.min(bytes.len())..]);
+    Some(out)
+}
+
+fn strip_png(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut out = bytes[..8].to_vec();
+    let mut i = 8;
+    while i + 12 <= bytes.len() {
+        let len = u32::from_be_bytes(bytes[i..i + 4].try_into().ok()?) as usize;
+        let end = i.checked_add(len + 12)?.min(bytes.len());
+        if !PNG_METADATA_CHUNKS.iter().any(|chunk| bytes[i + 4..i + 8] == chunk[..]) {
+            out.extend_from_slice(&bytes[i..end]);
+        }
+        i = end;
+    }
+    Some(out)
+}
+
+/// Points links to local images that carry metadata at stripped copies, for
+/// exports that embed them. Relative links are looked up in `resource_dirs`,
+/// as pandoc does.
+pub fn strip_linked_images(app: &AppHandle, md: &str, resource_dirs: &[PathBuf]) -> Result<String, String> {
+    let mut replacements = Vec::new();
+    for link in markdown::links(md) {
+        let url = link.url(md);
+        if !link.is_image || !assets::is_local_link(url) {
+            continue;
+        }
+        let Some(path) = assets::resolve_resource(url, resource_dirs) else { continue };
+        let Some(bytes) = std::fs::read(&path).ok().and_then(|bytes| strip_image(&bytes)) else {
+            continue;
+        };
+        let hex: String = Sha256::digest(&bytes).iter().take(16).map(|b| format!("{:02x}", b)).collect();
+        let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let copy = crate::paths::app_data_subdir(app, "redacted-images")?.join(format!("{}.{}", hex, extension));
+        if !copy.is_file() {
+            std::fs::write(&copy, &bytes).map_err(|e| format!("Impossible d'enregistrer l'image nettoyée: {}", e))?;
+        }
+        replacements.push(assets::replacement(md, &link, assets::link_to(&copy, None)));
+    }
+    Ok(markdown::replace_ranges(md, replacements))
+}
+
+/// `xml` without the elements named `names`, content included.
+fn remove_elements(xml: &str, names: &[&str]) -> String {
+    let mut out = xml.to_string();
+    for name in names {
+        let open = format!("<{}", name);
+        let mut from = 0;
+        while let Some(found) = out[from..].find(&open) {
+            let start = from + found;
+            let after = out[start + open.len()..].chars().next();
+            if !matches!(after, Some('>' | '/' | ' ' | '\t' | '\r' | '\n')) {
+                from = start + open.len();
+                continue;
+            }
+            let Some(tag_end) = out[start..].find('>').map(|e| start + e + 1) else { break };
+            let end = if out[..tag_end].ends_with("/>") {
+                tag_end
+            } else {
+                let close = format!("</{}>", name);
+                match out[tag_end..].find(&close) {
+                    Some(e) => tag_end + e + close.len(),
+                    None => break,
+                }
+            };
+            out.replace_range(start..end, "");
+            from = start;
+        }
+    }
+    out
+}
+
+/// `xml` with revision marks without their author, and without the ids
+/// Word gives each editing session.
+fn anonymize_revisions(xml: &str) -> String {
+    let mut out = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some(at) = rest.find(" w:") {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let name_end = rest.find("=\"").filter(|e| !rest[1..*e].contains([' ', '>', '<']));
+        let Some(name_end) = name_end else {
+            out.push_str(" w:");
+            rest = &rest[3..];
+            continue;
+        };
+        let name = &rest[1..name_end];
+        let value_end = rest[name_end + 2..].find('"').map(|e| name_end + 2 + e + 1).unwrap_or(rest.len());
+        if matches!(name, "w:author" | "w:initials") {
+            out.push_str(&format!(" {}=\"\"", name));
+        } else if !name.starts_with("w:rsid") {
+            out.push_str(&rest[..value_end]);
+        }
+        rest = &rest[value_end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn redact_entry(name: &str, data: Vec<u8>) -> Vec<u8> {
+    if name.ends_with(".xml") && (PROPERTY_FILES.contains(&name) || name.starts_with("word/")) {
+        let Ok(xml) = String::from_utf8(data.clone()) else { return data };
+        let xml = if PROPERTY_FILES.contains(&name) { remove_elements(&xml, PROPERTY_ELEMENTS) } else { xml };
+        let xml = if name.starts_with("word/") { anonymize_revisions(&xml) } else { xml };
+        return xml.into_bytes();
+    }
+    strip_image(&data).unwrap_or(data)
+}
+
+fn redact_package(path: &Path) -> Result<(), String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Archive invalide {}: {}", path.display(), e))?;
+    let partial = path.with_extension("redacted");
+    let out = std::fs::File::create(&partial).map_err(|e| format!("Impossible de créer {}: {}", partial.display(), e))?;
+    let mut zip = ZipWriter::new(out);
+    let error = |e: zip::result::ZipError| format!("Erreur lors de l'écriture de {}: {}", path.display(), e);
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(error)?;
+        if entry.is_dir() {
+            zip.raw_copy_file(entry).map_err(error)?;
+            continue;
+        }
+        let name = entry.name().to_string();
+        // Same order and compression: EPUB and ODT readers expect a stored
+        // `mimetype` entry first
+        let options = SimpleFileOptions::default().compression_method(entry.compression());
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).map_err(|e| format!("Impossible de lire {} dans {}: {}", name, path.display(), e))?;
+        zip.start_file(name.as_str(), options).map_err(error)?;
+        zip.write_all(&redact_entry(&name, data)).map_err(|e| format!("Erreur lors de l'écriture de {}: {}", path.display(), e))?;
+    }
+    zip.finish().map_err(error)?;
+    std::fs::rename(&partial, path).map_err(|e| format!("Impossible de finaliser {}: {}", path.display(), e))
+}
+
+/// Drops the document information (author, creator, producer…) and the XMP
+/// metadata of a PDF.
+fn redact_pdf(path: &Path) -> Result<(), String> {
+    let mut doc = lopdf::Document::load(path).map_err(|e| format!("PDF invalide {}: {}", path.display(), e))?;
+    doc.trailer.remove(b"Info");
+    if let Ok(catalog) = doc.catalog_mut() {
+        catalog.remove(b"Metadata");
+    }
+    doc.prune_objects();
+    doc.save(path).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))?;
+    Ok(())
+}
+
+/// Strips who made an export, and with what, from the file at `path`.
+/// Formats that carry no such metadata are left as they are.
+pub fn redact_file(path: &Path, to_format: &str) -> Result<(), String> {
+    // Beamer slides are PDFs too
+    if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("pdf")) {
+        redact_pdf(path)
+    } else if PACKAGE_FORMATS.contains(&to_format) {
+        redact_package(path)
+    } else {
+        Ok(())
+    }
+}
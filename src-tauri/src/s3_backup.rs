@@ -0,0 +1,482 @@
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::workspace::{self, Workspace};
+use crate::{http, locks, paths, safe_save};
+
+const SETTINGS_FILE: &str = "s3-backup.json";
+/// How often the scheduler checks whether a backup is due.
+const SCHEDULE_CHECK: Duration = Duration::from_secs(10 * 60);
+const SNAPSHOT_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+// Serializes read-modify-write cycles on the settings file
+static LOCK: Mutex<()> = Mutex::new(());
+// One backup or restore at a time
+static RUNNING: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct S3BackupSettings {
+    /// e.g. "https://s3.eu-west-3.amazonaws.com", "https://<account>.r2.cloudflarestorage.com"
+    /// or a MinIO server.
+    pub endpoint: String,
+    /// "auto" for services that ignore it.
+    pub region: String,
+    pub bucket: String,
+    /// Folder of the bucket backups go to.
+    #[serde(default)]
+    pub prefix: String,
+    pub access_key: String,
+    pub secret_key: String,
+    /// Hours between automatic backups of the open workspace; None backs up
+    /// on demand only.
+    #[serde(default)]
+    pub interval_hours: Option<u64>,
+}
+
+/// The settings without the secret, for the frontend.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S3BackupInfo {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub prefix: String,
+    pub access_key: String,
+    pub interval_hours: Option<u64>,
+}
+
+/// Files of the workspace at the time of a backup. Contents are stored once
+/// per hash under `objects/`, so each backup only uploads what changed.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    created_at: String,
+    root: String,
+    files: BTreeMap<String, ManifestFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ManifestFile {
+    hash: String,
+    size: u64,
+}
+
+/// When the open workspaces were last backed up, by workspace folder.
+type Schedule = BTreeMap<String, String>;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S3BackupReport {
+    pub snapshot: String,
+    pub files: usize,
+    /// Files whose content wasn't in the bucket yet.
+    pub uploaded: usize,
+    pub bytes_uploaded: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Snapshot {
+    pub id: String,
+    /// RFC 3339, local time.
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S3RestoreReport {
+    pub restored: usize,
+    /// Files already identical to the backup.
+    pub unchanged: usize,
+    pub errors: Vec<String>,
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_config_dir(app)?.join(SETTINGS_FILE))
+}
+
+fn load_settings(app: &AppHandle) -> Option<S3BackupSettings> {
+    let json = std::fs::read_to_string(settings_path(app).ok()?).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn save_settings(app: &AppHandle, settings: Option<&S3BackupSettings>) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap();
+    let path = settings_path(app)?;
+    let Some(settings) = settings else {
+        let _ = std::fs::remove_file(&path);
+        return Ok(());
+    };
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    // The file holds the secret key
+    crate::paths::write_private(&path, json.as_bytes()).map_err(|e| format!("Impossible d'enregistrer les réglages de sauvegarde: {}", e))
+}
+
+fn schedule_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_data_subdir(app, "s3-backup")?.join("schedule.json"))
+}
+
+fn load_schedule(app: &AppHandle) -> Schedule {
+    schedule_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex(&Sha256::digest(bytes))
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn encode(value: &str) -> String {
+    utf8_percent_encode(value, UNRESERVED).to_string()
+}
+
+/// Folder of a workspace's backups in the bucket: its name, plus a hash of
+/// its path to tell apart folders with the same name.
+fn workspace_prefix(settings: &S3BackupSettings, root: &Path) -> String {
+    let name = root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "workspace".to_string());
+    let hash = sha256_hex(root.to_string_lossy().as_bytes());
+    let prefix = settings.prefix.trim_matches('/');
+    let folder = format!("{}-{}", name, &hash[..8]);
+    if prefix.is_empty() {
+        folder
+    } else {
+        format!("{}/{}", prefix, folder)
+    }
+}
+
+/// Path-style S3 client with AWS Signature Version 4, which every
+/// S3-compatible service accepts.
+struct Client {
+    agent: ureq::Agent,
+    settings: S3BackupSettings,
+    endpoint: String,
+    host: String,
+}
+
+impl Client {
+    fn new(settings: &S3BackupSettings) -> Result<Self, String> {
+        let endpoint = settings.endpoint.trim().trim_end_matches('/').to_string();
+        let (scheme, rest) = endpoint.split_once("://").ok_or_else(|| format!("Adresse S3 invalide: {}", endpoint))?;
+        let host = rest.split('/').next().unwrap_or_default();
+        // ureq leaves default ports out of the Host header
+        let host = match (scheme, host.rsplit_once(':')) {
+            ("https", Some((name, "443"))) | ("http", Some((name, "80"))) => name,
+            _ => host,
+        };
+        if host.is_empty() {
+            return Err(format!("Adresse S3 invalide: {}", endpoint));
+        }
+        Ok(Client { agent: http::agent(), host: host.to_string(), endpoint, settings: settings.clone() })
+    }
+
+    fn request(&self, method: &str, key: &str, query: &[(&str, &str)], payload: &[u8]) -> ureq::Request {
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = sha256_hex(payload);
+
+        let mut uri = format!("/{}", encode(&self.settings.bucket));
+        if !key.is_empty() {
+            uri.push('/');
+            uri.push_str(&key.split('/').map(encode).collect::<Vec<_>>().join("/"));
+        }
+        let mut query: Vec<(String, String)> = query.iter().map(|(k, v)| (encode(k), encode(v))).collect();
+        query.sort();
+        let query = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, uri, query, self.host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.settings.region);
+        let to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, sha256_hex(canonical.as_bytes()));
+        let key = [date.as_str(), &self.settings.region, "s3", "aws4_request"]
+            .iter()
+            .fold(format!("AWS4{}", self.settings.secret_key).into_bytes(), |key, part| hmac(&key, part));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.settings.access_key,
+            scope,
+            signed_headers,
+            hex(&hmac(&key, &to_sign))
+        );
+
+        let url = if query.is_empty() { format!("{}{}", self.endpoint, uri) } else { format!("{}{}?{}", self.endpoint, uri, query) };
+        self.agent
+            .request(method, &url)
+            .set("x-amz-date", &amz_date)
+            .set("x-amz-content-sha256", &payload_hash)
+            .set("Authorization", &authorization)
+    }
+
+    /// Keys under `prefix`, following pagination.
+    fn list(&self, prefix: &str) -> Result<Vec<String>, String> {
+        let mut keys = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if let Some(token) = &token {
+                query.push(("continuation-token", token));
+            }
+            let xml = self
+                .request("GET", "", &query, b"")
+                .call()
+                .map_err(http::error_message)?
+                .into_string()
+                .map_err(|e| format!("Réponse S3 invalide: {}", e))?;
+            let (page, next) = parse_list(&xml)?;
+            keys.extend(page);
+            match next {
+                Some(next) => token = Some(next),
+                None => return Ok(keys),
+            }
+        }
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut self.request("GET", key, &[], b"").call().map_err(http::error_message)?.into_reader(), &mut bytes)
+            .map_err(|e| format!("Erreur pendant le téléchargement de {}: {}", key, e))?;
+        Ok(bytes)
+    }
+
+    fn put(&self, key: &str, bytes: &[u8]) -> Result<(), String> {
+        self.request("PUT", key, &[], bytes).send_bytes(bytes).map_err(http::error_message)?;
+        Ok(())
+    }
+}
+
+/// Keys of a ListObjectsV2 page, and the token of the next one.
+fn parse_list(xml: &str) -> Result<(Vec<String>, Option<String>), String> {
+    let invalid = |e: quick_xml::Error| format!("Réponse S3 invalide: {}", e);
+    let mut reader = Reader::from_str(xml);
+    let mut keys = Vec::new();
+    let (mut element, mut truncated, mut next) = (String::new(), false, None);
+    loop {
+        match reader.read_event().map_err(invalid)? {
+            Event::Start(e) => element = String::from_utf8_lossy(e.local_name().as_ref()).to_string(),
+            Event::Text(text) => {
+                let text = text.unescape().map_err(invalid)?.to_string();
+                match element.as_str() {
+                    "Key" => keys.push(text),
+                    "IsTruncated" => truncated = text == "true",
+                    "NextContinuationToken" => next = Some(text),
+                    _ => {}
+                }
+            }
+            Event::End(_) => element.clear(),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok((keys, next.filter(|_| truncated)))
+}
+
+fn backup(app: &AppHandle, settings: &S3BackupSettings, root: &Path) -> Result<S3BackupReport, String> {
+    let client = Client::new(settings)?;
+    let prefix = workspace_prefix(settings, root);
+    let objects = format!("{}/objects/", prefix);
+    let stored: HashSet<String> = client.list(&objects)?.into_iter().filter_map(|k| k.strip_prefix(&objects).map(str::to_string)).collect();
+
+    let now = chrono::Utc::now();
+    let mut manifest = Manifest { created_at: now.to_rfc3339(), root: root.to_string_lossy().to_string(), files: BTreeMap::new() };
+    let (mut uploaded, mut bytes_uploaded) = (0, 0);
+    let mut sent = HashSet::new();
+    for entry in workspace::walk(root).filter(|e| e.file_type().is_some_and(|t| t.is_file())) {
+        let Ok(relative) = entry.path().strip_prefix(root) else { continue };
+        let bytes = std::fs::read(entry.path()).map_err(|e| format!("Impossible de lire {}: {}", entry.path().display(), e))?;
+        let hash = sha256_hex(&bytes);
+        if !stored.contains(&hash) && sent.insert(hash.clone()) {
+            client.put(&format!("{}{}", objects, hash), &bytes)?;
+            uploaded += 1;
+            bytes_uploaded += bytes.len() as u64;
+        }
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        manifest.files.insert(relative, ManifestFile { hash, size: bytes.len() as u64 });
+    }
+
+    // Written last: a snapshot only exists once all its files are stored
+    let snapshot = now.format(SNAPSHOT_FORMAT).to_string();
+    let json = serde_json::to_vec(&manifest).map_err(|e| e.to_string())?;
+    client.put(&format!("{}/snapshots/{}.json", prefix, snapshot), &json)?;
+
+    let mut schedule = load_schedule(app);
+    schedule.insert(root.to_string_lossy().to_string(), manifest.created_at.clone());
+    let _ = std::fs::write(schedule_path(app)?, serde_json::to_string(&schedule).unwrap_or_default());
+    Ok(S3BackupReport { snapshot, files: manifest.files.len(), uploaded, bytes_uploaded })
+}
+
+/// Starts the thread backing up the open workspace every `interval_hours`.
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(SCHEDULE_CHECK);
+        let Some(settings) = load_settings(&app) else { continue };
+        let (Some(hours), Ok(root)) = (settings.interval_hours, app.state::<Workspace>().root()) else { continue };
+        let last = load_schedule(&app)
+            .get(&root.to_string_lossy().to_string())
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok());
+        let due = last.is_none_or(|last| chrono::Utc::now().signed_duration_since(last) >= chrono::Duration::hours(hours as i64));
+        if let (true, Ok(_guard)) = (due, RUNNING.try_lock()) {
+            let _ = backup(&app, &settings, &root);
+        }
+    });
+}
+
+fn configured(app: &AppHandle) -> Result<S3BackupSettings, String> {
+    load_settings(app).ok_or_else(|| "Aucun stockage S3 n'est configuré".to_string())
+}
+
+/// A manifest path, refused if it would write outside the target folder.
+fn safe_relative(relative: &str) -> Option<&Path> {
+    let path = Path::new(relative);
+    path.components().all(|c| matches!(c, Component::Normal(_))).then_some(path)
+}
+
+#[tauri::command]
+pub fn get_s3_backup_settings(app: AppHandle) -> Option<S3BackupInfo> {
+    load_settings(&app).map(|s| S3BackupInfo {
+        endpoint: s.endpoint,
+        region: s.region,
+        bucket: s.bucket,
+        prefix: s.prefix,
+        access_key: s.access_key,
+        interval_hours: s.interval_hours,
+    })
+}
+
+/// Saves the bucket settings after checking the credentials can list it.
+#[tauri::command]
+pub async fn set_s3_backup_settings(app: AppHandle, settings: S3BackupSettings) -> Result<(), String> {
+    if settings.bucket.trim().is_empty() || settings.access_key.trim().is_empty() {
+        return Err("Le bucket et la clé d'accès sont obligatoires".to_string());
+    }
+    let mut settings = settings;
+    if settings.region.trim().is_empty() {
+        settings.region = "us-east-1".to_string();
+    }
+    let settings = tauri::async_runtime::spawn_blocking(move || {
+        let client = Client::new(&settings)?;
+        client
+            .request("GET", "", &[("list-type", "2"), ("max-keys", "1")], b"")
+            .call()
+            .map_err(|e| format!("Accès au bucket impossible: {}", http::error_message(e)))?;
+        Ok::<_, String>(settings)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la connexion au stockage S3: {}", e))??;
+    save_settings(&app, Some(&settings))
+}
+
+#[tauri::command]
+pub fn remove_s3_backup_settings(app: AppHandle) -> Result<(), String> {
+    save_settings(&app, None)
+}
+
+/// Backs up the open workspace now. Only contents not already in the bucket
+/// are uploaded.
+#[tauri::command]
+pub async fn backup_workspace_to_s3(app: AppHandle) -> Result<S3BackupReport, String> {
+    let root = app.state::<Workspace>().root()?;
+    let settings = configured(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let _guard = RUNNING.try_lock().map_err(|_| "Une sauvegarde est déjà en cours".to_string())?;
+        backup(&app, &settings, &root)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la sauvegarde: {}", e))?
+}
+
+/// Backups of the open workspace in the bucket, newest first.
+#[tauri::command]
+pub async fn list_s3_snapshots(app: AppHandle) -> Result<Vec<S3Snapshot>, String> {
+    let root = app.state::<Workspace>().root()?;
+    let settings = configured(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let snapshots = format!("{}/snapshots/", workspace_prefix(&settings, &root));
+        let mut list: Vec<S3Snapshot> = Client::new(&settings)?
+            .list(&snapshots)?
+            .iter()
+            .filter_map(|key| key.strip_prefix(&snapshots)?.strip_suffix(".json"))
+            .filter_map(|id| {
+                let time = chrono::NaiveDateTime::parse_from_str(id, SNAPSHOT_FORMAT).ok()?;
+                let created_at = time.and_utc().with_timezone(&chrono::Local).to_rfc3339();
+                Some(S3Snapshot { id: id.to_string(), created_at })
+            })
+            .collect();
+        list.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(list)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la lecture des sauvegardes: {}", e))?
+}
+
+/// Restores a backup of the open workspace into `target_dir`, the workspace
+/// itself by default. Files missing from the backup are left alone, and
+/// overwritten ones keep a local backup copy.
+#[tauri::command]
+pub async fn restore_s3_snapshot(app: AppHandle, snapshot: String, target_dir: Option<String>) -> Result<S3RestoreReport, String> {
+    let root = app.state::<Workspace>().root()?;
+    let settings = configured(&app)?;
+    let target = match target_dir {
+        Some(dir) => crate::scopes::check(&app, &dir)?,
+        None => root.clone(),
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let _guard = RUNNING.try_lock().map_err(|_| "Une sauvegarde est déjà en cours".to_string())?;
+        let client = Client::new(&settings)?;
+        let prefix = workspace_prefix(&settings, &root);
+        let manifest = client.get(&format!("{}/snapshots/{}.json", prefix, snapshot))?;
+        let manifest: Manifest = serde_json::from_slice(&manifest).map_err(|e| format!("Sauvegarde invalide ({}): {}", snapshot, e))?;
+
+        let mut report = S3RestoreReport { restored: 0, unchanged: 0, errors: Vec::new() };
+        for (relative, file) in &manifest.files {
+            let Some(path) = safe_relative(relative).map(|r| target.join(r)) else {
+                report.errors.push(format!("{}: chemin refusé", relative));
+                continue;
+            };
+            if std::fs::read(&path).is_ok_and(|bytes| sha256_hex(&bytes) == file.hash) {
+                report.unchanged += 1;
+                continue;
+            }
+            let restored = locks::ensure_writable(&app, &path)
+                .and_then(|_| client.get(&format!("{}/objects/{}", prefix, file.hash)))
+                .and_then(|bytes| {
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer le dossier {}: {}", parent.display(), e))?;
+                    }
+                    safe_save::write_document(&app, &path, &bytes)
+                });
+            match restored {
+                Ok(()) => report.restored += 1,
+                Err(e) => report.errors.push(format!("{}: {}", relative, e)),
+            }
+        }
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la restauration: {}", e))?
+}
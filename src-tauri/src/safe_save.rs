@@ -0,0 +1,137 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::encoding::{self, TextFormat};
+
+/// Where backup retention was kept before it moved to the settings.
+const LEGACY_SETTINGS_FILE: &str = "save.json";
+const MAX_BACKUPS: usize = 10;
+
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    match n {
+        1 => path.with_file_name(format!("{}.bak", name)),
+        n => path.with_file_name(format!("{}.bak.{}", name, n)),
+    }
+}
+
+/// Deletes every backup kept next to `path`, for a file whose old contents
+/// must not outlive it.
+pub fn remove_backups(path: &Path) -> Result<(), String> {
+    for n in 1..=MAX_BACKUPS {
+        let backup = backup_path(path, n);
+        if backup.exists() {
+            std::fs::remove_file(&backup).map_err(|e| format!("Impossible de supprimer {}: {}", backup.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Shifts the existing backups up by one and copies the current file into
+/// the first slot.
+fn rotate_backups(path: &Path, count: usize) -> std::io::Result<()> {
+    let _ = std::fs::remove_file(backup_path(path, count));
+    for n in (1..count).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            std::fs::rename(&from, backup_path(path, n + 1))?;
+        }
+    }
+    std::fs::copy(path, backup_path(path, 1)).map(|_| ())
+}
+
+fn write_synced(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+/// Replaces `path` with `bytes` so that a crash at any point leaves either
+/// the old or the new content: the data is written and flushed to a
+/// temporary file next to it, which is then renamed over the original.
+pub fn write(app: &AppHandle, path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let error = |e: std::io::Error| format!("Impossible d'enregistrer {}: {}", path.display(), e);
+    let name = path.file_name().ok_or("Chemin invalide")?.to_string_lossy().to_string();
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()));
+    if let Err(e) = write_synced(&tmp, bytes) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(error(e));
+    }
+    commit(app, &tmp, path)
+}
+
+/// `write` for a document the user edits: refused when it is locked in the
+/// app or read-only on disk. Every rewrite of a note goes through here.
+pub fn write_document(app: &AppHandle, path: &Path, bytes: &[u8]) -> Result<(), String> {
+    crate::locks::ensure_writable(app, path)?;
+    write(app, path, bytes)
+}
+
+/// Text of a document the app is about to rewrite, with `\n` line endings,
+/// and the format to write it back in.
+pub fn read_text(path: &Path) -> Result<(String, TextFormat), String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+    Ok(encoding::decode(&bytes))
+}
+
+/// `write_document` for text, in the given encoding and line endings.
+pub fn write_text(app: &AppHandle, path: &Path, text: &str, format: &TextFormat) -> Result<(), String> {
+    write_document(app, path, &encoding::encode(text, format)?)
+}
+
+/// Moves a fully written and synced temporary file into place, keeping a
+/// backup of the file it replaces.
+pub fn commit(app: &AppHandle, tmp: &Path, path: &Path) -> Result<(), String> {
+    let error = |e: std::io::Error| format!("Impossible d'enregistrer {}: {}", path.display(), e);
+    if let Ok(metadata) = std::fs::metadata(path) {
+        // Keep the original's permissions rather than the temp file's defaults
+        let _ = std::fs::set_permissions(tmp, metadata.permissions());
+        let count = crate::settings::load(app).save.backup_count.min(MAX_BACKUPS);
+        if count > 0 {
+            rotate_backups(path, count).map_err(error)?;
+        }
+    }
+    if let Err(e) = std::fs::rename(tmp, path) {
+        let _ = std::fs::remove_file(tmp);
+        return Err(error(e));
+    }
+    // Persist the rename itself
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        let _ = File::open(dir).and_then(|d| d.sync_all());
+    }
+    crate::watcher::record_own_write(app, path);
+    Ok(())
+}
+
+/// Moves the backup retention of `save.json`, from older versions, into the
+/// settings.
+pub fn migrate_settings(app: &AppHandle) {
+    let Ok(path) = crate::paths::app_config_dir(app).map(|dir| dir.join(LEGACY_SETTINGS_FILE)) else {
+        return;
+    };
+    let Ok(json) = std::fs::read_to_string(&path) else {
+        return;
+    };
+    let count = serde_json::from_str::<serde_json::Value>(&json).ok().and_then(|v| v.get("backupCount").and_then(|c| c.as_u64()));
+    if let Some(count) = count {
+        if set_backup_retention(app.clone(), count as usize).is_err() {
+            return;
+        }
+    }
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tauri::command]
+pub fn get_backup_retention(app: AppHandle) -> usize {
+    crate::settings::load(&app).save.backup_count
+}
+
+/// Number of `.bak` copies kept per file, 0 to disable them.
+#[tauri::command]
+pub fn set_backup_retention(app: AppHandle, count: usize) -> Result<(), String> {
+    let patch = serde_json::json!({ "save": { "backupCount": count.min(MAX_BACKUPS) } });
+    crate::settings::update(&app, patch, None).map(|_| ())
+}
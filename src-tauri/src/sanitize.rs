@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use crate::{settings, svg};
+
+/// Tags allowed besides ammonia's defaults: media, task list checkboxes,
+/// and the SVG and MathML that diagrams and formulas render to.
+const EXTRA_TAGS: &[&str] = &[
+    "audio", "video", "source", "track", "picture", "input", "section", "main", "tfoot",
+    "svg", "g", "path", "rect", "circle", "ellipse", "line", "polyline", "polygon", "text", "tspan", "textPath",
+    "defs", "symbol", "use", "marker", "clipPath", "mask", "pattern", "linearGradient", "radialGradient", "stop",
+    "image", "title", "desc",
+    "math", "semantics", "annotation", "mrow", "mi", "mn", "mo", "ms", "mtext", "mspace", "msup", "msub",
+    "msubsup", "mfrac", "msqrt", "mroot", "mover", "munder", "munderover", "mtable", "mtr", "mtd", "mstyle",
+    "mpadded", "mphantom", "menclose",
+];
+
+const EXTRA_ATTRIBUTES: &[&str] = &[
+    "class", "id", "style", "dir", "role", "width", "height",
+    "viewBox", "xmlns", "preserveAspectRatio", "transform", "d", "x", "y", "x1", "y1", "x2", "y2", "cx", "cy",
+    "r", "rx", "ry", "dx", "dy", "points", "fill", "fill-opacity", "fill-rule", "stroke", "stroke-width",
+    "stroke-opacity", "stroke-dasharray", "stroke-linecap", "stroke-linejoin", "opacity", "font-family",
+    "font-size", "font-weight", "text-anchor", "dominant-baseline", "marker-end", "marker-start", "markerWidth",
+    "markerHeight", "refX", "refY", "orient", "offset", "stop-color", "stop-opacity", "gradientUnits",
+    "clip-path", "mask", "href",
+    "display", "mathvariant", "stretchy", "fence", "separator", "accent", "encoding", "columnalign", "rowspan",
+    "columnspan", "linethickness", "lspace", "rspace",
+];
+
+const TAG_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("audio", &["src", "controls", "loop", "muted", "preload"]),
+    ("video", &["src", "controls", "loop", "muted", "preload", "poster"]),
+    ("source", &["src", "srcset", "type", "media"]),
+    ("track", &["src", "kind", "srclang", "label", "default"]),
+    ("img", &["srcset", "sizes", "loading"]),
+    ("input", &["type", "checked", "disabled"]),
+];
+
+/// Pandoc formats whose exports are sanitized. Slide formats are left as
+/// they are: their players are scripts.
+pub const HTML_FORMATS: &[&str] = &["html", "html4", "html5"];
+
+/// Where preview and export images point, besides the web: the webview's
+/// asset protocol and embedded images.
+const EXTRA_SCHEMES: &[&str] = &["asset", "data"];
+
+/// Raw HTML of a document without what could run in the webview or in
+/// whoever opens the export: scripts, event handlers, `javascript:` links,
+/// frames, forms. Left as is when sanitizing is off in the settings.
+pub fn clean(html: &str) -> String {
+    let rules = settings::html();
+    if !rules.sanitize {
+        return html.to_string();
+    }
+    // ammonia asserts these are never allowed
+    let tags = rules.allowed_tags.iter().map(String::as_str).filter(|t| !matches!(t.to_lowercase().as_str(), "script" | "style"));
+    let attributes = rules.allowed_attributes.iter().map(String::as_str).filter(|a| {
+        let a = a.to_lowercase();
+        !a.starts_with("on") && a != "rel"
+    });
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_tags(EXTRA_TAGS)
+        .add_tags(tags)
+        .add_generic_attributes(EXTRA_ATTRIBUTES)
+        .add_generic_attributes(attributes)
+        .add_generic_attribute_prefixes(["data-", "aria-"])
+        .add_url_schemes(EXTRA_SCHEMES);
+    for (tag, attributes) in TAG_ATTRIBUTES {
+        builder.add_tag_attributes(tag, attributes.iter());
+    }
+    builder.clean(html).to_string()
+}
+
+/// Elements a page's head keeps when sanitized.
+const HEAD_ELEMENTS: &[&str] = &["meta", "title", "style", "link"];
+
+/// Inside of a `<head>`, with only `HEAD_ELEMENTS` left: the document's own
+/// `header-includes` end up there in standalone exports. Comments go too,
+/// since old conditional comments can hold scripts.
+fn clean_head(head: &str) -> String {
+    let lower = head.to_ascii_lowercase();
+    let mut out = String::with_capacity(head.len());
+    let mut pos = 0;
+    while let Some(offset) = head[pos..].find('<') {
+        let start = pos + offset;
+        out.extend(head[pos..start].chars().filter(|c| c.is_whitespace()));
+        if head[start..].starts_with("<!--") {
+            pos = head[start..].find("-->").map(|e| start + e + 3).unwrap_or(head.len());
+            continue;
+        }
+        let end = svg::tag_end(head, start);
+        let tag = svg::parse_tag(&head[start..end]);
+        let name = tag.name.to_ascii_lowercase();
+        // Raw text, or content of what is dropped: up to the close tag
+        let close = || {
+            if tag.self_closing || tag.closing || matches!(name.as_str(), "meta" | "link") {
+                return end;
+            }
+            lower[end..].find(&format!("</{}", name)).map(|e| svg::tag_end(head, end + e)).unwrap_or(head.len())
+        };
+        let refresh = tag.attributes.iter().any(|(attr, value, _)| attr.eq_ignore_ascii_case("http-equiv") && value.eq_ignore_ascii_case("refresh"));
+        if tag.closing || !HEAD_ELEMENTS.contains(&name.as_str()) || refresh {
+            pos = close();
+            continue;
+        }
+        out.push('<');
+        out.push_str(tag.name);
+        for (attr, _, text) in &tag.attributes {
+            if !attr.to_ascii_lowercase().starts_with("on") {
+                out.push(' ');
+                out.push_str(text);
+            }
+        }
+        out.push_str(if tag.self_closing { "/>" } else { ">" });
+        let content_end = close();
+        out.push_str(&head[end..content_end]);
+        pos = content_end;
+    }
+    out.extend(head[pos..].chars().filter(|c| c.is_whitespace()));
+    out
+}
+
+/// `clean` for a whole page: the body is cleaned as document content, and
+/// the head keeps only metadata and stylesheets.
+pub fn clean_document(html: &str) -> String {
+    if !settings::html().sanitize {
+        return html.to_string();
+    }
+    let lower = html.to_ascii_lowercase();
+    let body = lower.find("<body").and_then(|start| lower[start..].find('>').map(|end| start + end + 1));
+    let Some(body) = body else {
+        return clean(html);
+    };
+    let end = lower.rfind("</body>").filter(|end| *end >= body).unwrap_or(html.len());
+    let head = lower[..body].find("<head").and_then(|start| {
+        let open_end = start + lower[start..].find('>')? + 1;
+        let close = lower[open_end..body].find("</head").map(|e| open_end + e)?;
+        Some((open_end, close))
+    });
+    let prefix = match head {
+        Some((start, close)) => format!("{}{}{}", &html[..start], clean_head(&html[start..close]), &html[close..body]),
+        None => html[..body].to_string(),
+    };
+    format!("{}{}{}", prefix, clean(&html[body..end]), &html[end..])
+}
+
+/// Runs `clean_document` over the HTML file at `path`.
+pub fn clean_file(path: &Path) -> Result<(), String> {
+    let html = std::fs::read_to_string(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+    std::fs::write(path, clean_document(&html)).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))
+}
+
+/// Sanitizes rendered preview HTML before the webview shows it. `trusted`
+/// documents are shown as written.
+#[tauri::command]
+pub fn sanitize_html(html: String, trusted: Option<bool>) -> String {
+    if trusted.unwrap_or(false) {
+        html
+    } else {
+        clean(&html)
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_fs::FsExt;
+
+use crate::{assets, directories, paths, settings, workspace};
+
+const SCOPES_FILE: &str = "path-scopes.json";
+/// Folders of the user's home holding keys and credentials.
+const HOME_SECRETS: &[&str] = &[".ssh", ".gnupg", ".aws", ".kube", ".docker"];
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Why a path from the frontend was refused.
+pub enum PathError {
+    /// Empty or relative.
+    Invalid { path: String },
+    /// Inside a folder of the system, or of the user's keys.
+    System { path: String },
+    /// Outside the workspace and every folder the user chose.
+    OutOfScope { path: String },
+}
+
+impl PathError {
+    /// Stable code of the variant, which starts the message the frontend
+    /// receives, so it can tell a refused path from other failures.
+    pub fn code(&self) -> &'static str {
+        match self {
+            PathError::Invalid { .. } => "PATH_INVALID",
+            PathError::System { .. } => "PATH_SYSTEM",
+            PathError::OutOfScope { .. } => "PATH_OUT_OF_SCOPE",
+        }
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::Invalid { path } => write!(f, "Chemin invalide: {:?} (un chemin absolu est attendu)", path),
+            PathError::System { path } => write!(f, "Accès refusé: {} est un emplacement système", path),
+            PathError::OutOfScope { path } => {
+                write!(f, "Accès refusé: {} est en dehors des dossiers autorisés; choisissez-le depuis l'application", path)
+            }
+        }
+    }
+}
+
+impl From<PathError> for String {
+    fn from(e: PathError) -> String {
+        format!("{}: {}", e.code(), e)
+    }
+}
+
+/// `path` with symlinks resolved. A path that doesn't exist yet, such as the
+/// output of an export, is resolved from its nearest existing ancestor.
+fn canonical(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = parent;
+            }
+            _ => break,
+        }
+    }
+    let mut out = existing.canonicalize().unwrap_or_else(|_| existing.to_path_buf());
+    out.extend(rest.iter().rev());
+    out
+}
+
+fn system_dirs() -> &'static [PathBuf] {
+    static DIRS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+    DIRS.get_or_init(|| {
+        let mut dirs: Vec<PathBuf> = if cfg!(target_os = "windows") {
+            ["SystemRoot", "ProgramFiles", "ProgramFiles(x86)", "ProgramData"].iter().filter_map(std::env::var_os).map(PathBuf::from).collect()
+        } else {
+            ["/etc", "/bin", "/sbin", "/usr", "/lib", "/lib64", "/boot", "/dev", "/proc", "/sys", "/var/lib", "/System", "/Library", "/private/etc", "/private/var/db"]
+                .iter()
+                .map(PathBuf::from)
+                .collect()
+        };
+        let home = std::env::var_os(if cfg!(target_os = "windows") { "USERPROFILE" } else { "HOME" }).map(PathBuf::from);
+        if let Some(home) = home {
+            dirs.extend(HOME_SECRETS.iter().map(|dir| home.join(dir)));
+        }
+        dirs.iter().map(|dir| canonical(dir)).collect()
+    })
+}
+
+fn scopes_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_config_dir(app)?.join(SCOPES_FILE))
+}
+
+/// Folders and files the user opened in an earlier session.
+fn load(app: &AppHandle) -> Vec<String> {
+    scopes_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn within(path: &Path, scopes: impl IntoIterator<Item = PathBuf>) -> bool {
+    scopes.into_iter().any(|scope| path.starts_with(canonical(&scope)))
+}
+
+/// Folders the user picked for the app: the workspace, those of
+/// `directories`, and the temporary folders.
+fn chosen_scopes(app: &AppHandle) -> Vec<PathBuf> {
+    let mut scopes: Vec<PathBuf> = directories::media_dir(app).into_iter().collect();
+    scopes.extend([settings::temp_dir(), std::env::temp_dir()]);
+    scopes.extend(settings::load(app).directories.exports.filter(|dir| !dir.trim().is_empty()).map(PathBuf::from));
+    scopes.extend(app.state::<workspace::Workspace>().root().ok());
+    scopes
+}
+
+/// `path` resolved, when the app may touch it: inside the workspace, a
+/// folder of the app, or something the user chose through a dialog, this
+/// session or an earlier one; and never inside a system folder.
+pub fn check(app: &AppHandle, path: &str) -> Result<PathBuf, PathError> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() || !Path::new(trimmed).is_absolute() {
+        return Err(PathError::Invalid { path: path.to_string() });
+    }
+    let normalized = assets::normalize(Path::new(trimmed));
+    let resolved = canonical(&normalized);
+    // The app's own folders may sit anywhere in portable mode
+    if within(&resolved, [paths::app_data_dir(app), paths::app_config_dir(app)].into_iter().flatten()) {
+        return Ok(normalized);
+    }
+    // Before the scopes: the folders of the user's keys are inside the home,
+    // which a workspace may be
+    if system_dirs().iter().any(|dir| resolved.starts_with(dir)) {
+        return Err(PathError::System { path: normalized.display().to_string() });
+    }
+    let granted = chosen_scopes(app).into_iter().chain(load(app).into_iter().map(PathBuf::from));
+    if !within(&resolved, granted) && !app.fs_scope().is_allowed(&normalized) {
+        return Err(PathError::OutOfScope { path: normalized.display().to_string() });
+    }
+    Ok(normalized)
+}
+
+/// `check` for the paths a command may be given or not.
+pub fn check_optional(app: &AppHandle, path: Option<&str>) -> Result<Option<PathBuf>, PathError> {
+    path.map(|path| check(app, path)).transpose()
+}
+
+fn save(app: &AppHandle, scopes: &[String]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(scopes).map_err(|e| e.to_string())?;
+    std::fs::write(scopes_path(app)?, json).map_err(|e| format!("Impossible d'enregistrer les dossiers autorisés: {}", e))
+}
+
+/// Remembers `path`, already checked, in the scopes of later sessions, as
+/// done for the workspace and recently opened files.
+pub fn grant(app: &AppHandle, path: &Path) -> Result<(), String> {
+    let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+    let mut scopes = load(app);
+    let path = path.to_string_lossy().to_string();
+    if scopes.contains(&path) {
+        return Ok(());
+    }
+    scopes.push(path);
+    save(app, &scopes)
+}
+
+/// Folders and files the user granted in earlier sessions.
+#[tauri::command]
+pub fn get_path_scopes(app: AppHandle) -> Vec<String> {
+    load(&app)
+}
+
+#[tauri::command]
+pub fn revoke_path_scope(app: AppHandle, path: String) -> Result<Vec<String>, String> {
+    let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+    let mut scopes = load(&app);
+    scopes.retain(|scope| *scope != path);
+    save(&app, &scopes)?;
+    Ok(scopes)
+}
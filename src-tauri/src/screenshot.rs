@@ -0,0 +1,173 @@
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::assets;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CaptureMode {
+    Full,
+    Window,
+    Region,
+}
+
+impl CaptureMode {
+    fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "full" | "screen" => Ok(CaptureMode::Full),
+            "window" => Ok(CaptureMode::Window),
+            "region" => Ok(CaptureMode::Region),
+            other => Err(format!("Mode de capture inconnu: {}", other)),
+        }
+    }
+}
+
+/// Runs `program` and reports whether it could be started at all, so the
+/// caller can fall back to the next tool.
+fn run_tool(program: &str, args: &[&str], output: &Path) -> Option<bool> {
+    match Command::new(program).args(args).arg(output).status() {
+        Ok(status) => Some(status.success()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(_) => Some(false),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn capture_with_os_tool(mode: CaptureMode, output: &Path) -> Result<(), String> {
+    let args: &[&str] = match mode {
+        CaptureMode::Full => &["-x"],
+        CaptureMode::Window => &["-x", "-o", "-w"],
+        CaptureMode::Region => &["-x", "-i"],
+    };
+    run_tool("screencapture", args, output)
+        .map(|_| ())
+        .ok_or_else(|| "screencapture est introuvable".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn capture_with_os_tool(mode: CaptureMode, output: &Path) -> Result<(), String> {
+    if mode != CaptureMode::Full {
+        return capture_with_snipping_tool(output);
+    }
+    let script = "Add-Type -AssemblyName System.Windows.Forms,System.Drawing; \
+        $b = [System.Windows.Forms.SystemInformation]::VirtualScreen; \
+        $bmp = New-Object System.Drawing.Bitmap $b.Width, $b.Height; \
+        [System.Drawing.Graphics]::FromImage($bmp).CopyFromScreen($b.Left, $b.Top, 0, 0, $bmp.Size); \
+        $bmp.Save($env:OHMYMARKDOWN_SCREENSHOT, [System.Drawing.Imaging.ImageFormat]::Png)";
+    let status = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .env("OHMYMARKDOWN_SCREENSHOT", output)
+        .status()
+        .map_err(|e| format!("Erreur lors de l'exécution de PowerShell: {}", e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err("La capture d'écran a échoué".to_string())
+    }
+}
+
+/// The Windows snipping overlay only copies its result to the clipboard, so
+/// wait for a new image to show up there.
+#[cfg(target_os = "windows")]
+fn capture_with_snipping_tool(output: &Path) -> Result<(), String> {
+    let previous = crate::images::clipboard_image().ok().map(|img| img.into_bytes());
+    Command::new("explorer")
+        .arg("ms-screenclip:")
+        .status()
+        .map_err(|e| format!("Impossible de lancer l'outil Capture d'écran: {}", e))?;
+
+    for _ in 0..240 {
+        std::thread::sleep(Duration::from_millis(250));
+        if let Ok(img) = crate::images::clipboard_image() {
+            if previous.as_deref() != Some(img.as_bytes()) {
+                return crate::images::write_image(&img, output, image::ImageFormat::Png, 100);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn capture_with_os_tool(mode: CaptureMode, output: &Path) -> Result<(), String> {
+    if mode == CaptureMode::Region {
+        // Wayland: slurp picks the region, grim captures it
+        if let Ok(selection) = Command::new("slurp").output() {
+            if !selection.status.success() {
+                return Ok(());
+            }
+            let geometry = String::from_utf8_lossy(&selection.stdout).trim().to_string();
+            if run_tool("grim", &["-g", &geometry], output).is_some() {
+                return Ok(());
+            }
+        }
+    }
+
+    let candidates: &[(&str, &[&str])] = match mode {
+        CaptureMode::Full => &[
+            ("grim", &[]),
+            ("spectacle", &["-b", "-n", "-f", "-o"]),
+            ("gnome-screenshot", &["-f"]),
+            ("scrot", &["-o"]),
+            ("import", &["-window", "root"]),
+        ],
+        CaptureMode::Window => &[
+            ("spectacle", &["-b", "-n", "-a", "-o"]),
+            ("gnome-screenshot", &["-w", "-f"]),
+            ("scrot", &["-s", "-o"]),
+            ("import", &[]),
+        ],
+        CaptureMode::Region => &[
+            ("spectacle", &["-b", "-n", "-r", "-o"]),
+            ("gnome-screenshot", &["-a", "-f"]),
+            ("scrot", &["-s", "-o"]),
+            ("import", &[]),
+        ],
+    };
+    for (program, args) in candidates {
+        if run_tool(program, args, output).is_some() {
+            return Ok(());
+        }
+    }
+    Err("Aucun outil de capture d'écran trouvé (grim, spectacle, gnome-screenshot, scrot ou ImageMagick)".to_string())
+}
+
+fn capture(mode: CaptureMode, output: &Path) -> Result<(), String> {
+    capture_with_os_tool(mode, output)?;
+    if output.is_file() {
+        Ok(())
+    } else {
+        Err("Capture d'écran annulée".to_string())
+    }
+}
+
+/// Captures the screen, a window or a region with the platform's tool, saves
+/// it into the document's assets folder and returns the image markdown.
+#[tauri::command]
+pub async fn capture_screenshot(app: AppHandle, mode: String, document_path: Option<String>) -> Result<String, String> {
+    let mode = CaptureMode::parse(&mode)?;
+    let dir = assets::target_assets_dir(&app, document_path.as_deref())?;
+    let name = format!("screenshot-{}.png", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+    let path = assets::unique_path(&dir, &name);
+
+    // Keep the editor out of the shot.
+    let window = app.get_webview_window("main");
+    if let Some(window) = &window {
+        let _ = window.hide();
+    }
+    let result = tauri::async_runtime::spawn_blocking({
+        let path = path.clone();
+        move || {
+            std::thread::sleep(Duration::from_millis(300));
+            capture(mode, &path)
+        }
+    })
+    .await;
+    if let Some(window) = &window {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+    result.map_err(|e| format!("Erreur lors de la capture d'écran: {}", e))??;
+
+    Ok(format!("![]({})", assets::document_link(&path, document_path.as_deref())))
+}
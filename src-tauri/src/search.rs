@@ -0,0 +1,321 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::{markdown, watcher, workspace};
+
+const DEFAULT_LIMIT: usize = 100;
+const MAX_HITS_PER_FILE: usize = 5;
+const SNIPPET_CONTEXT: usize = 60;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub line: usize,
+    pub snippet: String,
+    /// UTF-16 offset of the match in the file.
+    pub offset: usize,
+    pub length: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    pub path: String,
+    pub score: f64,
+    pub match_count: usize,
+    pub hits: Vec<SearchHit>,
+}
+
+pub struct IndexedDoc {
+    content: String,
+    /// Lowercased content with the same byte offsets as `content`.
+    lower: String,
+    title: String,
+    tags: Vec<markdown::TagRef>,
+}
+
+impl IndexedDoc {
+    fn new(path: &Path, content: String) -> Self {
+        let lower = content.to_lowercase();
+        // Some characters change length when lowercased; keep offsets valid
+        let lower = if lower.len() == content.len() { lower } else { content.to_ascii_lowercase() };
+        IndexedDoc {
+            title: path.file_stem().map(|s| s.to_string_lossy().to_lowercase()).unwrap_or_default(),
+            tags: markdown::tags(&content),
+            lower,
+            content,
+        }
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    pub fn tags(&self) -> &[markdown::TagRef] {
+        &self.tags
+    }
+}
+
+/// In-memory index of the workspace's markdown files, built on the first
+/// search and kept current from watcher events.
+#[derive(Default)]
+pub struct SearchIndex {
+    root: Mutex<Option<PathBuf>>,
+    docs: Mutex<HashMap<PathBuf, IndexedDoc>>,
+}
+
+enum Term {
+    Word(String),
+    Prefix(String),
+    Phrase(String),
+}
+
+impl Term {
+    fn text(&self) -> &str {
+        match self {
+            Term::Word(t) | Term::Prefix(t) | Term::Phrase(t) => t,
+        }
+    }
+}
+
+struct Query {
+    terms: Vec<Term>,
+    tags: Vec<String>,
+}
+
+/// `"exact phrase"`, `prefix*`, `#tag` / `tag:name`, and plain words; every
+/// part must match.
+fn parse_query(query: &str) -> Query {
+    let mut terms = Vec::new();
+    let mut tags = Vec::new();
+    let mut rest = query.trim();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            let phrase = quoted[..end].split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+            if !phrase.is_empty() {
+                terms.push(Term::Phrase(phrase));
+            }
+            rest = quoted[(end + 1).min(quoted.len())..].trim_start();
+            continue;
+        }
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let token = rest[..end].to_lowercase();
+        rest = rest[end..].trim_start();
+
+        if let Some(tag) = token.strip_prefix('#').or_else(|| token.strip_prefix("tag:")) {
+            if !tag.is_empty() {
+                tags.push(tag.to_string());
+            }
+        } else if let Some(prefix) = token.strip_suffix('*') {
+            if !prefix.is_empty() {
+                terms.push(Term::Prefix(prefix.to_string()));
+            }
+        } else {
+            terms.push(Term::Word(token));
+        }
+    }
+    Query { terms, tags }
+}
+
+fn is_word_boundary(c: Option<char>) -> bool {
+    !c.is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Byte ranges where `term` matches in a lowercased text.
+fn find_term(lower: &str, term: &Term) -> Vec<(usize, usize)> {
+    let text = term.text();
+    lower
+        .match_indices(text)
+        .filter(|(start, _)| {
+            let end = start + text.len();
+            let starts_word = is_word_boundary(lower[..*start].chars().next_back());
+            let ends_word = is_word_boundary(lower[end..].chars().next());
+            match term {
+                Term::Prefix(_) => starts_word,
+                Term::Word(_) | Term::Phrase(_) => starts_word && ends_word,
+            }
+        })
+        .map(|(start, _)| (start, start + text.len()))
+        .collect()
+}
+
+/// Case-insensitive; `#a` also matches the nested tag `#a/b`.
+pub fn tag_matches(name: &str, tag: &str) -> bool {
+    let (name, tag) = (name.to_lowercase(), tag.to_lowercase());
+    name == tag || name.strip_prefix(&tag).is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn has_tags(doc: &IndexedDoc, tags: &[String]) -> bool {
+    tags.iter().all(|tag| doc.tags.iter().any(|t| tag_matches(&t.name, tag)))
+}
+
+/// Occurrences of the query's tags, used as hits for tag-only queries.
+fn tag_ranges(doc: &IndexedDoc, tags: &[String]) -> Vec<(usize, usize)> {
+    doc.tags
+        .iter()
+        .filter(|t| tags.iter().any(|tag| tag_matches(&t.name, tag)))
+        .map(|t| (t.start, t.end))
+        .collect()
+}
+
+fn snippet(line: &str, start: usize, end: usize) -> String {
+    let mut from = start.saturating_sub(SNIPPET_CONTEXT);
+    while !line.is_char_boundary(from) {
+        from -= 1;
+    }
+    let mut to = (end + SNIPPET_CONTEXT).min(line.len());
+    while !line.is_char_boundary(to) {
+        to += 1;
+    }
+    let mut text = line[from..to].trim().to_string();
+    if from > 0 {
+        text.insert(0, '…');
+    }
+    if to < line.len() {
+        text.push('…');
+    }
+    text
+}
+
+fn hits(doc: &IndexedDoc, matches: &[Vec<(usize, usize)>]) -> (usize, Vec<SearchHit>) {
+    let mut all: Vec<(usize, usize)> = matches.iter().flatten().copied().collect();
+    all.sort();
+    let count = all.len();
+
+    let mut hits: Vec<SearchHit> = Vec::new();
+    let mut last_line = 0;
+    for (start, end) in all {
+        let line = markdown::line_at(&doc.content, start);
+        if line == last_line {
+            continue;
+        }
+        last_line = line;
+        let line_start = doc.content[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = doc.content[start..].find('\n').map(|i| start + i).unwrap_or(doc.content.len());
+        let offset = markdown::utf16_offset(&doc.content, start);
+        hits.push(SearchHit {
+            line,
+            snippet: snippet(&doc.content[line_start..line_end], start - line_start, end - line_start),
+            offset,
+            length: markdown::utf16_offset(&doc.content, end) - offset,
+        });
+        if hits.len() == MAX_HITS_PER_FILE {
+            break;
+        }
+    }
+    (count, hits)
+}
+
+fn read_doc(path: &Path) -> Option<IndexedDoc> {
+    let content = std::fs::read_to_string(path).ok()?;
+    Some(IndexedDoc::new(path, content))
+}
+
+fn build(root: &Path) -> HashMap<PathBuf, IndexedDoc> {
+    let paths = workspace::markdown_files(root);
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    let chunk = paths.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .chunks(chunk)
+            .map(|chunk| scope.spawn(move || chunk.iter().filter_map(|p| Some((p.clone(), read_doc(p)?))).collect::<Vec<_>>()))
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap_or_default()).collect()
+    })
+}
+
+impl SearchIndex {
+    /// Makes sure the index covers `root`, rebuilding it when the workspace
+    /// changed.
+    fn ensure(&self, root: &Path) {
+        let mut indexed_root = self.root.lock().unwrap();
+        if indexed_root.as_deref() != Some(root) {
+            *self.docs.lock().unwrap() = build(root);
+            *indexed_root = Some(root.to_path_buf());
+        }
+    }
+
+    /// Runs `f` over the indexed notes of the workspace at `root`.
+    pub fn with_docs<R>(&self, root: &Path, f: impl FnOnce(&HashMap<PathBuf, IndexedDoc>) -> R) -> R {
+        self.ensure(root);
+        f(&self.docs.lock().unwrap())
+    }
+
+    /// Re-reads a file, or drops it when it is gone.
+    pub fn update(&self, path: &Path) {
+        let Some(root) = self.root.lock().unwrap().clone() else { return };
+        let mut docs = self.docs.lock().unwrap();
+        docs.remove(path);
+        if path.starts_with(&root) && markdown::is_markdown_file(path) {
+            if let Some(doc) = read_doc(path) {
+                docs.insert(path.to_path_buf(), doc);
+            }
+        }
+    }
+}
+
+/// Called by the watcher to keep the index in step with the files on disk.
+pub fn on_changes(app: &AppHandle, changes: &[watcher::FileChange]) {
+    let index = app.state::<SearchIndex>();
+    for change in changes {
+        index.update(Path::new(&change.path));
+        if let Some(new_path) = &change.new_path {
+            index.update(Path::new(new_path));
+        }
+    }
+}
+
+/// Searches the workspace's notes, best matches first.
+#[tauri::command]
+pub async fn search_workspace(app: AppHandle, query: String, limit: Option<usize>) -> Result<Vec<SearchResult>, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let index: State<'_, SearchIndex> = app.state();
+        index.ensure(&root);
+        let query = parse_query(&query);
+        if query.terms.is_empty() && query.tags.is_empty() {
+            return Vec::new();
+        }
+
+        let docs = index.docs.lock().unwrap();
+        let mut doc_freq = vec![0usize; query.terms.len()];
+        let mut matched = Vec::new();
+        for (path, doc) in docs.iter().filter(|(_, doc)| has_tags(doc, &query.tags)) {
+            let matches: Vec<_> = query.terms.iter().map(|term| find_term(&doc.lower, term)).collect();
+            for (count, found) in doc_freq.iter_mut().zip(&matches) {
+                *count += usize::from(!found.is_empty());
+            }
+            if matches.iter().all(|m| !m.is_empty()) {
+                matched.push((path, doc, matches));
+            }
+        }
+
+        // tf-idf, with a bonus for terms found in the file name
+        let total = docs.len() as f64;
+        let mut results: Vec<SearchResult> = matched
+            .into_iter()
+            .map(|(path, doc, matches)| {
+                let score = query.terms.iter().zip(&matches).zip(&doc_freq).map(|((term, found), &freq)| {
+                    let idf = (1.0 + total / freq as f64).ln();
+                    let title_bonus = if doc.title.contains(term.text()) { 2.0 } else { 0.0 };
+                    (1.0 + (found.len() as f64).ln() + title_bonus) * idf
+                }).sum();
+                let (match_count, hits) = if matches.is_empty() {
+                    hits(doc, &[tag_ranges(doc, &query.tags)])
+                } else {
+                    hits(doc, &matches)
+                };
+                SearchResult { path: path.to_string_lossy().to_string(), score, match_count, hits }
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+        results.truncate(limit.unwrap_or(DEFAULT_LIMIT));
+        results
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la recherche: {}", e))
+}
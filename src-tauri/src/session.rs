@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize};
+
+const SESSION_FILE: &str = "session.json";
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTab {
+    /// Frontend tab id; also the autosave id of an unsaved tab's content.
+    pub id: String,
+    pub path: Option<String>,
+    /// UTF-16 offsets of the selection.
+    pub selection_start: Option<usize>,
+    pub selection_end: Option<usize>,
+    pub scroll_top: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowLayout {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Session {
+    pub tabs: Vec<SessionTab>,
+    pub active_tab: Option<String>,
+    /// Filled in by the backend from the main window when saving.
+    pub window: Option<WindowLayout>,
+    /// Frontend view state (split mode, sidebar width...), stored as is.
+    pub view: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SessionFile {
+    restore_on_launch: bool,
+    session: Option<Session>,
+}
+
+impl Default for SessionFile {
+    fn default() -> Self {
+        SessionFile { restore_on_launch: true, session: None }
+    }
+}
+
+fn session_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(crate::paths::app_config_dir(app)?.join(SESSION_FILE))
+}
+
+fn load(app: &AppHandle) -> Result<SessionFile, String> {
+    let path = session_path(app)?;
+    if !path.exists() {
+        return Ok(SessionFile::default());
+    }
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Impossible de lire la session: {}", e))?;
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+fn save(app: &AppHandle, file: &SessionFile) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+    std::fs::write(session_path(app)?, json)
+        .map_err(|e| format!("Impossible d'enregistrer la session: {}", e))
+}
+
+fn window_layout(app: &AppHandle) -> Option<WindowLayout> {
+    let window = app.get_webview_window("main")?;
+    let size = window.outer_size().ok()?;
+    let position = window.outer_position().ok()?;
+    Some(WindowLayout {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized: window.is_maximized().unwrap_or(false),
+    })
+}
+
+/// Puts the main window back where the last session left it. Called from
+/// setup so the window doesn't visibly jump once the frontend loads.
+pub fn restore_window(app: &AppHandle) {
+    let Ok(SessionFile { restore_on_launch: true, session: Some(session) }) = load(app) else {
+        return;
+    };
+    let (Some(layout), Some(window)) = (session.window, app.get_webview_window("main")) else {
+        return;
+    };
+    if layout.maximized {
+        let _ = window.maximize();
+    } else if layout.width > 0 && layout.height > 0 {
+        let _ = window.set_size(PhysicalSize::new(layout.width, layout.height));
+        let _ = window.set_position(PhysicalPosition::new(layout.x, layout.y));
+    }
+}
+
+#[tauri::command]
+pub fn save_session(app: AppHandle, session: Session) -> Result<(), String> {
+    let mut file = load(&app)?;
+    file.session = Some(Session { window: window_layout(&app).or(session.window), ..session });
+    save(&app, &file)
+}
+
+/// The session to restore on launch, without tabs whose file is gone; None
+/// when there is none or the user chose to start clean.
+#[tauri::command]
+pub fn restore_session(app: AppHandle) -> Result<Option<Session>, String> {
+    let file = load(&app)?;
+    if !file.restore_on_launch {
+        return Ok(None);
+    }
+    Ok(file.session.map(|mut session| {
+        session.tabs.retain(|tab| tab.path.as_deref().is_none_or(|p| Path::new(p).is_file()));
+        if !session.tabs.iter().any(|tab| Some(&tab.id) == session.active_tab.as_ref()) {
+            session.active_tab = session.tabs.first().map(|tab| tab.id.clone());
+        }
+        session
+    }))
+}
+
+#[tauri::command]
+pub fn get_session_restore_enabled(app: AppHandle) -> Result<bool, String> {
+    Ok(load(&app)?.restore_on_launch)
+}
+
+#[tauri::command]
+pub fn set_session_restore_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut file = load(&app)?;
+    file.restore_on_launch = enabled;
+    save(&app, &file)
+}
+
+#[tauri::command]
+pub fn clear_session(app: AppHandle) -> Result<(), String> {
+    let mut file = load(&app)?;
+    file.session = None;
+    save(&app, &file)
+}
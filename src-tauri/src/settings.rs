@@ -0,0 +1,419 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::filters::Stage;
+use crate::{export_profiles, paths, workspace};
+
+const SETTINGS_FILE: &str = "settings.json";
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// Upgrades of the settings file: step `i` turns a version `i + 1` file into
+/// a version `i + 2` one. Appending a step bumps the schema version.
+const MIGRATIONS: &[fn(&mut Value)] = &[];
+const SETTINGS_VERSION: u32 = MIGRATIONS.len() as u32 + 1;
+
+/// Settings a workspace can't override: which programs run and where the
+/// app writes are the user's choice, not a cloned folder's. Export profiles
+/// name templates, defaults files and Lua filters, which run at export.
+/// `section.field` names a field of a section.
+const APP_ONLY_KEYS: &[&str] = &[
+    "version", "tools", "directories", "setup", "keybindings", "usageStats", "updates", "html", "import", "filters",
+    "export.profiles", "export.latexEngine",
+];
+
+static LOCK: Mutex<()> = Mutex::new(());
+/// Tool paths, temporary folder and HTML rules of the saved settings, for
+/// conversions that run without an AppHandle.
+static TOOLS: RwLock<Option<ToolPaths>> = RwLock::new(None);
+static TEMP_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+static HTML: RwLock<Option<HtmlSettings>> = RwLock::new(None);
+
+/// Paths of external programs, for those not on the PATH.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ToolPaths {
+    pub pandoc: Option<String>,
+    pub wkhtmltopdf: Option<String>,
+}
+
+/// Folders the app writes to, where the defaults don't suit; see
+/// `directories`.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Directories {
+    /// Temporary files: previews, pandoc inputs.
+    pub temp: Option<String>,
+    /// Where exports given a bare file name are written.
+    pub exports: Option<String>,
+    /// Media extracted by imports into documents not saved yet.
+    pub media: Option<String>,
+}
+
+/// Progress of the first-run setup.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SetupState {
+    pub completed_at: Option<String>,
+    /// Workspace created by the setup.
+    pub workspace: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportSettings {
+    /// Reuses the output of an identical earlier export.
+    pub cache: bool,
+    /// LaTeX engine of beamer exports that don't pick one.
+    pub latex_engine: Option<String>,
+    /// Options by output format, laid over the saved export profile.
+    pub profiles: BTreeMap<String, Value>,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        ExportSettings { cache: true, latex_engine: None, profiles: BTreeMap::new() }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ImportSettings {
+    /// Runs pandoc with `--sandbox`, so a document's includes can't read
+    /// other files or the network.
+    pub sandbox: bool,
+}
+
+impl Default for ImportSettings {
+    fn default() -> Self {
+        ImportSettings { sandbox: true }
+    }
+}
+
+/// Raw HTML of documents; see `sanitize`.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HtmlSettings {
+    /// Strips scripts and unknown markup from previews and HTML exports.
+    pub sanitize: bool,
+    /// Tags allowed besides the default ones.
+    pub allowed_tags: Vec<String>,
+    /// Attributes allowed on every tag besides the default ones.
+    pub allowed_attributes: Vec<String>,
+}
+
+impl Default for HtmlSettings {
+    fn default() -> Self {
+        HtmlSettings { sanitize: true, allowed_tags: Vec::new(), allowed_attributes: Vec::new() }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UpdateSettings {
+    /// "stable" or "beta"; see `updates`.
+    pub channel: String,
+}
+
+impl Default for UpdateSettings {
+    fn default() -> Self {
+        UpdateSettings { channel: "stable".to_string() }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SaveSettings {
+    /// Previous versions kept next to each saved file: `notes.md.bak`, then
+    /// `notes.md.bak.2`… 0 disables backups.
+    pub backup_count: usize,
+}
+
+impl Default for SaveSettings {
+    fn default() -> Self {
+        SaveSettings { backup_count: 1 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub version: u32,
+    pub tools: ToolPaths,
+    pub directories: Directories,
+    pub setup: SetupState,
+    pub export: ExportSettings,
+    pub import: ImportSettings,
+    /// Language of the spellchecker, e.g. "fr-FR".
+    pub spellcheck_language: Option<String>,
+    /// Stages of the app's Lua filters by name, over those chosen in the
+    /// filters list; null disables one.
+    pub filters: BTreeMap<String, Option<Stage>>,
+    /// Shortcuts the user changed, by action; see `keybindings`.
+    pub keybindings: BTreeMap<String, Vec<String>>,
+    /// Opt-in to local usage counts; see `usage`.
+    pub usage_stats: bool,
+    pub updates: UpdateSettings,
+    pub html: HtmlSettings,
+    pub save: SaveSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: SETTINGS_VERSION,
+            tools: ToolPaths::default(),
+            directories: Directories::default(),
+            setup: SetupState::default(),
+            export: ExportSettings::default(),
+            import: ImportSettings::default(),
+            spellcheck_language: None,
+            filters: BTreeMap::new(),
+            keybindings: BTreeMap::new(),
+            usage_stats: false,
+            updates: UpdateSettings::default(),
+            html: HtmlSettings::default(),
+            save: SaveSettings::default(),
+        }
+    }
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_config_dir(app)?.join(SETTINGS_FILE))
+}
+
+/// Brings settings written by an older version to the current schema.
+fn migrate(raw: &mut Value) {
+    // Files from before versioning are version 1
+    let version = raw.get("version").and_then(Value::as_u64).unwrap_or(1) as usize;
+    for step in MIGRATIONS.iter().skip(version.saturating_sub(1)) {
+        step(raw);
+    }
+    if version < SETTINGS_VERSION as usize {
+        raw["version"] = SETTINGS_VERSION.into();
+    }
+}
+
+/// The settings file as JSON, migrated to the current version. Fields this
+/// version doesn't know are kept, so a newer version's settings survive.
+fn load_raw(app: &AppHandle) -> Result<Value, String> {
+    let path = settings_path(app)?;
+    let Ok(json) = std::fs::read_to_string(&path) else {
+        return serde_json::to_value(Settings::default()).map_err(|e| e.to_string());
+    };
+    let mut raw: Value = serde_json::from_str(&json).map_err(|e| format!("Réglages invalides ({}): {}", path.display(), e))?;
+    if !raw.is_object() {
+        return Err(format!("Réglages invalides ({}): objet attendu", path.display()));
+    }
+    migrate(&mut raw);
+    Ok(raw)
+}
+
+fn workspace_settings_path(app: &AppHandle) -> Option<PathBuf> {
+    let root = app.state::<workspace::Workspace>().root().ok()?;
+    Some(paths::workspace_config_dir(&root).join(SETTINGS_FILE))
+}
+
+/// The open workspace's `.ohmymarkdown/settings.json`, without the keys it
+/// can't set; an empty object when there is none.
+fn load_workspace_raw(app: &AppHandle) -> Result<Value, String> {
+    let Some(json) = workspace_settings_path(app).and_then(|path| std::fs::read_to_string(path).ok()) else {
+        return Ok(Value::Object(Default::default()));
+    };
+    let mut raw: Value = serde_json::from_str(&json).map_err(|e| format!("Réglages de l'espace de travail invalides: {}", e))?;
+    let Some(fields) = raw.as_object_mut() else {
+        return Err("Réglages de l'espace de travail invalides: objet attendu".to_string());
+    };
+    for key in APP_ONLY_KEYS {
+        match key.split_once('.') {
+            Some((section, field)) => {
+                if let Some(section) = fields.get_mut(section).and_then(Value::as_object_mut) {
+                    section.remove(field);
+                }
+            }
+            None => {
+                fields.remove(*key);
+            }
+        }
+    }
+    Ok(raw)
+}
+
+/// Whether `fields` set the app-only `key`.
+fn sets(fields: &serde_json::Map<String, Value>, key: &str) -> bool {
+    match key.split_once('.') {
+        Some((section, field)) => fields.get(section).and_then(Value::as_object).is_some_and(|section| section.contains_key(field)),
+        None => fields.contains_key(key),
+    }
+}
+
+fn parse(raw: Value) -> Result<Settings, String> {
+    serde_json::from_value(raw).map_err(|e| format!("Réglages invalides: {}", e))
+}
+
+fn remember(settings: &Settings) {
+    if let Ok(mut tools) = TOOLS.write() {
+        *tools = Some(settings.tools.clone());
+    }
+    if let Ok(mut temp) = TEMP_DIR.write() {
+        *temp = settings.directories.temp.as_deref().filter(|d| !d.trim().is_empty()).map(PathBuf::from);
+    }
+    if let Ok(mut html) = HTML.write() {
+        *html = Some(settings.html.clone());
+    }
+}
+
+/// The settings in effect: the defaults, overridden by the app's settings,
+/// overridden by the open workspace's.
+fn effective(raw: Value, workspace: Value) -> Result<Settings, String> {
+    let mut raw = raw;
+    export_profiles::merge(&mut raw, workspace);
+    let settings = parse(raw)?;
+    remember(&settings);
+    Ok(settings)
+}
+
+/// The settings in effect. An unreadable workspace file is ignored, and an
+/// unreadable app file gives the defaults.
+pub fn load(app: &AppHandle) -> Settings {
+    let raw = load_raw(app).unwrap_or_else(|_| serde_json::to_value(Settings::default()).unwrap_or_default());
+    let workspace = load_workspace_raw(app).unwrap_or_else(|_| Value::Object(Default::default()));
+    effective(raw, workspace).unwrap_or_default()
+}
+
+/// The program to run for `name`: its configured path, or the name to look
+/// up on the PATH. In portable mode, relative paths are from the portable
+/// folder, since the drive letter of a USB stick changes.
+pub fn tool(name: &str) -> String {
+    let tools = TOOLS.read().ok();
+    let configured = tools.as_ref().and_then(|t| t.as_ref()).and_then(|t| match name {
+        "pandoc" => t.pandoc.clone(),
+        "wkhtmltopdf" => t.wkhtmltopdf.clone(),
+        _ => None,
+    });
+    match (configured.filter(|p| !p.trim().is_empty()), paths::portable_dir()) {
+        (Some(path), Some(portable)) if Path::new(&path).is_relative() => portable.join(path).to_string_lossy().to_string(),
+        (Some(path), _) => path,
+        (None, _) => name.to_string(),
+    }
+}
+
+/// Folder for temporary files: the configured one, or the system's.
+pub fn temp_dir() -> PathBuf {
+    TEMP_DIR.read().ok().and_then(|dir| dir.clone()).filter(|dir| dir.is_dir()).unwrap_or_else(std::env::temp_dir)
+}
+
+/// How raw HTML is sanitized.
+pub fn html() -> HtmlSettings {
+    HTML.read().ok().and_then(|html| html.clone()).unwrap_or_default()
+}
+
+/// The settings in effect, the open workspace's overrides included.
+#[tauri::command]
+pub fn get_settings(app: AppHandle) -> Result<Settings, String> {
+    effective(load_raw(&app)?, load_workspace_raw(&app)?)
+}
+
+/// What the open workspace overrides, as written in its settings file.
+#[tauri::command]
+pub fn get_workspace_settings(app: AppHandle) -> Result<Value, String> {
+    load_workspace_raw(&app)
+}
+
+fn check_version(raw: &Value) -> Result<(), String> {
+    if raw.get("version").and_then(Value::as_u64).unwrap_or(1) > SETTINGS_VERSION as u64 {
+        return Err("Ces réglages viennent d'une version plus récente de l'application; mettez-la à jour pour les modifier".to_string());
+    }
+    Ok(())
+}
+
+/// Saves `written` to `path` and tells every window `settings`, the
+/// settings now in effect.
+fn write(app: &AppHandle, path: &Path, written: &Value, settings: Settings) -> Result<Settings, String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(written).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("Impossible d'enregistrer les réglages: {}", e))?;
+    let _ = app.emit(SETTINGS_CHANGED_EVENT, &settings);
+    Ok(settings)
+}
+
+/// Merges `patch` into the settings of `scope`, saves them and tells every
+/// window the settings now in effect.
+pub fn update(app: &AppHandle, patch: Value, scope: Option<&str>) -> Result<Settings, String> {
+    let Some(fields) = patch.as_object() else {
+        return Err("Les réglages à modifier doivent être un objet".to_string());
+    };
+    let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+    match scope.unwrap_or("app") {
+        "app" => {
+            let mut raw = load_raw(app)?;
+            check_version(&raw)?;
+            export_profiles::merge(&mut raw, patch);
+            raw["version"] = SETTINGS_VERSION.into();
+            let settings = effective(raw.clone(), load_workspace_raw(app)?)?;
+            write(app, &settings_path(app)?, &raw, settings)
+        }
+        "workspace" => {
+            if let Some(key) = APP_ONLY_KEYS.iter().find(|key| sets(fields, key)) {
+                return Err(format!("Le réglage {} ne peut pas être propre à un espace de travail", key));
+            }
+            let path = workspace_settings_path(app).ok_or("Aucun espace de travail ouvert")?;
+            let mut workspace = load_workspace_raw(app)?;
+            export_profiles::merge(&mut workspace, patch);
+            let settings = effective(load_raw(app)?, workspace.clone())?;
+            write(app, &path, &workspace, settings)
+        }
+        other => Err(format!("Portée de réglages inconnue: {} (app ou workspace)", other)),
+    }
+}
+
+/// Sets the app setting `key` as a whole, for maps whose entries a merge
+/// can't remove.
+pub fn replace(app: &AppHandle, key: &str, value: Value) -> Result<Settings, String> {
+    let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+    let mut raw = load_raw(app)?;
+    check_version(&raw)?;
+    raw[key] = value;
+    raw["version"] = SETTINGS_VERSION.into();
+    let settings = effective(raw.clone(), load_workspace_raw(app)?)?;
+    write(app, &settings_path(app)?, &raw, settings)
+}
+
+/// Applies `patch`, a partial settings object merged key by key, to the app's
+/// settings or, with `scope` "workspace", to the open workspace's.
+#[tauri::command]
+pub fn update_settings(app: AppHandle, patch: Value, scope: Option<String>) -> Result<Settings, String> {
+    if patch.get("directories").is_some() {
+        return Err("Les dossiers se changent un par un, pour vérifier le nouvel emplacement et y déplacer le contenu".to_string());
+    }
+    update(&app, patch, scope.as_deref())
+}
+
+/// Replaces the app's settings with `raw`, from another machine: its tool
+/// paths, folders and setup state are dropped in favor of this machine's.
+pub fn import(app: &AppHandle, mut raw: Value) -> Result<Settings, String> {
+    if !raw.is_object() {
+        return Err("Réglages invalides: objet attendu".to_string());
+    }
+    let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+    let local = load_raw(app).unwrap_or_else(|_| Value::Object(Default::default()));
+    for key in ["tools", "directories", "setup"] {
+        match local.get(key) {
+            Some(value) => raw[key] = value.clone(),
+            None => {
+                if let Some(fields) = raw.as_object_mut() {
+                    fields.remove(key);
+                }
+            }
+        }
+    }
+    check_version(&raw)?;
+    migrate(&mut raw);
+    let settings = effective(raw.clone(), load_workspace_raw(app)?)?;
+    write(app, &settings_path(app)?, &raw, settings)
+}
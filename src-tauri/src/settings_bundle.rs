@@ -0,0 +1,190 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use tauri::AppHandle;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::{paths, scopes, settings};
+
+const MANIFEST: &str = "manifest.json";
+const BUNDLE_FORMAT: &str = "ohmymarkdown-settings";
+const BUNDLE_VERSION: u64 = 1;
+
+/// Config files that travel between machines. Accounts, remotes and webhooks
+/// hold credentials, and recent files, session and locks are paths of this
+/// machine, so they stay.
+const CONFIG_FILES: &[&str] = &["settings.json", "export-profiles.json", "lua-filters.json", "docx-styles.json"];
+/// App data folders of things the user added: document and pandoc templates,
+/// defaults presets, highlight themes, Lua filters and citation styles.
+const DATA_DIRS: &[&str] = &["templates", "pandoc-templates", "pandoc-defaults", "highlight-themes", "filters", "csl"];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsBundleReport {
+    /// Bundle entries written or read, manifest excepted.
+    pub files: Vec<String>,
+}
+
+fn write_error(e: impl std::fmt::Display) -> String {
+    format!("Erreur lors de l'écriture des réglages: {}", e)
+}
+
+fn add(zip: &mut ZipWriter<File>, name: &str, data: &[u8]) -> Result<(), String> {
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(name, options).and_then(|_| zip.write_all(data).map_err(Into::into)).map_err(write_error)
+}
+
+/// Files under `dir`, as paths relative to it with `/` separators.
+fn files_under(dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut out = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current).into_iter().flatten().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                let name = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+                out.push((name, path));
+            }
+        }
+    }
+    out.sort();
+    out
+}
+
+fn export(app: &AppHandle, output: &Path) -> Result<SettingsBundleReport, String> {
+    let config_dir = paths::app_config_dir(app)?;
+    let data_dir = paths::app_data_dir(app)?;
+    let file = File::create(output).map_err(|e| format!("Impossible de créer {}: {}", output.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    let manifest = serde_json::json!({
+        "format": BUNDLE_FORMAT,
+        "version": BUNDLE_VERSION,
+        "appVersion": app.package_info().version.to_string(),
+        "created": chrono::Utc::now().to_rfc3339(),
+    });
+    add(&mut zip, MANIFEST, manifest.to_string().as_bytes())?;
+    let mut files = Vec::new();
+    for name in CONFIG_FILES {
+        if let Ok(data) = std::fs::read(config_dir.join(name)) {
+            let entry = format!("config/{}", name);
+            add(&mut zip, &entry, &data)?;
+            files.push(entry);
+        }
+    }
+    for dir in DATA_DIRS {
+        for (relative, path) in files_under(&data_dir.join(dir)) {
+            let data = std::fs::read(&path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+            let entry = format!("data/{}/{}", dir, relative);
+            add(&mut zip, &entry, &data)?;
+            files.push(entry);
+        }
+    }
+    zip.finish().map_err(write_error)?;
+    Ok(SettingsBundleReport { files })
+}
+
+/// Where the bundle entry `name` goes, or None for one this version doesn't
+/// restore.
+fn destination(app: &AppHandle, name: &str) -> Result<Option<PathBuf>, String> {
+    if let Some(file) = name.strip_prefix("config/") {
+        return Ok(match CONFIG_FILES.contains(&file) {
+            true => Some(paths::app_config_dir(app)?.join(file)),
+            false => None,
+        });
+    }
+    let Some((dir, relative)) = name.strip_prefix("data/").and_then(|rest| rest.split_once('/')) else {
+        return Ok(None);
+    };
+    let relative = Path::new(relative);
+    if !DATA_DIRS.contains(&dir) || relative.as_os_str().is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Ok(None);
+    }
+    Ok(Some(paths::app_data_dir(app)?.join(dir).join(relative)))
+}
+
+fn import(app: &AppHandle, bundle: &Path) -> Result<SettingsBundleReport, String> {
+    let file = File::open(bundle).map_err(|e| format!("Impossible d'ouvrir {}: {}", bundle.display(), e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Archive de réglages invalide: {}", e))?;
+    let read = |archive: &mut ZipArchive<File>, index: usize| -> Result<(String, Vec<u8>), String> {
+        let mut entry = archive.by_index(index).map_err(|e| format!("Archive de réglages invalide: {}", e))?;
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).map_err(|e| format!("Archive de réglages invalide: {}", e))?;
+        Ok((entry.name().to_string(), data))
+    };
+
+    let manifest = archive
+        .index_for_name(MANIFEST)
+        .ok_or("Ce fichier n'est pas une archive de réglages OhMyMarkdown")?;
+    let manifest: Value = serde_json::from_slice(&read(&mut archive, manifest)?.1).map_err(|e| format!("Manifeste invalide: {}", e))?;
+    if manifest["format"] != BUNDLE_FORMAT {
+        return Err("Ce fichier n'est pas une archive de réglages OhMyMarkdown".to_string());
+    }
+    if manifest["version"].as_u64().unwrap_or(0) > BUNDLE_VERSION {
+        return Err("Cette archive vient d'une version plus récente de l'application".to_string());
+    }
+
+    // Everything is read and checked before anything is written, so a bad
+    // bundle changes nothing
+    let mut entries = Vec::new();
+    let mut app_settings = None;
+    for index in 0..archive.len() {
+        let (name, data) = read(&mut archive, index)?;
+        if name.ends_with('/') {
+            continue;
+        }
+        let Some(path) = destination(app, &name)? else { continue };
+        if name.starts_with("config/") {
+            let json: Value = serde_json::from_slice(&data).map_err(|e| format!("{} invalide: {}", name, e))?;
+            if name == "config/settings.json" {
+                app_settings = Some(json);
+                continue;
+            }
+        }
+        entries.push((name, path, data));
+    }
+
+    let mut files = Vec::new();
+    for (name, path, data) in entries {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+        }
+        std::fs::write(&path, data).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))?;
+        files.push(name);
+    }
+    if let Some(raw) = app_settings {
+        settings::import(app, raw)?;
+        files.push("config/settings.json".to_string());
+    }
+    Ok(SettingsBundleReport { files })
+}
+
+/// Zips the app's settings, export profiles and presets, templates, themes,
+/// filters and citation styles, to set up another machine the same way.
+#[tauri::command]
+pub async fn export_settings_bundle(app: AppHandle, output_path: String) -> Result<SettingsBundleReport, String> {
+    let output = scopes::check(&app, &output_path)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = export(&app, &output);
+        if result.is_err() {
+            let _ = std::fs::remove_file(&output);
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de l'export des réglages: {}", e))?
+}
+
+/// Restores a bundle made by `export_settings_bundle`, replacing the files it
+/// holds and keeping this machine's tool paths.
+#[tauri::command]
+pub async fn import_settings_bundle(app: AppHandle, bundle_path: String) -> Result<SettingsBundleReport, String> {
+    let bundle = scopes::check(&app, &bundle_path)?;
+    tauri::async_runtime::spawn_blocking(move || import(&app, &bundle))
+        .await
+        .map_err(|e| format!("Erreur lors de l'import des réglages: {}", e))?
+}
@@ -0,0 +1,189 @@
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::Command;
+use tauri::{AppHandle, Manager};
+
+use crate::{diagnostics, scopes, settings, templates};
+
+/// Folder created in Documents when the user doesn't pick a workspace.
+const DEFAULT_WORKSPACE_NAME: &str = "OhMyMarkdown";
+
+struct SetupTool {
+    name: &'static str,
+    /// Program whose version tells the tool is there.
+    program: &'static str,
+    version_arg: &'static str,
+    required: bool,
+    /// What the app uses it for, for the wizard.
+    purpose: &'static str,
+    download_url: &'static str,
+    windows: &'static [&'static str],
+    macos: &'static [&'static str],
+    linux: &'static [&'static str],
+}
+
+const TOOLS: &[SetupTool] = &[
+    SetupTool {
+        name: "pandoc",
+        program: "pandoc",
+        version_arg: "--version",
+        required: true,
+        purpose: "Import et export de documents (Word, PDF, EPUB...)",
+        download_url: "https://pandoc.org/installing.html",
+        windows: &["winget", "install", "-e", "--id", "JohnMacFarlane.Pandoc", "--accept-source-agreements", "--accept-package-agreements"],
+        macos: &["brew", "install", "pandoc"],
+        linux: &["sudo", "apt", "install", "pandoc"],
+    },
+    SetupTool {
+        name: "wkhtmltopdf",
+        program: "wkhtmltopdf",
+        version_arg: "--version",
+        required: false,
+        purpose: "Export PDF",
+        download_url: "https://wkhtmltopdf.org/downloads.html",
+        windows: &["winget", "install", "-e", "--id", "wkhtmltopdf.wkhtmltox", "--accept-source-agreements", "--accept-package-agreements"],
+        macos: &["brew", "install", "--cask", "wkhtmltopdf"],
+        linux: &["sudo", "apt", "install", "wkhtmltopdf"],
+    },
+    SetupTool {
+        name: "latex",
+        program: "xelatex",
+        version_arg: "--version",
+        required: false,
+        purpose: "Diapositives beamer et formules en SVG",
+        download_url: "https://www.latex-project.org/get/",
+        windows: &["winget", "install", "-e", "--id", "MiKTeX.MiKTeX", "--accept-source-agreements", "--accept-package-agreements"],
+        macos: &["brew", "install", "--cask", "mactex-no-gui"],
+        linux: &["sudo", "apt", "install", "texlive-xetex", "texlive-latex-extra", "dvisvgm"],
+    },
+    SetupTool {
+        name: "java",
+        program: "java",
+        version_arg: "-version",
+        required: false,
+        purpose: "Correcteur grammatical LanguageTool",
+        download_url: "https://adoptium.net/",
+        windows: &["winget", "install", "-e", "--id", "EclipseAdoptium.Temurin.21.JRE", "--accept-source-agreements", "--accept-package-agreements"],
+        macos: &["brew", "install", "--cask", "temurin"],
+        linux: &["sudo", "apt", "install", "default-jre"],
+    },
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCheck {
+    pub name: &'static str,
+    pub required: bool,
+    pub purpose: &'static str,
+    pub version: Option<String>,
+    /// Command installing it on this platform.
+    pub install_command: Vec<&'static str>,
+    /// False when the command needs a terminal (sudo): shown for the user
+    /// to run rather than run by `install_setup_tool`.
+    pub can_install: bool,
+    pub download_url: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetupStatus {
+    pub completed: bool,
+    pub tools: Vec<ToolCheck>,
+    /// Required tools still missing.
+    pub missing_required: Vec<&'static str>,
+    /// The workspace `complete_setup` creates when given none.
+    pub default_workspace: Option<String>,
+}
+
+fn install_command(tool: &SetupTool) -> &'static [&'static str] {
+    if cfg!(target_os = "windows") {
+        tool.windows
+    } else if cfg!(target_os = "macos") {
+        tool.macos
+    } else {
+        tool.linux
+    }
+}
+
+fn check(tool: &SetupTool) -> ToolCheck {
+    let command = install_command(tool);
+    ToolCheck {
+        name: tool.name,
+        required: tool.required,
+        purpose: tool.purpose,
+        version: diagnostics::tool_version(tool.program, tool.version_arg),
+        install_command: command.to_vec(),
+        can_install: command.first().is_some_and(|program| *program != "sudo"),
+        download_url: tool.download_url,
+    }
+}
+
+fn default_workspace(app: &AppHandle) -> Option<PathBuf> {
+    app.path().document_dir().ok().map(|dir| dir.join(DEFAULT_WORKSPACE_NAME))
+}
+
+fn status(app: &AppHandle) -> SetupStatus {
+    let tools: Vec<ToolCheck> = TOOLS.iter().map(check).collect();
+    SetupStatus {
+        completed: settings::load(app).setup.completed_at.is_some(),
+        missing_required: tools.iter().filter(|t| t.required && t.version.is_none()).map(|t| t.name).collect(),
+        tools,
+        default_workspace: default_workspace(app).map(|dir| dir.to_string_lossy().to_string()),
+    }
+}
+
+/// Whether the first-run setup was done, and which tools are missing.
+#[tauri::command]
+pub async fn get_setup_status(app: AppHandle) -> Result<SetupStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || status(&app))
+        .await
+        .map_err(|e| format!("Erreur lors de la vérification de l'installation: {}", e))
+}
+
+/// Runs the install command of `tool` for this platform, with the system's
+/// package manager.
+#[tauri::command]
+pub async fn install_setup_tool(tool: String) -> Result<ToolCheck, String> {
+    let setup_tool = TOOLS.iter().find(|t| t.name == tool).ok_or_else(|| format!("Outil inconnu: {}", tool))?;
+    let command = install_command(setup_tool);
+    let Some((program, args)) = command.split_first().filter(|(program, _)| **program != "sudo") else {
+        return Err(format!("Installez {} depuis un terminal: {}", tool, command.join(" ")));
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let output = Command::new(program)
+            .args(args)
+            .output()
+            .map_err(|e| format!("Erreur lors de l'exécution de {}: {}", program, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Installation échouée: {} {}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(check(setup_tool))
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de l'installation: {}", e))?
+}
+
+/// Creates the workspace (Documents/OhMyMarkdown by default) and the
+/// templates folder, then records the setup as done. Returns the workspace
+/// folder for the frontend to open.
+#[tauri::command]
+pub fn complete_setup(app: AppHandle, workspace_dir: Option<String>) -> Result<String, String> {
+    let workspace = match workspace_dir.map(|dir| dir.trim().to_string()).filter(|dir| !dir.is_empty()) {
+        Some(dir) => scopes::check(&app, &dir)?,
+        None => default_workspace(&app).ok_or("Impossible de déterminer le dossier Documents")?,
+    };
+    std::fs::create_dir_all(&workspace).map_err(|e| format!("Impossible de créer le dossier {}: {}", workspace.display(), e))?;
+    scopes::grant(&app, &workspace)?;
+    templates::app_templates_dir(&app)?;
+    let workspace = workspace.to_string_lossy().to_string();
+    settings::update(
+        &app,
+        serde_json::json!({ "setup": { "completedAt": chrono::Local::now().to_rfc3339(), "workspace": workspace } }),
+        None,
+    )?;
+    Ok(workspace)
+}
@@ -0,0 +1,416 @@
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag, TagEnd};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+use crate::workspace::{self, TreeNode};
+use crate::{assets, backlinks, frontmatter, markdown, obsidian, sanitize, scopes, svg, updates};
+
+pub const STYLE: &str = include_str!("site_export/style.css");
+const SEARCH_SCRIPT: &str = include_str!("site_export/search.js");
+/// Folder of the site's own files, named so it can't clash with a note.
+const SITE_DIR: &str = "_site";
+const THEMES: &[&str] = &["auto", "light", "dark"];
+/// Notes that introduce their folder, in order of preference.
+const SECTION_INTROS: &[&str] = &["index", "readme"];
+/// Text kept per page in the search index.
+const MAX_INDEXED_CHARS: usize = 20_000;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteExportReport {
+    pub pages: usize,
+    pub sections: usize,
+    pub assets: usize,
+    /// Linked files left out because they live outside the workspace or are
+    /// missing.
+    pub skipped: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct SearchEntry {
+    title: String,
+    url: String,
+    text: String,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn url_string(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// URL of `to` from the page at `from`, both relative to the site root.
+fn relative_url(from: &Path, to: &Path) -> String {
+    let base = from.parent().unwrap_or(Path::new(""));
+    url_string(&pathdiff::diff_paths(to, base).unwrap_or_else(|| to.to_path_buf()))
+}
+
+fn is_intro(node: &TreeNode, name: &str) -> bool {
+    node.kind == "markdown" && Path::new(&node.name).file_stem().is_some_and(|s| s.to_string_lossy().eq_ignore_ascii_case(name))
+}
+
+fn has_pages(node: &TreeNode) -> bool {
+    node.kind == "markdown" || node.children.iter().any(has_pages)
+}
+
+/// Renders markdown to HTML, giving every heading an id so `#heading` links
+/// land. Raw HTML is sanitized.
+pub fn render(md: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_HEADING_ATTRIBUTES;
+    let mut events: Vec<Event> = Parser::new_ext(md, options).collect();
+    let mut used = HashSet::new();
+    for i in 0..events.len() {
+        if !matches!(&events[i], Event::Start(Tag::Heading { id: None, .. })) {
+            continue;
+        }
+        let mut text = String::new();
+        for event in &events[i + 1..] {
+            match event {
+                Event::Text(t) | Event::Code(t) => text.push_str(t),
+                Event::End(TagEnd::Heading(_)) => break,
+                _ => {}
+            }
+        }
+        let slug = Some(markdown::slug(&text)).filter(|s| !s.is_empty()).unwrap_or_else(|| "section".to_string());
+        let mut unique = slug.clone();
+        let mut n = 1;
+        while !used.insert(unique.clone()) {
+            unique = format!("{}-{}", slug, n);
+            n += 1;
+        }
+        if let Event::Start(Tag::Heading { id, .. }) = &mut events[i] {
+            *id = Some(CowStr::from(unique));
+        }
+    }
+    let mut out = String::new();
+    html::push_html(&mut out, events.into_iter());
+    sanitize::clean(&svg::sanitize_embedded(&out))
+}
+
+/// Page of a note in the exported site, relative to its root: the folder's
+/// index for the note introducing it.
+pub fn page_path(root: &Path, note: &Path) -> PathBuf {
+    let relative = note.strip_prefix(root).unwrap_or(note);
+    let folder = note.parent().unwrap_or(root);
+    let siblings: Vec<PathBuf> = std::fs::read_dir(folder)
+        .map(|entries| entries.flatten().map(|e| e.path()).filter(|p| markdown::is_markdown_file(p)).collect())
+        .unwrap_or_default();
+    let stem_is = |path: &Path, name: &str| path.file_stem().is_some_and(|s| s.to_string_lossy().eq_ignore_ascii_case(name));
+    let intro = SECTION_INTROS.iter().find_map(|name| siblings.iter().find(|p| stem_is(p, name)));
+    if intro.is_some_and(|i| i == note) {
+        relative.parent().unwrap_or(Path::new("")).join("index.html")
+    } else {
+        relative.with_extension("html")
+    }
+}
+
+struct Site<'a> {
+    root: &'a Path,
+    /// Built-in theme the pages ask for.
+    theme: &'a str,
+    name: String,
+    /// Page of every note, relative to the site root.
+    pages: HashMap<PathBuf, PathBuf>,
+    titles: HashMap<PathBuf, String>,
+    by_stem: HashMap<String, Vec<PathBuf>>,
+    /// Files by lowercased name, for `![[file.png]]` embeds.
+    files: HashMap<String, PathBuf>,
+    linked: BTreeSet<PathBuf>,
+    skipped: BTreeSet<String>,
+    search: Vec<SearchEntry>,
+}
+
+impl Site<'_> {
+    fn relative(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(self.root).unwrap_or(path).to_path_buf()
+    }
+
+    /// Maps the notes of `folder` to their pages, its intro note becoming the
+    /// folder's index.
+    fn collect(&mut self, folder: &TreeNode) {
+        let intro = SECTION_INTROS.iter().find_map(|name| folder.children.iter().find(|c| is_intro(c, name)));
+        let folder_url = self.relative(Path::new(&folder.path));
+        for child in &folder.children {
+            match child.kind {
+                "folder" => self.collect(child),
+                "markdown" => {
+                    let path = PathBuf::from(&child.path);
+                    let url = if intro.is_some_and(|i| i.path == child.path) {
+                        folder_url.join("index.html")
+                    } else {
+                        self.relative(&path).with_extension("html")
+                    };
+                    if let Some(stem) = path.file_stem() {
+                        self.by_stem.entry(stem.to_string_lossy().to_lowercase()).or_default().push(path.clone());
+                    }
+                    self.pages.insert(path, url);
+                }
+                _ => {
+                    self.files.entry(child.name.to_lowercase()).or_insert_with(|| PathBuf::from(&child.path));
+                }
+            }
+        }
+    }
+
+    fn wikilink_page(&self, target: &str) -> Option<&PathBuf> {
+        let last = target.trim().replace('\\', "/").rsplit('/').next().unwrap_or_default().to_string();
+        let stem = if markdown::is_markdown_file(Path::new(&last)) {
+            Path::new(&last).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+        } else {
+            last
+        };
+        let note = self.by_stem.get(&stem.to_lowercase())?.iter().find(|n| backlinks::wikilink_points_to(target, n, self.root))?;
+        self.pages.get(note)
+    }
+
+    /// Points links to notes at their pages, turns wikilinks into markdown
+    /// links and records the linked files to copy.
+    fn rewrite_links(&mut self, md: &str, note: &Path, page: &Path) -> String {
+        let dir = note.parent();
+        let mut replacements = Vec::new();
+        for link in markdown::links(md) {
+            let url = link.url(md);
+            if !assets::is_local_link(url) {
+                continue;
+            }
+            let (path, fragment) = url.split_at(url.find('#').unwrap_or(url.len()));
+            let target = assets::resolve_link(path, dir);
+            if markdown::is_markdown_file(&target) {
+                if let Some(target_page) = self.pages.get(&target) {
+                    let href = format!("{}{}", assets::link_to(target_page, page.parent()), fragment);
+                    replacements.push(assets::replacement(md, &link, href));
+                }
+            } else if target.starts_with(self.root) && target.is_file() {
+                self.linked.insert(target);
+            } else {
+                self.skipped.insert(target.to_string_lossy().to_string());
+            }
+        }
+
+        for link in markdown::wikilinks(md) {
+            let Some(close) = md[link.end..].find("]]") else { continue };
+            let rest = &md[link.end..link.end + close];
+            let embed = link.start >= 3 && md.as_bytes()[link.start - 3] == b'!';
+            let start = link.start - if embed { 3 } else { 2 };
+            let target = link.target(md);
+            let label = rest.split_once('|').map(|(_, alias)| alias.trim()).unwrap_or(target);
+            let label = label.replace('[', "\\[").replace(']', "\\]");
+            let heading = rest.split('|').next().and_then(|h| h.strip_prefix('#')).map(str::trim).filter(|h| !h.is_empty());
+
+            // Embeds name a file relative to the note or anywhere in the workspace
+            let file = Some(assets::resolve_link(target, dir))
+                .filter(|f| f.is_file())
+                .or_else(|| self.files.get(&target.trim().to_lowercase()).cloned())
+                .unwrap_or_default();
+            let text = if embed && !markdown::is_markdown_file(&file) && file.starts_with(self.root) && file.is_file() {
+                let text = format!("![{}]({})", label, assets::link_to(&file, dir));
+                self.linked.insert(file);
+                text
+            } else {
+                match self.wikilink_page(target) {
+                    Some(target_page) => {
+                        let fragment = heading.map(|h| format!("#{}", markdown::slug(h))).unwrap_or_default();
+                        format!("[{}]({}{})", label, assets::link_to(target_page, page.parent()), fragment)
+                    }
+                    None => label,
+                }
+            };
+            replacements.push((start, link.end + close + 2, text));
+        }
+        markdown::replace_ranges(md, replacements)
+    }
+
+    fn layout(&self, page: &Path, title: &str, content: &str) -> String {
+        let site_file = |name: &str| escape(&relative_url(page, &Path::new(SITE_DIR).join(name)));
+        let mut crumbs = vec![format!("<a href=\"{}\">{}</a>", escape(&relative_url(page, Path::new("index.html"))), escape(&self.name))];
+        let mut folder = PathBuf::new();
+        for component in page.parent().into_iter().flat_map(Path::components) {
+            folder.push(component);
+            let index = folder.join("index.html");
+            if index != page {
+                let name = component.as_os_str().to_string_lossy();
+                crumbs.push(format!("<a href=\"{}\">{}</a>", escape(&relative_url(page, &index)), escape(&name)));
+            }
+        }
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"fr\" data-theme=\"{theme}\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n<title>{title} – {site}</title>\n\
+             <link rel=\"stylesheet\" href=\"{style}\">\n<link rel=\"stylesheet\" href=\"{theme_css}\">\n</head>\n<body>\n\
+             <header>\n<nav class=\"breadcrumbs\">{crumbs}</nav>\n\
+             <form class=\"search\" action=\"{search}\"><input type=\"search\" name=\"q\" placeholder=\"Rechercher\"></form>\n</header>\n\
+             <main>\n{content}</main>\n</body>\n</html>\n",
+            theme = self.theme,
+            title = escape(title),
+            site = escape(&self.name),
+            style = site_file("style.css"),
+            theme_css = site_file("theme.css"),
+            crumbs = crumbs.join(" / "),
+            search = site_file("search.html"),
+            content = content,
+        )
+    }
+
+    /// Links to the subsections and pages of a folder.
+    fn listing(&self, folder: &TreeNode, page: &Path) -> String {
+        let mut sections = String::new();
+        let mut pages = String::new();
+        for child in &folder.children {
+            let path = PathBuf::from(&child.path);
+            if child.kind == "folder" && has_pages(child) {
+                let index = self.relative(&path).join("index.html");
+                sections.push_str(&format!("<li><a href=\"{}\">{}</a></li>\n", escape(&relative_url(page, &index)), escape(&child.name)));
+            } else if let Some(url) = self.pages.get(&path).filter(|url| *url != page) {
+                let title = self.titles.get(&path).map(String::as_str).unwrap_or(&child.name);
+                pages.push_str(&format!("<li><a href=\"{}\">{}</a></li>\n", escape(&relative_url(page, url)), escape(title)));
+            }
+        }
+        let mut out = String::new();
+        if !sections.is_empty() {
+            out.push_str(&format!("<section class=\"sections\">\n<h2>Sections</h2>\n<ul>\n{}</ul>\n</section>\n", sections));
+        }
+        if !pages.is_empty() {
+            out.push_str(&format!("<section class=\"pages\">\n<h2>Pages</h2>\n<ul>\n{}</ul>\n</section>\n", pages));
+        }
+        out
+    }
+}
+
+fn write(output: &Path, relative: &Path, data: &[u8]) -> Result<(), String> {
+    let path = output.join(relative);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer le dossier {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&path, data).map_err(|e| format!("Impossible d'écrire {}: {}", path.display(), e))
+}
+
+/// Drops the output folder from the tree when it lives in the workspace.
+fn prune(node: &mut TreeNode, output: &Path) {
+    node.children.retain(|c| !Path::new(&c.path).starts_with(output));
+    for child in &mut node.children {
+        prune(child, output);
+    }
+}
+
+fn export(root: &Path, output: &Path, theme: &str) -> Result<SiteExportReport, String> {
+    let theme_css = if THEMES.contains(&theme) {
+        String::new()
+    } else {
+        std::fs::read_to_string(theme).map_err(|_| format!("Thème inconnu: {} (auto, light, dark ou fichier CSS)", theme))?
+    };
+    let mut tree = workspace::build_tree(root);
+    prune(&mut tree, output);
+
+    let mut site = Site {
+        root,
+        // A custom stylesheet goes on top of the automatic theme
+        theme: if THEMES.contains(&theme) { theme } else { THEMES[0] },
+        name: root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        pages: HashMap::new(),
+        titles: HashMap::new(),
+        by_stem: HashMap::new(),
+        files: HashMap::new(),
+        linked: BTreeSet::new(),
+        skipped: BTreeSet::new(),
+        search: Vec::new(),
+    };
+    site.collect(&tree);
+    let vault = obsidian::is_vault(root);
+    let notes: HashMap<PathBuf, String> = site
+        .pages
+        .keys()
+        .filter_map(|note| std::fs::read_to_string(note).ok().map(|md| (note.clone(), md)))
+        .collect();
+    for (note, md) in &notes {
+        site.titles.insert(note.clone(), frontmatter::title(md, Some(note)));
+    }
+
+    // Depth first, so every folder is rendered with its intro note if any
+    let mut folders = vec![&tree];
+    let mut pages = 0;
+    let mut sections = 0;
+    while let Some(folder) = folders.pop() {
+        let folder_path = Path::new(&folder.path);
+        let index = site.relative(folder_path).join("index.html");
+        let mut has_index = false;
+        for child in &folder.children {
+            let path = PathBuf::from(&child.path);
+            if child.kind == "folder" && has_pages(child) {
+                folders.push(child);
+                continue;
+            }
+            let (Some(md), Some(page)) = (notes.get(&path), site.pages.get(&path).cloned()) else { continue };
+            let body = site.rewrite_links(frontmatter::body(md), &path, &page);
+            let body = if vault { obsidian::convert_blocks(&body) } else { body };
+            let title = site.titles.get(&path).cloned().unwrap_or_default();
+            let mut content = render(&body);
+            if page == index {
+                content.push_str(&site.listing(folder, &page));
+                has_index = true;
+            }
+            write(output, &page, site.layout(&page, &title, &content).as_bytes())?;
+            let text: String = markdown::to_plain_text(md).text.chars().take(MAX_INDEXED_CHARS).collect();
+            site.search.push(SearchEntry { title, url: url_string(&page), text });
+            pages += 1;
+        }
+        if !has_index {
+            let name = if folder_path == root { site.name.clone() } else { folder.name.clone() };
+            let content = format!("<h1>{}</h1>\n{}", escape(&name), site.listing(folder, &index));
+            write(output, &index, site.layout(&index, &name, &content).as_bytes())?;
+        }
+        sections += 1;
+    }
+
+    let assets: Vec<PathBuf> = site.linked.iter().filter(|p| !p.starts_with(output)).cloned().collect();
+    for asset in &assets {
+        let target = output.join(site.relative(asset));
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer le dossier {}: {}", parent.display(), e))?;
+        }
+        std::fs::copy(asset, &target).map_err(|e| format!("Impossible de copier {}: {}", asset.display(), e))?;
+    }
+
+    let search_page = Path::new(SITE_DIR).join("search.html");
+    // Inline, so search also works from file:// where fetch is blocked
+    let index_json = serde_json::to_string(&site.search).map_err(|e| e.to_string())?.replace("</", "<\\/");
+    let search = format!(
+        "<h1>Rechercher</h1>\n<input type=\"search\" id=\"search-input\" placeholder=\"Rechercher dans le site\" autofocus>\n\
+         <ol id=\"search-results\" data-base=\"../\"></ol>\n\
+         <script type=\"application/json\" id=\"search-index\">{}</script>\n<script src=\"search.js\"></script>\n",
+        index_json
+    );
+    write(output, &search_page, site.layout(&search_page, "Rechercher", &search).as_bytes())?;
+    write(output, &Path::new(SITE_DIR).join("style.css"), STYLE.as_bytes())?;
+    write(output, &Path::new(SITE_DIR).join("theme.css"), theme_css.as_bytes())?;
+    write(output, &Path::new(SITE_DIR).join("search.js"), SEARCH_SCRIPT.as_bytes())?;
+
+    Ok(SiteExportReport { pages, sections, assets: assets.len(), skipped: site.skipped.into_iter().collect() })
+}
+
+/// Renders the workspace as a static HTML site in `output_dir`: a page per
+/// note with links between notes pointing at their pages, an index page per
+/// folder (its index or README note when there is one) and a search page.
+/// `theme` is "auto" (the default), "light", "dark" or a CSS file applied on
+/// top.
+#[tauri::command]
+pub async fn export_site(app: AppHandle, output_dir: String, theme: Option<String>) -> Result<SiteExportReport, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    let output = scopes::check(&app, &output_dir)?;
+    let theme = theme.unwrap_or_else(|| THEMES[0].to_string());
+    tauri::async_runtime::spawn_blocking(move || {
+        let _busy = updates::busy()?;
+        if output == root {
+            return Err("Choisissez un dossier de sortie distinct du dossier de travail".to_string());
+        }
+        export(&root, &output, &theme)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de l'export du site: {}", e))?
+}
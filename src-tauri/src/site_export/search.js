@@ -0,0 +1,64 @@
+(function () {
+  var index = JSON.parse(document.getElementById("search-index").textContent);
+  var input = document.getElementById("search-input");
+  var results = document.getElementById("search-results");
+  var base = results.dataset.base;
+  var maxResults = 50;
+
+  // Accents are ignored, as in the app's search
+  function normalize(text) {
+    return text.normalize("NFD").replace(/[\u0300-\u036f]/g, "").toLowerCase();
+  }
+
+  index.forEach(function (page) {
+    page.normalizedTitle = normalize(page.title);
+    page.normalizedText = normalize(page.text);
+  });
+
+  function snippet(page, term) {
+    var at = Math.max(0, page.normalizedText.indexOf(term));
+    var start = Math.max(0, at - 60);
+    var text = page.text.slice(start, at + 140).replace(/\s+/g, " ").trim();
+    return (start > 0 ? "… " : "") + text + " …";
+  }
+
+  function update() {
+    var terms = normalize(input.value).split(/\s+/).filter(Boolean);
+    results.replaceChildren();
+    if (!terms.length) {
+      return;
+    }
+    index
+      .filter(function (page) {
+        return terms.every(function (term) {
+          return page.normalizedTitle.includes(term) || page.normalizedText.includes(term);
+        });
+      })
+      .sort(function (a, b) {
+        var inTitle = function (page) {
+          return terms.every(function (term) { return page.normalizedTitle.includes(term); }) ? 0 : 1;
+        };
+        return inTitle(a) - inTitle(b) || a.title.localeCompare(b.title);
+      })
+      .slice(0, maxResults)
+      .forEach(function (page) {
+        var item = document.createElement("li");
+        var link = document.createElement("a");
+        link.href = base + page.url;
+        link.textContent = page.title;
+        var excerpt = document.createElement("p");
+        excerpt.textContent = snippet(page, terms[0]);
+        item.append(link, excerpt);
+        results.append(item);
+      });
+    if (!results.children.length) {
+      var empty = document.createElement("li");
+      empty.textContent = "Aucun résultat";
+      results.append(empty);
+    }
+  }
+
+  input.addEventListener("input", update);
+  input.value = new URLSearchParams(location.search).get("q") || "";
+  update();
+})();
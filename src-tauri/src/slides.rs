@@ -0,0 +1,154 @@
+use serde::Deserialize;
+
+use crate::{latex, markdown};
+
+const REVEALJS_THEMES: &[&str] =
+    &["black", "white", "league", "beige", "sky", "night", "serif", "simple", "solarized", "blood", "moon", "dracula"];
+const BEAMER_THEMES: &[&str] = &[
+    "default", "AnnArbor", "Antibes", "Bergen", "Berkeley", "Berlin", "Boadilla", "CambridgeUS", "Copenhagen", "Darmstadt", "Dresden",
+    "Frankfurt", "Goettingen", "Hannover", "Ilmenau", "JuanLesPins", "Luebeck", "Madrid", "Malmoe", "Marburg", "Montpellier",
+    "PaloAlto", "Pittsburgh", "Rochester", "Singapore", "Szeged", "Warsaw",
+];
+const BEAMER_COLOR_THEMES: &[&str] = &[
+    "default", "albatross", "beaver", "beetle", "crane", "dolphin", "dove", "fly", "lily", "orchid", "rose", "seagull", "seahorse", "whale",
+    "wolverine",
+];
+/// Beamer's `aspectratio` class option values.
+const BEAMER_ASPECT_RATIOS: &[&str] = &["43", "169", "1610", "149", "141", "54", "32"];
+
+/// Options of presentation exports (reveal.js, PowerPoint, beamer).
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SlideOptions {
+    /// Heading level that starts a slide; pandoc picks one by default.
+    pub slide_level: Option<u8>,
+    /// Reveals list items one at a time.
+    pub incremental: bool,
+    /// reveal.js or beamer theme; PowerPoint takes a reference document.
+    pub theme: Option<String>,
+    /// Beamer color theme.
+    pub color_theme: Option<String>,
+    /// Beamer slide proportions, e.g. "169" for 16:9 or "43" for 4:3.
+    pub aspect_ratio: Option<String>,
+    /// LaTeX engine for beamer, the first installed by default.
+    pub pdf_engine: Option<String>,
+    /// What happens to `::: notes` divs: "keep" (the default) for the
+    /// speaker view, "remove" for handouts, or "show" on beamer slides.
+    pub speaker_notes: Option<String>,
+}
+
+pub fn is_slide_format(to_format: &str) -> bool {
+    matches!(to_format, "revealjs" | "pptx" | "beamer" | "slidy" | "slideous" | "dzslides" | "s5")
+}
+
+fn is_notes_opening(line: &str) -> bool {
+    let Some(rest) = line.trim().strip_prefix(":::") else {
+        return false;
+    };
+    let attributes = rest.trim_start_matches(':').trim();
+    attributes == "notes" || (attributes.starts_with('{') && attributes.split_whitespace().any(|a| a.trim_matches(['{', '}']) == ".notes"))
+}
+
+/// Drops the `::: notes` divs of a presentation, nested divs included.
+fn remove_notes(md: &str) -> String {
+    let mut out = String::with_capacity(md.len());
+    let mut fences = markdown::FenceState::default();
+    // Open divs inside the notes being dropped
+    let mut depth = 0;
+    for line in md.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let in_code = fences.is_code(content);
+        if depth == 0 {
+            if !in_code && is_notes_opening(content) {
+                depth = 1;
+            } else {
+                out.push_str(line);
+            }
+            continue;
+        }
+        let trimmed = content.trim();
+        if !in_code && trimmed.starts_with(":::") {
+            if trimmed.trim_start_matches(':').trim().is_empty() {
+                depth -= 1;
+            } else {
+                depth += 1;
+            }
+        }
+    }
+    out
+}
+
+impl SlideOptions {
+    /// The markdown exported to `to_format`.
+    pub fn prepare(&self, md: &str, to_format: &str) -> String {
+        if is_slide_format(to_format) && self.speaker_notes.as_deref() == Some("remove") {
+            remove_notes(md)
+        } else {
+            md.to_string()
+        }
+    }
+
+    pub fn pandoc_args(&self, to_format: &str) -> Result<Vec<String>, String> {
+        if !is_slide_format(to_format) {
+            return Ok(Vec::new());
+        }
+        // Fragments of slides don't show
+        let mut args = vec!["--standalone".to_string()];
+        if let Some(level) = self.slide_level {
+            if level > 6 {
+                return Err(format!("Niveau de diapositive invalide: {} (0 à 6)", level));
+            }
+            args.push(format!("--slide-level={}", level));
+        }
+        if self.incremental {
+            args.push("--incremental".to_string());
+        }
+        if let Some(theme) = self.theme.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+            let known = match to_format {
+                "revealjs" => REVEALJS_THEMES,
+                "beamer" => BEAMER_THEMES,
+                _ => return Err(format!("Le format {} ne prend pas de thème; utilisez un document de référence", to_format)),
+            };
+            if !known.contains(&theme) {
+                return Err(format!("Thème inconnu pour {}: {}", to_format, theme));
+            }
+            args.push(format!("--variable=theme:{}", theme));
+        }
+        if to_format == "beamer" {
+            for (value, known, variable, label) in [
+                (&self.color_theme, BEAMER_COLOR_THEMES, "colortheme", "Thème de couleurs"),
+                (&self.aspect_ratio, BEAMER_ASPECT_RATIOS, "aspectratio", "Format de diapositive"),
+            ] {
+                if let Some(value) = value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                    if !known.contains(&value) {
+                        return Err(format!("{} beamer inconnu: {}", label, value));
+                    }
+                    args.push(format!("--variable={}:{}", variable, value));
+                }
+            }
+            args.push(latex::pdf_engine_arg(self.pdf_engine.as_deref())?);
+        }
+        match self.speaker_notes.as_deref() {
+            None | Some("keep") | Some("remove") => {}
+            Some("show") if to_format == "beamer" => args.push("--variable=classoption:notes".to_string()),
+            Some("show") => return Err("Seul beamer peut afficher les notes sur les diapositives".to_string()),
+            Some(other) => return Err(format!("Traitement des notes inconnu: {} (keep, remove ou show)", other)),
+        }
+        Ok(args)
+    }
+}
+
+/// Themes a presentation format offers.
+#[tauri::command]
+pub fn list_slide_themes(format: String) -> Vec<&'static str> {
+    match format.as_str() {
+        "revealjs" => REVEALJS_THEMES.to_vec(),
+        "beamer" => BEAMER_THEMES.to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+#[tauri::command]
+pub fn list_beamer_color_themes() -> Vec<&'static str> {
+    BEAMER_COLOR_THEMES.to_vec()
+}
@@ -0,0 +1,376 @@
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Stdio};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::workspace::Workspace;
+use crate::{assets, diagrams, frontmatter, markdown};
+
+pub const SITE_OUTPUT_EVENT: &str = "site-output";
+pub const SITE_EXITED_EVENT: &str = "site-exited";
+
+const NOT_A_SITE: &str = "Le dossier de travail n'est pas un site Hugo, Jekyll ou Zola";
+/// Sections new Hugo and Zola posts go to, the first existing one or else
+/// the first.
+const POST_SECTIONS: &[&str] = &["posts", "post", "blog"];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Generator {
+    Hugo,
+    Jekyll,
+    Zola,
+}
+
+impl Generator {
+    fn name(self) -> &'static str {
+        match self {
+            Generator::Hugo => "hugo",
+            Generator::Jekyll => "jekyll",
+            Generator::Zola => "zola",
+        }
+    }
+
+    fn content_dir(self, root: &Path) -> PathBuf {
+        match self {
+            Generator::Jekyll => root.to_path_buf(),
+            _ => root.join("content"),
+        }
+    }
+
+    fn static_dir(self, root: &Path) -> PathBuf {
+        match self {
+            Generator::Jekyll => root.join("assets"),
+            _ => root.join("static"),
+        }
+    }
+
+    /// URL the static folder is served at.
+    fn static_url(self) -> &'static str {
+        match self {
+            Generator::Jekyll => "/assets",
+            _ => "",
+        }
+    }
+
+    fn command(self, root: &Path, action: &str) -> Result<Vec<&'static str>, String> {
+        let serve = match action {
+            "build" => false,
+            "serve" => true,
+            _ => return Err(format!("Commande inconnue: {}", action)),
+        };
+        Ok(match (self, serve) {
+            (Generator::Hugo, false) => vec!["hugo"],
+            (Generator::Hugo, true) => vec!["hugo", "server", "--buildDrafts"],
+            (Generator::Zola, false) => vec!["zola", "build"],
+            (Generator::Zola, true) => vec!["zola", "serve", "--drafts"],
+            (Generator::Jekyll, _) => {
+                let mut command = if root.join("Gemfile").is_file() { vec!["bundle", "exec", "jekyll"] } else { vec!["jekyll"] };
+                command.extend(if serve { &["serve", "--drafts"][..] } else { &["build"][..] });
+                command
+            }
+        })
+    }
+}
+
+fn read(path: &Path) -> String {
+    std::fs::read_to_string(path).unwrap_or_default()
+}
+
+fn detect(root: &Path) -> Option<Generator> {
+    if ["hugo.toml", "hugo.yaml", "hugo.yml", "hugo.json"].iter().any(|f| root.join(f).is_file()) {
+        return Some(Generator::Hugo);
+    }
+    // Zola is the one spelling it base_url
+    let config = root.join("config.toml");
+    if config.is_file() && read(&config).lines().any(|l| l.trim_start().starts_with("base_url")) {
+        return Some(Generator::Zola);
+    }
+    if root.join("_config.yml").is_file() || root.join("_config.yaml").is_file() {
+        return Some(Generator::Jekyll);
+    }
+    let hugo_config = ["config.toml", "config.yaml", "config.yml", "config.json"].iter().any(|f| root.join(f).is_file())
+        || root.join("config").is_dir();
+    (hugo_config && root.join("content").is_dir()).then_some(Generator::Hugo)
+}
+
+fn default_section(generator: Generator, root: &Path) -> PathBuf {
+    let content = generator.content_dir(root);
+    if generator == Generator::Jekyll {
+        return content;
+    }
+    let section = POST_SECTIONS.iter().find(|s| content.join(s).is_dir()).unwrap_or(&POST_SECTIONS[0]);
+    content.join(section)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteProject {
+    /// "hugo", "jekyll" or "zola".
+    pub generator: &'static str,
+    pub content_dir: String,
+    pub static_dir: String,
+    /// Where new posts go by default.
+    pub posts_dir: String,
+}
+
+/// The static site generator the workspace is a project of, if any.
+#[tauri::command]
+pub fn detect_site_project(workspace: State<'_, Workspace>) -> Result<Option<SiteProject>, String> {
+    let root = workspace.root()?;
+    Ok(detect(&root).map(|generator| {
+        let posts_dir = match generator {
+            Generator::Jekyll => root.join("_posts"),
+            _ => default_section(generator, &root),
+        };
+        SiteProject {
+            generator: generator.name(),
+            content_dir: generator.content_dir(&root).to_string_lossy().to_string(),
+            static_dir: generator.static_dir(&root).to_string_lossy().to_string(),
+            posts_dir: posts_dir.to_string_lossy().to_string(),
+        }
+    }))
+}
+
+fn yaml(fields: &[(&str, String)], tags: &[String]) -> String {
+    let mut out = String::from("---\n");
+    for (key, value) in fields {
+        out.push_str(&format!("{}: {}\n", key, value));
+    }
+    if !tags.is_empty() {
+        // A JSON array is a valid YAML flow sequence
+        out.push_str(&format!("tags: {}\n", serde_json::json!(tags)));
+    }
+    out.push_str("---\n\n");
+    out
+}
+
+/// Zola frontmatter is TOML, whose basic strings and arrays JSON also
+/// writes. Tags are a taxonomy only when the site defines it; Zola refuses
+/// unknown ones.
+fn zola_frontmatter(root: &Path, title: &str, date: &str, draft: bool, tags: &[String]) -> String {
+    let mut out = format!("+++\ntitle = {}\ndate = {}\ndraft = {}\n", serde_json::json!(title), date, draft);
+    if !tags.is_empty() {
+        let config: String = read(&root.join("config.toml")).split_whitespace().collect();
+        let table = if config.contains("name=\"tags\"") { "taxonomies" } else { "extra" };
+        out.push_str(&format!("\n[{}]\ntags = {}\n", table, serde_json::json!(tags)));
+    }
+    out.push_str("+++\n\n");
+    out
+}
+
+/// Creates a post with the frontmatter and file name the workspace's
+/// generator expects, in `section` (relative to the content folder) or the
+/// default posts section. Jekyll drafts go to `_drafts` without a date.
+#[tauri::command]
+pub fn create_site_post(
+    workspace: State<'_, Workspace>,
+    title: &str,
+    section: Option<&str>,
+    draft: Option<bool>,
+    tags: Option<Vec<String>>,
+) -> Result<String, String> {
+    let root = workspace.root()?;
+    let generator = detect(&root).ok_or(NOT_A_SITE)?;
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("Le titre de l'article est requis".to_string());
+    }
+    let slug = Some(markdown::slug(title)).filter(|s| !s.is_empty()).unwrap_or_else(|| "post".to_string());
+    let draft = draft.unwrap_or(true);
+    let tags = tags.unwrap_or_default();
+    let now = chrono::Local::now();
+    let iso_date = now.format("%Y-%m-%dT%H:%M:%S%:z").to_string();
+
+    let dir = match section.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(section) => {
+            let dir = assets::normalize(&generator.content_dir(&root).join(section));
+            if !dir.starts_with(&root) {
+                return Err(format!("{} est en dehors du dossier de travail", section));
+            }
+            dir
+        }
+        None => default_section(generator, &root),
+    };
+    let (path, content) = match generator {
+        Generator::Hugo => (
+            dir.join(format!("{}.md", slug)),
+            yaml(&[("title", frontmatter::quote(title)), ("date", iso_date), ("draft", draft.to_string())], &tags),
+        ),
+        Generator::Zola => (dir.join(format!("{}.md", slug)), zola_frontmatter(&root, title, &iso_date, draft, &tags)),
+        Generator::Jekyll if draft => (
+            dir.join("_drafts").join(format!("{}.md", slug)),
+            yaml(&[("layout", "post".to_string()), ("title", frontmatter::quote(title))], &tags),
+        ),
+        Generator::Jekyll => (
+            dir.join("_posts").join(format!("{}-{}.md", now.format("%Y-%m-%d"), slug)),
+            yaml(
+                &[
+                    ("layout", "post".to_string()),
+                    ("title", frontmatter::quote(title)),
+                    ("date", now.format("%Y-%m-%d %H:%M:%S %z").to_string()),
+                ],
+                &tags,
+            ),
+        ),
+    };
+    if path.exists() {
+        return Err(format!("{} existe déjà", path.display()));
+    }
+
+    let parent = path.parent().unwrap_or(&root);
+    // Zola only builds pages that belong to a section
+    if generator == Generator::Zola && !parent.join("_index.md").exists() {
+        let name = parent.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer le dossier {}: {}", parent.display(), e))?;
+        std::fs::write(parent.join("_index.md"), format!("+++\ntitle = {}\nsort_by = \"date\"\n+++\n", serde_json::json!(name)))
+            .map_err(|e| format!("Impossible de créer la section {}: {}", parent.display(), e))?;
+    }
+    std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer le dossier {}: {}", parent.display(), e))?;
+    std::fs::write(&path, content).map_err(|e| format!("Impossible de créer le fichier: {}", e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Where the images of a site page go, and the URL that folder is served at.
+pub struct SiteAssets {
+    pub dir: PathBuf,
+    pub url: String,
+}
+
+/// `static/images/<slug>/` (`assets/images/<slug>/` for Jekyll) when
+/// `document` is a page of a Hugo, Jekyll or Zola site, whose generator only
+/// publishes what is in its static folder.
+pub fn assets_location(document: &Path) -> Option<SiteAssets> {
+    let (root, generator) = document.ancestors().skip(1).find_map(|dir| detect(dir).map(|g| (dir, g)))?;
+    if !document.starts_with(generator.content_dir(root)) {
+        return None;
+    }
+    let stem = document.file_stem()?.to_string_lossy().to_string();
+    // Page bundles are named after their folder, Jekyll posts after their date
+    let name = match stem.as_str() {
+        "index" | "_index" => document.parent()?.file_name()?.to_string_lossy().to_string(),
+        _ if generator == Generator::Jekyll => {
+            let dated = stem.len() > 11 && stem.as_bytes()[10] == b'-' && chrono::NaiveDate::parse_from_str(&stem[..10], "%Y-%m-%d").is_ok();
+            if dated { stem[11..].to_string() } else { stem }
+        }
+        _ => stem,
+    };
+    let slug = markdown::slug(&name);
+    Some(SiteAssets {
+        dir: generator.static_dir(root).join("images").join(&slug),
+        url: format!("{}/images/{}", generator.static_url(), slug),
+    })
+}
+
+/// The build or serve command of the workspace's generator, if one runs.
+#[derive(Default)]
+pub struct SiteProcess {
+    process: Mutex<Option<Child>>,
+}
+
+impl Drop for SiteProcess {
+    fn drop(&mut self) {
+        if let Ok(mut guard) = self.process.lock() {
+            if let Some(mut child) = guard.take() {
+                let _ = child.kill();
+            }
+        }
+    }
+}
+
+/// Payload of the `site-output` event.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteOutput {
+    /// "stdout" or "stderr".
+    pub stream: &'static str,
+    pub line: String,
+}
+
+/// Payload of the `site-exited` event.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteExit {
+    /// None when the command was stopped.
+    pub code: Option<i32>,
+}
+
+fn forward(app: AppHandle, source: impl Read + Send + 'static, stream: &'static str) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(source);
+        let mut line = Vec::new();
+        while matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+            let text = String::from_utf8_lossy(&line).trim_end().to_string();
+            let _ = app.emit(SITE_OUTPUT_EVENT, SiteOutput { stream, line: text });
+            line.clear();
+        }
+    })
+}
+
+/// Runs the generator's "build" or "serve" command in the workspace and
+/// returns its command line. Output comes as `site-output` events, then a
+/// `site-exited` event. One command runs at a time.
+#[tauri::command]
+pub fn run_site_command(
+    app: AppHandle,
+    workspace: State<'_, Workspace>,
+    site: State<'_, SiteProcess>,
+    action: &str,
+) -> Result<String, String> {
+    let root = workspace.root()?;
+    let generator = detect(&root).ok_or(NOT_A_SITE)?;
+    let mut guard = site.process.lock().map_err(|e| e.to_string())?;
+    if let Some(child) = guard.as_mut() {
+        if matches!(child.try_wait(), Ok(None)) {
+            return Err("Une commande du site est déjà en cours".to_string());
+        }
+    }
+
+    let command = generator.command(&root, action)?;
+    let mut child = diagrams::tool_command(command[0])
+        .args(&command[1..])
+        .current_dir(&root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Impossible de lancer {}: {}. Est-il installé ?", command[0], e))?;
+    let readers: Vec<JoinHandle<()>> = [
+        child.stdout.take().map(|out| forward(app.clone(), out, "stdout")),
+        child.stderr.take().map(|err| forward(app.clone(), err, "stderr")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let id = child.id();
+    *guard = Some(child);
+
+    std::thread::spawn(move || {
+        for reader in readers {
+            let _ = reader.join();
+        }
+        // Still ours unless it was stopped in the meantime
+        let child = app.state::<SiteProcess>().process.lock().ok().and_then(|mut guard| {
+            if guard.as_ref().is_some_and(|c| c.id() == id) {
+                guard.take()
+            } else {
+                None
+            }
+        });
+        let code = child.and_then(|mut c| c.wait().ok()).and_then(|status| status.code());
+        let _ = app.emit(SITE_EXITED_EVENT, SiteExit { code });
+    });
+    Ok(command.join(" "))
+}
+
+#[tauri::command]
+pub fn stop_site_command(site: State<'_, SiteProcess>) -> Result<(), String> {
+    let mut guard = site.process.lock().map_err(|e| e.to_string())?;
+    if let Some(mut child) = guard.take() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    Ok(())
+}
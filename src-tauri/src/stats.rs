@@ -0,0 +1,115 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager};
+
+use crate::search::SearchIndex;
+use crate::{backlinks, markdown, tags, workspace};
+
+/// Weeks of activity reported, the current one included.
+const ACTIVITY_WEEKS: i64 = 26;
+const LARGEST_FILES: usize = 10;
+const TOP_TAGS: usize = 20;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeekActivity {
+    /// Monday of the week, "YYYY-MM-DD".
+    pub week: String,
+    pub created: usize,
+    pub edited: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSize {
+    pub path: String,
+    pub size: u64,
+    pub words: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagCount {
+    pub name: String,
+    pub file_count: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStats {
+    pub note_count: usize,
+    pub total_words: usize,
+    /// Oldest week first. Creation dates are missing on some filesystems, in
+    /// which case only edits are counted.
+    pub activity: Vec<WeekActivity>,
+    pub largest_files: Vec<NoteSize>,
+    /// Notes with no link to or from another note, sorted by path.
+    pub orphans: Vec<String>,
+    /// Most used tags by number of notes.
+    pub tags: Vec<TagCount>,
+}
+
+fn week_of(time: SystemTime) -> NaiveDate {
+    let date = DateTime::<Local>::from(time).date_naive();
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Data for the workspace dashboard.
+#[tauri::command]
+pub fn get_workspace_stats(app: AppHandle) -> Result<WorkspaceStats, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    let notes: Vec<(String, usize)> = app.state::<SearchIndex>().with_docs(&root, |docs| {
+        docs.iter()
+            .map(|(path, doc)| (path.to_string_lossy().to_string(), markdown::word_count(doc.content())))
+            .collect()
+    });
+
+    let this_week = week_of(SystemTime::now());
+    let mut weeks: BTreeMap<NaiveDate, (usize, usize)> = (0..ACTIVITY_WEEKS)
+        .map(|n| (this_week - Duration::weeks(n), (0, 0)))
+        .collect();
+    let mut sizes = Vec::new();
+    for (path, words) in &notes {
+        let Ok(metadata) = std::fs::metadata(path) else { continue };
+        if let Some(week) = metadata.created().ok().and_then(|t| weeks.get_mut(&week_of(t))) {
+            week.0 += 1;
+        }
+        if let Some(week) = metadata.modified().ok().and_then(|t| weeks.get_mut(&week_of(t))) {
+            week.1 += 1;
+        }
+        sizes.push(NoteSize { path: path.clone(), size: metadata.len(), words: *words });
+    }
+    sizes.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    sizes.truncate(LARGEST_FILES);
+
+    let connected: HashSet<PathBuf> = backlinks::resolved_links(&app)?.into_iter().flat_map(|(a, b)| [a, b]).collect();
+    let mut orphans: Vec<String> = notes
+        .iter()
+        .map(|(path, _)| path)
+        .filter(|path| !connected.contains(Path::new(path)))
+        .cloned()
+        .collect();
+    orphans.sort();
+
+    let mut tags: Vec<TagCount> = tags::list_tags(app.clone())?
+        .into_iter()
+        .map(|t| TagCount { name: t.name, file_count: t.file_count })
+        .collect();
+    tags.sort_by(|a, b| b.file_count.cmp(&a.file_count).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+    tags.truncate(TOP_TAGS);
+
+    Ok(WorkspaceStats {
+        note_count: notes.len(),
+        total_words: notes.iter().map(|(_, words)| words).sum(),
+        activity: weeks
+            .into_iter()
+            .map(|(week, (created, edited))| WeekActivity { week: week.format("%Y-%m-%d").to_string(), created, edited })
+            .collect(),
+        largest_files: sizes,
+        orphans,
+        tags,
+    })
+}
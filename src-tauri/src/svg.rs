@@ -0,0 +1,289 @@
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use crate::{assets, markdown};
+
+/// Elements dropped together with their content.
+const BLOCKED_ELEMENTS: &[&str] = &["script", "foreignobject", "iframe", "embed", "object", "handler", "listener"];
+
+const SCRIPT_SCHEMES: &[&str] = &["javascript:", "vbscript:", "data:text/html"];
+
+fn starts_with_ignore_case(s: &str, prefix: &str) -> bool {
+    s.len() >= prefix.len() && s.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.to_ascii_lowercase().find(&needle.to_ascii_lowercase())
+}
+
+/// Decodes numeric character references and drops whitespace, so that
+/// `java&#09;script&#58;` is seen as `javascript:`.
+fn normalized_value(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let end = rest.find(';').unwrap_or(rest.len());
+        let entity = &rest[1..end];
+        let decoded = match entity.strip_prefix(['#']) {
+            Some(num) => match num.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                None => num.parse().ok(),
+            }
+            .and_then(char::from_u32),
+            None if entity.eq_ignore_ascii_case("colon") => Some(':'),
+            None => None,
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[(end + 1).min(rest.len())..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out.chars().filter(|c| !c.is_whitespace() && !c.is_control()).collect::<String>().to_ascii_lowercase()
+}
+
+fn is_unsafe_attribute(name: &str, value: &str) -> bool {
+    if starts_with_ignore_case(name, "on") {
+        return true;
+    }
+    let value = normalized_value(value);
+    SCRIPT_SCHEMES.iter().any(|scheme| value.contains(scheme))
+}
+
+/// Byte index just past the `>` closing the tag that starts at `start`,
+/// ignoring `>` inside quoted attribute values.
+pub fn tag_end(svg: &str, start: usize) -> usize {
+    let mut quote: Option<u8> = None;
+    for (i, &b) in svg.as_bytes().iter().enumerate().skip(start + 1) {
+        match (quote, b) {
+            (Some(q), _) if b == q => quote = None,
+            (Some(_), _) => {}
+            (None, b'"' | b'\'') => quote = Some(b),
+            (None, b'>') => return i + 1,
+            _ => {}
+        }
+    }
+    svg.len()
+}
+
+pub struct Tag<'a> {
+    pub name: &'a str,
+    pub closing: bool,
+    pub self_closing: bool,
+    /// (name, value, original text) of each attribute.
+    pub attributes: Vec<(&'a str, &'a str, &'a str)>,
+}
+
+pub fn parse_tag(tag: &str) -> Tag<'_> {
+    let inner = tag.trim_start_matches('<').trim_end_matches('>');
+    let self_closing = inner.ends_with('/');
+    let inner = inner.trim_end_matches('/');
+    let closing = inner.starts_with('/');
+    let inner = inner.trim_start_matches('/');
+    let name_end = inner.find(|c: char| c.is_whitespace()).unwrap_or(inner.len());
+    let name = &inner[..name_end];
+
+    let bytes = inner.as_bytes();
+    let mut attributes = Vec::new();
+    let mut i = name_end;
+    while i < bytes.len() {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        let start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'=' {
+            i += 1;
+        }
+        let attr_name = &inner[start..i];
+        let mut value = "";
+        if bytes.get(i) == Some(&b'=') {
+            i += 1;
+            match bytes.get(i) {
+                Some(&q @ (b'"' | b'\'')) => {
+                    let close = inner[i + 1..].find(q as char).map(|c| i + 1 + c).unwrap_or(inner.len());
+                    value = &inner[i + 1..close];
+                    i = (close + 1).min(inner.len());
+                }
+                _ => {
+                    let value_start = i;
+                    while i < bytes.len() && !bytes[i].is_ascii_whitespace() {
+                        i += 1;
+                    }
+                    value = &inner[value_start..i];
+                }
+            }
+        }
+        if !attr_name.is_empty() {
+            attributes.push((attr_name, value, &inner[start..i]));
+        }
+    }
+    Tag { name, closing, self_closing, attributes }
+}
+
+/// Removes everything in an SVG that can run code: script-like elements
+/// (including `<foreignObject>`, which can carry arbitrary HTML), event
+/// handler attributes, `javascript:` URLs and the DOCTYPE, whose entities
+/// could smuggle markup back in.
+pub fn sanitize(svg: &str) -> String {
+    let mut out = String::with_capacity(svg.len());
+    // Name and nesting depth of the blocked element being skipped
+    let mut skipping: Option<(String, usize)> = None;
+    let mut pos = 0;
+
+    while let Some(offset) = svg[pos..].find('<') {
+        let start = pos + offset;
+        if skipping.is_none() {
+            out.push_str(&svg[pos..start]);
+        }
+        let rest = &svg[start..];
+
+        let (end, keep) = if rest.starts_with("<!--") {
+            (rest.find("-->").map(|e| start + e + 3).unwrap_or(svg.len()), true)
+        } else if rest.starts_with("<![CDATA[") {
+            (rest.find("]]>").map(|e| start + e + 3).unwrap_or(svg.len()), true)
+        } else if rest.starts_with("<?") {
+            (rest.find("?>").map(|e| start + e + 2).unwrap_or(svg.len()), true)
+        } else if rest.starts_with("<!") {
+            let subset_end = match (rest.find('['), rest.find('>')) {
+                (Some(open), Some(close)) if open < close => rest.find("]>").map(|e| e + 2),
+                (_, close) => close.map(|e| e + 1),
+            };
+            (subset_end.map(|e| start + e).unwrap_or(svg.len()), false)
+        } else {
+            let end = tag_end(svg, start);
+            let tag = parse_tag(&svg[start..end]);
+            let name = tag.name.to_ascii_lowercase();
+
+            if let Some((skipped, depth)) = &mut skipping {
+                if name == *skipped && !tag.self_closing {
+                    if tag.closing {
+                        *depth -= 1;
+                    } else {
+                        *depth += 1;
+                    }
+                }
+                if *depth == 0 {
+                    skipping = None;
+                }
+            } else if BLOCKED_ELEMENTS.contains(&name.as_str()) {
+                if !tag.closing && !tag.self_closing {
+                    // Script content is raw text, so jump straight to its close tag
+                    if name == "script" {
+                        pos = find_ignore_case(&svg[end..], "</script")
+                            .map(|e| tag_end(svg, end + e))
+                            .unwrap_or(svg.len());
+                        continue;
+                    }
+                    skipping = Some((name, 1));
+                }
+            } else if tag.closing {
+                out.push_str(&svg[start..end]);
+            } else {
+                out.push('<');
+                out.push_str(tag.name);
+                for (attr_name, value, text) in &tag.attributes {
+                    if !is_unsafe_attribute(attr_name, value) {
+                        out.push(' ');
+                        out.push_str(text);
+                    }
+                }
+                out.push_str(if tag.self_closing { "/>" } else { ">" });
+            }
+            pos = end;
+            continue;
+        };
+
+        if keep && skipping.is_none() {
+            out.push_str(&svg[start..end]);
+        }
+        pos = end;
+    }
+    if skipping.is_none() {
+        out.push_str(&svg[pos..]);
+    }
+    out
+}
+
+/// Sanitizes every inline `<svg>` element of an HTML document.
+pub fn sanitize_embedded(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let lower = html.to_ascii_lowercase();
+    let mut pos = 0;
+    while let Some(offset) = lower[pos..].find("<svg") {
+        let start = pos + offset;
+        if !lower[start + 4..].starts_with(|c: char| c.is_whitespace() || c == '>' || c == '/') {
+            out.push_str(&html[pos..start + 4]);
+            pos = start + 4;
+            continue;
+        }
+        let mut depth = 0;
+        let mut cursor = start;
+        let mut end = html.len();
+        while let Some(next) = lower[cursor..].find('<') {
+            let tag_start = cursor + next;
+            if lower[tag_start..].starts_with("</svg") {
+                depth -= 1;
+                if depth == 0 {
+                    end = tag_end(html, tag_start);
+                    break;
+                }
+            } else if lower[tag_start..].starts_with("<svg") {
+                depth += 1;
+            }
+            cursor = tag_start + 1;
+        }
+        out.push_str(&html[pos..start]);
+        out.push_str(&sanitize(&html[start..end]));
+        pos = end;
+    }
+    out.push_str(&html[pos..]);
+    out
+}
+
+/// Points links to local SVG files at sanitized copies, for exports that
+/// embed images in the output. Files that are already clean keep their link.
+/// Relative links are looked up in `resource_dirs`.
+pub fn sanitize_linked(app: &AppHandle, md: &str, resource_dirs: &[PathBuf]) -> Result<String, String> {
+    let mut replacements = Vec::new();
+    for link in markdown::links(md) {
+        let url = link.url(md);
+        if !assets::is_local_link(url) {
+            continue;
+        }
+        let Some(path) = assets::resolve_resource(url, resource_dirs) else { continue };
+        if !path.extension().is_some_and(|e| e.eq_ignore_ascii_case("svg")) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let clean = sanitize(&content);
+        if clean == content {
+            continue;
+        }
+
+        let hex: String = Sha256::digest(clean.as_bytes()).iter().take(16).map(|b| format!("{:02x}", b)).collect();
+        let copy = crate::paths::app_data_subdir(app, "sanitized-svg")?.join(format!("{}.svg", hex));
+        if !copy.is_file() {
+            std::fs::write(&copy, &clean)
+                .map_err(|e| format!("Impossible d'enregistrer l'image nettoyée: {}", e))?;
+        }
+        replacements.push(assets::replacement(md, &link, assets::link_to(&copy, None)));
+    }
+    Ok(markdown::replace_ranges(md, replacements))
+}
+
+/// Sanitizes SVG markup before the preview inlines it.
+#[tauri::command]
+pub fn sanitize_svg(svg: &str) -> String {
+    sanitize(svg)
+}
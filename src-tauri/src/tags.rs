@@ -0,0 +1,142 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::search::{self, SearchIndex};
+use crate::{markdown, workspace};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagInfo {
+    /// The most common spelling of the tag.
+    pub name: String,
+    pub count: usize,
+    pub file_count: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaggedFile {
+    pub path: String,
+    pub count: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagRename {
+    pub files: Vec<String>,
+    pub occurrences: usize,
+}
+
+fn clean(tag: &str) -> &str {
+    tag.trim().trim_start_matches('#')
+}
+
+/// Tags of the workspace, grouped case-insensitively and sorted by name.
+#[tauri::command]
+pub fn list_tags(app: AppHandle) -> Result<Vec<TagInfo>, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    let mut groups: HashMap<String, (HashMap<String, usize>, usize)> = HashMap::new();
+    app.state::<SearchIndex>().with_docs(&root, |docs| {
+        for doc in docs.values() {
+            let mut seen = Vec::new();
+            for tag in doc.tags() {
+                let key = tag.name.to_lowercase();
+                let (spellings, files) = groups.entry(key.clone()).or_default();
+                *spellings.entry(tag.name.clone()).or_default() += 1;
+                if !seen.contains(&key) {
+                    *files += 1;
+                    seen.push(key);
+                }
+            }
+        }
+    });
+
+    let mut tags: Vec<TagInfo> = groups
+        .into_values()
+        .map(|(spellings, file_count)| {
+            let count = spellings.values().sum();
+            let name = spellings.into_iter().max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0))).unwrap_or_default().0;
+            TagInfo { name, count, file_count }
+        })
+        .collect();
+    tags.sort_by_key(|t| t.name.to_lowercase());
+    Ok(tags)
+}
+
+/// Files having the tag or one nested under it, sorted by path.
+#[tauri::command]
+pub fn get_files_by_tag(app: AppHandle, tag: &str) -> Result<Vec<TaggedFile>, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    let tag = clean(tag);
+    let mut files: Vec<TaggedFile> = app.state::<SearchIndex>().with_docs(&root, |docs| {
+        docs.iter()
+            .map(|(path, doc)| TaggedFile {
+                path: path.to_string_lossy().to_string(),
+                count: doc.tags().iter().filter(|t| search::tag_matches(&t.name, tag)).count(),
+            })
+            .filter(|f| f.count > 0)
+            .collect()
+    });
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Renames a tag in every note of the workspace, nested tags included:
+/// renaming `a` to `b` turns `#a/c` into `#b/c`.
+#[tauri::command]
+pub async fn rename_tag(app: AppHandle, old_name: String, new_name: String) -> Result<TagRename, String> {
+    let root = app.state::<workspace::Workspace>().root()?;
+    let old_name = clean(&old_name).to_string();
+    let new_name = clean(&new_name).to_string();
+    if old_name.is_empty() {
+        return Err("Aucun tag à renommer".to_string());
+    }
+    if new_name.is_empty() || !new_name.chars().all(markdown::is_tag_char) || new_name.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Nom de tag invalide: {}", new_name));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let index = app.state::<SearchIndex>();
+        let paths: Vec<PathBuf> = index.with_docs(&root, |docs| {
+            docs.iter()
+                .filter(|(_, doc)| doc.tags().iter().any(|t| search::tag_matches(&t.name, &old_name)))
+                .map(|(path, _)| path.clone())
+                .collect()
+        });
+
+        let prefix_chars = old_name.chars().count();
+        // Every change is worked out first, so a locked note stops the
+        // rename before any note is written
+        let mut changes = Vec::new();
+        for path in paths {
+            // The index may be behind the file on disk; rename what is there now
+            let (md, format) = crate::safe_save::read_text(&path)?;
+            let replacements: Vec<(usize, usize, String)> = markdown::tags(&md)
+                .into_iter()
+                .filter(|t| search::tag_matches(&t.name, &old_name))
+                .map(|t| {
+                    let rest = t.name.char_indices().nth(prefix_chars).map(|(i, _)| &t.name[i..]).unwrap_or("");
+                    (t.start, t.end, format!("{}{}", new_name, rest))
+                })
+                .collect();
+            if !replacements.is_empty() {
+                changes.push((path, replacements.len(), markdown::replace_ranges(&md, replacements), format));
+            }
+        }
+        crate::locks::ensure_all_writable(&app, changes.iter().map(|(path, ..)| path.as_path()))?;
+
+        let mut result = TagRename { files: Vec::new(), occurrences: 0 };
+        for (path, occurrences, updated, format) in changes {
+            crate::safe_save::write_text(&app, &path, &updated, &format)?;
+            index.update(&path);
+            result.occurrences += occurrences;
+            result.files.push(path.to_string_lossy().to_string());
+        }
+        result.files.sort();
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors du renommage du tag: {}", e))?
+}
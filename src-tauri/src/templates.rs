@@ -0,0 +1,190 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::{markdown, paths, workspace};
+
+const TEMPLATES_DIR: &str = "templates";
+
+/// Written when the templates directory is first created.
+const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "Compte rendu de réunion.md",
+        "# {{title}}\n\n**Date :** {{date}}\n**Participants :** {{participants|Participants}}\n\n## Ordre du jour\n\n- \n\n## Décisions\n\n- \n\n## Actions\n\n- [ ] \n",
+    ),
+    (
+        "Décision d'architecture (ADR).md",
+        "# {{title}}\n\n- **Statut :** {{status|Statut (proposé, accepté, remplacé)}}\n- **Date :** {{date}}\n\n## Contexte\n\n## Décision\n\n## Conséquences\n",
+    ),
+];
+
+const BUILTIN_VARIABLES: &[&str] = &["title", "date", "time", "datetime", "year"];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateVariable {
+    pub name: String,
+    /// Question shown to the user, from `{{name|Question}}`.
+    pub prompt: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Template {
+    pub name: String,
+    pub path: String,
+    /// "app" or "workspace".
+    pub source: &'static str,
+    /// Placeholders the user has to fill in, built-in ones excluded.
+    pub variables: Vec<TemplateVariable>,
+}
+
+pub fn app_templates_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = paths::app_data_dir(app)?.join(TEMPLATES_DIR);
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+        for (name, content) in DEFAULT_TEMPLATES {
+            let _ = std::fs::write(dir.join(name), content);
+        }
+    }
+    Ok(dir)
+}
+
+/// Template directories, lowest priority first: a workspace template
+/// overrides an app template of the same name.
+fn template_dirs(app: &AppHandle) -> Result<Vec<(&'static str, PathBuf)>, String> {
+    let mut dirs = vec![("app", app_templates_dir(app)?)];
+    if let Ok(root) = app.state::<workspace::Workspace>().root() {
+        dirs.push(("workspace", paths::workspace_config_dir(&root).join(TEMPLATES_DIR)));
+    }
+    Ok(dirs)
+}
+
+/// `{{name}}` and `{{name|prompt}}` placeholders, as (byte range, name, prompt).
+fn placeholders(template: &str) -> Vec<(usize, usize, &str, &str)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(open) = template[from..].find("{{").map(|i| from + i) {
+        let Some(close) = template[open + 2..].find("}}").map(|i| open + 2 + i) else { break };
+        let inner = &template[open + 2..close];
+        let (name, prompt) = inner.split_once('|').unwrap_or((inner, ""));
+        if !name.trim().is_empty() && !inner.contains('\n') {
+            found.push((open, close + 2, name.trim(), prompt.trim()));
+        }
+        from = close + 2;
+    }
+    found
+}
+
+fn is_builtin(name: &str) -> bool {
+    BUILTIN_VARIABLES.contains(&name) || name.starts_with("date:")
+}
+
+fn variables(template: &str) -> Vec<TemplateVariable> {
+    let mut out: Vec<TemplateVariable> = Vec::new();
+    for (_, _, name, prompt) in placeholders(template) {
+        if is_builtin(name) || out.iter().any(|v| v.name == name) {
+            continue;
+        }
+        let prompt = if prompt.is_empty() { name } else { prompt };
+        out.push(TemplateVariable { name: name.to_string(), prompt: prompt.to_string() });
+    }
+    out
+}
+
+fn builtin_value(name: &str, title: &str) -> Option<String> {
+    let format = match name {
+        "title" => return Some(title.to_string()),
+        "date" => "%Y-%m-%d",
+        "time" => "%H:%M",
+        "datetime" => "%Y-%m-%d %H:%M",
+        "year" => "%Y",
+        _ => name.strip_prefix("date:")?,
+    };
+    // An invalid custom format fails here instead of panicking in to_string
+    let mut value = String::new();
+    write!(value, "{}", chrono::Local::now().format(format)).ok()?;
+    Some(value)
+}
+
+/// Fills the placeholders; user values win over built-in ones, and unknown
+/// placeholders are left empty.
+fn render(template: &str, values: &HashMap<String, String>, title: &str) -> String {
+    let replacements = placeholders(template)
+        .into_iter()
+        .map(|(start, end, name, _)| {
+            let value = values.get(name).cloned().or_else(|| builtin_value(name, title)).unwrap_or_default();
+            (start, end, value)
+        })
+        .collect();
+    markdown::replace_ranges(template, replacements)
+}
+
+fn find_template(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(format!("Nom de modèle invalide: {}", name));
+    }
+    template_dirs(app)?
+        .into_iter()
+        .rev()
+        .flat_map(|(_, dir)| markdown::MARKDOWN_EXTENSIONS.iter().map(move |ext| dir.join(format!("{}.{}", name, ext))))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("Modèle introuvable: {}", name))
+}
+
+#[tauri::command]
+pub fn list_templates(app: AppHandle) -> Result<Vec<Template>, String> {
+    let mut templates: Vec<Template> = Vec::new();
+    for (source, dir) in template_dirs(&app)? {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            if !path.is_file() || !markdown::is_markdown_file(&path) {
+                continue;
+            }
+            let Ok(content) = std::fs::read_to_string(&path) else { continue };
+            let name = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+            templates.retain(|t| t.name != name);
+            templates.push(Template {
+                name,
+                path: path.to_string_lossy().to_string(),
+                source,
+                variables: variables(&content),
+            });
+        }
+    }
+    templates.sort_by_key(|t| t.name.to_lowercase());
+    Ok(templates)
+}
+
+/// Renders a template. With `path`, the document is also written there;
+/// its file name is the default `{{title}}`.
+#[tauri::command]
+pub fn create_from_template(
+    app: AppHandle,
+    name: &str,
+    variables: Option<HashMap<String, String>>,
+    path: Option<&str>,
+) -> Result<String, String> {
+    let target = path.map(|path| crate::scopes::check(&app, path)).transpose()?;
+    let template_path = find_template(&app, name)?;
+    let template = std::fs::read_to_string(&template_path)
+        .map_err(|e| format!("Impossible de lire le modèle: {}", e))?;
+    let title = target
+        .as_deref()
+        .and_then(|p| p.file_stem())
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| name.to_string());
+    let content = render(&template, &variables.unwrap_or_default(), &title);
+
+    if let Some(target) = &target {
+        if target.exists() {
+            return Err(format!("{} existe déjà", target.display()));
+        }
+        std::fs::write(target, &content)
+            .map_err(|e| format!("Impossible de créer le fichier: {}", e))?;
+    }
+    Ok(content)
+}
@@ -0,0 +1,43 @@
+use crate::pandoc::TempFile;
+
+const CRITICMARKUP_FILTER: &str = include_str!("track_changes/criticmarkup.lua");
+
+/// What a docx import does with tracked changes. Comments are kept as
+/// CriticMarkup whatever the mode.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrackChanges {
+    /// Keeps the document as if every change were accepted (pandoc's default).
+    Accept,
+    Reject,
+    /// Keeps insertions and deletions as CriticMarkup.
+    All,
+}
+
+impl TrackChanges {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "accept" => Ok(TrackChanges::Accept),
+            "reject" => Ok(TrackChanges::Reject),
+            "all" => Ok(TrackChanges::All),
+            other => Err(format!("Mode de suivi des modifications inconnu: {} (accept, reject ou all)", other)),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            TrackChanges::Accept => "accept",
+            TrackChanges::Reject => "reject",
+            TrackChanges::All => "all",
+        }
+    }
+
+    /// Pandoc arguments, and the filter file they name, to keep until pandoc
+    /// is done. Pandoc drops comments unless it reads every revision, so the
+    /// filter applies the mode instead.
+    pub fn pandoc_args(self) -> Result<(Vec<String>, TempFile), String> {
+        let filter = format!("local MODE = \"{}\"\n{}", self.name(), CRITICMARKUP_FILTER);
+        let filter = TempFile::write("criticmarkup", "lua", &filter)?;
+        let args = vec!["--track-changes=all".to_string(), format!("--lua-filter={}", filter.path().display())];
+        Ok((args, filter))
+    }
+}
@@ -0,0 +1,55 @@
+-- Applies the tracked changes pandoc reads from a docx with
+-- --track-changes=all as MODE says ("accept", "reject", or "all" to keep them
+-- as CriticMarkup), and keeps comments as CriticMarkup in every mode. The app
+-- defines MODE above this line.
+
+local function raw(text)
+  return pandoc.RawInline(FORMAT, text)
+end
+
+local function wrap(open, inlines, close)
+  local out = pandoc.List({ raw(open) })
+  out:extend(inlines)
+  out:insert(raw(close))
+  return out
+end
+
+local comments = {}
+
+local function describe(span)
+  local author = span.attributes.author or ""
+  local date = (span.attributes.date or ""):sub(1, 10)
+  local text = pandoc.utils.stringify(span.content)
+  local by = author
+  if date ~= "" then
+    by = (by ~= "" and by .. ", " or "") .. date
+  end
+  if by ~= "" then
+    text = by .. " : " .. text
+  end
+  -- Would end the comment early
+  return (text:gsub("<<}", "<< }"))
+end
+
+function Span(span)
+  local classes = span.classes
+  if classes:includes("insertion") then
+    if MODE == "all" then
+      return wrap("{++", span.content, "++}")
+    end
+    return MODE == "reject" and {} or span.content
+  elseif classes:includes("deletion") then
+    if MODE == "all" then
+      return wrap("{--", span.content, "--}")
+    end
+    return MODE == "accept" and {} or span.content
+  elseif classes:includes("comment-start") then
+    comments[span.attributes.id or ""] = describe(span)
+    return raw("{==")
+  elseif classes:includes("comment-end") then
+    local comment = comments[span.attributes.id or ""] or ""
+    return raw("==}{>>" .. comment .. "<<}")
+  elseif classes:includes("paragraph-insertion") or classes:includes("paragraph-deletion") then
+    return {}
+  end
+end
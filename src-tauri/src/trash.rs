@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::AppHandle;
+
+use crate::assets;
+
+const MANIFEST: &str = "item.json";
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrashedPath {
+    original_path: String,
+    /// Location inside the trash entry's folder.
+    stored: String,
+}
+
+/// Manifest of a trash entry: a note and its exclusive assets, or a folder,
+/// deleted together.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    deleted_at: String,
+    paths: Vec<TrashedPath>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashItem {
+    pub id: String,
+    pub name: String,
+    pub original_path: String,
+    pub deleted_at: String,
+    pub is_dir: bool,
+    /// Other files deleted along with it (exclusive assets).
+    pub attachments: Vec<String>,
+    pub size: u64,
+}
+
+fn trash_root(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::paths::app_data_subdir(app, "trash")
+}
+
+fn entry_dir(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+        return Err(format!("Élément de corbeille invalide: {}", id));
+    }
+    Ok(trash_root(app)?.join(id))
+}
+
+fn size_of(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+fn read_manifest(dir: &Path) -> Option<Manifest> {
+    serde_json::from_str(&std::fs::read_to_string(dir.join(MANIFEST)).ok()?).ok()
+}
+
+/// Moves `paths` to a new trash entry, the first one being the item shown in
+/// the trash. Returns the entry's folder.
+pub fn move_to_trash(app: &AppHandle, paths: &[PathBuf]) -> Result<PathBuf, String> {
+    let stamp = chrono::Local::now();
+    let dir = assets::unique_path(&trash_root(app)?, &stamp.format("%Y%m%d-%H%M%S-%3f").to_string());
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Impossible de créer le dossier {}: {}", dir.display(), e))?;
+
+    let mut manifest = Manifest { deleted_at: stamp.to_rfc3339(), paths: Vec::new() };
+    for (i, path) in paths.iter().enumerate() {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let stored = format!("{}/{}", i, name);
+        assets::move_path(path, &dir.join(&stored))?;
+        manifest.paths.push(TrashedPath { original_path: path.to_string_lossy().to_string(), stored });
+        // Written after each move so a failure midway still leaves a restorable entry
+        let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+        std::fs::write(dir.join(MANIFEST), json)
+            .map_err(|e| format!("Impossible d'écrire dans la corbeille: {}", e))?;
+    }
+    Ok(dir)
+}
+
+/// Files linked from `note` that no other note of the workspace links to.
+pub fn exclusive_assets(note: &Path, root: &Path) -> Vec<PathBuf> {
+    let Ok(md) = std::fs::read_to_string(note) else { return Vec::new() };
+    let others: Vec<PathBuf> = crate::workspace::markdown_files(root).into_iter().filter(|p| p != note).collect();
+    let shared = assets::referenced_files(&others);
+    let mut exclusive: Vec<PathBuf> = crate::markdown::links(&md)
+        .iter()
+        .map(|link| link.url(&md))
+        .filter(|url| assets::is_local_link(url))
+        .map(|url| assets::resolve_link(url, note.parent()))
+        .filter(|p| p.starts_with(root) && p.is_file() && !crate::markdown::is_markdown_file(p) && !shared.contains(p))
+        .collect();
+    exclusive.sort();
+    exclusive.dedup();
+    exclusive
+}
+
+/// Trashed items, most recently deleted first.
+#[tauri::command]
+pub fn list_trash(app: AppHandle) -> Result<Vec<TrashItem>, String> {
+    let entries = std::fs::read_dir(trash_root(&app)?)
+        .map_err(|e| format!("Impossible de lire la corbeille: {}", e))?;
+    let mut items: Vec<TrashItem> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let dir = entry.path();
+            let manifest = read_manifest(&dir)?;
+            let (first, rest) = manifest.paths.split_first()?;
+            let original = Path::new(&first.original_path);
+            Some(TrashItem {
+                id: entry.file_name().to_string_lossy().to_string(),
+                name: original.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+                original_path: first.original_path.clone(),
+                deleted_at: manifest.deleted_at.clone(),
+                is_dir: dir.join(&first.stored).is_dir(),
+                attachments: rest.iter().map(|p| p.original_path.clone()).collect(),
+                size: size_of(&dir),
+            })
+        })
+        .collect();
+    items.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(items)
+}
+
+/// Puts a trashed item and its attachments back where they were deleted
+/// from, and returns the item's path.
+#[tauri::command]
+pub fn restore_from_trash(app: AppHandle, id: &str) -> Result<String, String> {
+    let dir = entry_dir(&app, id)?;
+    let manifest = read_manifest(&dir).ok_or_else(|| format!("Élément introuvable dans la corbeille: {}", id))?;
+    if let Some(taken) = manifest.paths.iter().find(|p| Path::new(&p.original_path).exists()) {
+        return Err(format!("{} existe déjà", taken.original_path));
+    }
+    for path in &manifest.paths {
+        assets::move_path(&dir.join(&path.stored), Path::new(&path.original_path))?;
+    }
+    std::fs::remove_dir_all(&dir).map_err(|e| format!("Impossible de nettoyer la corbeille: {}", e))?;
+    Ok(manifest.paths.first().map(|p| p.original_path.clone()).unwrap_or_default())
+}
+
+/// Permanently deletes trashed items, all of them or only those deleted more
+/// than `older_than_days` days ago. Returns how many were removed.
+#[tauri::command]
+pub fn empty_trash(app: AppHandle, older_than_days: Option<u64>) -> Result<usize, String> {
+    // An age too large to represent counts as no limit
+    let cutoff = older_than_days
+        .and_then(|days| days.checked_mul(24 * 60 * 60))
+        .and_then(|secs| SystemTime::now().checked_sub(Duration::from_secs(secs)));
+    let entries = std::fs::read_dir(trash_root(&app)?)
+        .map_err(|e| format!("Impossible de lire la corbeille: {}", e))?;
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        // Entry folders are last written when the item is deleted
+        let modified = entry.metadata().and_then(|m| m.modified()).unwrap_or_else(|_| SystemTime::now());
+        if cutoff.is_some_and(|cutoff| modified > cutoff) {
+            continue;
+        }
+        let path = entry.path();
+        let result = if path.is_dir() { std::fs::remove_dir_all(&path) } else { std::fs::remove_file(&path) };
+        result.map_err(|e| format!("Impossible de vider la corbeille: {}", e))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
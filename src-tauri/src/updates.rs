@@ -0,0 +1,172 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+use crate::{diagnostics, settings};
+
+/// Release manifests by channel. Beta builds are published to the `beta`
+/// release, which is overwritten on each one.
+const CHANNELS: &[(&str, &str)] = &[
+    ("stable", "https://github.com/devohmycode/ohmymarkdown/releases/latest/download/latest.json"),
+    ("beta", "https://github.com/devohmycode/ohmymarkdown/releases/download/beta/latest.json"),
+];
+/// Commands of this module, by name.
+const COMMANDS: &[&str] = &["check_for_updates", "install_update", "set_update_channel"];
+/// Emitted when a downloaded update waits for exports to finish.
+pub const UPDATE_DEFERRED_EVENT: &str = "update-deferred";
+
+/// Exports running now; an update restarts the app only once this is 0.
+static BUSY: AtomicUsize = AtomicUsize::new(0);
+/// Set from the moment an update waits to restart; new exports are refused
+/// until then, so none is cut short.
+static RESTARTING: AtomicBool = AtomicBool::new(false);
+static SIGNED: AtomicBool = AtomicBool::new(false);
+
+/// Held for the length of work an update restart would cut short.
+pub struct BusyGuard(());
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        BUSY.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub fn busy() -> Result<BusyGuard, String> {
+    BUSY.fetch_add(1, Ordering::SeqCst);
+    let guard = BusyGuard(());
+    if RESTARTING.load(Ordering::SeqCst) {
+        return Err("Une mise à jour est en cours d'installation: l'application va redémarrer".to_string());
+    }
+    Ok(guard)
+}
+
+/// The update found by the last check, and whether its install has started.
+#[derive(Default)]
+pub struct Updates {
+    available: Mutex<Option<Update>>,
+    installing: Mutex<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub channel: String,
+    pub current_version: String,
+    pub version: String,
+    /// Release notes, in Markdown.
+    pub notes: Option<String>,
+    pub date: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallStatus {
+    pub version: String,
+    /// True when the restart waits for running exports.
+    pub deferred: bool,
+}
+
+fn endpoint(channel: &str) -> Result<Url, String> {
+    let (_, url) = CHANNELS.iter().find(|(name, _)| *name == channel).ok_or_else(|| {
+        format!("Canal de mise à jour inconnu: {} ({})", channel, CHANNELS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", "))
+    })?;
+    Url::parse(url).map_err(|e| e.to_string())
+}
+
+/// Whether the build carries the release signing key, without which no
+/// update could be verified.
+fn is_signed(app: &AppHandle) -> bool {
+    let pubkey = app.config().plugins.0.get("updater").and_then(|updater| updater.get("pubkey")).and_then(|key| key.as_str());
+    pubkey.is_some_and(|key| !key.trim().is_empty())
+}
+
+/// Reads the signing key from the config, once the app is set up.
+pub fn init(app: &AppHandle) {
+    SIGNED.store(is_signed(app), Ordering::SeqCst);
+}
+
+/// Whether `command` is served: the update commands are left out of builds
+/// without the signing key.
+pub fn is_available(command: &str) -> bool {
+    SIGNED.load(Ordering::SeqCst) || !COMMANDS.contains(&command)
+}
+
+/// Looks for a newer version on the channel chosen in the settings.
+/// Returns None when this one is the latest.
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle, updates: State<'_, Updates>) -> Result<Option<UpdateInfo>, String> {
+    let channel = settings::load(&app).updates.channel;
+    let update = app
+        .updater_builder()
+        .endpoints(vec![endpoint(&channel)?])
+        .and_then(|builder| builder.build())
+        .map_err(|e| format!("Erreur de configuration des mises à jour: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("Impossible de vérifier les mises à jour: {}", e))?;
+    let info = update.as_ref().map(|update| UpdateInfo {
+        channel,
+        current_version: update.current_version.clone(),
+        version: update.version.clone(),
+        notes: update.body.clone(),
+        date: update.raw_json.get("pub_date").and_then(|d| d.as_str()).map(str::to_string),
+    });
+    *updates.available.lock().map_err(|e| e.to_string())? = update;
+    Ok(info)
+}
+
+/// Downloads the update found by `check_for_updates`, then installs it and
+/// restarts, waiting first for running exports to finish.
+#[tauri::command]
+pub async fn install_update(app: AppHandle, updates: State<'_, Updates>) -> Result<InstallStatus, String> {
+    let update = updates.available.lock().map_err(|e| e.to_string())?.clone().ok_or("Aucune mise à jour disponible")?;
+    {
+        let mut installing = updates.installing.lock().map_err(|e| e.to_string())?;
+        if *installing {
+            return Err("La mise à jour est déjà en cours d'installation".to_string());
+        }
+        *installing = true;
+    }
+    let bytes = match update.download(|_, _| {}, || {}).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            *updates.installing.lock().map_err(|e| e.to_string())? = false;
+            return Err(format!("Erreur lors du téléchargement de la mise à jour: {}", e));
+        }
+    };
+    let deferred = BUSY.load(Ordering::SeqCst) > 0;
+    if deferred {
+        let _ = app.emit(UPDATE_DEFERRED_EVENT, &update.version);
+    }
+    let version = update.version.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        RESTARTING.store(true, Ordering::SeqCst);
+        while BUSY.load(Ordering::SeqCst) > 0 {
+            std::thread::sleep(Duration::from_secs(1));
+        }
+        match update.install(bytes) {
+            Ok(()) => app.restart(),
+            Err(e) => {
+                RESTARTING.store(false, Ordering::SeqCst);
+                if let Ok(mut installing) = app.state::<Updates>().installing.lock() {
+                    *installing = false;
+                }
+                diagnostics::record_error("update", &e.to_string());
+            }
+        }
+    });
+    Ok(InstallStatus { version, deferred })
+}
+
+/// Picks the channel updates come from: "stable" or "beta".
+#[tauri::command]
+pub fn set_update_channel(app: AppHandle, updates: State<'_, Updates>, channel: String) -> Result<(), String> {
+    endpoint(&channel)?;
+    settings::update(&app, serde_json::json!({ "updates": { "channel": channel } }), None)?;
+    // What was found belongs to the other channel
+    *updates.available.lock().map_err(|e| e.to_string())? = None;
+    Ok(())
+}
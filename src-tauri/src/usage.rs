@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::{paths, settings};
+
+const USAGE_FILE: &str = "usage-stats.json";
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Counts of what the app was used for. Only names of features and formats
+/// are kept, never file names or content, and nothing leaves the machine:
+/// the user decides whether to share it.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UsageStats {
+    pub enabled: bool,
+    /// When counting started, RFC 3339.
+    pub since: Option<String>,
+    pub features: BTreeMap<String, u64>,
+    /// Successful conversions by "import:format" or "export:format".
+    pub conversions: BTreeMap<String, u64>,
+    /// Failed conversions, by the same keys.
+    pub errors: BTreeMap<String, u64>,
+}
+
+fn usage_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_data_dir(app)?.join(USAGE_FILE))
+}
+
+fn load(app: &AppHandle) -> UsageStats {
+    usage_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Runs `change` on the saved counts, when the user opted in.
+fn record(app: &AppHandle, change: impl FnOnce(&mut UsageStats)) {
+    if !settings::load(app).usage_stats {
+        return;
+    }
+    let Ok(_guard) = LOCK.lock() else { return };
+    let mut stats = load(app);
+    stats.since.get_or_insert_with(|| chrono::Local::now().to_rfc3339());
+    change(&mut stats);
+    if let (Ok(path), Ok(json)) = (usage_path(app), serde_json::to_string_pretty(&stats)) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+/// Keeps names to what a feature or format id looks like, so nothing
+/// personal ends up counted.
+fn is_id(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 64 && name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.+".contains(c))
+}
+
+/// Counts a conversion to or from `format` as a success or an error.
+pub fn record_conversion<T>(app: &AppHandle, direction: &str, format: &str, result: &Result<T, String>) {
+    if !is_id(format) {
+        return;
+    }
+    let key = format!("{}:{}", direction, format.to_lowercase());
+    record(app, |stats| {
+        let counts = if result.is_ok() { &mut stats.conversions } else { &mut stats.errors };
+        *counts.entry(key).or_default() += 1;
+    });
+}
+
+#[tauri::command]
+pub fn get_usage_stats(app: AppHandle) -> UsageStats {
+    UsageStats { enabled: settings::load(&app).usage_stats, ..load(&app) }
+}
+
+/// Counts a use of `feature`, an id such as "focus-mode".
+#[tauri::command]
+pub fn record_feature_use(app: AppHandle, feature: String) -> Result<(), String> {
+    if !is_id(&feature) {
+        return Err(format!("Nom de fonctionnalité invalide: {}", feature));
+    }
+    record(&app, |stats| *stats.features.entry(feature).or_default() += 1);
+    Ok(())
+}
+
+/// Turns counting on or off; turning it off also deletes what was counted.
+#[tauri::command]
+pub fn set_usage_stats_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    settings::update(&app, serde_json::json!({ "usageStats": enabled }), None)?;
+    if !enabled {
+        clear_usage_stats(app)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn clear_usage_stats(app: AppHandle) -> Result<(), String> {
+    let _guard = LOCK.lock().map_err(|e| e.to_string())?;
+    let path = usage_path(&app)?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("Impossible de supprimer les statistiques d'utilisation: {}", e))?;
+    }
+    Ok(())
+}
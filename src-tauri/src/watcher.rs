@@ -0,0 +1,210 @@
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const FILE_CHANGED_EVENT: &str = "file-changed";
+/// Folders tools write to constantly, whose changes are never reported.
+const IGNORED_DIRS: &[&str] = &[".git", crate::obsidian::CONFIG_DIR];
+
+/// Payload of the `file-changed` event.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileChange {
+    /// "modified", "created", "deleted" or "renamed".
+    pub kind: &'static str,
+    pub path: String,
+    /// Destination of a rename, when known.
+    pub new_path: Option<String>,
+}
+
+#[derive(Default)]
+struct WatchedPaths {
+    /// Open files. Their parent directory is watched rather than the file
+    /// itself, so the watch survives editors and sync tools that save by
+    /// replacing the file.
+    files: HashSet<PathBuf>,
+    folders: HashSet<PathBuf>,
+}
+
+/// Temporary files and backups `safe_save` leaves next to a saved file:
+/// `.notes.md.123.tmp`, `notes.md.bak`, `notes.md.bak.2`…
+fn is_save_artifact(path: &Path) -> bool {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy()) else { return false };
+    (name.starts_with('.') && name.ends_with(".tmp"))
+        || name.ends_with(".bak")
+        || name.rsplit_once(".bak.").is_some_and(|(_, n)| n.parse::<usize>().is_ok())
+}
+
+impl WatchedPaths {
+    fn is_watched(&self, path: &Path) -> bool {
+        if is_save_artifact(path) {
+            return false;
+        }
+        let ignored = path.components().any(|c| IGNORED_DIRS.iter().any(|d| c.as_os_str() == *d));
+        self.files.contains(path) || (!ignored && self.folders.iter().any(|f| path.starts_with(f)))
+    }
+
+    /// Whether a directory is already watched, for a folder or an open file.
+    fn covers(&self, dir: &Path) -> bool {
+        self.folders.iter().any(|f| dir.starts_with(f)) || self.files.iter().any(|f| f.parent() == Some(dir))
+    }
+}
+
+/// The watcher and the paths it reports are locked separately: notify runs
+/// the event callback on its own thread, which must be able to read the paths
+/// while `watch` waits for that thread.
+#[derive(Default)]
+pub struct FileWatcher {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    paths: Mutex<WatchedPaths>,
+    /// Files the app saved itself, with their modification time and size
+    /// right after. Their events don't reach the editor while they match.
+    own_writes: Mutex<HashMap<PathBuf, (SystemTime, u64)>>,
+}
+
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Called by `safe_save` once it has written `path`.
+pub fn record_own_write(app: &AppHandle, path: &Path) {
+    if let Some(stamp) = stamp(path) {
+        app.state::<FileWatcher>().own_writes.lock().unwrap().insert(path.to_path_buf(), stamp);
+    }
+}
+
+impl FileWatcher {
+    /// Whether `change` comes from a save of the app: the file is still as
+    /// it was written. A file changed since is forgotten.
+    fn is_own_write(&self, change: &FileChange) -> bool {
+        if change.kind == "deleted" || change.new_path.is_some() {
+            return false;
+        }
+        let path = Path::new(&change.path);
+        let mut writes = self.own_writes.lock().unwrap();
+        match writes.get(path) {
+            Some(written) if stamp(path).as_ref() == Some(written) => true,
+            Some(_) => {
+                writes.remove(path);
+                false
+            }
+            None => false,
+        }
+    }
+}
+
+fn to_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+fn changes(event: notify::Event, state: &WatchedPaths) -> Vec<FileChange> {
+    let paths: Vec<&PathBuf> = event.paths.iter().filter(|p| state.is_watched(p)).collect();
+    let change = |kind, path: &PathBuf| FileChange { kind, path: to_string(path), new_path: None };
+
+    match event.kind {
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+            let (from, to) = (&event.paths[0], &event.paths[1]);
+            if !state.is_watched(from) && state.is_watched(to) {
+                // Replaced by a file written elsewhere
+                let kind = if state.files.contains(to) { "modified" } else { "created" };
+                return vec![change(kind, to)];
+            }
+            paths.first().map(|_| FileChange { kind: "renamed", path: to_string(from), new_path: Some(to_string(to)) })
+                .into_iter()
+                .collect()
+        }
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => paths.into_iter().map(|p| change("renamed", p)).collect(),
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => paths
+            .into_iter()
+            .map(|p| change(if state.files.contains(p) { "modified" } else { "created" }, p))
+            .collect(),
+        EventKind::Modify(_) => paths.into_iter().map(|p| change("modified", p)).collect(),
+        EventKind::Remove(_) => paths.into_iter().map(|p| change("deleted", p)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn create_watcher(app: AppHandle) -> Result<RecommendedWatcher, String> {
+    notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else { return };
+        let watcher = app.state::<FileWatcher>();
+        let found = changes(event, &watcher.paths.lock().unwrap());
+        crate::workspace::on_changes(&app, &found);
+        crate::search::on_changes(&app, &found);
+        crate::backlinks::on_changes(&app, &found);
+        // The indexes see every change; the editor only those made elsewhere
+        for change in found.into_iter().filter(|change| !watcher.is_own_write(change)) {
+            let _ = app.emit(FILE_CHANGED_EVENT, change);
+        }
+    })
+    .map_err(|e| format!("Impossible de surveiller les fichiers: {}", e))
+}
+
+/// Watches an open file, or a folder recursively.
+pub fn watch(app: &AppHandle, path: &Path) -> Result<(), String> {
+    let watcher = app.state::<FileWatcher>();
+    let (dir, mode) = if path.is_dir() {
+        (path.to_path_buf(), RecursiveMode::Recursive)
+    } else {
+        let parent = path.parent().ok_or("Chemin invalide")?;
+        (parent.to_path_buf(), RecursiveMode::NonRecursive)
+    };
+
+    let mut notifier = watcher.watcher.lock().unwrap();
+    if notifier.is_none() {
+        *notifier = Some(create_watcher(app.clone())?);
+    }
+    let covered = watcher.paths.lock().unwrap().covers(&dir);
+    if !covered || mode == RecursiveMode::Recursive {
+        if let Some(w) = notifier.as_mut() {
+            w.watch(&dir, mode)
+                .map_err(|e| format!("Impossible de surveiller {}: {}", path.display(), e))?;
+        }
+    }
+
+    let mut paths = watcher.paths.lock().unwrap();
+    if mode == RecursiveMode::Recursive {
+        paths.folders.insert(dir);
+    } else {
+        paths.files.insert(path.to_path_buf());
+    }
+    Ok(())
+}
+
+pub fn unwatch(app: &AppHandle, path: &Path) {
+    let watcher = app.state::<FileWatcher>();
+    let mut notifier = watcher.watcher.lock().unwrap();
+    let mut paths = watcher.paths.lock().unwrap();
+    let dir = if paths.folders.remove(path) {
+        path.to_path_buf()
+    } else if paths.files.remove(path) {
+        path.parent().map(Path::to_path_buf).unwrap_or_default()
+    } else {
+        return;
+    };
+    let still_covered = paths.covers(&dir);
+    drop(paths);
+    if !still_covered {
+        if let Some(w) = notifier.as_mut() {
+            let _ = w.unwatch(&dir);
+        }
+    }
+}
+
+/// Emits `file-changed` events when the file or folder is changed from
+/// outside the editor.
+#[tauri::command]
+pub fn watch_path(app: AppHandle, path: &str) -> Result<(), String> {
+    watch(&app, &crate::scopes::check(&app, path)?)
+}
+
+#[tauri::command]
+pub fn unwatch_path(app: AppHandle, path: &str) {
+    unwatch(&app, Path::new(path))
+}
@@ -0,0 +1,594 @@
+use base64::Engine;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::workspace::{self, Workspace};
+use crate::{assets, http, locks, paths, safe_save, trash};
+
+const REMOTES_FILE: &str = "sync-remotes.json";
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+const PROPFIND_BODY: &str = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+<d:propfind xmlns:d=\"DAV:\"><d:prop><d:resourcetype/><d:getetag/><d:getlastmodified/><d:getcontentlength/></d:prop></d:propfind>";
+
+// Serializes read-modify-write cycles on the remotes file
+static LOCK: Mutex<()> = Mutex::new(());
+// One sync at a time
+static SYNCING: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct WebDavRemote {
+    /// Folder the workspace syncs with, e.g.
+    /// "https://cloud.example.com/remote.php/dav/files/alice/Notes/".
+    url: String,
+    username: String,
+    /// Password or app password.
+    password: String,
+}
+
+/// Remotes by workspace folder.
+type Remotes = BTreeMap<String, WebDavRemote>;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncRemoteInfo {
+    pub url: String,
+    pub username: String,
+}
+
+/// A file as both sides had it after the last sync.
+#[derive(Serialize, Deserialize, Clone)]
+struct FileState {
+    hash: String,
+    /// Local modification time and size, to skip hashing unchanged files.
+    modified: u64,
+    size: u64,
+    etag: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct SyncState {
+    /// Remote the files were synced with; another one starts from scratch.
+    remote: String,
+    files: BTreeMap<String, FileState>,
+}
+
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pub uploaded: usize,
+    pub downloaded: usize,
+    pub deleted_local: usize,
+    pub deleted_remote: usize,
+    /// Conflict copies created for files changed on both sides. The local
+    /// version keeps the original name.
+    pub conflicts: Vec<String>,
+    /// Files left unsynced, with the reason.
+    pub errors: Vec<String>,
+}
+
+fn remotes_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_config_dir(app)?.join(REMOTES_FILE))
+}
+
+fn load_remotes(app: &AppHandle) -> Remotes {
+    remotes_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn update_remotes(app: &AppHandle, change: impl FnOnce(&mut Remotes)) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap();
+    let mut remotes = load_remotes(app);
+    change(&mut remotes);
+    let json = serde_json::to_string_pretty(&remotes).map_err(|e| e.to_string())?;
+    let path = remotes_path(app)?;
+    // The file holds passwords
+    crate::paths::write_private(&path, json.as_bytes()).map_err(|e| format!("Impossible d'enregistrer les serveurs de synchronisation: {}", e))
+}
+
+fn state_path(app: &AppHandle, root: &Path) -> Result<PathBuf, String> {
+    let mut hasher = Sha256::new();
+    hasher.update(root.to_string_lossy().as_bytes());
+    let key: String = hasher.finalize().iter().take(16).map(|b| format!("{:02x}", b)).collect();
+    Ok(paths::app_data_subdir(app, "sync")?.join(format!("{}.json", key)))
+}
+
+fn hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A file or folder listed by the server.
+struct Entry {
+    path: String,
+    collection: bool,
+    etag: String,
+}
+
+fn local_name(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).to_string()
+}
+
+/// Reads a PROPFIND multistatus. Paths come back relative to `base_path`,
+/// the decoded path of the remote folder.
+fn parse_multistatus(xml: &str, base_path: &str) -> Result<Vec<Entry>, String> {
+    let invalid = |e: quick_xml::Error| format!("Réponse WebDAV invalide: {}", e);
+    let mut reader = Reader::from_str(xml);
+    let mut entries = Vec::new();
+    let mut element = String::new();
+    let (mut href, mut etag, mut modified, mut length, mut collection) = (String::new(), String::new(), String::new(), String::new(), false);
+    loop {
+        match reader.read_event().map_err(invalid)? {
+            Event::Start(e) => {
+                element = local_name(e.local_name().as_ref());
+                match element.as_str() {
+                    "response" => {
+                        (href, etag, modified, length, collection) = Default::default();
+                    }
+                    "collection" => collection = true,
+                    _ => {}
+                }
+            }
+            Event::Empty(e) if e.local_name().as_ref() == b"collection" => collection = true,
+            Event::Text(text) => {
+                let text = text.unescape().map_err(invalid)?;
+                let text = text.trim();
+                match element.as_str() {
+                    "href" => href.push_str(text),
+                    "getetag" => etag = text.to_string(),
+                    "getlastmodified" => modified = text.to_string(),
+                    "getcontentlength" => length = text.to_string(),
+                    _ => {}
+                }
+            }
+            Event::End(e) => {
+                if e.local_name().as_ref() == b"response" {
+                    // Hrefs are absolute paths or full URLs
+                    let href = match href.find("://") {
+                        Some(i) => href[i + 3..].find('/').map(|j| &href[i + 3 + j..]).unwrap_or("/"),
+                        None => href.as_str(),
+                    };
+                    let decoded = percent_decode_str(href).decode_utf8_lossy();
+                    if let Some(path) = decoded.strip_prefix(base_path) {
+                        let path = path.trim_matches('/').to_string();
+                        if !path.is_empty() {
+                            // Servers without ETags still give a date and a size
+                            let etag = if etag.is_empty() { format!("{}-{}", modified, length) } else { etag.clone() };
+                            entries.push(Entry { path, collection, etag });
+                        }
+                    }
+                }
+                element.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+/// Only strong ETags can be used in conditional requests.
+fn strong(etag: &str) -> Option<&str> {
+    etag.starts_with('"').then_some(etag)
+}
+
+enum Put {
+    Saved(String),
+    /// The remote file changed since it was last seen.
+    Conflict,
+}
+
+struct Client {
+    agent: ureq::Agent,
+    base: String,
+    base_path: String,
+    auth: String,
+}
+
+impl Client {
+    fn new(remote: &WebDavRemote) -> Self {
+        let base = format!("{}/", remote.url.trim_end_matches('/'));
+        let path = match base.find("://") {
+            Some(i) => base[i + 3..].find('/').map(|j| base[i + 3 + j..].to_string()).unwrap_or_else(|| "/".to_string()),
+            None => base.clone(),
+        };
+        let credentials = format!("{}:{}", remote.username, remote.password);
+        Client {
+            agent: http::agent(),
+            base_path: percent_decode_str(&path).decode_utf8_lossy().to_string(),
+            base,
+            auth: format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(credentials)),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        let encoded: Vec<String> = path.split('/').map(|s| utf8_percent_encode(s, SEGMENT).to_string()).collect();
+        format!("{}{}", self.base, encoded.join("/"))
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        self.agent.request(method, &self.url(path)).set("Authorization", &self.auth)
+    }
+
+    fn propfind(&self, path: &str, depth: &str) -> Result<Vec<Entry>, String> {
+        let xml = self
+            .request("PROPFIND", path)
+            .set("Depth", depth)
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .send_string(PROPFIND_BODY)
+            .map_err(http::error_message)?
+            .into_string()
+            .map_err(|e| format!("Réponse WebDAV invalide: {}", e))?;
+        parse_multistatus(&xml, &self.base_path)
+    }
+
+    /// Every file under the remote folder with its ETag, and the folders.
+    /// Folders are listed one level at a time since many servers refuse
+    /// `Depth: infinity`.
+    fn list(&self) -> Result<(BTreeMap<String, String>, HashSet<String>), String> {
+        let mut files = BTreeMap::new();
+        let mut folders = HashSet::new();
+        let mut pending = vec![String::new()];
+        while let Some(folder) = pending.pop() {
+            let url_path = if folder.is_empty() { String::new() } else { format!("{}/", folder) };
+            for entry in self.propfind(&url_path, "1")? {
+                // Hidden files stay local, as in the workspace tree; a `\` could
+                // climb out of it on Windows
+                if entry.path == folder || entry.path.contains('\\') || entry.path.split('/').any(|s| s.starts_with('.')) {
+                    continue;
+                }
+                if entry.collection {
+                    if folders.insert(entry.path.clone()) {
+                        pending.push(entry.path);
+                    }
+                } else {
+                    files.insert(entry.path, entry.etag);
+                }
+            }
+        }
+        Ok((files, folders))
+    }
+
+    fn get(&self, path: &str) -> Result<Vec<u8>, String> {
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut self.request("GET", path).call().map_err(http::error_message)?.into_reader(), &mut bytes)
+            .map_err(|e| format!("Erreur pendant le téléchargement de {}: {}", path, e))?;
+        Ok(bytes)
+    }
+
+    /// Creates the parent folders of `path` that don't exist yet.
+    fn make_parents(&self, path: &str, folders: &mut HashSet<String>) -> Result<(), String> {
+        let mut folder = String::new();
+        for segment in path.split('/').collect::<Vec<_>>().split_last().map(|(_, parents)| parents).unwrap_or_default() {
+            if !folder.is_empty() {
+                folder.push('/');
+            }
+            folder.push_str(segment);
+            if folders.insert(folder.clone()) {
+                match self.request("MKCOL", &format!("{}/", folder)).call() {
+                    // Already there
+                    Ok(_) | Err(ureq::Error::Status(405, _)) => {}
+                    Err(e) => return Err(http::error_message(e)),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Uploads a file, only over the version `etag` (or only if absent when
+    /// None), and returns its new ETag.
+    fn put(&self, path: &str, bytes: &[u8], etag: Option<&str>) -> Result<Put, String> {
+        let request = match etag {
+            Some(etag) => match strong(etag) {
+                Some(etag) => self.request("PUT", path).set("If-Match", etag),
+                None => self.request("PUT", path),
+            },
+            None => self.request("PUT", path).set("If-None-Match", "*"),
+        };
+        match request.send_bytes(bytes) {
+            Err(ureq::Error::Status(412, _)) => Ok(Put::Conflict),
+            Err(e) => Err(http::error_message(e)),
+            Ok(response) => match response.header("ETag").or_else(|| response.header("OC-ETag")) {
+                Some(etag) => Ok(Put::Saved(etag.to_string())),
+                None => {
+                    let entry = self.propfind(path, "0")?.into_iter().next();
+                    Ok(Put::Saved(entry.map(|e| e.etag).unwrap_or_default()))
+                }
+            },
+        }
+    }
+
+    /// Deletes a file unless it changed since `etag`. False when it did.
+    fn delete(&self, path: &str, etag: &str) -> Result<bool, String> {
+        let request = match strong(etag) {
+            Some(etag) => self.request("DELETE", path).set("If-Match", etag),
+            None => self.request("DELETE", path),
+        };
+        match request.call() {
+            Ok(_) | Err(ureq::Error::Status(404, _)) => Ok(true),
+            Err(ureq::Error::Status(412, _)) => Ok(false),
+            Err(e) => Err(http::error_message(e)),
+        }
+    }
+}
+
+/// The local side of a file: its content hash and metadata.
+struct Local {
+    hash: String,
+    modified: u64,
+    size: u64,
+}
+
+fn local(path: &Path, previous: Option<&FileState>) -> Option<Local> {
+    let metadata = std::fs::metadata(path).ok().filter(|m| m.is_file())?;
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let size = metadata.len();
+    let hash = match previous {
+        Some(previous) if previous.modified == modified && previous.size == size => previous.hash.clone(),
+        _ => hash(&std::fs::read(path).ok()?),
+    };
+    Some(Local { hash, modified, size })
+}
+
+/// Name for the remote version of a file changed on both sides, next to it.
+fn conflict_copy(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let suffix = chrono::Local::now().format("%Y-%m-%d %H%M%S");
+    let name = match path.extension() {
+        Some(ext) => format!("{} (conflit {}).{}", stem, suffix, ext.to_string_lossy()),
+        None => format!("{} (conflit {})", stem, suffix),
+    };
+    assets::unique_path(path.parent().unwrap_or(Path::new(".")), &name)
+}
+
+struct Sync<'a> {
+    app: &'a AppHandle,
+    root: &'a Path,
+    client: Client,
+    folders: HashSet<String>,
+    state: SyncState,
+    report: SyncReport,
+}
+
+impl Sync<'_> {
+    fn record(&mut self, relative: &str, path: &Path, etag: String) {
+        match local(path, None) {
+            Some(local) => {
+                let state = FileState { hash: local.hash, modified: local.modified, size: local.size, etag };
+                self.state.files.insert(relative.to_string(), state);
+            }
+            None => {
+                self.state.files.remove(relative);
+            }
+        }
+    }
+
+    fn write_local(&self, path: &Path, bytes: &[u8]) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Impossible de créer le dossier {}: {}", parent.display(), e))?;
+        }
+        safe_save::write_document(self.app, path, bytes)
+    }
+
+    fn download(&mut self, relative: &str, path: &Path, etag: &str) -> Result<(), String> {
+        locks::ensure_writable(self.app, path)?;
+        let bytes = self.client.get(relative)?;
+        self.write_local(path, &bytes)?;
+        self.record(relative, path, etag.to_string());
+        self.report.downloaded += 1;
+        Ok(())
+    }
+
+    fn upload(&mut self, relative: &str, path: &Path, etag: Option<&str>) -> Result<(), String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+        self.client.make_parents(relative, &mut self.folders)?;
+        match self.client.put(relative, &bytes, etag)? {
+            Put::Saved(etag) => {
+                self.record(relative, path, etag);
+                self.report.uploaded += 1;
+                Ok(())
+            }
+            Put::Conflict => self.resolve_conflict(relative, path),
+        }
+    }
+
+    /// Keeps both versions: the remote one goes to a conflict copy, which is
+    /// uploaded too, and the local one replaces it on the server.
+    fn resolve_conflict(&mut self, relative: &str, path: &Path) -> Result<(), String> {
+        let remote = self.client.get(relative)?;
+        let local = std::fs::read(path).map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?;
+        if remote != local {
+            let copy = conflict_copy(path);
+            self.write_local(&copy, &remote)?;
+            let copy_relative = copy.strip_prefix(self.root).unwrap_or(&copy).to_string_lossy().replace('\\', "/");
+            self.report.conflicts.push(copy.to_string_lossy().to_string());
+            if let Put::Saved(etag) = self.client.put(&copy_relative, &remote, None)? {
+                self.record(&copy_relative, &copy, etag);
+            }
+        }
+        // Current ETag, since the server's copy moved on
+        let etag = self.client.propfind(relative, "0")?.into_iter().next().map(|e| e.etag).unwrap_or_default();
+        match self.client.put(relative, &local, Some(&etag))? {
+            Put::Saved(etag) => {
+                self.record(relative, path, etag);
+                self.report.uploaded += 1;
+                Ok(())
+            }
+            Put::Conflict => Err("modifié de nouveau sur le serveur pendant la synchronisation".to_string()),
+        }
+    }
+
+    fn sync_file(&mut self, relative: &str, remote: Option<&String>) -> Result<(), String> {
+        let path = self.root.join(relative);
+        let inside = !relative.contains('\\') && Path::new(relative).components().all(|c| matches!(c, Component::Normal(_)));
+        if !inside || !path.starts_with(self.root) {
+            return Err("chemin refusé".to_string());
+        }
+        let base = self.state.files.get(relative).cloned();
+        let local = local(&path, base.as_ref());
+        let local_changed = match (&local, &base) {
+            (Some(local), Some(base)) => local.hash != base.hash,
+            (None, None) => false,
+            _ => true,
+        };
+        let remote_changed = match (remote, &base) {
+            (Some(etag), Some(base)) => *etag != base.etag,
+            (None, None) => false,
+            _ => true,
+        };
+
+        match (&local, remote) {
+            (Some(local), Some(etag)) => match (local_changed, remote_changed) {
+                (false, false) => {
+                    if let Some(state) = self.state.files.get_mut(relative) {
+                        // Touched without changes
+                        state.modified = local.modified;
+                    }
+                    Ok(())
+                }
+                (true, false) => self.upload(relative, &path, Some(etag)),
+                (false, true) => self.download(relative, &path, etag),
+                (true, true) => self.resolve_conflict(relative, &path),
+            },
+            (Some(_), None) if base.is_some() && !local_changed => {
+                // Deleted on the server
+                trash::move_to_trash(self.app, std::slice::from_ref(&path))?;
+                self.state.files.remove(relative);
+                self.report.deleted_local += 1;
+                Ok(())
+            }
+            (Some(_), None) => self.upload(relative, &path, None),
+            (None, Some(etag)) if base.is_some() && !remote_changed => {
+                // Deleted here
+                if self.client.delete(relative, etag)? {
+                    self.state.files.remove(relative);
+                    self.report.deleted_remote += 1;
+                    Ok(())
+                } else {
+                    let etag = self.client.propfind(relative, "0")?.into_iter().next().map(|e| e.etag).unwrap_or_default();
+                    self.download(relative, &path, &etag)
+                }
+            }
+            (None, Some(etag)) => self.download(relative, &path, etag),
+            (None, None) => {
+                self.state.files.remove(relative);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn sync(app: &AppHandle, root: &Path, remote: &WebDavRemote) -> Result<SyncReport, String> {
+    let state_file = state_path(app, root)?;
+    let state: SyncState = std::fs::read_to_string(&state_file)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .filter(|state: &SyncState| state.remote == remote.url)
+        .unwrap_or_else(|| SyncState { remote: remote.url.clone(), files: BTreeMap::new() });
+
+    let client = Client::new(remote);
+    let (remote_files, folders) = client.list()?;
+    // An empty listing after a sync is more likely a moved server than
+    // every file deleted: nothing is sent to the trash for it
+    if remote_files.is_empty() && !state.files.is_empty() {
+        return Err(format!(
+            "Le serveur n'a listé aucun fichier alors que {} étaient synchronisés: synchronisation interrompue, vérifiez l'adresse du serveur",
+            state.files.len()
+        ));
+    }
+    let mut paths: BTreeSet<String> = workspace::walk(root)
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|e| e.path().strip_prefix(root).ok().map(|p| p.to_string_lossy().replace('\\', "/")))
+        .collect();
+    paths.extend(remote_files.keys().cloned());
+    paths.extend(state.files.keys().cloned());
+
+    let mut sync = Sync { app, root, client, folders, state, report: SyncReport::default() };
+    for relative in &paths {
+        if let Err(e) = sync.sync_file(relative, remote_files.get(relative)) {
+            sync.report.errors.push(format!("{}: {}", relative, e));
+        }
+    }
+
+    let json = serde_json::to_string(&sync.state).map_err(|e| e.to_string())?;
+    // Losing it would make the next sync compare every file from scratch
+    safe_save::write(app, &state_file, json.as_bytes())?;
+    Ok(sync.report)
+}
+
+fn workspace_key(app: &AppHandle) -> Result<(PathBuf, String), String> {
+    let root = app.state::<Workspace>().root()?;
+    let key = root.to_string_lossy().to_string();
+    Ok((root, key))
+}
+
+/// The WebDAV remote of the open workspace; the password stays in the
+/// backend.
+#[tauri::command]
+pub fn get_sync_remote(app: AppHandle) -> Result<Option<SyncRemoteInfo>, String> {
+    let (_, key) = workspace_key(&app)?;
+    Ok(load_remotes(&app).remove(&key).map(|r| SyncRemoteInfo { url: r.url, username: r.username }))
+}
+
+/// Sets the WebDAV folder (Nextcloud, ownCloud…) the open workspace syncs
+/// with, after checking the server accepts the credentials.
+#[tauri::command]
+pub async fn set_sync_remote(app: AppHandle, url: String, username: String, password: String) -> Result<SyncRemoteInfo, String> {
+    let (_, key) = workspace_key(&app)?;
+    let url = url.trim().to_string();
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(format!("Adresse WebDAV invalide: {}", url));
+    }
+    let remote = WebDavRemote { url: format!("{}/", url.trim_end_matches('/')), username: username.trim().to_string(), password };
+    let remote = tauri::async_runtime::spawn_blocking(move || {
+        Client::new(&remote).propfind("", "0").map_err(|e| format!("Connexion au serveur WebDAV impossible: {}", e))?;
+        Ok::<_, String>(remote)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la connexion au serveur WebDAV: {}", e))??;
+    let info = SyncRemoteInfo { url: remote.url.clone(), username: remote.username.clone() };
+    update_remotes(&app, |remotes| {
+        remotes.insert(key, remote);
+    })?;
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn remove_sync_remote(app: AppHandle) -> Result<(), String> {
+    let (root, key) = workspace_key(&app)?;
+    update_remotes(&app, |remotes| {
+        remotes.remove(&key);
+    })?;
+    let _ = std::fs::remove_file(state_path(&app, &root)?);
+    Ok(())
+}
+
+/// Two-way sync of the open workspace with its WebDAV remote. Changes on one
+/// side are applied to the other, deletions included (local files go to the
+/// trash). A file changed on both sides keeps the local version and gets a
+/// conflict copy of the remote one.
+#[tauri::command]
+pub async fn sync_workspace(app: AppHandle) -> Result<SyncReport, String> {
+    let (root, key) = workspace_key(&app)?;
+    let remote = load_remotes(&app).remove(&key).ok_or("Aucun serveur de synchronisation n'est configuré pour ce dossier")?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let _guard = SYNCING.try_lock().map_err(|_| "Une synchronisation est déjà en cours".to_string())?;
+        sync(&app, &root, &remote)
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la synchronisation: {}", e))?
+}
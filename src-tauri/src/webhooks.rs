@@ -0,0 +1,595 @@
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::{frontmatter, http, paths};
+
+const WEBHOOKS_FILE: &str = "webhooks.json";
+/// Rate-limited posts are retried this many times.
+const MAX_RETRIES: usize = 3;
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(30);
+
+// Serializes read-modify-write cycles on the webhooks file
+static LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookKind {
+    Slack,
+    Discord,
+    Teams,
+}
+
+impl WebhookKind {
+    /// Characters per message, under each service's limit.
+    fn max_message(self) -> usize {
+        match self {
+            WebhookKind::Slack => 3500,
+            WebhookKind::Discord => 2000,
+            WebhookKind::Teams => 20_000,
+        }
+    }
+
+    fn payload(self, text: &str) -> Value {
+        match self {
+            WebhookKind::Slack => json!({ "text": text }),
+            // Nobody gets pinged by an @everyone in the document
+            WebhookKind::Discord => json!({ "content": text, "allowed_mentions": { "parse": [] } }),
+            WebhookKind::Teams => json!({
+                "type": "message",
+                "attachments": [{
+                    "contentType": "application/vnd.microsoft.card.adaptive",
+                    "content": {
+                        "type": "AdaptiveCard",
+                        "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                        "version": "1.4",
+                        "body": [{ "type": "TextBlock", "text": text, "wrap": true }],
+                    },
+                }],
+            }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Webhook {
+    pub kind: WebhookKind,
+    pub url: String,
+}
+
+/// A saved webhook without its URL, which is a secret.
+#[derive(Serialize)]
+pub struct WebhookInfo {
+    pub name: String,
+    pub kind: WebhookKind,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookShare {
+    /// Messages the document was split into.
+    pub messages: usize,
+}
+
+type Webhooks = BTreeMap<String, Webhook>;
+
+fn webhooks_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(paths::app_config_dir(app)?.join(WEBHOOKS_FILE))
+}
+
+fn load(app: &AppHandle) -> Webhooks {
+    webhooks_path(app)
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn update(app: &AppHandle, change: impl FnOnce(&mut Webhooks)) -> Result<(), String> {
+    let _guard = LOCK.lock().unwrap();
+    let mut webhooks = load(app);
+    change(&mut webhooks);
+    let json = serde_json::to_string_pretty(&webhooks).map_err(|e| e.to_string())?;
+    let path = webhooks_path(app)?;
+    // Anyone with a webhook URL can post to the channel
+    crate::paths::write_private(&path, json.as_bytes()).map_err(|e| format!("Impossible d'enregistrer les webhooks: {}", e))
+}
+
+/// Where rendered text goes: a block being built, or an inline element
+/// waiting for its end to be written out.
+enum Frame {
+    Item,
+    Quote,
+    Link(String),
+    Image(String),
+    Cell,
+}
+
+/// Renders markdown events to a chat service's markup.
+struct Renderer {
+    kind: WebhookKind,
+    /// Output of the open frames, the document itself at the bottom.
+    buffers: Vec<String>,
+    frames: Vec<Frame>,
+    /// Next number of each open list, None for bullets.
+    lists: Vec<Option<u64>>,
+    code: bool,
+    table: Vec<Vec<String>>,
+    in_table: bool,
+    /// A footnote label was just written, its text follows on the same line.
+    footnote: bool,
+}
+
+impl Renderer {
+    fn out(&mut self) -> &mut String {
+        self.buffers.last_mut().unwrap()
+    }
+
+    fn push(&mut self, text: &str) {
+        self.out().push_str(text);
+    }
+
+    /// Formatting markers, left out of table cells which end up in a code
+    /// block.
+    fn mark(&mut self, marker: &str) {
+        if !self.in_table {
+            self.push(marker);
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        let text = match self.kind {
+            // Slack's only escapes; `<` would start a mention or a link
+            WebhookKind::Slack => text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"),
+            _ if self.code || self.in_table => text.to_string(),
+            _ => {
+                let mut escaped = String::with_capacity(text.len());
+                for c in text.chars() {
+                    if matches!(c, '\\' | '*' | '_' | '~' | '`' | '|') {
+                        escaped.push('\\');
+                    }
+                    escaped.push(c);
+                }
+                escaped
+            }
+        };
+        self.push(&text);
+    }
+
+    /// Separates a block from what precedes it: a line within a list item, a
+    /// blank line elsewhere.
+    fn block(&mut self) {
+        if std::mem::take(&mut self.footnote) {
+            return;
+        }
+        let tight = matches!(self.frames.last(), Some(Frame::Item));
+        let out = self.out();
+        let trimmed = out.trim_end_matches('\n').len();
+        if trimmed == 0 {
+            out.clear();
+            return;
+        }
+        out.truncate(trimmed);
+        out.push_str(if tight { "\n" } else { "\n\n" });
+    }
+
+    fn open(&mut self, frame: Frame) {
+        self.frames.push(frame);
+        self.buffers.push(String::new());
+    }
+
+    fn close(&mut self) -> (Frame, String) {
+        let frame = self.frames.pop().unwrap();
+        let buffer = self.buffers.pop().unwrap();
+        (frame, buffer)
+    }
+
+    fn link(&mut self, url: &str, text: &str) {
+        let remote = url.starts_with("https://") || url.starts_with("http://") || url.starts_with("mailto:");
+        let link = match self.kind {
+            _ if !remote || self.in_table => text.to_string(),
+            WebhookKind::Slack if text.is_empty() || text == url => format!("<{}>", url),
+            WebhookKind::Slack => format!("<{}|{}>", url, text.replace('|', "¦")),
+            _ if text.is_empty() || text == url => url.to_string(),
+            _ => format!("[{}]({})", text, url),
+        };
+        self.push(&link);
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Paragraph => self.block(),
+            Tag::Heading { level, .. } => {
+                self.block();
+                let level = level as usize;
+                match self.kind {
+                    WebhookKind::Discord if level <= 3 => self.push(&format!("{} ", "#".repeat(level))),
+                    WebhookKind::Slack => self.push("*"),
+                    _ => self.push("**"),
+                }
+            }
+            Tag::BlockQuote(_) => {
+                self.block();
+                self.open(Frame::Quote);
+            }
+            Tag::CodeBlock(kind) => {
+                self.block();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(lang) if self.kind != WebhookKind::Slack => lang.split_whitespace().next().unwrap_or_default().to_string(),
+                    _ => String::new(),
+                };
+                self.push(&format!("```{}\n", lang));
+                self.code = true;
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.block();
+                } else {
+                    self.push("\n");
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => self.open(Frame::Item),
+            Tag::FootnoteDefinition(label) => {
+                self.block();
+                self.push(&format!("[{}] ", label));
+                self.footnote = true;
+            }
+            Tag::Table(_) => {
+                self.block();
+                self.in_table = true;
+                self.table.clear();
+            }
+            Tag::TableHead | Tag::TableRow => self.table.push(Vec::new()),
+            Tag::TableCell => self.open(Frame::Cell),
+            Tag::Emphasis => self.mark("_"),
+            Tag::Strong => self.mark(if self.kind == WebhookKind::Slack { "*" } else { "**" }),
+            Tag::Strikethrough => self.mark(if self.kind == WebhookKind::Slack { "~" } else { "~~" }),
+            Tag::Link { dest_url, .. } => self.open(Frame::Link(dest_url.to_string())),
+            Tag::Image { dest_url, .. } => self.open(Frame::Image(dest_url.to_string())),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Heading(level) => match self.kind {
+                WebhookKind::Discord if level as usize <= 3 => {}
+                WebhookKind::Slack => self.push("*"),
+                _ => self.push("**"),
+            },
+            TagEnd::BlockQuote(_) => {
+                let (_, quote) = self.close();
+                let quoted: Vec<String> = quote.trim_end().lines().map(|l| format!("> {}", l).trim_end().to_string()).collect();
+                self.push(&quoted.join("\n"));
+            }
+            TagEnd::CodeBlock => {
+                if !self.out().ends_with('\n') {
+                    self.push("\n");
+                }
+                self.push("```");
+                self.code = false;
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+            }
+            TagEnd::Item => {
+                let (_, item) = self.close();
+                let slack = self.kind == WebhookKind::Slack;
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ if slack => "• ".to_string(),
+                    _ => "- ".to_string(),
+                };
+                let indent = " ".repeat(marker.chars().count());
+                let mut lines = item.trim().lines();
+                let mut rendered = format!("{}{}", marker, lines.next().unwrap_or_default());
+                for line in lines {
+                    rendered.push('\n');
+                    if !line.is_empty() {
+                        rendered.push_str(&indent);
+                        rendered.push_str(line);
+                    }
+                }
+                let out = self.out();
+                if !out.is_empty() && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+                out.push_str(&rendered);
+            }
+            TagEnd::Table => {
+                self.in_table = false;
+                let table = std::mem::take(&mut self.table);
+                let columns = table.iter().map(Vec::len).max().unwrap_or(0);
+                let widths: Vec<usize> =
+                    (0..columns).map(|c| table.iter().filter_map(|r| r.get(c)).map(|s| s.chars().count()).max().unwrap_or(0)).collect();
+                // Chat services have no tables; aligned columns in a code block read well
+                let mut rendered = String::from("```\n");
+                for (i, row) in table.iter().enumerate() {
+                    let cells: Vec<String> =
+                        widths.iter().enumerate().map(|(c, w)| format!("{:<w$}", row.get(c).map(String::as_str).unwrap_or(""), w = w)).collect();
+                    rendered.push_str(cells.join(" | ").trim_end());
+                    rendered.push('\n');
+                    if i == 0 {
+                        let rule: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+                        rendered.push_str(&rule.join("-+-"));
+                        rendered.push('\n');
+                    }
+                }
+                rendered.push_str("```");
+                self.push(&rendered);
+            }
+            TagEnd::TableCell => {
+                let (_, cell) = self.close();
+                if let Some(row) = self.table.last_mut() {
+                    row.push(cell.trim().to_string());
+                }
+            }
+            TagEnd::Emphasis => self.mark("_"),
+            TagEnd::Strong => self.mark(if self.kind == WebhookKind::Slack { "*" } else { "**" }),
+            TagEnd::Strikethrough => self.mark(if self.kind == WebhookKind::Slack { "~" } else { "~~" }),
+            TagEnd::Link | TagEnd::Image => match self.close() {
+                (Frame::Link(url), text) | (Frame::Image(url), text) => self.link(&url, &text),
+                (_, text) => self.push(&text),
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Converts markdown to the markup of `kind`: Slack's mrkdwn, Discord
+/// markdown, or the subset of Teams cards.
+fn convert(md: &str, kind: WebhookKind) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_FOOTNOTES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut renderer = Renderer {
+        kind,
+        buffers: vec![String::new()],
+        frames: Vec::new(),
+        lists: Vec::new(),
+        code: false,
+        table: Vec::new(),
+        in_table: false,
+        footnote: false,
+    };
+    for event in Parser::new_ext(frontmatter::body(md), options) {
+        match event {
+            Event::Start(tag) => renderer.start(tag),
+            Event::End(tag) => renderer.end(tag),
+            Event::Text(text) => renderer.text(&text),
+            Event::Code(code) => {
+                let code = if kind == WebhookKind::Slack { code.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;") } else { code.to_string() };
+                if renderer.in_table {
+                    renderer.push(&code);
+                } else {
+                    renderer.push(&format!("`{}`", code));
+                }
+            }
+            Event::SoftBreak => renderer.push(" "),
+            Event::HardBreak => renderer.push("\n"),
+            Event::Rule => {
+                renderer.block();
+                renderer.push("──────────");
+            }
+            Event::TaskListMarker(done) => renderer.push(if done { "☑ " } else { "☐ " }),
+            Event::FootnoteReference(label) => renderer.push(&format!("[{}]", label)),
+            _ => {}
+        }
+    }
+    renderer.buffers.swap_remove(0).trim().to_string()
+}
+
+/// Blocks of the text: paragraphs, lists, and code blocks whole even with
+/// blank lines inside.
+fn blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut fence = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            fence = !fence;
+        }
+        if line.trim().is_empty() && !fence {
+            if !current.is_empty() {
+                blocks.push(current.join("\n"));
+                current.clear();
+            }
+            continue;
+        }
+        current.push(line);
+    }
+    if !current.is_empty() {
+        blocks.push(current.join("\n"));
+    }
+    blocks
+}
+
+/// Pieces of at most `max` characters of a line, cut between words where
+/// possible.
+fn cut_line(line: &str, max: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest: Vec<char> = line.chars().collect();
+    while rest.len() > max {
+        let cut = rest[..max].iter().rposition(|c| c.is_whitespace()).filter(|&i| i > 0).unwrap_or(max);
+        pieces.push(rest[..cut].iter().collect());
+        let next = if rest[cut].is_whitespace() { cut + 1 } else { cut };
+        rest.drain(..next);
+    }
+    pieces.push(rest.into_iter().collect());
+    pieces
+}
+
+/// Cuts a block too long for one message line by line, closing and reopening
+/// a code block it cuts through.
+fn split_block(block: &str, limit: usize, out: &mut Vec<String>) {
+    let mut current = String::new();
+    let mut fence: Option<String> = None;
+    for line in block.lines() {
+        for piece in cut_line(line, limit.saturating_sub(8).max(1)) {
+            let reserve = if fence.is_some() { 4 } else { 0 };
+            if !current.is_empty() && current.chars().count() + 1 + piece.chars().count() + reserve > limit {
+                if fence.is_some() {
+                    current.push_str("\n```");
+                }
+                out.push(std::mem::take(&mut current));
+                if let Some(open) = &fence {
+                    current = open.clone();
+                }
+            }
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(&piece);
+            if piece.trim_start().starts_with("```") {
+                fence = if fence.is_some() { None } else { Some(piece.trim().to_string()) };
+            }
+        }
+    }
+    if !current.trim().is_empty() {
+        out.push(current.trim_end().to_string());
+    }
+}
+
+/// Splits the text into messages of at most `limit` characters, between
+/// blocks where possible.
+fn split(text: &str, limit: usize) -> Vec<String> {
+    let mut messages = Vec::new();
+    let mut current = String::new();
+    for block in blocks(text) {
+        let len = block.chars().count();
+        if !current.is_empty() && current.chars().count() + 2 + len > limit {
+            messages.push(std::mem::take(&mut current));
+        }
+        if len > limit {
+            split_block(&block, limit, &mut messages);
+            continue;
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(&block);
+    }
+    if !current.is_empty() {
+        messages.push(current);
+    }
+    messages
+}
+
+fn messages(markdown: &str, kind: WebhookKind) -> Vec<String> {
+    split(&convert(markdown, kind), kind.max_message())
+}
+
+/// How long a rate-limited service asks to wait.
+fn retry_after(response: ureq::Response) -> Duration {
+    let header = response.header("Retry-After").and_then(|s| s.trim().parse::<f64>().ok());
+    let body = || response.into_json::<Value>().ok()?.get("retry_after")?.as_f64();
+    let seconds = header.or_else(body).unwrap_or(1.0);
+    Duration::from_secs_f64(seconds.max(0.0)).min(MAX_RETRY_WAIT)
+}
+
+fn post(webhook: &Webhook, text: &str) -> Result<(), String> {
+    let agent = http::agent();
+    let payload = webhook.kind.payload(text);
+    let mut attempt = 0;
+    loop {
+        match agent.post(&webhook.url).send_json(&payload) {
+            Ok(_) => return Ok(()),
+            Err(ureq::Error::Status(429, response)) if attempt < MAX_RETRIES => {
+                attempt += 1;
+                std::thread::sleep(retry_after(response));
+            }
+            Err(e) => return Err(http::error_message(e)),
+        }
+    }
+}
+
+#[tauri::command]
+pub fn list_webhooks(app: AppHandle) -> Vec<WebhookInfo> {
+    load(&app).into_iter().map(|(name, webhook)| WebhookInfo { name, kind: webhook.kind }).collect()
+}
+
+#[tauri::command]
+pub fn set_webhook(app: AppHandle, name: &str, webhook: Webhook) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Le nom du webhook est vide".to_string());
+    }
+    if !webhook.url.trim().starts_with("https://") {
+        return Err(format!("Adresse de webhook invalide: {}", webhook.url));
+    }
+    let webhook = Webhook { kind: webhook.kind, url: webhook.url.trim().to_string() };
+    update(&app, |webhooks| {
+        webhooks.insert(name.trim().to_string(), webhook);
+    })
+}
+
+#[tauri::command]
+pub fn remove_webhook(app: AppHandle, name: &str) -> Result<(), String> {
+    update(&app, |webhooks| {
+        webhooks.remove(name);
+    })
+}
+
+/// The messages `markdown` becomes on a service, for a preview before
+/// sharing.
+#[tauri::command]
+pub fn preview_webhook_messages(markdown: &str, kind: WebhookKind) -> Vec<String> {
+    messages(markdown, kind)
+}
+
+/// Posts a document, or a selection of it, to a saved webhook, converted to
+/// the service's markup and split into as many messages as its length
+/// limit requires.
+#[tauri::command]
+pub async fn share_to_webhook(app: AppHandle, name: String, markdown: String) -> Result<WebhookShare, String> {
+    let webhook = load(&app).remove(&name).ok_or_else(|| format!("Webhook inconnu: {}", name))?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let messages = messages(&markdown, webhook.kind);
+        if messages.is_empty() {
+            return Err("Rien à partager".to_string());
+        }
+        for (i, message) in messages.iter().enumerate() {
+            post(&webhook, message).map_err(|e| format!("Message {}/{} non envoyé: {}", i + 1, messages.len(), e))?;
+        }
+        Ok(WebhookShare { messages: messages.len() })
+    })
+    .await
+    .map_err(|e| format!("Erreur lors du partage: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_keeps_messages_under_the_limit() {
+        let text = format!("{}\n\n{}", "é".repeat(30), "mot ".repeat(40));
+        for message in split(&text, 50) {
+            assert!(message.chars().count() <= 50, "{:?}", message);
+        }
+    }
+
+    #[test]
+    fn split_reopens_a_code_block_it_cuts() {
+        let code = (0..20).map(|i| format!("ligne {}", i)).collect::<Vec<_>>().join("\n");
+        let messages = split(&format!("```rust\n{}\n```", code), 60);
+        assert!(messages.len() > 1);
+        for message in &messages {
+            assert!(message.chars().count() <= 60, "{:?}", message);
+            assert!(message.starts_with("```"), "{:?}", message);
+            assert!(message.ends_with("```"), "{:?}", message);
+        }
+    }
+
+    #[test]
+    fn cut_line_never_splits_a_character() {
+        let pieces = cut_line("😀😀😀😀😀", 2);
+        assert_eq!(pieces, ["😀😀", "😀😀", "😀"]);
+    }
+}
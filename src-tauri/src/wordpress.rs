@@ -0,0 +1,178 @@
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::publish::{self, Published};
+use crate::{http, pandoc};
+
+const TARGET: &str = "wordpress";
+const ID_KEY: &str = "wordpress_id";
+const STATUSES: &[&str] = &["publish", "draft", "pending", "private", "future"];
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WordPressSite {
+    /// Address of the site, e.g. "https://blog.example.com".
+    url: String,
+    username: String,
+    /// Generated under Users › Profile › Application Passwords; the account
+    /// password itself is refused by the REST API.
+    application_password: String,
+}
+
+pub fn validate(account: &Value) -> Result<(), String> {
+    let site: WordPressSite = serde_json::from_value(account.clone()).map_err(|e| format!("Compte WordPress invalide: {}", e))?;
+    if !site.url.starts_with("https://") && !site.url.starts_with("http://") {
+        return Err(format!("Adresse du site invalide: {}", site.url));
+    }
+    if site.username.is_empty() || site.application_password.is_empty() {
+        return Err("Identifiant et mot de passe d'application requis".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct Media {
+    id: u64,
+    source_url: String,
+}
+
+#[derive(Deserialize)]
+struct Term {
+    id: u64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Post {
+    id: u64,
+    link: String,
+}
+
+struct Client {
+    agent: ureq::Agent,
+    base: String,
+    auth: String,
+}
+
+impl Client {
+    fn new(site: &WordPressSite) -> Self {
+        // Application passwords are shown with spaces, which are optional
+        let password = site.application_password.replace(' ', "");
+        let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", site.username, password));
+        Client {
+            agent: http::agent(),
+            base: format!("{}/wp-json/wp/v2", site.url.trim_end_matches('/')),
+            auth: format!("Basic {}", credentials),
+        }
+    }
+
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        self.agent.request(method, &format!("{}/{}", self.base, path)).set("Authorization", &self.auth)
+    }
+
+    fn upload(&self, file: &Path) -> Result<Media, String> {
+        let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let bytes = std::fs::read(file).map_err(|e| format!("Impossible de lire {}: {}", file.display(), e))?;
+        self.request("POST", "media")
+            .set("Content-Type", publish::image_mime(file))
+            .set("Content-Disposition", &format!("attachment; filename=\"{}\"", name.replace('"', "")))
+            .send_bytes(&bytes)
+            .map_err(http::error_message)?
+            .into_json()
+            .map_err(|e| format!("Réponse inattendue de WordPress: {}", e))
+    }
+
+    /// Ids of the categories or tags with these names, creating the missing
+    /// ones.
+    fn term_ids(&self, taxonomy: &str, names: &[String]) -> Result<Vec<u64>, String> {
+        let mut ids = Vec::new();
+        for name in names {
+            let found: Vec<Term> = self
+                .request("GET", taxonomy)
+                .query("search", name)
+                .query("per_page", "100")
+                .call()
+                .map_err(http::error_message)?
+                .into_json()
+                .map_err(|e| format!("Réponse inattendue de WordPress: {}", e))?;
+            // Names come back HTML-escaped
+            let existing = found.iter().find(|t| t.name.replace("&amp;", "&").eq_ignore_ascii_case(name));
+            let id = match existing {
+                Some(term) => term.id,
+                None => {
+                    let created: Term = self
+                        .request("POST", taxonomy)
+                        .send_json(json!({ "name": name }))
+                        .map_err(http::error_message)?
+                        .into_json()
+                        .map_err(|e| format!("Réponse inattendue de WordPress: {}", e))?;
+                    created.id
+                }
+            };
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+}
+
+/// Publishes the document as a WordPress post through the REST API, or
+/// updates the post it was published as before. Local images go to the
+/// media library; the title, categories, tags, status, excerpt and cover
+/// image come from the frontmatter. Posts are drafts unless it says
+/// otherwise.
+#[tauri::command]
+pub async fn publish_to_wordpress(app: AppHandle, account: String, content: String, path: Option<String>) -> Result<Published, String> {
+    publish::blocking(move || {
+        let site: WordPressSite = publish::account(&app, TARGET, &account)?;
+        let client = Client::new(&site);
+        let document_path = crate::scopes::check_optional(&app, path.as_deref())?;
+        let document_dir = document_path.as_deref().and_then(Path::parent);
+        let post = publish::post(&content, document_path.as_deref(), ID_KEY);
+
+        let (body, images_uploaded) = publish::upload_images(&app, &post.body, document_dir, |file| Ok(client.upload(file)?.source_url))?;
+        let html = pandoc::to_html_fragment(&body, document_dir)?;
+        let status = match &post.status {
+            Some(status) if STATUSES.contains(&status.as_str()) => status.as_str(),
+            _ if post.draft => "draft",
+            _ => "publish",
+        };
+        let mut fields = json!({
+            "title": post.title,
+            "content": html,
+            "status": status,
+            "categories": client.term_ids("categories", &post.categories)?,
+            "tags": client.term_ids("tags", &post.tags)?,
+        });
+        if let Some(summary) = &post.summary {
+            fields["excerpt"] = json!(summary);
+        }
+        // Featured images must be in the media library; remote ones are left out
+        if let Some(image) = post.feature_image.as_deref().map(|i| publish::local_image(&app, i, document_dir)).transpose()?.flatten() {
+            fields["featured_media"] = json!(client.upload(&image)?.id);
+        }
+
+        let response = match &post.id {
+            Some(id) => match client.request("POST", &format!("posts/{}", publish::checked_id(id, |c| c.is_ascii_digit())?)).send_json(fields.clone()) {
+                // Deleted on the site since: publish it again
+                Err(ureq::Error::Status(404, _)) => client.request("POST", "posts").send_json(fields),
+                result => result,
+            },
+            None => client.request("POST", "posts").send_json(fields),
+        };
+        let created: Post = response
+            .map_err(http::error_message)?
+            .into_json()
+            .map_err(|e| format!("Réponse inattendue de WordPress: {}", e))?;
+        let id = created.id.to_string();
+        Ok(Published {
+            markdown: publish::record_id(&content, ID_KEY, &id),
+            id,
+            url: created.link,
+            images_uploaded,
+        })
+    })
+    .await
+}
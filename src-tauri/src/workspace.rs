@@ -0,0 +1,285 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::{assets, favorites, markdown, scopes, watcher};
+
+pub const WORKSPACE_CHANGED_EVENT: &str = "workspace-changed";
+
+pub const ASSET_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "svg", "avif", "bmp", "tif", "tiff", "heic", "heif", "pdf",
+];
+
+/// Delay before rebuilding the tree, so a burst of changes (git checkout,
+/// sync) triggers a single refresh.
+const REFRESH_DELAY: Duration = Duration::from_millis(300);
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeNode {
+    pub name: String,
+    pub path: String,
+    /// "folder", "markdown", "encrypted" or "asset".
+    pub kind: &'static str,
+    pub favorite: bool,
+    pub children: Vec<TreeNode>,
+}
+
+#[derive(Default)]
+pub struct Workspace {
+    root: Mutex<Option<PathBuf>>,
+    refresh_scheduled: AtomicBool,
+}
+
+impl Workspace {
+    pub fn root(&self) -> Result<PathBuf, String> {
+        self.root.lock().unwrap().clone().ok_or_else(|| "Aucun dossier de travail n'est ouvert".to_string())
+    }
+
+    /// Resolves a path from the frontend, refusing anything outside the
+    /// workspace.
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        let root = self.root()?;
+        let resolved = assets::normalize(&root.join(path));
+        if resolved.starts_with(&root) {
+            Ok(resolved)
+        } else {
+            Err(format!("{} est en dehors du dossier de travail", path))
+        }
+    }
+}
+
+fn kind_of(path: &Path) -> Option<&'static str> {
+    if markdown::is_markdown_file(path) {
+        return Some("markdown");
+    }
+    if path.extension().is_some_and(|e| e == crate::encryption::ENCRYPTED_EXTENSION)
+        && markdown::is_markdown_file(&path.with_extension(""))
+    {
+        return Some("encrypted");
+    }
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    ASSET_EXTENSIONS.contains(&ext.as_str()).then_some("asset")
+}
+
+fn node(path: &Path, kind: &'static str, children: Vec<TreeNode>) -> TreeNode {
+    TreeNode {
+        name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        path: path.to_string_lossy().to_string(),
+        kind,
+        favorite: false,
+        children,
+    }
+}
+
+/// Entries under `root`, skipping hidden and git-ignored ones.
+pub fn walk(root: &Path) -> impl Iterator<Item = ignore::DirEntry> {
+    ignore::WalkBuilder::new(root)
+        .hidden(true)
+        .require_git(false)
+        .build()
+        .filter_map(|e| e.ok())
+}
+
+/// Markdown files of the workspace.
+pub fn markdown_files(root: &Path) -> Vec<PathBuf> {
+    walk(root)
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()) && markdown::is_markdown_file(e.path()))
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// Builds the folder tree in the folders' manual order, otherwise folders
+/// first, then files, each sorted by name.
+pub fn build_tree(root: &Path) -> TreeNode {
+    let mut children: HashMap<PathBuf, Vec<TreeNode>> = HashMap::new();
+    let mut folders = Vec::new();
+    for entry in walk(root).filter(|e| e.depth() > 0) {
+        let path = entry.path();
+        if entry.file_type().is_some_and(|t| t.is_dir()) {
+            folders.push(path.to_path_buf());
+        } else if let (Some(kind), Some(parent)) = (kind_of(path), path.parent()) {
+            children.entry(parent.to_path_buf()).or_default().push(node(path, kind, Vec::new()));
+        }
+    }
+
+    // Deepest folders first, so each one is complete when its parent takes it
+    folders.sort_by_key(|f| std::cmp::Reverse(f.components().count()));
+    for folder in folders {
+        let content = children.remove(&folder).unwrap_or_default();
+        if let Some(parent) = folder.parent() {
+            children.entry(parent.to_path_buf()).or_default().push(node(&folder, "folder", content));
+        }
+    }
+
+    let mut tree = node(root, "folder", children.remove(root).unwrap_or_default());
+    sort(&mut tree, root, &favorites::load(root));
+    tree
+}
+
+fn sort(tree: &mut TreeNode, root: &Path, layout: &favorites::TreeLayout) {
+    let folder = PathBuf::from(&tree.path);
+    // Manually placed children first, in their order
+    tree.children.sort_by_cached_key(|c| {
+        let position = layout.position(root, &folder, &c.name).unwrap_or(usize::MAX);
+        (position, c.kind != "folder", c.name.to_lowercase())
+    });
+    for child in &mut tree.children {
+        child.favorite = layout.is_favorite(root, Path::new(&child.path));
+        sort(child, root, layout);
+    }
+}
+
+/// Called by the watcher: schedules a tree refresh when files appeared,
+/// disappeared or moved inside the workspace.
+pub fn on_changes(app: &AppHandle, changes: &[watcher::FileChange]) {
+    let workspace = app.state::<Workspace>();
+    let Ok(root) = workspace.root() else { return };
+    let structural = changes.iter().any(|c| c.kind != "modified" && Path::new(&c.path).starts_with(&root));
+    if !structural || workspace.refresh_scheduled.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(REFRESH_DELAY);
+        let workspace = app.state::<Workspace>();
+        workspace.refresh_scheduled.store(false, Ordering::SeqCst);
+        if let Ok(root) = workspace.root() {
+            let _ = app.emit(WORKSPACE_CHANGED_EVENT, build_tree(&root));
+        }
+    });
+}
+
+/// Opens a folder as the workspace and returns its tree. The tree is pushed
+/// again through `workspace-changed` events whenever it changes on disk.
+#[tauri::command]
+pub fn open_workspace(app: AppHandle, workspace: State<'_, Workspace>, dir: &str) -> Result<TreeNode, String> {
+    let dir = scopes::check(&app, dir)?;
+    if !dir.is_dir() {
+        return Err(format!("Dossier introuvable: {}", dir.display()));
+    }
+    scopes::grant(&app, &dir)?;
+    let previous = workspace.root.lock().unwrap().replace(dir.clone());
+    if let Some(previous) = previous {
+        watcher::unwatch(&app, &previous);
+    }
+    watcher::watch(&app, &dir)?;
+    Ok(build_tree(&dir))
+}
+
+#[tauri::command]
+pub fn close_workspace(app: AppHandle, workspace: State<'_, Workspace>) {
+    if let Some(root) = workspace.root.lock().unwrap().take() {
+        watcher::unwatch(&app, &root);
+    }
+}
+
+#[tauri::command]
+pub fn get_workspace_tree(workspace: State<'_, Workspace>) -> Result<TreeNode, String> {
+    Ok(build_tree(&workspace.root()?))
+}
+
+#[tauri::command]
+pub fn create_workspace_file(workspace: State<'_, Workspace>, path: &str, content: Option<&str>) -> Result<String, String> {
+    let path = workspace.resolve(path)?;
+    if path.exists() {
+        return Err(format!("{} existe déjà", path.display()));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Impossible de créer le dossier {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(&path, content.unwrap_or(""))
+        .map_err(|e| format!("Impossible de créer le fichier: {}", e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn create_workspace_folder(workspace: State<'_, Workspace>, path: &str) -> Result<String, String> {
+    let path = workspace.resolve(path)?;
+    std::fs::create_dir_all(&path)
+        .map_err(|e| format!("Impossible de créer le dossier {}: {}", path.display(), e))?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+fn move_entry(from: &Path, to: &Path) -> Result<String, String> {
+    if to.exists() {
+        return Err(format!("{} existe déjà", to.display()));
+    }
+    std::fs::rename(from, to)
+        .map_err(|e| format!("Impossible de déplacer {}: {}", from.display(), e))?;
+    Ok(to.to_string_lossy().to_string())
+}
+
+/// Renames a file or folder in place and returns its new path.
+#[tauri::command]
+pub fn rename_workspace_entry(app: AppHandle, workspace: State<'_, Workspace>, path: &str, new_name: &str) -> Result<String, String> {
+    if new_name.is_empty() || new_name.contains(['/', '\\']) || new_name == "." || new_name == ".." {
+        return Err(format!("Nom invalide: {}", new_name));
+    }
+    let from = workspace.resolve(path)?;
+    ensure_movable(&app, &from)?;
+    let to = from.with_file_name(new_name);
+    let moved = move_entry(&from, &to)?;
+    favorites::on_moved(&workspace.root()?, &from, &to)?;
+    Ok(moved)
+}
+
+/// Refuses to move a locked note, or a folder holding one, as
+/// `backlinks::rename_note` does.
+fn ensure_movable(app: &AppHandle, path: &Path) -> Result<(), String> {
+    let files: Vec<PathBuf> = walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+    crate::locks::ensure_all_writable(app, files.iter().map(PathBuf::as_path))
+}
+
+/// Moves a file or folder into another folder and returns its new path.
+#[tauri::command]
+pub fn move_workspace_entry(app: AppHandle, workspace: State<'_, Workspace>, path: &str, target_dir: &str) -> Result<String, String> {
+    let from = workspace.resolve(path)?;
+    ensure_movable(&app, &from)?;
+    let target_dir = workspace.resolve(target_dir)?;
+    if target_dir.starts_with(&from) {
+        return Err("Impossible de déplacer un dossier dans lui-même".to_string());
+    }
+    let name = from.file_name().ok_or("Chemin invalide")?;
+    let to = target_dir.join(name);
+    let moved = move_entry(&from, &to)?;
+    favorites::on_moved(&workspace.root()?, &from, &to)?;
+    Ok(moved)
+}
+
+/// Moves a file or folder to the trash. A note takes along the assets that
+/// no other note links to.
+#[tauri::command]
+pub fn delete_workspace_entry(app: AppHandle, workspace: State<'_, Workspace>, path: &str) -> Result<(), String> {
+    let path = workspace.resolve(path)?;
+    let root = workspace.root()?;
+    if path == root {
+        return Err("Impossible de supprimer le dossier de travail".to_string());
+    }
+    if !path.exists() {
+        return Err(format!("Chemin introuvable: {}", path.display()));
+    }
+    let mut paths = vec![path.clone()];
+    if markdown::is_markdown_file(&path) {
+        paths.extend(crate::trash::exclusive_assets(&path, &root));
+    }
+    crate::trash::move_to_trash(&app, &paths)?;
+
+    // Drop the note's assets folder when nothing is left in it
+    let assets_dir = assets::assets_dir_for(&path);
+    if assets_dir.is_dir() && std::fs::read_dir(&assets_dir).is_ok_and(|mut d| d.next().is_none()) {
+        let _ = std::fs::remove_dir(&assets_dir);
+    }
+    Ok(())
+}
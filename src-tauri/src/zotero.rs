@@ -0,0 +1,191 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+use crate::frontmatter::{self, Frontmatter};
+use crate::{http, markdown};
+
+/// Better BibTeX's endpoints in the running Zotero.
+const BBT_URL: &str = "http://127.0.0.1:23119/better-bibtex";
+/// Same translator as pandoc reads `.bib` files with.
+const TRANSLATOR: &str = "Better BibLaTeX";
+const MAX_LISTED_AUTHORS: usize = 3;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Citation {
+    pub citekey: String,
+    pub title: String,
+    /// "Dupont, Martin" or "Dupont et al.".
+    pub authors: String,
+    pub year: Option<String>,
+    /// What to insert: `[@citekey]`.
+    pub markdown: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BibliographyExport {
+    pub path: String,
+    /// The document, with `bibliography` set in its frontmatter when it
+    /// wasn't.
+    pub markdown: String,
+    pub entries: usize,
+    /// Cited keys not in the Zotero library.
+    pub missing: Vec<String>,
+}
+
+fn unreachable(err: ureq::Error) -> String {
+    match err {
+        ureq::Error::Transport(_) => "Zotero n'est pas lancé ou Better BibTeX n'est pas installé".to_string(),
+        err => http::error_message(err),
+    }
+}
+
+fn rpc(method: &str, params: Value) -> Result<Value, String> {
+    let response: Value = http::agent()
+        .post(&format!("{}/json-rpc", BBT_URL))
+        .send_json(json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 }))
+        .map_err(unreachable)?
+        .into_json()
+        .map_err(|e| format!("Réponse de Zotero invalide: {}", e))?;
+    if let Some(error) = response.get("error") {
+        let message = error.get("message").and_then(Value::as_str).unwrap_or("erreur inconnue");
+        return Err(format!("Zotero: {}", message));
+    }
+    Ok(response.get("result").cloned().unwrap_or(Value::Null))
+}
+
+/// Authors of a CSL-JSON item, shortened past a few.
+fn authors(item: &Value) -> String {
+    let names: Vec<String> = item
+        .get("author")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|a| a.get("family").or_else(|| a.get("literal")).and_then(Value::as_str).map(str::to_string))
+        .collect();
+    if names.len() > MAX_LISTED_AUTHORS {
+        format!("{} et al.", names[0])
+    } else {
+        names.join(", ")
+    }
+}
+
+fn year(item: &Value) -> Option<String> {
+    let issued = item.get("issued")?;
+    match issued.pointer("/date-parts/0/0") {
+        Some(Value::Number(year)) => Some(year.to_string()),
+        Some(Value::String(year)) => Some(year.clone()),
+        _ => issued.get("raw").or_else(|| issued.get("literal")).and_then(Value::as_str).map(|s| s.chars().take(4).collect()),
+    }
+}
+
+/// Exported BibLaTeX text; older Better BibTeX versions answer
+/// `[status, type, body]`.
+fn export(citekeys: &[String]) -> Result<String, String> {
+    match rpc("item.export", json!([citekeys, TRANSLATOR]))? {
+        Value::String(bib) => Ok(bib),
+        Value::Array(parts) => parts.last().and_then(Value::as_str).map(str::to_string).ok_or_else(|| "Réponse de Zotero invalide".to_string()),
+        _ => Err("Réponse de Zotero invalide".to_string()),
+    }
+}
+
+/// Searches the Zotero library (titles, authors, years, citation keys).
+#[tauri::command]
+pub async fn search_citations(query: String) -> Result<Vec<Citation>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let items = rpc("item.search", json!([query.trim()]))?;
+        Ok(items
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|item| {
+                let citekey = ["citekey", "citationKey", "citation-key"].iter().find_map(|k| item.get(k)?.as_str())?.to_string();
+                Some(Citation {
+                    title: item.get("title").and_then(Value::as_str).unwrap_or_default().to_string(),
+                    authors: authors(item),
+                    year: year(item),
+                    markdown: format!("[@{}]", citekey),
+                    citekey,
+                })
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de la recherche dans Zotero: {}", e))?
+}
+
+/// Opens Zotero's own citation picker. Returns the citations to insert, e.g.
+/// `[@dupont2020; @martin2019]`, or None when it was cancelled.
+#[tauri::command]
+pub async fn pick_citations() -> Result<Option<String>, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let picked = http::agent()
+            .get(&format!("{}/cayw", BBT_URL))
+            .query("format", "pandoc")
+            .query("brackets", "1")
+            .call()
+            .map_err(unreachable)?
+            .into_string()
+            .map_err(|e| format!("Réponse de Zotero invalide: {}", e))?;
+        Ok(Some(picked.trim().to_string()).filter(|p| !p.is_empty()))
+    })
+    .await
+    .map_err(|e| format!("Erreur lors du choix des citations: {}", e))?
+}
+
+/// Exports the Zotero entries the document cites to a `.bib` file next to
+/// it, named after it, and points its `bibliography` at that file so
+/// exports through pandoc's citeproc find them.
+#[tauri::command]
+pub async fn export_bibliography(app: AppHandle, markdown: String, document_path: String) -> Result<BibliographyExport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let document = crate::scopes::check(&app, &document_path)?;
+        let mut citekeys: Vec<String> = Vec::new();
+        for citation in markdown::citations(&markdown) {
+            let key = citation.key(&markdown).to_string();
+            if !citekeys.contains(&key) {
+                citekeys.push(key);
+            }
+        }
+        if citekeys.is_empty() {
+            return Err("Le document ne cite aucune référence".to_string());
+        }
+
+        // Better BibTeX refuses the whole export over one unknown key
+        let mut missing = Vec::new();
+        let bib = match export(&citekeys) {
+            Ok(bib) => bib,
+            Err(_) => {
+                let mut found = Vec::new();
+                for key in citekeys.drain(..) {
+                    match export(std::slice::from_ref(&key)) {
+                        Ok(_) => found.push(key),
+                        Err(_) => missing.push(key),
+                    }
+                }
+                citekeys = found;
+                if citekeys.is_empty() {
+                    String::new()
+                } else {
+                    export(&citekeys)?
+                }
+            }
+        };
+
+        let path = document.with_extension("bib");
+        std::fs::write(&path, bib).map_err(|e| format!("Impossible d'écrire la bibliographie: {}", e))?;
+        let fields: Frontmatter = frontmatter::parse(&markdown);
+        let markdown = match fields.get("bibliography") {
+            Some(_) => markdown,
+            None => {
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                frontmatter::set(&markdown, "bibliography", &name)
+            }
+        };
+        Ok(BibliographyExport { path: path.to_string_lossy().to_string(), markdown, entries: citekeys.len(), missing })
+    })
+    .await
+    .map_err(|e| format!("Erreur lors de l'export de la bibliographie: {}", e))?
+}